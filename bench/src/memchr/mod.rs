@@ -765,6 +765,74 @@ pub fn all(c: &mut Criterion) {
             });
         },
     );
+
+    // These search many 16 byte slices of the same corpus, which is where
+    // the cost of selecting an implementation on every call is most visible.
+    let slices = || HUGE.corpus.chunks_exact(16);
+    let expected = slices().filter(|s| s.contains(&b'a')).count();
+    define(
+        c,
+        "memchr1/krate/slices16",
+        HUGE.corpus,
+        Box::new(move |b| {
+            b.iter(|| {
+                let found = slices().filter_map(|s| memchr::memchr(b'a', s));
+                assert_eq!(expected, found.count());
+            });
+        }),
+    );
+    let mut out = Vec::with_capacity(slices().len());
+    define(
+        c,
+        "memchr1/krate_batch/slices16",
+        HUGE.corpus,
+        Box::new(move |b| {
+            b.iter(|| {
+                out.clear();
+                memchr::memchr_batch(b'a', slices(), &mut out);
+                assert_eq!(expected, out.iter().flatten().count());
+            });
+        }),
+    );
+
+    early_match(c);
+}
+
+/// Benchmarks a single search whose match is near the start of a long
+/// haystack, as is typical when looking for a delimiter in a protocol
+/// message. The naive scalar scan is included since it shows whether
+/// starting with a scalar scan of the first few bytes would pay off.
+fn early_match(c: &mut Criterion) {
+    const OFFSETS: &[usize] = &[0, 1, 3, 7, 15, 31, 63];
+    const LEN: usize = 1024;
+
+    for &offset in OFFSETS {
+        let mut haystack = vec![b'a'; LEN];
+        haystack[offset] = b'\n';
+        // Leaked so that the benchmark closures can borrow it, just like the
+        // static corpora.
+        let haystack: &'static [u8] = Box::leak(haystack.into_boxed_slice());
+        define(
+            c,
+            &format!("memchr1/krate/early-{}", offset),
+            haystack,
+            Box::new(move |b| {
+                b.iter(|| {
+                    assert_eq!(Some(offset), memchr::memchr(b'\n', haystack));
+                });
+            }),
+        );
+        define(
+            c,
+            &format!("memchr1/naive/early-{}", offset),
+            haystack,
+            Box::new(move |b| {
+                b.iter(|| {
+                    assert_eq!(Some(offset), naive::memchr(b'\n', haystack));
+                });
+            }),
+        );
+    }
 }
 
 fn define_memchr_input1<'i>(
@@ -254,4 +254,22 @@ pub const INPUTS: &'static [Input] = &[
         }],
         common: &[],
     },
+    // This input is aimed at reverse searches, which have no prefilter, and
+    // so rely entirely on Two-Way's handling of the needle's period to stay
+    // linear. See data/pathological/README.md.
+    Input {
+        name: "pathological-periodic-rev",
+        corpus: data::PATHOLOGICAL_PERIODIC_REV,
+        never: &[Query {
+            name: "periodic",
+            needle: "dababababababababababababababababababababababababab",
+            count: 0,
+        }],
+        rare: &[Query {
+            name: "periodic",
+            needle: "cababababababababababababababababababababababababab",
+            count: 1,
+        }],
+        common: &[],
+    },
 ];
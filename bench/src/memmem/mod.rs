@@ -19,9 +19,20 @@ follows:
     A brief name describing the implementation under test. Possible values:
 
     krate
-      The implementation provided by this crate.
+      The implementation provided by this crate. The prefilter it uses
+      depends on the target: AVX2 or SSE2 on x86_64, NEON on aarch64 and
+      simd128 on wasm32. Comparing it with krate-nopre on each target shows
+      how much the vectorized prefilter helps there.
+    krate (with the sse42-prefilter feature)
+      When the benchmarks are built with '--features sse42-prefilter', krate
+      uses the experimental PCMPESTRI prefilter for needles of at most 16
+      bytes on x86_64 CPUs with SSE4.2. Compare the results against a run
+      without the feature (e.g., with critcmp) to evaluate it.
     krate-nopre
       The implementation provided by this crate without prefilters enabled.
+    krate_find_n
+      This crate's memmem::find_n, which takes a needle whose length is
+      known at compile time.
     bstr
       The implementation provided by the bstr crate.
       N.B. This is only applicable at time of writing, since bstr will
@@ -83,6 +94,11 @@ follows:
     sliceslice-i386
       The haystack is an Intel 80386 reference manual.
       This was also taken from the sliceslice crate benchmarks.
+    lensweep-{n}
+      A synthetic haystack of exactly n bytes in which the needle never
+      occurs. These are swept across lengths from 0 to 4096 to show where
+      each implementation's per-call overhead stops dominating, which is
+      useful for tuning thresholds like FinderBuilder::simd_threshold.
 
   needle
     A brief name describing the needle used. Unlike other variables, there
@@ -105,6 +121,7 @@ pub fn all(c: &mut Criterion) {
     prebuilt(c);
     oneshot_iter(c);
     prebuilt_iter(c);
+    length_sweep(c);
     sliceslice::all(c);
     misc(c);
 }
@@ -383,11 +400,84 @@ fn prebuilt_iter(c: &mut Criterion) {
     }
 }
 
-use memchr::memmem::HeuristicFrequencyRank;
+fn length_sweep(c: &mut Criterion) {
+    // Powers of two, plus the sizes on either side of the vector widths
+    // where implementations tend to switch strategies.
+    const LENGTHS: &[usize] = &[
+        0, 1, 2, 3, 4, 7, 8, 9, 15, 16, 17, 31, 32, 33, 63, 64, 65, 128, 256,
+        512, 1024, 2048, 4096,
+    ];
+    // The needle's bytes never occur in the haystack, so every search scans
+    // the whole haystack.
+    const NEEDLE: &str = "xyz";
+
+    // Every haystack is a prefix of this one. It's leaked so that the
+    // benchmark closures can borrow it, just like the static corpora.
+    let longest: String = "abcdefghijklmnopqrstuvw"
+        .chars()
+        .cycle()
+        .take(*LENGTHS.last().unwrap())
+        .collect();
+    let longest: &'static str = Box::leak(longest.into_boxed_str());
+
+    macro_rules! def_impl {
+        ($haystack:expr, $impl:ident) => {
+            let haystack: &'static str = $haystack;
+            let available = imp::$impl::available(NEEDLE);
+            let name = |config: &str| {
+                format!(
+                    "memmem/{imp}/{config}/lensweep-{len}/never-xyz",
+                    imp = stringify!($impl),
+                    config = config,
+                    len = haystack.len(),
+                )
+            };
+            if available.contains(&"oneshot") {
+                define(
+                    c,
+                    &name("oneshot"),
+                    haystack.as_bytes(),
+                    Box::new(move |b| {
+                        b.iter(|| {
+                            assert!(!imp::$impl::fwd::oneshot(
+                                haystack, NEEDLE
+                            ));
+                        });
+                    }),
+                );
+            }
+            if available.contains(&"prebuilt") {
+                define(
+                    c,
+                    &name("prebuilt"),
+                    haystack.as_bytes(),
+                    Box::new(move |b| {
+                        let find = imp::$impl::fwd::prebuilt(NEEDLE);
+                        b.iter(|| {
+                            assert!(!find(haystack));
+                        });
+                    }),
+                );
+            }
+        };
+    }
+    for &len in LENGTHS {
+        def_impl!(&longest[..len], krate);
+        def_impl!(&longest[..len], krate_nopre);
+        def_impl!(&longest[..len], libc);
+    }
+}
+
+use memchr::memmem::{
+    freq::X86Executable, FinderBuilder, FrequencyTable, Verifier,
+};
 
 fn misc(c: &mut Criterion) {
     finder_construction(c);
     byte_frequencies(c);
+    one_byte_needle(c);
+    verifier(c);
+    packed_pair(c);
 }
 
 fn finder_construction(c: &mut Criterion) {
@@ -421,75 +511,218 @@ fn finder_construction(c: &mut Criterion) {
             Box::new(move |b| {
                 b.iter(|| {
                     memchr::memmem::FinderBuilder::new()
-                        .build_heuristic(needle.as_bytes(), Hfrx86)
+                        .build_heuristic(needle.as_bytes(), X86Executable)
                 });
             }),
         );
     }
 }
 
-fn byte_frequencies(c: &mut Criterion) {
-    // This benchmark exists to demonstrate a common use case for
-    // customizing the byte frequency table used by a `Finder`
-    // and the relative performance gain from using an optimal table.
-    // This is essentially why `HeuristicFrequencyRank` was added.
+fn one_byte_needle(c: &mut Criterion) {
+    // A `Finder` built for a one-byte needle skips the prefilter and Two-Way
+    // entirely and searches with memchr. These benchmarks compare it with
+    // calling memchr directly, so the overhead of the `Finder` layer (if any)
+    // is visible.
+    const NEEDLES: [(&str, u8); 2] = [("rare", b'%'), ("common", b'e')];
 
-    // Bytes we want to scan for that are rare in strings but common in executables
-    const NEEDLE: &[u8] = b"\x00\x00\xdd\xdd'";
+    let corpus = crate::data::SHERLOCK_HUGE;
+    for (name, needle) in NEEDLES {
+        let count = memchr::memchr_iter(needle, corpus).count();
+        define(
+            c,
+            &format!("memmem/krate/misc/one-byte/{}-finder", name),
+            corpus,
+            Box::new(move |b| {
+                let needle = [needle];
+                let finder = memchr::memmem::Finder::new(&needle);
+                b.iter(|| {
+                    assert_eq!(count, finder.find_iter(corpus).count());
+                });
+            }),
+        );
+        define(
+            c,
+            &format!("memmem/krate/misc/one-byte/{}-memchr", name),
+            corpus,
+            Box::new(move |b| {
+                b.iter(|| {
+                    assert_eq!(
+                        count,
+                        memchr::memchr_iter(needle, corpus).count()
+                    );
+                });
+            }),
+        );
+    }
+}
 
-    // The input for the benchmark is the benchmark binary itself
-    let exe = std::env::args().next().unwrap();
-    let corpus = std::fs::read(exe).unwrap();
+fn verifier(c: &mut Criterion) {
+    // These benchmarks compare the ways the vectorized searchers can verify
+    // candidates. The haystack repeats the needle with its last byte changed,
+    // so every block is a candidate that only fails verification at its last
+    // byte. Needles shorter than 16 bytes are always verified with scalar
+    // code, so they're only here to show that nothing changes for them.
+    const LENS: [usize; 4] = [8, 16, 24, 32];
+    const VERIFIERS: [(&str, Verifier); 2] =
+        [("simd", Verifier::Simd), ("scalar", Verifier::Scalar)];
 
-    let bin = corpus.clone();
-    define(
-        c,
-        &format!("memmem/krate/misc/frequency-table/default"),
-        &corpus,
-        Box::new(move |b| {
-            let finder =
-                memchr::memmem::FinderBuilder::new().build_forward(NEEDLE);
-            b.iter(|| {
-                assert_eq!(1, finder.find_iter(&bin).count());
-            });
-        }),
-    );
+    for len in LENS {
+        let mut needle = b"QZ".to_vec();
+        needle.resize(len - 1, b'e');
+        needle.push(b't');
+        let mut block = needle.clone();
+        *block.last_mut().unwrap() = b'o';
+        let haystack: Vec<u8> =
+            block.iter().cycle().take(1 << 16).copied().collect();
+        for (name, verifier) in VERIFIERS {
+            let finder = FinderBuilder::new()
+                .verifier(verifier)
+                .build_forward(&needle)
+                .into_owned();
+            let hay = haystack.clone();
+            define(
+                c,
+                &format!("memmem/krate/misc/verifier/{}/len-{}", name, len),
+                &haystack,
+                Box::new(move |b| {
+                    b.iter(|| {
+                        assert_eq!(None, finder.find(&hay));
+                    });
+                }),
+            );
+        }
+    }
+}
+
+fn packed_pair(c: &mut Criterion) {
+    // These benchmarks count every candidate reported by a packed pair
+    // finder, without verifying any of them. The portable finder is measured
+    // on every target, so that the vectorized finder for the current target
+    // (if there is one here) can be compared against it.
+    const NEEDLE: &[u8] = b"Sherlock Holmes";
 
-    let bin = corpus.clone();
+    fn count(haystack: &[u8], find: impl Fn(&[u8]) -> Option<usize>) -> usize {
+        let (mut at, mut count) = (0, 0);
+        while let Some(i) = find(&haystack[at..]) {
+            at += i + 1;
+            count += 1;
+        }
+        count
+    }
+
+    let corpus = crate::data::SHERLOCK_HUGE;
+    let finder = memchr::arch::all::packedpair::Finder::new(NEEDLE).unwrap();
+    let expected = count(corpus, |h| finder.find_prefilter(h));
     define(
         c,
-        &format!("memmem/krate/misc/frequency-table/custom"),
-        &corpus,
+        "memmem/krate/misc/packed-pair/portable",
+        corpus,
         Box::new(move |b| {
-            let finder = memchr::memmem::FinderBuilder::new()
-                .build_heuristic(NEEDLE, Hfrx86);
             b.iter(|| {
-                assert_eq!(1, finder.find_iter(&bin).count());
+                assert_eq!(
+                    expected,
+                    count(corpus, |h| finder.find_prefilter(h))
+                );
             });
         }),
     );
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        use memchr::arch::aarch64::neon::packedpair::Finder;
+
+        let finder = Finder::new(NEEDLE).unwrap();
+        define(
+            c,
+            "memmem/krate/misc/packed-pair/neon",
+            corpus,
+            Box::new(move |b| {
+                b.iter(|| {
+                    assert_eq!(
+                        expected,
+                        count(corpus, |h| finder.find_prefilter(h))
+                    );
+                });
+            }),
+        );
+    }
 }
 
-// A byte-frequency table that is good for scanning binary executables
-struct Hfrx86;
-impl HeuristicFrequencyRank for Hfrx86 {
-    fn rank(&self, byte: u8) -> u8 {
-        const TABLE: [u8; 256] = [
-            255, 128, 61, 43, 50, 41, 27, 28, 57, 15, 21, 13, 24, 17, 17, 89,
-            58, 16, 11, 7, 14, 23, 7, 6, 24, 9, 6, 5, 9, 4, 7, 16, 68, 11, 9,
-            6, 88, 7, 4, 4, 23, 9, 4, 8, 8, 5, 10, 4, 30, 11, 9, 24, 11, 5, 5,
-            5, 19, 11, 6, 17, 9, 9, 6, 8, 48, 58, 11, 14, 53, 40, 9, 9, 254,
-            35, 3, 6, 52, 23, 6, 6, 27, 4, 7, 11, 14, 13, 10, 11, 11, 5, 2,
-            10, 16, 12, 6, 19, 19, 20, 5, 14, 16, 31, 19, 7, 14, 20, 4, 4, 19,
-            8, 18, 20, 24, 1, 25, 19, 58, 29, 10, 5, 15, 20, 2, 2, 9, 4, 3, 5,
-            51, 11, 4, 53, 23, 39, 6, 4, 13, 81, 4, 186, 5, 67, 3, 2, 15, 0,
-            0, 1, 3, 2, 0, 0, 5, 0, 0, 0, 2, 0, 0, 0, 12, 2, 1, 1, 3, 1, 1, 1,
-            6, 1, 2, 1, 3, 1, 1, 2, 9, 1, 1, 0, 2, 2, 4, 4, 11, 6, 7, 3, 6, 9,
-            4, 5, 46, 18, 8, 18, 17, 3, 8, 20, 16, 10, 3, 7, 175, 4, 6, 7, 13,
-            3, 7, 3, 3, 1, 3, 3, 10, 3, 1, 5, 2, 0, 1, 2, 16, 3, 5, 1, 6, 1,
-            1, 2, 58, 20, 3, 14, 12, 2, 1, 3, 16, 3, 5, 8, 3, 1, 8, 6, 17, 6,
-            5, 3, 8, 6, 13, 175,
+fn byte_frequencies(c: &mut Criterion) {
+    // This benchmark exists to demonstrate a common use case for
+    // customizing the byte frequency table used by a `Finder`
+    // and the relative performance gain from using an optimal table.
+    // This is essentially why `HeuristicFrequencyRank` was added.
+    //
+    // Each of the tables in `memmem::freq` is measured on both a binary
+    // corpus and a text corpus, so that the cost of using a table on the
+    // "wrong" kind of data is visible too.
+
+    // Bytes we want to scan for that are rare in strings but common in executables
+    const BIN_NEEDLE: &[u8] = b"\x00\x00\xdd\xdd'";
+    // A needle that occurs many times in the text corpus.
+    const TEXT_NEEDLE: &[u8] = b"Sherlock Holmes";
+
+    // The binary input for the benchmark is the benchmark binary itself
+    let exe = std::env::args().next().unwrap();
+    let bin_corpus = std::fs::read(exe).unwrap();
+    let bin_count = FinderBuilder::new()
+        .build_forward(BIN_NEEDLE)
+        .find_iter(&bin_corpus)
+        .count();
+    let text_corpus = crate::data::SHERLOCK_HUGE.to_vec();
+    let text_count = FinderBuilder::new()
+        .build_forward(TEXT_NEEDLE)
+        .find_iter(&text_corpus)
+        .count();
+
+    let corpora = [
+        ("binary", BIN_NEEDLE, bin_corpus, bin_count),
+        ("text", TEXT_NEEDLE, text_corpus, text_count),
+    ];
+    for (name, needle, corpus, count) in corpora {
+        let tables = [
+            ("default", FrequencyTable::Default),
+            ("x86", FrequencyTable::X86Executable),
+            ("uniform", FrequencyTable::Uniform),
         ];
-        TABLE[byte as usize]
+        for (table_name, table) in tables {
+            let haystack = corpus.clone();
+            define(
+                c,
+                &format!(
+                    "memmem/krate/misc/frequency-table/{}/{}",
+                    name, table_name
+                ),
+                &corpus,
+                Box::new(move |b| {
+                    let finder =
+                        FinderBuilder::new().build_heuristic(needle, table);
+                    b.iter(|| {
+                        assert_eq!(count, finder.find_iter(&haystack).count());
+                    });
+                }),
+            );
+
+            let haystack = corpus.clone();
+            define(
+                c,
+                &format!(
+                    "memmem/krate/misc/frequency-table/{}/{}-reverse",
+                    name, table_name
+                ),
+                &corpus,
+                Box::new(move |b| {
+                    let finder = FinderBuilder::new()
+                        .build_heuristic_reverse(needle, table);
+                    b.iter(|| {
+                        assert_eq!(
+                            count,
+                            finder.rfind_iter(&haystack).count()
+                        );
+                    });
+                }),
+            );
+        }
     }
 }
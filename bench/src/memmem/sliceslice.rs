@@ -24,7 +24,7 @@ with many needles against potentially many haystacks. Nevertheless, these have
 proven useful targets for optimization.
 */
 use criterion::{black_box, Criterion};
-use memchr::memmem;
+use memchr::{arch::all::rabinkarp, memmem};
 
 use crate::{data::*, define};
 
@@ -87,6 +87,106 @@ fn search_short_haystack(c: &mut Criterion) {
         }),
     );
 
+    for &threshold in &[0, 32] {
+        let needles = words.clone();
+        define(
+            c,
+            &format!(
+                "memmem/krate_simd_threshold{}/prebuilt/sliceslice-words/words",
+                threshold,
+            ),
+            &[],
+            Box::new(move |b| {
+                let searchers = needles
+                    .iter()
+                    .map(|needle| {
+                        memmem::FinderBuilder::new()
+                            .simd_threshold(threshold)
+                            .build_forward(needle)
+                    })
+                    .collect::<Vec<_>>();
+                b.iter(|| {
+                    for (i, searcher) in searchers.iter().enumerate() {
+                        for haystack in &needles[i..] {
+                            black_box(
+                                searcher.find(haystack.as_bytes()).is_some(),
+                            );
+                        }
+                    }
+                });
+            }),
+        );
+    }
+
+    let needles = words.clone();
+    define(
+        c,
+        "memmem/krate_rabinkarp/prebuilt/sliceslice-words/words",
+        &[],
+        Box::new(move |b| {
+            let searchers = needles
+                .iter()
+                .map(|needle| rabinkarp::Finder::new(needle.as_bytes()))
+                .collect::<Vec<_>>();
+            b.iter(|| {
+                for (i, searcher) in searchers.iter().enumerate() {
+                    for haystack in &needles[i..] {
+                        black_box(
+                            searcher.find(haystack.as_bytes()).is_some(),
+                        );
+                    }
+                }
+            });
+        }),
+    );
+
+    // These search for the first two bytes of each word instead of the whole
+    // word, since find_n needs a needle whose length is known at compile
+    // time. This compares it to a oneshot search for the same needles.
+    let pairs = words
+        .iter()
+        .filter_map(|word| match *word.as_bytes() {
+            [b1, b2, ..] => Some([b1, b2]),
+            _ => None,
+        })
+        .collect::<Vec<[u8; 2]>>();
+    let needles = words.clone();
+    let pairs2 = pairs.clone();
+    define(
+        c,
+        "memmem/krate/oneshot/sliceslice-words/pairs",
+        &[],
+        Box::new(move |b| {
+            b.iter(|| {
+                for (i, pair) in pairs2.iter().enumerate() {
+                    for haystack in &needles[i..] {
+                        black_box(
+                            memmem::find(haystack.as_bytes(), pair).is_some(),
+                        );
+                    }
+                }
+            });
+        }),
+    );
+    let needles = words.clone();
+    define(
+        c,
+        "memmem/krate_find_n/oneshot/sliceslice-words/pairs",
+        &[],
+        Box::new(move |b| {
+            b.iter(|| {
+                for (i, pair) in pairs.iter().enumerate() {
+                    for haystack in &needles[i..] {
+                        black_box(
+                            memmem::find_n(haystack.as_bytes(), pair)
+                                .is_some(),
+                        );
+                    }
+                }
+            });
+        }),
+    );
+
     let needles = words.clone();
     define(
         c,
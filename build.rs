@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, process::Command, str};
 
 fn main() {
     enable_simd_optimizations();
@@ -8,14 +8,17 @@ fn main() {
 // This adds various simd cfgs if this compiler and target support it.
 //
 // This can be disabled with RUSTFLAGS="--cfg memchr_disable_auto_simd", but
-// this is generally only intended for testing.
+// this is generally only intended for testing. It is also disabled by the
+// 'force-fallback' feature, which is easier to toggle from Cargo.
 //
 // On targets which don't feature SSE2, this is disabled, as LLVM wouln't know
 // how to work with SSE2 operands. Enabling SSE4.2 and AVX on SSE2-only targets
 // is not a problem. In that case, the fastest option will be chosen at
 // runtime.
 fn enable_simd_optimizations() {
-    if is_env_set("CARGO_CFG_MEMCHR_DISABLE_AUTO_SIMD") {
+    if is_env_set("CARGO_CFG_MEMCHR_DISABLE_AUTO_SIMD")
+        || is_feature_set("FORCE_FALLBACK")
+    {
         return;
     }
     let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
@@ -28,6 +31,13 @@ fn enable_simd_optimizations() {
             println!("cargo:rustc-cfg=memchr_runtime_sse2");
             println!("cargo:rustc-cfg=memchr_runtime_sse42");
             println!("cargo:rustc-cfg=memchr_runtime_avx");
+            // The AVX-512 intrinsics were only stabilized in Rust 1.89, so
+            // the 'avx512' feature does nothing on older compilers.
+            if is_feature_set("AVX512")
+                && rustc_minor_version().unwrap_or(0) >= 89
+            {
+                println!("cargo:rustc-cfg=memchr_runtime_avx512");
+            }
         }
         "wasm32" | "wasm64" => {
             if !target_has_feature("simd128") {
@@ -36,6 +46,20 @@ fn enable_simd_optimizations() {
             println!("cargo:rustc-cfg=memchr_runtime_simd");
             println!("cargo:rustc-cfg=memchr_runtime_wasm128");
         }
+        "aarch64" => {
+            // NEON is part of the baseline for aarch64, so we only use it to
+            // accelerate the substring prefilter. There are no vectorized
+            // memchr or substring search routines for aarch64 yet.
+            //
+            // The NEON intrinsics were only stabilized in Rust 1.59, so older
+            // compilers get the portable prefilter instead.
+            if !target_has_feature("neon")
+                || rustc_minor_version().unwrap_or(0) < 59
+            {
+                return;
+            }
+            println!("cargo:rustc-cfg=memchr_runtime_neon");
+        }
         _ => {}
     }
 }
@@ -54,7 +78,7 @@ fn enable_libc() {
     const NO_ARCH: &'static [&'static str] = &["wasm32", "windows"];
     const NO_ENV: &'static [&'static str] = &["sgx"];
 
-    if !is_feature_set("LIBC") {
+    if !is_feature_set("LIBC") || is_feature_set("FORCE_FALLBACK") {
         return;
     }
 
@@ -81,6 +105,19 @@ fn is_env_set(name: &str) -> bool {
     env::var_os(name).is_some()
 }
 
+// Returns the minor version of the Rust compiler used for this build, e.g.,
+// `59` for Rust 1.59.0, or `None` if it couldn't be determined.
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = str::from_utf8(&output.stdout).ok()?;
+    let mut pieces = version.split('.');
+    if pieces.next() != Some("rustc 1") {
+        return None;
+    }
+    pieces.next()?.parse().ok()
+}
+
 fn target_has_feature(feature: &str) -> bool {
     env::var("CARGO_CFG_TARGET_FEATURE")
         .map(|features| features.contains(feature))
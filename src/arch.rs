@@ -0,0 +1,205 @@
+/*!
+This module reports which implementation of single byte search is used on the
+current target and CPU, and exposes some implementations directly.
+
+[`detected`] is purely informational. It is mostly useful for logging or
+debugging performance problems, since the implementation chosen never changes
+which matches are reported.
+
+The [`all`] sub-module contains implementations that work on all targets and
+never use vector instructions, for when the routines at the top-level of this
+crate make the wrong trade off. Sub-modules like `x86_64` and `aarch64` contain
+vectorized implementations of some of the same building blocks.
+*/
+
+#[cfg(all(target_arch = "aarch64", memchr_runtime_neon))]
+pub mod aarch64;
+pub mod all;
+#[cfg(all(target_arch = "x86_64", memchr_runtime_simd))]
+pub mod x86_64;
+
+/// The implementation used by single byte search routines like
+/// [`memchr`](crate::memchr).
+///
+/// The same implementation is used by the other routines in the top-level
+/// module, except that some implementations only accelerate a subset of them.
+/// (For example, `Simd128` only accelerates `memchr`, `memchr2`, `memchr3` and
+/// their reverse counterparts.)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Backend {
+    /// AVX-512 vectors are used. This is only possible on `x86_64` CPUs that
+    /// support AVX-512BW, and only when the `avx512` feature is enabled.
+    Avx512 = 1,
+    /// AVX2 vectors are used. This is only possible on `x86_64` CPUs that
+    /// support AVX2, and only when the `std` feature is enabled.
+    Avx2,
+    /// SSE2 vectors are used. This is always possible on `x86_64`.
+    Sse2,
+    /// WebAssembly `v128` vectors are used. This is only possible on `wasm32`
+    /// when compiled with the `simd128` target feature enabled.
+    Simd128,
+    /// Your platform's libc is used. This is only possible when the `libc`
+    /// feature is enabled and no vectorized implementation is available.
+    Libc,
+    /// A portable implementation that doesn't use any vector instructions.
+    Scalar,
+}
+
+impl Backend {
+    /// Decode a backend from its discriminant. Any value that isn't a valid
+    /// discriminant is decoded as `Scalar`.
+    #[cfg(all(
+        feature = "std",
+        not(miri),
+        target_arch = "x86_64",
+        memchr_runtime_simd
+    ))]
+    pub(crate) fn from_u8(byte: u8) -> Backend {
+        match byte {
+            1 => Backend::Avx512,
+            2 => Backend::Avx2,
+            3 => Backend::Sse2,
+            4 => Backend::Simd128,
+            5 => Backend::Libc,
+            _ => Backend::Scalar,
+        }
+    }
+}
+
+/// Records that the given implementation handled the current search.
+///
+/// This is called by `memchr` and `memrchr` once they have decided which
+/// implementation to use for a particular haystack, which may differ from
+/// [`detected`] for short haystacks. It does nothing outside of this crate's
+/// own tests.
+#[inline(always)]
+pub(crate) fn record_backend(backend: Backend) {
+    #[cfg(all(test, feature = "std"))]
+    LAST_BACKEND.with(|last| last.set(Some(backend)));
+    #[cfg(not(all(test, feature = "std")))]
+    let _ = backend;
+}
+
+#[cfg(all(test, feature = "std"))]
+std::thread_local! {
+    static LAST_BACKEND: core::cell::Cell<Option<Backend>> =
+        const { core::cell::Cell::new(None) };
+}
+
+/// Returns the implementation that handled the most recent call to `memchr`
+/// or `memrchr` on the current thread, and forgets it.
+///
+/// This lets tests check that a search actually took a vectorized path
+/// instead of silently falling back to a scalar loop. `None` is returned if
+/// no search has been recorded since the last call, which is the case for
+/// searches of an empty haystack, since those never reach an implementation.
+#[cfg(all(test, feature = "std"))]
+pub(crate) fn last_backend_used() -> Option<Backend> {
+    LAST_BACKEND.with(|last| last.take())
+}
+
+/// Returns the implementation used for single byte search on the current
+/// target and CPU.
+///
+/// On `x86_64` with the `std` feature enabled, the implementation is chosen
+/// by CPU feature detection the first time a search is executed. This returns
+/// the result of that detection (executing a search first if necessary), so
+/// it never probes the CPU on its own. On all other targets, the
+/// implementation is chosen at compile time.
+///
+/// When the `force-fallback` feature is enabled, this always returns
+/// `Backend::Scalar`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::arch::{self, Backend};
+///
+/// let backend = arch::detected();
+/// println!("memchr is using {:?}", backend);
+/// // The result never changes once it has been determined.
+/// assert_eq!(backend, arch::detected());
+/// if cfg!(target_arch = "x86_64") {
+///     assert_ne!(Backend::Simd128, backend);
+/// }
+/// ```
+pub fn detected() -> Backend {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp() -> Backend {
+        Backend::Scalar
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp() -> Backend {
+        crate::memchr::x86::detected()
+    }
+
+    #[cfg(all(target_arch = "wasm32", memchr_runtime_wasm128, not(miri)))]
+    #[inline(always)]
+    fn imp() -> Backend {
+        Backend::Simd128
+    }
+
+    #[cfg(all(
+        memchr_libc,
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp() -> Backend {
+        Backend::Libc
+    }
+
+    #[cfg(all(
+        not(memchr_libc),
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp() -> Backend {
+        Backend::Scalar
+    }
+
+    imp()
+}
+
+/// Returns true if and only if a vectorized implementation of single byte
+/// search was compiled for the current target.
+///
+/// Unlike [`detected`], this doesn't depend on the CPU the program runs on.
+/// It returns false when the target doesn't enable the vector instructions
+/// this crate needs (for example, `x86_64` built with
+/// `-C target-feature=-sse2`, or `wasm32` without `simd128`), when the
+/// `force-fallback` feature is enabled, and under Miri. In those cases,
+/// [`detected`] always returns [`Backend::Libc`] or [`Backend::Scalar`] and
+/// searches are likely to be much slower, so libraries may want to surface
+/// this as a performance advisory.
+///
+/// NEON on `aarch64` doesn't count, since it only accelerates the substring
+/// search prefilter.
+///
+/// # Example
+///
+/// ```
+/// use memchr::arch::{self, Backend};
+///
+/// const SIMD: bool = arch::has_simd_backend();
+/// if !SIMD {
+///     eprintln!("warning: memchr was built without SIMD support");
+///     assert!(matches!(arch::detected(), Backend::Libc | Backend::Scalar));
+/// }
+/// ```
+pub const fn has_simd_backend() -> bool {
+    cfg!(all(
+        not(miri),
+        any(
+            all(target_arch = "x86_64", memchr_runtime_simd),
+            all(target_arch = "wasm32", memchr_runtime_wasm128),
+        )
+    ))
+}
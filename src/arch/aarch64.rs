@@ -0,0 +1,8 @@
+/*!
+Vectorized implementations for `aarch64`.
+
+These are only available on `aarch64` targets with NEON, which is part of the
+baseline of every standard `aarch64` target.
+*/
+
+pub mod neon;
@@ -0,0 +1,6 @@
+/*!
+Implementations that use NEON vectors, which are always available on
+`aarch64`.
+*/
+
+pub mod packedpair;
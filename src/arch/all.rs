@@ -0,0 +1,15 @@
+/*!
+Implementations of search algorithms that work on all targets.
+
+The routines in this module never use vector instructions, CPU feature
+detection or prefilters. Each one runs a single algorithm, so its performance
+characteristics are those of the algorithm and don't depend on heuristics
+about the input. This makes them useful when the routines at the top-level of
+this crate make the wrong trade off, e.g., when the input may be adversarial
+or when the haystacks are always tiny.
+*/
+
+pub mod compare;
+pub mod packedpair;
+pub mod rabinkarp;
+pub mod twoway;
@@ -0,0 +1,156 @@
+/*!
+Routines for comparing two slices of bytes.
+
+These compare one word (i.e., `usize`) at a time instead of one byte at a
+time, which is much faster when the slices share a long prefix or suffix.
+This is useful for verifying candidate matches, for diffing, or for building
+tries of byte strings.
+
+# Example
+
+```
+use memchr::arch::all::compare::{common_prefix_len, common_suffix_len};
+
+assert_eq!(6, common_prefix_len(b"foo bar", b"foo baz"));
+assert_eq!(3, common_suffix_len(b"foo.rs", b"bar.rs"));
+```
+*/
+
+use core::{cmp, convert::TryInto, mem::size_of};
+
+/// The number of bytes compared at a time.
+const WORD: usize = size_of::<usize>();
+
+/// Returns the length of the longest common prefix of the given slices.
+///
+/// That is, this returns the largest `n` such that `a[..n] == b[..n]`. This is
+/// never greater than the length of the shorter slice.
+///
+/// # Example
+///
+/// ```
+/// use memchr::arch::all::compare::common_prefix_len;
+///
+/// assert_eq!(3, common_prefix_len(b"abc", b"abcdef"));
+/// assert_eq!(0, common_prefix_len(b"abc", b"xbc"));
+/// assert_eq!(0, common_prefix_len(b"", b"abc"));
+/// ```
+#[inline]
+pub fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    let len = cmp::min(a.len(), b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let mut i = 0;
+    while i + WORD <= len {
+        let diff = load(&a[i..i + WORD]) ^ load(&b[i..i + WORD]);
+        if diff != 0 {
+            // Since words are loaded in little endian order, the first byte
+            // in memory is the least significant byte.
+            return i + (diff.trailing_zeros() / 8) as usize;
+        }
+        i += WORD;
+    }
+    while i < len && a[i] == b[i] {
+        i += 1;
+    }
+    i
+}
+
+/// Returns the length of the longest common suffix of the given slices.
+///
+/// That is, this returns the largest `n` such that
+/// `a[a.len() - n..] == b[b.len() - n..]`. This is never greater than the
+/// length of the shorter slice.
+///
+/// # Example
+///
+/// ```
+/// use memchr::arch::all::compare::common_suffix_len;
+///
+/// assert_eq!(3, common_suffix_len(b"def", b"abcdef"));
+/// assert_eq!(0, common_suffix_len(b"abc", b"abx"));
+/// assert_eq!(0, common_suffix_len(b"abc", b""));
+/// ```
+#[inline]
+pub fn common_suffix_len(a: &[u8], b: &[u8]) -> usize {
+    let len = cmp::min(a.len(), b.len());
+    let (a, b) = (&a[a.len() - len..], &b[b.len() - len..]);
+    let mut n = 0;
+    while n + WORD <= len {
+        let (start, end) = (len - n - WORD, len - n);
+        let diff = load(&a[start..end]) ^ load(&b[start..end]);
+        if diff != 0 {
+            // The last byte in memory is the most significant byte.
+            return n + (diff.leading_zeros() / 8) as usize;
+        }
+        n += WORD;
+    }
+    while n < len && a[len - n - 1] == b[len - n - 1] {
+        n += 1;
+    }
+    n
+}
+
+/// Reads a word from the given slice, which must have length `WORD`, in
+/// little endian order.
+#[inline(always)]
+fn load(bytes: &[u8]) -> usize {
+    usize::from_le_bytes(bytes.try_into().unwrap())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{common_prefix_len, common_suffix_len};
+
+    fn naive_prefix_len(a: &[u8], b: &[u8]) -> usize {
+        a.iter().zip(b).take_while(|(x, y)| x == y).count()
+    }
+
+    fn naive_suffix_len(a: &[u8], b: &[u8]) -> usize {
+        a.iter().rev().zip(b.iter().rev()).take_while(|(x, y)| x == y).count()
+    }
+
+    #[test]
+    fn every_mismatch_position() {
+        let a = [b'a'; 40];
+        for len in 0..a.len() {
+            assert_eq!(len, common_prefix_len(&a[..len], &a));
+            assert_eq!(len, common_suffix_len(&a, &a[..len]));
+            for at in 0..len {
+                let mut b = a;
+                b[at] = b'b';
+                assert_eq!(at, common_prefix_len(&a[..len], &b[..len]));
+                assert_eq!(
+                    len - at - 1,
+                    common_suffix_len(&a[..len], &b[..len])
+                );
+            }
+        }
+    }
+
+    quickcheck::quickcheck! {
+        fn qc_prefix_len_matches_naive(a: Vec<u8>, b: Vec<u8>) -> bool {
+            common_prefix_len(&a, &b) == naive_prefix_len(&a, &b)
+        }
+
+        fn qc_suffix_len_matches_naive(a: Vec<u8>, b: Vec<u8>) -> bool {
+            common_suffix_len(&a, &b) == naive_suffix_len(&a, &b)
+        }
+
+        fn qc_shared_prefix_and_suffix(
+            shared: Vec<u8>,
+            a: Vec<u8>,
+            b: Vec<u8>
+        ) -> bool {
+            // Random slices rarely share more than a few bytes, so glue a
+            // shared part onto each side.
+            let (mut pa, mut pb) = (shared.clone(), shared.clone());
+            pa.extend_from_slice(&a);
+            pb.extend_from_slice(&b);
+            let (mut sa, mut sb) = (a.clone(), b.clone());
+            sa.extend_from_slice(&shared);
+            sb.extend_from_slice(&shared);
+            common_prefix_len(&pa, &pb) == naive_prefix_len(&pa, &pb)
+                && common_suffix_len(&sa, &sb) == naive_suffix_len(&sa, &sb)
+        }
+    }
+}
@@ -0,0 +1,317 @@
+/*!
+A candidate finder that looks for a pair of bytes at fixed offsets.
+
+A "packed pair" is two bytes from a needle, along with their offsets in the
+needle. A position `i` in a haystack is a *candidate* if the haystack contains
+both bytes at those offsets relative to `i`. This is the idea behind the
+prefilter used by [`memmem`](crate::memmem): picking two bytes that are
+believed to be rare makes candidates rare, and looking for a pair of bytes
+can be done very quickly.
+
+The [`Finder`] in this module is a portable implementation, built on a
+non-vectorized [`memchr`](crate::memchr()). Vectorized implementations with the
+same API are provided for some targets, e.g., `arch::x86_64::avx2::packedpair`.
+Every implementation reports the same candidates.
+
+# Candidates are not matches
+
+A candidate is only a position at which a match *may* start. A finder never
+misses a match (every match is a candidate), but it may report candidates
+that are not matches. Callers must therefore verify each candidate, e.g., by
+comparing the needle with the haystack at that position. This is what makes
+these finders useful as building blocks for other searchers.
+
+# Example
+
+```
+use memchr::arch::all::packedpair::Finder;
+
+let needle = b"zab";
+let haystack = b"xyzabq zaq zab";
+let finder = Finder::new(needle).unwrap();
+
+let mut at = 0;
+let mut matches = vec![];
+while let Some(i) = finder.find_prefilter(&haystack[at..]) {
+    let candidate = at + i;
+    if haystack[candidate..].starts_with(needle) {
+        matches.push(candidate);
+    }
+    at = candidate + 1;
+}
+assert_eq!(matches, vec![2, 11]);
+```
+*/
+
+use crate::memmem::{rarebytes::RareNeedleBytes, HeuristicFrequencyRank};
+
+/// A pair of distinct offsets into a needle.
+///
+/// The offsets are at most `255`, so only the first 256 bytes of a needle can
+/// be used in a pair.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Pair {
+    index1: u8,
+    index2: u8,
+}
+
+impl Pair {
+    /// Create a pair of offsets for the given needle, using the heuristic
+    /// that [`memmem`](crate::memmem) uses by default to pick the two bytes
+    /// believed to be rarest.
+    ///
+    /// This returns `None` if the needle is shorter than two bytes. For
+    /// needles longer than 255 bytes, only the first 255 bytes are
+    /// considered.
+    #[inline]
+    pub fn new(needle: &[u8]) -> Option<Pair> {
+        Pair::with_ranker(needle, crate::memmem::DefaultHFR)
+    }
+
+    /// Like [`Pair::new`], but picks the rarest bytes according to the given
+    /// frequency heuristic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::{arch::all::packedpair::Pair, memmem::freq};
+    ///
+    /// let pair = Pair::with_ranker(b"\x00\x01\x02", freq::Uniform).unwrap();
+    /// assert_eq!((0, 1), (pair.index1(), pair.index2()));
+    /// ```
+    #[inline]
+    pub fn with_ranker<R: HeuristicFrequencyRank>(
+        needle: &[u8],
+        ranker: R,
+    ) -> Option<Pair> {
+        if needle.len() < 2 {
+            return None;
+        }
+        let needle = &needle[..core::cmp::min(needle.len(), 255)];
+        let (index1, index2) =
+            RareNeedleBytes::forward(needle, &ranker).as_rare_usize();
+        Some(Pair { index1: index1 as u8, index2: index2 as u8 })
+    }
+
+    /// Create a pair from the given offsets.
+    ///
+    /// This returns `None` if the offsets are equal or if either one is out
+    /// of bounds for the given needle.
+    #[inline]
+    pub fn with_indices(
+        needle: &[u8],
+        index1: u8,
+        index2: u8,
+    ) -> Option<Pair> {
+        if index1 == index2
+            || usize::from(index1) >= needle.len()
+            || usize::from(index2) >= needle.len()
+        {
+            return None;
+        }
+        Some(Pair { index1, index2 })
+    }
+
+    /// Returns the first offset of this pair. When the pair was picked by a
+    /// heuristic, this is the offset of the rarest byte.
+    #[inline]
+    pub fn index1(&self) -> u8 {
+        self.index1
+    }
+
+    /// Returns the second offset of this pair.
+    #[inline]
+    pub fn index2(&self) -> u8 {
+        self.index2
+    }
+
+    /// Returns the offsets of this pair such that the first is always less
+    /// than the second.
+    #[inline]
+    pub(crate) fn ordered(&self) -> (usize, usize) {
+        let (i1, i2) = (usize::from(self.index1), usize::from(self.index2));
+        if i1 < i2 {
+            (i1, i2)
+        } else {
+            (i2, i1)
+        }
+    }
+}
+
+/// A portable packed pair candidate finder.
+///
+/// This looks for the byte at the pair's first offset with
+/// [`memchr`](crate::memchr()), and then checks the byte at the second offset.
+#[derive(Clone, Copy, Debug)]
+pub struct Finder {
+    pair: Pair,
+    byte1: u8,
+    byte2: u8,
+}
+
+impl Finder {
+    /// Create a new finder for the given needle, using [`Pair::new`] to pick
+    /// the pair of offsets.
+    ///
+    /// This returns `None` if the needle is shorter than two bytes.
+    #[inline]
+    pub fn new(needle: &[u8]) -> Option<Finder> {
+        Finder::with_pair(needle, Pair::new(needle)?)
+    }
+
+    /// Create a new finder for the given needle and pair of offsets.
+    ///
+    /// This returns `None` if either offset is out of bounds for the needle.
+    #[inline]
+    pub fn with_pair(needle: &[u8], pair: Pair) -> Option<Finder> {
+        let byte1 = *needle.get(usize::from(pair.index1))?;
+        let byte2 = *needle.get(usize::from(pair.index2))?;
+        Some(Finder { pair, byte1, byte2 })
+    }
+
+    /// Returns the first candidate position in the given haystack.
+    ///
+    /// A position `i` is a candidate when `haystack[i + index1]` and
+    /// `haystack[i + index2]` are equal to the needle's bytes at the pair's
+    /// offsets. A candidate is not necessarily a match, so callers must
+    /// verify it. But every match is a candidate, so if this returns `None`,
+    /// then the needle does not occur in the haystack.
+    ///
+    /// Note that a candidate may be reported even if the needle doesn't fit
+    /// in the haystack at that position.
+    #[inline]
+    pub fn find_prefilter(&self, haystack: &[u8]) -> Option<usize> {
+        let index1 = usize::from(self.pair.index1);
+        let index2 = usize::from(self.pair.index2);
+        let mut at = index1;
+        while at < haystack.len() {
+            let found =
+                crate::memchr::fallback::memchr(self.byte1, &haystack[at..])?;
+            let candidate = at + found - index1;
+            if haystack.get(candidate + index2) == Some(&self.byte2) {
+                return Some(candidate);
+            }
+            at += found + 1;
+        }
+        None
+    }
+
+    /// Returns the pair of offsets used by this finder.
+    #[inline]
+    pub fn pair(&self) -> &Pair {
+        &self.pair
+    }
+
+    /// Returns the needle bytes at the offsets returned by `Pair::ordered`,
+    /// in the same order.
+    #[inline]
+    pub(crate) fn ordered_bytes(&self) -> (u8, u8) {
+        if self.pair.index1 < self.pair.index2 {
+            (self.byte1, self.byte2)
+        } else {
+            (self.byte2, self.byte1)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+pub(crate) mod tests {
+    use super::{Finder, Pair};
+
+    /// Collect every candidate reported by the given prefilter function.
+    pub(crate) fn candidates(
+        haystack: &[u8],
+        find: impl Fn(&[u8]) -> Option<usize>,
+    ) -> Vec<usize> {
+        let mut at = 0;
+        let mut got = vec![];
+        while let Some(i) = find(&haystack[at..]) {
+            got.push(at + i);
+            at += i + 1;
+        }
+        got
+    }
+
+    /// Returns every position at which the given pair's bytes occur, which is
+    /// the candidate set that every packed pair finder must report.
+    pub(crate) fn naive_candidates(
+        haystack: &[u8],
+        needle: &[u8],
+        pair: Pair,
+    ) -> Vec<usize> {
+        let (i1, i2) =
+            (usize::from(pair.index1()), usize::from(pair.index2()));
+        (0..haystack.len())
+            .filter(|&i| {
+                haystack.get(i + i1) == Some(&needle[i1])
+                    && haystack.get(i + i2) == Some(&needle[i2])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pair_offsets() {
+        assert_eq!(None, Pair::new(b""));
+        assert_eq!(None, Pair::new(b"a"));
+        assert!(Pair::new(b"ab").is_some());
+        assert!(Pair::new(&[b'a'; 1000]).is_some());
+        assert_eq!(None, Pair::with_indices(b"abc", 1, 1));
+        assert_eq!(None, Pair::with_indices(b"abc", 0, 3));
+        let pair = Pair::with_indices(b"abc", 2, 0).unwrap();
+        assert_eq!((2, 0), (pair.index1(), pair.index2()));
+        assert!(Finder::with_pair(b"ab", pair).is_none());
+    }
+
+    #[test]
+    fn candidates_are_not_confirmed() {
+        let finder = Finder::with_pair(
+            b"abcd",
+            Pair::with_indices(b"abcd", 0, 3).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(0), finder.find_prefilter(b"axxd"));
+        assert_eq!(Some(2), finder.find_prefilter(b"xxabcd"));
+        assert_eq!(None, finder.find_prefilter(b"abc"));
+        assert_eq!(
+            vec![0, 4],
+            candidates(b"axxdabcd", |h| finder.find_prefilter(h))
+        );
+    }
+
+    quickcheck::quickcheck! {
+        fn qc_candidates_superset_of_matches(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            let finder = match Finder::new(&needle) {
+                None => return true,
+                Some(finder) => finder,
+            };
+            let got = candidates(&haystack, |h| finder.find_prefilter(h));
+            let every_match_is_candidate =
+                crate::memmem::find_iter(&haystack, &needle)
+                    .all(|m| got.contains(&m));
+            every_match_is_candidate
+                && got == naive_candidates(&haystack, &needle, *finder.pair())
+        }
+
+        fn qc_candidates_superset_of_matches_in_haystack(
+            haystack: Vec<u8>,
+            start: usize,
+            len: usize
+        ) -> bool {
+            // Random needles rarely occur in random haystacks, so also try
+            // needles taken from the haystack.
+            if haystack.len() < 2 {
+                return true;
+            }
+            let start = start % (haystack.len() - 1);
+            let len = 2 + len % (haystack.len() - start - 1);
+            let needle = &haystack[start..start + len];
+            let finder = Finder::new(needle).unwrap();
+            let got = candidates(&haystack, |h| finder.find_prefilter(h));
+            got.contains(&start)
+                && got == naive_candidates(&haystack, needle, *finder.pair())
+        }
+    }
+}
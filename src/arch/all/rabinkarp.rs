@@ -0,0 +1,192 @@
+/*!
+A standalone implementation of the Rabin-Karp substring search algorithm.
+
+Rabin-Karp compares a rolling hash of the haystack with a hash of the needle,
+and only compares bytes when the hashes are equal. Building a searcher only
+requires hashing the needle, which is much cheaper than the setup done by
+[`memmem::Finder`](crate::memmem::Finder). This makes these searchers a good
+choice when searching very short haystacks, where that setup can dominate the
+time spent searching. `memmem` uses Rabin-Karp internally for such haystacks,
+but still pays for its setup when the searcher is built.
+
+The needle hash is computed once when a searcher is built, and is reused by
+every search executed with it.
+
+# Complexity
+
+Unlike the other substring searchers in this crate, Rabin-Karp's worst case
+time complexity is `O(n * m)`, where `n` is the length of the needle and `m`
+is the length of the haystack. This worst case only occurs when many windows
+of the haystack have the same hash as the needle, but these searchers should
+still not be used on long haystacks that may be adversarial. Prefer
+[`arch::all::twoway`](crate::arch::all::twoway) in that case.
+
+# Example
+
+```
+use memchr::arch::all::rabinkarp::{Finder, FinderRev};
+
+let finder = Finder::new("foo");
+assert_eq!(Some(4), finder.find(b"bar foo"));
+assert_eq!(Some(0), finder.find(b"foo bar"));
+assert_eq!(None, finder.find(b"quux"));
+
+let finder = FinderRev::new("foo");
+assert_eq!(Some(8), finder.rfind(b"foo bar foo"));
+```
+*/
+
+use crate::{
+    cow::CowBytes,
+    memmem::rabinkarp::{self, NeedleHash},
+};
+
+/// A forward substring searcher using the Rabin-Karp algorithm.
+///
+/// `'n` is the lifetime of the needle.
+#[derive(Clone, Debug)]
+pub struct Finder<'n> {
+    needle: CowBytes<'n>,
+    nhash: NeedleHash,
+}
+
+impl<'n> Finder<'n> {
+    /// Create a new forward Rabin-Karp searcher for the given needle.
+    ///
+    /// This runs in `O(n)` time, where `n` is the length of the needle.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &'n B) -> Finder<'n> {
+        let needle = needle.as_ref();
+        Finder {
+            needle: CowBytes::new(needle),
+            nhash: NeedleHash::forward(needle),
+        }
+    }
+
+    /// Returns the index of the first occurrence of this needle in the given
+    /// haystack.
+    ///
+    /// An empty needle matches at offset `0`, even in an empty haystack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::arch::all::rabinkarp::Finder;
+    ///
+    /// assert_eq!(Some(4), Finder::new("bar").find(b"foo bar"));
+    /// assert_eq!(None, Finder::new("quux").find(b"foo bar"));
+    /// ```
+    #[inline]
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        rabinkarp::find_with(&self.nhash, haystack, &self.needle)
+    }
+
+    /// Returns the needle that this finder searches for.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Convert this finder into its owned variant, such that it no longer
+    /// borrows the needle.
+    ///
+    /// If this is already an owned finder, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> Finder<'static> {
+        Finder { needle: self.needle.into_owned(), nhash: self.nhash }
+    }
+}
+
+/// A reverse substring searcher using the Rabin-Karp algorithm.
+///
+/// `'n` is the lifetime of the needle.
+#[derive(Clone, Debug)]
+pub struct FinderRev<'n> {
+    needle: CowBytes<'n>,
+    nhash: NeedleHash,
+}
+
+impl<'n> FinderRev<'n> {
+    /// Create a new reverse Rabin-Karp searcher for the given needle.
+    ///
+    /// This runs in `O(n)` time, where `n` is the length of the needle.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &'n B) -> FinderRev<'n> {
+        let needle = needle.as_ref();
+        FinderRev {
+            needle: CowBytes::new(needle),
+            nhash: NeedleHash::reverse(needle),
+        }
+    }
+
+    /// Returns the index of the last occurrence of this needle in the given
+    /// haystack.
+    ///
+    /// An empty needle matches at offset `haystack.len()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::arch::all::rabinkarp::FinderRev;
+    ///
+    /// assert_eq!(Some(8), FinderRev::new("foo").rfind(b"foo bar foo"));
+    /// assert_eq!(None, FinderRev::new("quux").rfind(b"foo bar foo"));
+    /// ```
+    #[inline]
+    pub fn rfind(&self, haystack: &[u8]) -> Option<usize> {
+        rabinkarp::rfind_with(&self.nhash, haystack, &self.needle)
+    }
+
+    /// Returns the needle that this finder searches for.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Convert this finder into its owned variant, such that it no longer
+    /// borrows the needle.
+    ///
+    /// If this is already an owned finder, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> FinderRev<'static> {
+        FinderRev { needle: self.needle.into_owned(), nhash: self.nhash }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Finder, FinderRev};
+
+    #[test]
+    fn simple() {
+        use crate::memmem::testsimples;
+
+        testsimples::run_search_tests_fwd(|h, n| Finder::new(n).find(h));
+        testsimples::run_search_tests_rev(|h, n| FinderRev::new(n).rfind(h));
+    }
+
+    #[test]
+    fn reused_across_haystacks() {
+        let fwd = Finder::new("ab");
+        let rev = FinderRev::new("ab");
+        for (haystack, first, last) in [
+            (&b"ab"[..], Some(0), Some(0)),
+            (b"xabab", Some(1), Some(3)),
+            (b"a", None, None),
+            (b"", None, None),
+            (b"ba", None, None),
+        ] {
+            assert_eq!(first, fwd.find(haystack));
+            assert_eq!(last, rev.rfind(haystack));
+            assert_eq!(crate::memmem::find(haystack, b"ab"), first);
+        }
+    }
+}
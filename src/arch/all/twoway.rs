@@ -0,0 +1,244 @@
+/*!
+A standalone implementation of the Two-Way substring search algorithm.
+
+The searchers in this module run the Two-Way algorithm by Crochemore and
+Perrin without any prefilter. The [`memmem`](crate::memmem) searchers use the
+same algorithm, but first try to find candidate matches by looking for bytes
+that are believed to be rare. That is usually much faster, and while it is
+disabled when found to be ineffective, it still makes the time taken depend
+on the contents of the haystack in ways that are hard to predict.
+
+By contrast, a search with these types always runs in `O(n + m)` time and
+`O(1)` space, where `n` is the length of the needle and `m` is the length of
+the haystack. The time taken depends only on those lengths and on the
+comparisons made by Two-Way itself, so there is no data dependent slowdown
+beyond Two-Way's own bounds. This is useful when searching input that may have
+been crafted to defeat heuristics.
+
+# Example
+
+```
+use memchr::arch::all::twoway::{Finder, FinderRev};
+
+let haystack = b"qazqazqbzqaz";
+assert_eq!(Some(6), Finder::new("qbz").find(haystack));
+assert_eq!(Some(9), FinderRev::new("qaz").rfind(haystack));
+```
+*/
+
+use crate::{cow::CowBytes, memmem::twoway};
+
+/// A forward substring searcher using the Two-Way algorithm, without any
+/// prefilter.
+///
+/// `'n` is the lifetime of the needle.
+#[derive(Clone, Debug)]
+pub struct Finder<'n> {
+    needle: CowBytes<'n>,
+    tw: twoway::Forward,
+}
+
+impl<'n> Finder<'n> {
+    /// Create a new forward Two-Way searcher for the given needle.
+    ///
+    /// This runs in `O(n)` time, where `n` is the length of the needle.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &'n B) -> Finder<'n> {
+        let needle = needle.as_ref();
+        Finder {
+            needle: CowBytes::new(needle),
+            tw: twoway::Forward::new(needle),
+        }
+    }
+
+    /// Returns the index of the first occurrence of this needle in the given
+    /// haystack.
+    ///
+    /// An empty needle matches at offset `0`, even in an empty haystack.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack, and worst case
+    /// constant space complexity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::arch::all::twoway::Finder;
+    ///
+    /// assert_eq!(Some(4), Finder::new("bar").find(b"foo bar"));
+    /// assert_eq!(None, Finder::new("quux").find(b"foo bar"));
+    /// ```
+    #[inline]
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.tw.find_general(None, haystack, &self.needle)
+    }
+
+    /// Returns the needle that this finder searches for.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Convert this finder into its owned variant, such that it no longer
+    /// borrows the needle.
+    ///
+    /// If this is already an owned finder, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> Finder<'static> {
+        Finder { needle: self.needle.into_owned(), tw: self.tw }
+    }
+}
+
+/// A reverse substring searcher using the Two-Way algorithm, without any
+/// prefilter.
+///
+/// `'n` is the lifetime of the needle.
+#[derive(Clone, Debug)]
+pub struct FinderRev<'n> {
+    needle: CowBytes<'n>,
+    tw: twoway::Reverse,
+}
+
+impl<'n> FinderRev<'n> {
+    /// Create a new reverse Two-Way searcher for the given needle.
+    ///
+    /// This runs in `O(n)` time, where `n` is the length of the needle.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &'n B) -> FinderRev<'n> {
+        let needle = needle.as_ref();
+        FinderRev {
+            needle: CowBytes::new(needle),
+            tw: twoway::Reverse::new(needle),
+        }
+    }
+
+    /// Returns the index of the last occurrence of this needle in the given
+    /// haystack.
+    ///
+    /// An empty needle matches at offset `haystack.len()`.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack, and worst case
+    /// constant space complexity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::arch::all::twoway::FinderRev;
+    ///
+    /// assert_eq!(Some(8), FinderRev::new("foo").rfind(b"foo bar foo"));
+    /// assert_eq!(None, FinderRev::new("quux").rfind(b"foo bar foo"));
+    /// ```
+    #[inline]
+    pub fn rfind(&self, haystack: &[u8]) -> Option<usize> {
+        self.tw.rfind_general(None, haystack, &self.needle)
+    }
+
+    /// Returns the needle that this finder searches for.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Convert this finder into its owned variant, such that it no longer
+    /// borrows the needle.
+    ///
+    /// If this is already an owned finder, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> FinderRev<'static> {
+        FinderRev { needle: self.needle.into_owned(), tw: self.tw }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{Finder, FinderRev};
+
+    fn naive_find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        (0..=haystack.len().checked_sub(needle.len())?)
+            .find(|&i| haystack[i..].starts_with(needle))
+    }
+
+    fn naive_rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        (0..=haystack.len().checked_sub(needle.len())?)
+            .rev()
+            .find(|&i| haystack[i..].starts_with(needle))
+    }
+
+    #[test]
+    fn simple() {
+        use crate::memmem::testsimples;
+
+        testsimples::run_search_tests_fwd(|h, n| Finder::new(n).find(h));
+        testsimples::run_search_tests_rev(|h, n| FinderRev::new(n).rfind(h));
+    }
+
+    /// Smaller versions of the corpora in `bench/data/pathological`, which
+    /// are crafted to defeat the prefilters used by `memmem`. Each is paired
+    /// with the needles that the benchmarks search for.
+    fn pathological() -> Vec<(Vec<u8>, Vec<&'static [u8]>)> {
+        let mut repeated_rare = b"z".repeat(1_000);
+        repeated_rare.push(b'\n');
+        let mut defeat_simple_vector = b"qaz".repeat(10_000);
+        defeat_simple_vector.extend_from_slice(b"qbz");
+        defeat_simple_vector.extend_from_slice(&b"qaz".repeat(100));
+        let mut defeat_freq = b"qjaz".repeat(10_000);
+        defeat_freq.extend_from_slice(b"qj");
+        defeat_freq.extend_from_slice(&b"a".repeat(49));
+        defeat_freq.push(b'z');
+        let mut defeat_repeated = b"z".repeat(30_000);
+        let n = defeat_repeated.len();
+        defeat_repeated[n - 2] = b'a';
+        vec![
+            (repeated_rare, vec![b"abczdef", b"zzzzzzzzzz"]),
+            (defeat_simple_vector, vec![b"qbz", b"qaz"]),
+            (
+                defeat_freq,
+                vec![b"qjaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaz"],
+            ),
+            (defeat_repeated, vec![&b"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzaz"[..]]),
+        ]
+    }
+
+    #[test]
+    fn pathological_corpora() {
+        for (haystack, needles) in pathological() {
+            for needle in needles {
+                let fwd = Finder::new(needle);
+                let rev = FinderRev::new(needle);
+                assert_eq!(naive_find(&haystack, needle), fwd.find(&haystack));
+                assert_eq!(
+                    naive_rfind(&haystack, needle),
+                    rev.rfind(&haystack)
+                );
+                assert_eq!(
+                    crate::memmem::find(&haystack, needle),
+                    fwd.find(&haystack)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn owned() {
+        let needle = String::from("bar");
+        let fwd = Finder::new(&needle).into_owned();
+        let rev = FinderRev::new(&needle).into_owned();
+        drop(needle);
+        assert_eq!(b"bar", fwd.needle());
+        assert_eq!(Some(4), fwd.find(b"foo bar bar"));
+        assert_eq!(Some(8), rev.rfind(b"foo bar bar"));
+    }
+}
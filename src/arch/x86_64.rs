@@ -0,0 +1,11 @@
+/*!
+Vectorized implementations for `x86_64`.
+
+These are only available on `x86_64` targets. Implementations that require CPU
+features beyond SSE2 check for them at runtime when they are constructed,
+which requires the `std` feature.
+*/
+
+#[cfg(feature = "std")]
+pub mod avx2;
+pub mod sse2;
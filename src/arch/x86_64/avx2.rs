@@ -0,0 +1,8 @@
+/*!
+Implementations that use AVX2 vectors.
+
+These are only available when the CPU supports AVX2, which is detected at
+runtime. This module requires the `std` feature.
+*/
+
+pub mod packedpair;
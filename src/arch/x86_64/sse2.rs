@@ -0,0 +1,6 @@
+/*!
+Implementations that use SSE2 vectors, which are always available on
+`x86_64`.
+*/
+
+pub mod packedpair;
@@ -0,0 +1,117 @@
+/*!
+An SSE2 accelerated packed pair candidate finder.
+
+See [`arch::all::packedpair`](crate::arch::all::packedpair) for a description
+of packed pairs and of what a candidate is.
+*/
+
+use core::{arch::x86_64::__m128i, mem::size_of};
+
+use crate::{
+    arch::all::packedpair::{self, Pair},
+    memmem::prefilter::genericsimd,
+};
+
+/// A packed pair candidate finder that uses SSE2 vectors.
+///
+/// Haystacks that are too short for a vector are searched with the portable
+/// [`arch::all::packedpair::Finder`](crate::arch::all::packedpair::Finder).
+/// The candidates reported are the same either way.
+#[derive(Clone, Copy, Debug)]
+pub struct Finder(packedpair::Finder);
+
+impl Finder {
+    /// Create a new finder for the given needle, using [`Pair::new`] to pick
+    /// the pair of offsets.
+    ///
+    /// This returns `None` if the needle is shorter than two bytes.
+    #[inline]
+    pub fn new(needle: &[u8]) -> Option<Finder> {
+        Finder::with_pair(needle, Pair::new(needle)?)
+    }
+
+    /// Create a new finder for the given needle and pair of offsets.
+    ///
+    /// This returns `None` if either offset is out of bounds for the needle.
+    #[inline]
+    pub fn with_pair(needle: &[u8], pair: Pair) -> Option<Finder> {
+        packedpair::Finder::with_pair(needle, pair).map(Finder)
+    }
+
+    /// Returns true if this finder can be used on the current CPU. SSE2 is
+    /// always available on `x86_64`, so this always returns true.
+    #[inline]
+    pub fn is_available() -> bool {
+        true
+    }
+
+    /// Returns the first candidate position in the given haystack.
+    ///
+    /// This reports the same candidates as
+    /// [`arch::all::packedpair::Finder::find_prefilter`](crate::arch::all::packedpair::Finder::find_prefilter).
+    /// In particular, candidates must be verified by the caller.
+    #[inline]
+    pub fn find_prefilter(&self, haystack: &[u8]) -> Option<usize> {
+        // SAFETY: SSE2 is always available on x86_64.
+        unsafe { find_prefilter_imp(&self.0, haystack) }
+    }
+
+    /// Returns the pair of offsets used by this finder.
+    #[inline]
+    pub fn pair(&self) -> &Pair {
+        self.0.pair()
+    }
+}
+
+/// Search for a candidate with SSE2 vectors, falling back to the portable
+/// finder when the haystack is too short.
+///
+/// # Safety
+///
+/// Callers must ensure that the sse2 CPU feature is enabled in the current
+/// environment.
+#[target_feature(enable = "sse2")]
+pub(crate) unsafe fn find_prefilter_imp(
+    finder: &packedpair::Finder,
+    haystack: &[u8],
+) -> Option<usize> {
+    let (index1, index2) = finder.pair().ordered();
+    if haystack.len() < index2 + size_of::<__m128i>() {
+        return finder.find_prefilter(haystack);
+    }
+    let (byte1, byte2) = finder.ordered_bytes();
+    genericsimd::find_pair::<__m128i>(index1, index2, byte1, byte2, haystack)
+}
+
+#[cfg(all(test, feature = "std", not(miri)))]
+mod tests {
+    use crate::arch::all::packedpair::tests::{candidates, naive_candidates};
+
+    use super::Finder;
+
+    quickcheck::quickcheck! {
+        fn qc_same_candidates_as_portable(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            let finder = match Finder::new(&needle) {
+                None => return true,
+                Some(finder) => finder,
+            };
+            let got = candidates(&haystack, |h| finder.find_prefilter(h));
+            got == naive_candidates(&haystack, &needle, *finder.pair())
+        }
+    }
+
+    #[test]
+    fn long_haystacks() {
+        let needle = b"qbz";
+        let finder = Finder::new(needle).unwrap();
+        let mut haystack = b"qaz".repeat(100);
+        haystack.extend_from_slice(needle);
+        haystack.extend_from_slice(&b"qaz".repeat(7));
+        let got = candidates(&haystack, |h| finder.find_prefilter(h));
+        assert_eq!(naive_candidates(&haystack, needle, *finder.pair()), got);
+        assert!(got.contains(&300));
+    }
+}
@@ -0,0 +1,216 @@
+/// An extension trait that provides the searches in this crate as methods on
+/// byte slices.
+///
+/// Every method delegates to the free function of the same meaning, e.g.,
+/// `haystack.find_byte(b'x')` is equivalent to `memchr(b'x', haystack)`. The
+/// method names follow those used by the `bstr` crate's `ByteSlice` trait,
+/// which makes migrating between the two straightforward. Note though that
+/// importing both traits at the same time makes calls to methods with the
+/// same name ambiguous.
+///
+/// This trait is sealed, so it cannot be implemented outside of this crate.
+/// This permits adding new methods in the future.
+///
+/// # Example
+///
+/// ```
+/// use memchr::ByteSearch;
+///
+/// let haystack = &b"foo bar baz"[..];
+/// assert_eq!(Some(4), haystack.find_byte(b'b'));
+/// assert_eq!(Some(8), haystack.rfind_byte(b'b'));
+/// assert_eq!(Some(4), haystack.find("bar"));
+/// ```
+pub trait ByteSearch: private::Sealed {
+    /// Returns the index of the first occurrence of the given byte.
+    ///
+    /// This is equivalent to [`memchr`](crate::memchr()).
+    fn find_byte(&self, byte: u8) -> Option<usize>;
+
+    /// Returns the index of the last occurrence of the given byte.
+    ///
+    /// This is equivalent to [`memrchr`](crate::memrchr()).
+    fn rfind_byte(&self, byte: u8) -> Option<usize>;
+
+    /// Returns the index of the first occurrence of either of the given
+    /// bytes.
+    ///
+    /// This is equivalent to [`memchr2`](crate::memchr2()).
+    fn find_byte2(&self, byte1: u8, byte2: u8) -> Option<usize>;
+
+    /// Returns the index of the last occurrence of either of the given
+    /// bytes.
+    ///
+    /// This is equivalent to [`memrchr2`](crate::memrchr2()).
+    fn rfind_byte2(&self, byte1: u8, byte2: u8) -> Option<usize>;
+
+    /// Returns the index of the first occurrence of any of the given bytes.
+    ///
+    /// This is equivalent to [`memchr3`](crate::memchr3()).
+    fn find_byte3(&self, byte1: u8, byte2: u8, byte3: u8) -> Option<usize>;
+
+    /// Returns the index of the last occurrence of any of the given bytes.
+    ///
+    /// This is equivalent to [`memrchr3`](crate::memrchr3()).
+    fn rfind_byte3(&self, byte1: u8, byte2: u8, byte3: u8) -> Option<usize>;
+
+    /// Returns the index of the first occurrence of the given needle.
+    ///
+    /// This is equivalent to [`memmem::find`](crate::memmem::find).
+    fn find<B: ?Sized + AsRef<[u8]>>(&self, needle: &B) -> Option<usize>;
+
+    /// Returns the index of the last occurrence of the given needle.
+    ///
+    /// This is equivalent to [`memmem::rfind`](crate::memmem::rfind).
+    fn rfind<B: ?Sized + AsRef<[u8]>>(&self, needle: &B) -> Option<usize>;
+}
+
+impl ByteSearch for [u8] {
+    #[inline]
+    fn find_byte(&self, byte: u8) -> Option<usize> {
+        crate::memchr(byte, self)
+    }
+
+    #[inline]
+    fn rfind_byte(&self, byte: u8) -> Option<usize> {
+        crate::memrchr(byte, self)
+    }
+
+    #[inline]
+    fn find_byte2(&self, byte1: u8, byte2: u8) -> Option<usize> {
+        crate::memchr2(byte1, byte2, self)
+    }
+
+    #[inline]
+    fn rfind_byte2(&self, byte1: u8, byte2: u8) -> Option<usize> {
+        crate::memrchr2(byte1, byte2, self)
+    }
+
+    #[inline]
+    fn find_byte3(&self, byte1: u8, byte2: u8, byte3: u8) -> Option<usize> {
+        crate::memchr3(byte1, byte2, byte3, self)
+    }
+
+    #[inline]
+    fn rfind_byte3(&self, byte1: u8, byte2: u8, byte3: u8) -> Option<usize> {
+        crate::memrchr3(byte1, byte2, byte3, self)
+    }
+
+    #[inline]
+    fn find<B: ?Sized + AsRef<[u8]>>(&self, needle: &B) -> Option<usize> {
+        crate::memmem::find(self, needle.as_ref())
+    }
+
+    #[inline]
+    fn rfind<B: ?Sized + AsRef<[u8]>>(&self, needle: &B) -> Option<usize> {
+        crate::memmem::rfind(self, needle.as_ref())
+    }
+}
+
+/// A needle that can be searched for in a byte haystack.
+///
+/// This permits writing generic code that searches for either a single byte
+/// or a sequence of bytes. A single `u8` needle is searched for with
+/// [`memchr`](crate::memchr()), while a slice or array needle is searched for
+/// with [`memmem::find`](crate::memmem::find). Both are monomorphized, so
+/// there is no dispatch at runtime.
+///
+/// This is implemented for references to byte arrays of up to 32 bytes.
+/// Longer arrays can be searched for by converting them to slices.
+///
+/// # Example
+///
+/// ```
+/// use memchr::Needle;
+///
+/// fn position<N: Needle>(haystack: &[u8], needle: N) -> Option<usize> {
+///     needle.find_in(haystack)
+/// }
+///
+/// assert_eq!(Some(4), position(b"foo bar", b'b'));
+/// assert_eq!(Some(4), position(b"foo bar", b"bar"));
+/// assert_eq!(Some(4), position(b"foo bar", &b"bar"[..]));
+/// ```
+pub trait Needle {
+    /// Returns the index of the first occurrence of this needle in the
+    /// given haystack.
+    fn find_in(&self, haystack: &[u8]) -> Option<usize>;
+}
+
+impl Needle for u8 {
+    #[inline]
+    fn find_in(&self, haystack: &[u8]) -> Option<usize> {
+        crate::memchr(*self, haystack)
+    }
+}
+
+impl Needle for &[u8] {
+    #[inline]
+    fn find_in(&self, haystack: &[u8]) -> Option<usize> {
+        crate::memmem::find(haystack, self)
+    }
+}
+
+macro_rules! impl_needle_array {
+    ($($len:expr),*) => {
+        $(
+            impl Needle for &[u8; $len] {
+                #[inline]
+                fn find_in(&self, haystack: &[u8]) -> Option<usize> {
+                    crate::memmem::find(haystack, &self[..])
+                }
+            }
+        )*
+    };
+}
+
+// Like the standard library before const generics, this only covers arrays
+// of up to 32 bytes. Longer arrays can be searched for as slices.
+impl_needle_array!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+    21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
+);
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for [u8] {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByteSearch, Needle};
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn methods_match_free_functions() {
+        let haystack = &b"abc xyz abc"[..];
+        assert_eq!(Some(1), haystack.find_byte(b'b'));
+        assert_eq!(Some(9), haystack.rfind_byte(b'b'));
+        assert_eq!(Some(1), haystack.find_byte2(b'z', b'b'));
+        assert_eq!(Some(9), haystack.rfind_byte2(b'z', b'b'));
+        assert_eq!(Some(2), haystack.find_byte3(b'x', b'c', b'q'));
+        assert_eq!(Some(10), haystack.rfind_byte3(b'x', b'c', b'q'));
+        assert_eq!(None, haystack.find_byte(b'q'));
+        assert_eq!(Some(0), haystack.find("abc"));
+        assert_eq!(Some(8), haystack.rfind(b"abc"));
+        assert_eq!(None, haystack.find(&vec![b'q']));
+
+        let owned: Vec<u8> = haystack.to_vec();
+        assert_eq!(Some(4), owned.find_byte(b'x'));
+    }
+
+    #[test]
+    fn needle_byte_matches_array() {
+        let haystack = b"a.b.c.d";
+        for byte in 0..=255u8 {
+            let array = [byte];
+            assert_eq!(byte.find_in(haystack), (&array).find_in(haystack));
+            assert_eq!(byte.find_in(haystack), (&array[..]).find_in(haystack));
+        }
+        assert_eq!(Some(1), b'.'.find_in(haystack));
+        assert_eq!(Some(2), b"b.c".find_in(haystack));
+        assert_eq!(Some(0), b"".find_in(haystack));
+        assert_eq!(None, b'x'.find_in(haystack));
+    }
+}
@@ -9,8 +9,19 @@ This section gives a brief high level overview of what this crate offers.
   in the forward or reverse direction. When searching for more than one byte,
   positions are considered a match if the byte at that position matches any
   of the bytes.
+* Routines like [`memchr_u16`] do the same for slices of 16-bit code units,
+  such as UTF-16 text, and [`find_elem`] searches slices of any
+  [`SimdElement`] type.
+* [`find_ascii_control`] finds the first ASCII control character, which is
+  useful for sanitizing text before it is displayed.
+* Routines like [`trim`] and [`trim_any`] strip leading and trailing runs of
+  bytes, such as padding or whitespace.
 * The [`memmem`] sub-module provides forward and reverse substring search
   routines.
+* The [`arch::all::twoway`] sub-module provides substring searchers without
+  any prefilters, whose worst case is guaranteed to be linear time.
+* The [`ByteSearch`] trait provides the most common of these searches as
+  methods on `[u8]`.
 
 In all such cases, routines operate on `&[u8]` without regard to encoding. This
 is exactly what you want when searching either UTF-8 or arbitrary bytes.
@@ -131,11 +142,13 @@ implementation in the standard library, even if only for searching on UTF-8?
 The reason is that the implementation details for using SIMD in the standard
 library haven't quite been worked out yet.
 
-**NOTE:** Currently, only `x86_64` targets have highly accelerated
-implementations of substring search. For `memchr`, all targets have
-somewhat-accelerated implementations, while only `x86_64` targets have highly
-accelerated implementations. This limitation is expected to be lifted once the
-standard library exposes a platform independent SIMD API.
+**NOTE:** Currently, only `x86_64` targets, and `wasm32` targets compiled with
+the `simd128` target feature, have highly accelerated implementations of
+substring search. For `memchr`, all targets have somewhat-accelerated
+implementations, while only those targets have highly accelerated
+implementations (on `wasm32`, this only applies to `memchr`, `memchr2`,
+`memchr3` and their reverse counterparts). This limitation is expected to be
+lifted once the standard library exposes a platform independent SIMD API.
 
 # Crate features
 
@@ -145,6 +158,25 @@ standard library exposes a platform independent SIMD API.
   that this feature must be enabled to get AVX accelerated routines. When
   `std` is not enabled, this crate will still attempt to use SSE2 accelerated
   routines on `x86_64`.
+* **avx512** - When enabled (**not** the default), `memchr`, `memchr2`,
+  `memchr3` and their reverse counterparts will use AVX-512 accelerated
+  routines on `x86_64` CPUs that support AVX-512BW, as detected at runtime.
+  This implies the `std` feature. It is opt-in because AVX-512 instructions
+  can cause some CPUs to lower their clock speed. It requires Rust 1.89 or
+  newer, and has no effect on older compilers.
+* **force-fallback** - When enabled (**not** the default), every routine in
+  this crate uses its portable implementation, even when a faster one is
+  available for the current target or CPU. This is intended for testing.
+  [`arch::detected`] reports [`arch::Backend::Scalar`] and
+  [`arch::has_simd_backend`] returns `false` when it is enabled.
+* **tokio** - When enabled (**not** the default), this adds
+  `memmem::find_async_reader`, which searches the bytes read from a
+  `tokio::io::AsyncRead`. It is only available when the `std` feature is
+  enabled too.
+* **serde** - When enabled (**not** the default), this implements `serde`'s
+  `Serialize` and `Deserialize` traits for [`memmem::FinderConfig`] and the
+  types it's made of. `FinderConfig` itself is only available when the
+  `std` feature is enabled too.
 * **libc** - When enabled (**not** the default), this library will use your
   platform's libc implementation of `memchr` (and `memrchr` on Linux). This
   can be useful on non-`x86_64` targets where the fallback implementation in
@@ -168,13 +200,30 @@ standard library exposes a platform independent SIMD API.
 )))]
 compile_error!("memchr currently not supported on non-{16,32,64}");
 
+pub use crate::ext::{ByteSearch, Needle};
+#[cfg(feature = "std")]
+pub use crate::memchr::memchr_batch;
 pub use crate::memchr::{
-    memchr, memchr2, memchr2_iter, memchr3, memchr3_iter, memchr_iter,
-    memrchr, memrchr2, memrchr2_iter, memrchr3, memrchr3_iter, memrchr_iter,
-    Memchr, Memchr2, Memchr3,
+    count, find_ascii_control, find_ascii_control_except, find_elem, lines,
+    lines_with_terminator, memchr, memchr2, memchr2_count, memchr2_iter,
+    memchr2_u16, memchr2_which, memchr3, memchr3_count, memchr3_iter,
+    memchr3_u16, memchr3_which, memchr_all_into, memchr_ascii_whitespace,
+    memchr_fn, memchr_in, memchr_iter, memchr_mask, memchr_nearest,
+    memchr_not, memchr_not_ascii_whitespace, memchr_not_range, memchr_nth,
+    memchr_range, memchr_set, memchr_u16, memrchr, memrchr2, memrchr2_iter,
+    memrchr2_u16, memrchr3, memrchr3_iter, memrchr3_u16, memrchr_all_into,
+    memrchr_ascii_whitespace, memrchr_in, memrchr_iter, memrchr_not,
+    memrchr_not_ascii_whitespace, memrchr_not_range, memrchr_nth,
+    memrchr_range, memrchr_set, memrchr_u16, mismatch, rlines, rsplit_once,
+    split_once, trim, trim_any, trim_end, trim_end_any, trim_start,
+    trim_start_any, try_memchr_in, try_memrchr_in, ByteSet, Config, Lines,
+    LinesWithTerminator, Memchr, Memchr2, Memchr3, MemchrNot, MemchrNotRev,
+    MemchrRev, MemchrSet, MemchrSetRev, RangeError, SimdElement,
 };
 
+pub mod arch;
 mod cow;
+mod ext;
 mod memchr;
 pub mod memmem;
 #[cfg(test)]
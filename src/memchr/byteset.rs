@@ -0,0 +1,254 @@
+use core::{convert::TryInto, ops::RangeInclusive};
+
+/// A set of bytes.
+///
+/// A `ByteSet` can contain any of the 256 possible byte values, and is
+/// primarily used with [`memchr_set`](crate::memchr_set) to search for the
+/// first byte in a haystack that belongs to the set. Unlike
+/// [`memchr3`](crate::memchr3), this supports any number of needles.
+///
+/// # Representation
+///
+/// A set is stored as a 256-bit bitset of 32 bytes, laid out so that it can be
+/// used as a pair of lookup tables indexed by the low four bits of a byte.
+/// This permits vectorized routines to test many bytes for membership at
+/// once, for sets of any size.
+///
+/// # Example
+///
+/// Sets can be built from a list of bytes, and combined with set operations.
+///
+/// ```
+/// use memchr::{memchr_set, ByteSet};
+///
+/// let delimiters =
+///     ByteSet::from_bytes(b",;:!?").union(ByteSet::ascii_whitespace());
+///
+/// assert!(delimiters.contains(b'!'));
+/// assert!(!delimiters.contains(b'.'));
+/// assert_eq!(Some(5), memchr_set(&delimiters, b"hello, world!"));
+/// ```
+///
+/// Sets can also be built incrementally.
+///
+/// ```
+/// use memchr::ByteSet;
+///
+/// let mut set = ByteSet::new();
+/// set.insert(b'a');
+/// set.insert(b'z');
+/// assert_eq!(2, set.len());
+/// set.remove(b'a');
+/// assert!(!set.contains(b'a'));
+/// ```
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct ByteSet {
+    // For a byte `b`, its bit is bit `(b >> 4) & 7` of the table entry at
+    // `(b >> 7) * 16 + (b & 0xF)`. So the first 16 bytes hold the members
+    // less than 128, and the last 16 bytes hold the rest.
+    bits: [u8; 32],
+}
+
+impl ByteSet {
+    /// Create a new empty set.
+    #[inline]
+    pub const fn new() -> ByteSet {
+        ByteSet { bits: [0; 32] }
+    }
+
+    /// Create a new set containing each of the given bytes.
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> ByteSet {
+        let mut set = ByteSet::new();
+        for &byte in bytes {
+            set.insert(byte);
+        }
+        set
+    }
+
+    /// Create a new set containing every byte in the given range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::ByteSet;
+    ///
+    /// let digits = ByteSet::from_range(b'0'..=b'9');
+    /// assert!(digits.contains(b'7'));
+    /// assert_eq!(10, digits.len());
+    /// ```
+    #[inline]
+    pub fn from_range(range: RangeInclusive<u8>) -> ByteSet {
+        let mut set = ByteSet::new();
+        for byte in range {
+            set.insert(byte);
+        }
+        set
+    }
+
+    /// Create a new set containing every byte for which the given predicate
+    /// returns true.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::ByteSet;
+    ///
+    /// let set = ByteSet::from_fn(|b| b.is_ascii_uppercase() || b == b'_');
+    /// assert!(set.contains(b'Q') && set.contains(b'_'));
+    /// assert!(!set.contains(b'q'));
+    /// ```
+    #[inline]
+    pub fn from_fn<F: FnMut(u8) -> bool>(mut predicate: F) -> ByteSet {
+        let mut set = ByteSet::new();
+        for byte in 0..=255 {
+            if predicate(byte) {
+                set.insert(byte);
+            }
+        }
+        set
+    }
+
+    /// The set of ASCII decimal digits, `0-9`.
+    #[inline]
+    pub fn ascii_digit() -> ByteSet {
+        ByteSet::from_range(b'0'..=b'9')
+    }
+
+    /// The set of ASCII hexadecimal digits, `0-9`, `a-f` and `A-F`.
+    #[inline]
+    pub fn ascii_hexdigit() -> ByteSet {
+        ByteSet::ascii_digit()
+            .union(ByteSet::from_range(b'a'..=b'f'))
+            .union(ByteSet::from_range(b'A'..=b'F'))
+    }
+
+    /// The set of ASCII letters, `a-z` and `A-Z`.
+    #[inline]
+    pub fn ascii_alphabetic() -> ByteSet {
+        ByteSet::from_range(b'a'..=b'z')
+            .union(ByteSet::from_range(b'A'..=b'Z'))
+    }
+
+    /// The set of ASCII letters and digits, `a-z`, `A-Z` and `0-9`.
+    #[inline]
+    pub fn ascii_alphanumeric() -> ByteSet {
+        ByteSet::ascii_alphabetic().union(ByteSet::ascii_digit())
+    }
+
+    /// The set of ASCII whitespace bytes, as defined by
+    /// `u8::is_ascii_whitespace`. That is, space, `\t`, `\n`, `\x0C` and
+    /// `\r`.
+    #[inline]
+    pub fn ascii_whitespace() -> ByteSet {
+        ByteSet::from_bytes(b" \t\n\x0C\r")
+    }
+
+    /// The set of ASCII punctuation bytes, as defined by
+    /// `u8::is_ascii_punctuation`.
+    #[inline]
+    pub fn ascii_punctuation() -> ByteSet {
+        ByteSet::from_range(b'!'..=b'/')
+            .union(ByteSet::from_range(b':'..=b'@'))
+            .union(ByteSet::from_range(b'['..=b'`'))
+            .union(ByteSet::from_range(b'{'..=b'~'))
+    }
+
+    /// Add the given byte to this set.
+    #[inline]
+    pub fn insert(&mut self, byte: u8) {
+        let (index, bit) = ByteSet::position(byte);
+        self.bits[index] |= bit;
+    }
+
+    /// Remove the given byte from this set.
+    #[inline]
+    pub fn remove(&mut self, byte: u8) {
+        let (index, bit) = ByteSet::position(byte);
+        self.bits[index] &= !bit;
+    }
+
+    /// Returns true if and only if the given byte is in this set.
+    #[inline]
+    pub const fn contains(&self, byte: u8) -> bool {
+        let (index, bit) = ByteSet::position(byte);
+        self.bits[index] & bit != 0
+    }
+
+    /// Returns the number of bytes in this set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Returns true if and only if this set contains no bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the set of bytes in this set, the other set, or both.
+    #[inline]
+    pub fn union(mut self, other: ByteSet) -> ByteSet {
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a |= b;
+        }
+        self
+    }
+
+    /// Returns the set of bytes in both this set and the other set.
+    #[inline]
+    pub fn intersection(mut self, other: ByteSet) -> ByteSet {
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a &= b;
+        }
+        self
+    }
+
+    /// Returns the set of bytes that are not in this set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::{memchr_set, ByteSet};
+    ///
+    /// let not_digit = ByteSet::ascii_digit().complement();
+    /// assert_eq!(Some(3), memchr_set(&not_digit, b"123px"));
+    /// ```
+    #[inline]
+    pub fn complement(mut self) -> ByteSet {
+        for b in self.bits.iter_mut() {
+            *b = !*b;
+        }
+        self
+    }
+
+    /// Returns the lookup table for members less than 128, followed by the
+    /// table for all other members.
+    ///
+    /// Bit `k` of entry `i` of the first table is set when the byte
+    /// `(k << 4) | i` is in the set. In the second table, it is set when the
+    /// byte `((k + 8) << 4) | i` is in the set.
+    #[inline]
+    pub(crate) fn tables(&self) -> (&[u8; 16], &[u8; 16]) {
+        let (lo, hi) = self.bits.split_at(16);
+        // These can't fail since `bits` has exactly 32 bytes.
+        (lo.try_into().unwrap(), hi.try_into().unwrap())
+    }
+
+    /// Returns the index into `bits` of the given byte, and the mask of its
+    /// bit in that entry.
+    #[inline(always)]
+    const fn position(byte: u8) -> (usize, u8) {
+        let index = (byte >> 7) as usize * 16 + (byte & 0xF) as usize;
+        (index, 1 << ((byte >> 4) & 7))
+    }
+}
+
+impl core::fmt::Debug for ByteSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_set()
+            .entries((0..=255u8).filter(|&b| self.contains(b)))
+            .finish()
+    }
+}
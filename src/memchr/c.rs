@@ -5,7 +5,10 @@
 
 use libc::{c_int, c_void, size_t};
 
+use crate::arch::{record_backend, Backend};
+
 pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    record_backend(Backend::Libc);
     // SAFETY: This is safe to call since all pointers are valid.
     let p = unsafe {
         libc::memchr(
@@ -24,6 +27,7 @@ pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
 // memrchr is a GNU extension. We know it's available on Linux at least.
 #[cfg(target_os = "linux")]
 pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    record_backend(Backend::Libc);
     // GNU's memrchr() will - unlike memchr() - error if haystack is empty.
     if haystack.is_empty() {
         return None;
@@ -0,0 +1,141 @@
+use crate::{memchr, memchr2, memchr3, memrchr, memrchr2, memrchr3};
+
+/// Configuration for single byte search.
+///
+/// The routines in this crate use vectorized search when possible. Setting up
+/// a vectorized search and handling the bytes at its end has a fixed cost,
+/// which can outweigh comparing bytes one at a time when the haystack is
+/// tiny. Where the crossover lies depends on the machine, so a `Config`
+/// permits tuning it: haystacks shorter than
+/// [`simd_threshold`](Config::simd_threshold) are searched with a simple
+/// byte loop, and all others with the corresponding top-level routine.
+///
+/// The results never depend on the threshold, only the time taken to find
+/// them.
+///
+/// This is the single byte equivalent of
+/// [`memmem::FinderBuilder::simd_threshold`](crate::memmem::FinderBuilder::simd_threshold).
+///
+/// # Example
+///
+/// ```
+/// use memchr::Config;
+///
+/// const CONFIG: Config = Config::new().simd_threshold(8);
+///
+/// assert_eq!(Some(3), CONFIG.memchr(b'=', b"key=val"));
+/// assert_eq!(Some(1), CONFIG.memrchr2(b'a', b'b', b"abc"));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Config {
+    simd_threshold: usize,
+}
+
+impl Config {
+    /// The default value of [`Config::simd_threshold`].
+    ///
+    /// This is the size of an SSE2 vector. The vectorized routines handle
+    /// shorter haystacks with scalar code anyway, and a byte loop avoids the
+    /// cost of dispatching to them. When searching haystacks of 2 to 16
+    /// bytes, it was measured to be at least as fast as delegating.
+    pub const DEFAULT_SIMD_THRESHOLD: usize = 16;
+
+    /// Create a new configuration with default settings.
+    #[inline]
+    pub const fn new() -> Config {
+        Config { simd_threshold: Config::DEFAULT_SIMD_THRESHOLD }
+    }
+
+    /// Set the haystack length, in bytes, below which searches use a simple
+    /// byte loop.
+    #[inline]
+    pub const fn simd_threshold(self, bytes: usize) -> Config {
+        Config { simd_threshold: bytes }
+    }
+
+    /// Returns the haystack length below which searches use a simple byte
+    /// loop.
+    #[inline]
+    pub const fn get_simd_threshold(&self) -> usize {
+        self.simd_threshold
+    }
+
+    /// Like [`memchr`](crate::memchr()), but respects this configuration.
+    #[inline]
+    pub fn memchr(&self, n1: u8, haystack: &[u8]) -> Option<usize> {
+        if haystack.len() < self.simd_threshold {
+            haystack.iter().position(|&b| b == n1)
+        } else {
+            memchr(n1, haystack)
+        }
+    }
+
+    /// Like [`memchr2`](crate::memchr2()), but respects this configuration.
+    #[inline]
+    pub fn memchr2(&self, n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+        if haystack.len() < self.simd_threshold {
+            haystack.iter().position(|&b| b == n1 || b == n2)
+        } else {
+            memchr2(n1, n2, haystack)
+        }
+    }
+
+    /// Like [`memchr3`](crate::memchr3()), but respects this configuration.
+    #[inline]
+    pub fn memchr3(
+        &self,
+        n1: u8,
+        n2: u8,
+        n3: u8,
+        haystack: &[u8],
+    ) -> Option<usize> {
+        if haystack.len() < self.simd_threshold {
+            haystack.iter().position(|&b| b == n1 || b == n2 || b == n3)
+        } else {
+            memchr3(n1, n2, n3, haystack)
+        }
+    }
+
+    /// Like [`memrchr`](crate::memrchr()), but respects this configuration.
+    #[inline]
+    pub fn memrchr(&self, n1: u8, haystack: &[u8]) -> Option<usize> {
+        if haystack.len() < self.simd_threshold {
+            haystack.iter().rposition(|&b| b == n1)
+        } else {
+            memrchr(n1, haystack)
+        }
+    }
+
+    /// Like [`memrchr2`](crate::memrchr2()), but respects this configuration.
+    #[inline]
+    pub fn memrchr2(&self, n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+        if haystack.len() < self.simd_threshold {
+            haystack.iter().rposition(|&b| b == n1 || b == n2)
+        } else {
+            memrchr2(n1, n2, haystack)
+        }
+    }
+
+    /// Like [`memrchr3`](crate::memrchr3()), but respects this configuration.
+    #[inline]
+    pub fn memrchr3(
+        &self,
+        n1: u8,
+        n2: u8,
+        n3: u8,
+        haystack: &[u8],
+    ) -> Option<usize> {
+        if haystack.len() < self.simd_threshold {
+            haystack.iter().rposition(|&b| b == n1 || b == n2 || b == n3)
+        } else {
+            memrchr3(n1, n2, n3, haystack)
+        }
+    }
+}
+
+impl Default for Config {
+    #[inline]
+    fn default() -> Config {
+        Config::new()
+    }
+}
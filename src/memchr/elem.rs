@@ -0,0 +1,94 @@
+use core::slice;
+
+/// A fixed-width primitive that can be searched for with
+/// [`find_elem`](crate::find_elem).
+///
+/// This is implemented for `u8`, `u16`, `u32`, `i8`, `i16` and `i32`. Each
+/// type is searched for with a vectorized routine that compares lanes of the
+/// same width, so a needle only ever matches a whole element.
+///
+/// This trait is sealed, so it cannot be implemented outside of this crate.
+pub trait SimdElement: Copy + private::Sealed {}
+
+/// Search for the first occurrence of an element in a slice.
+///
+/// This returns the index corresponding to the first occurrence of `needle`
+/// in `haystack`, or `None` if one is not found. If an index is returned, it
+/// is guaranteed to be less than `usize::MAX`.
+///
+/// This is generic over every element type implementing [`SimdElement`]. For
+/// bytes and 16-bit code units, it is equivalent to [`memchr`](crate::memchr())
+/// and [`memchr_u16`](crate::memchr_u16) respectively, which can be called
+/// directly when the element type is known.
+///
+/// # Example
+///
+/// This shows how to find a sentinel in a stream of 32-bit tokens.
+///
+/// ```
+/// use memchr::find_elem;
+///
+/// let tokens: &[u32] = &[7, 0x0001_0000, 3, u32::MAX, 9];
+/// assert_eq!(find_elem(u32::MAX, tokens), Some(3));
+/// assert_eq!(find_elem(-1i32, &[5, -1, 0]), Some(1));
+/// assert_eq!(find_elem(1u32, tokens), None);
+/// ```
+#[inline]
+pub fn find_elem<T: SimdElement>(needle: T, haystack: &[T]) -> Option<usize> {
+    T::find(needle, haystack)
+}
+
+/// Reinterpret a slice of signed integers as a slice of the unsigned integers
+/// of the same width.
+///
+/// # Safety
+///
+/// `T` and `U` must have the same size and alignment, and every bit pattern
+/// must be valid for `U`.
+#[inline(always)]
+unsafe fn cast<T, U>(slice: &[T]) -> &[U] {
+    slice::from_raw_parts(slice.as_ptr() as *const U, slice.len())
+}
+
+macro_rules! impl_unsigned {
+    ($ty:ty, $find:path) => {
+        impl SimdElement for $ty {}
+
+        impl private::Sealed for $ty {
+            #[inline(always)]
+            fn find(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                $find(needle, haystack)
+            }
+        }
+    };
+}
+
+macro_rules! impl_signed {
+    ($ty:ty, $unsigned:ty) => {
+        impl SimdElement for $ty {}
+
+        impl private::Sealed for $ty {
+            #[inline(always)]
+            fn find(needle: $ty, haystack: &[$ty]) -> Option<usize> {
+                // SAFETY: Signed and unsigned integers of the same width have
+                // the same size and alignment, and every bit pattern is a
+                // valid unsigned integer.
+                let haystack = unsafe { cast::<$ty, $unsigned>(haystack) };
+                <$unsigned>::find(needle as $unsigned, haystack)
+            }
+        }
+    };
+}
+
+impl_unsigned!(u8, crate::memchr::memchr);
+impl_unsigned!(u16, crate::memchr::memchr_u16);
+impl_unsigned!(u32, crate::memchr::memchr_u32);
+impl_signed!(i8, u8);
+impl_signed!(i16, u16);
+impl_signed!(i32, u32);
+
+mod private {
+    pub trait Sealed: Sized {
+        fn find(needle: Self, haystack: &[Self]) -> Option<usize>;
+    }
+}
@@ -4,6 +4,9 @@
 
 use core::{cmp, usize};
 
+use super::ByteSet;
+use crate::arch::{record_backend, Backend};
+
 #[cfg(target_pointer_width = "16")]
 const USIZE_BYTES: usize = 2;
 
@@ -34,6 +37,35 @@ fn contains_zero_byte(x: usize) -> bool {
     x.wrapping_sub(LO_USIZE) & !x & HI_USIZE != 0
 }
 
+/// Return a word where the most significant bit of each byte is set if and
+/// only if the corresponding byte in `x` is non-zero. All other bits are
+/// unspecified.
+///
+/// Unlike the borrow trick used in `contains_zero_byte`, this is exact. That
+/// trick can report false positives for bytes more significant than a zero
+/// byte, which is fine for detecting whether a zero byte exists, but not for
+/// counting them. Instead, we add `0x7F` to the low 7 bits of each byte (which
+/// can never carry into the next byte) and OR in the original byte.
+#[inline(always)]
+fn nonzero_bytes(x: usize) -> usize {
+    const LO7_U64: u64 = 0x7F7F7F7F7F7F7F7F;
+    const LO7_USIZE: usize = LO7_U64 as usize;
+
+    (x & LO7_USIZE).wrapping_add(LO7_USIZE) | x
+}
+
+/// Return the number of bytes in `x` whose most significant bit is not set.
+///
+/// When given the result of `nonzero_bytes`, this returns the number of zero
+/// bytes.
+#[inline(always)]
+fn count_unset_high_bits(x: usize) -> usize {
+    const HI_U64: u64 = 0x8080808080808080;
+    const HI_USIZE: usize = HI_U64 as usize;
+
+    (!x & HI_USIZE).count_ones() as usize
+}
+
 /// Repeat the given byte into a word size number. That is, every 8 bits
 /// is equivalent to the given byte. For example, if `b` is `\x4E` or
 /// `01001110` in binary, then the returned value on a 32-bit system would be:
@@ -44,6 +76,7 @@ fn repeat_byte(b: u8) -> usize {
 }
 
 pub fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    record_backend(Backend::Scalar);
     let vn1 = repeat_byte(n1);
     let confirm = |byte| byte == n1;
     let loop_size = cmp::min(LOOP_SIZE, haystack.len());
@@ -166,6 +199,7 @@ pub fn memchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
 
 /// Return the last index matching the byte `x` in `text`.
 pub fn memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    record_backend(Backend::Scalar);
     let vn1 = repeat_byte(n1);
     let confirm = |byte| byte == n1;
     let loop_size = cmp::min(LOOP_SIZE, haystack.len());
@@ -283,6 +317,380 @@ pub fn memrchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
     }
 }
 
+/// Return the first index not matching the byte `n1` in `haystack`.
+pub fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = repeat_byte(n1);
+    let confirm = |byte| byte != n1;
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let align = USIZE_BYTES - 1;
+    let start_ptr = haystack.as_ptr();
+    let mut ptr = start_ptr;
+
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        if haystack.len() < USIZE_BYTES {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        // Unlike in `memchr`, no bit twiddling is needed here: a chunk
+        // contains a non-matching byte precisely when it isn't equal to the
+        // needle repeated across the entire word.
+        let chunk = (ptr as *const usize).read_unaligned();
+        if chunk != vn1 {
+            return forward_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        ptr = ptr.add(USIZE_BYTES - (start_ptr as usize & align));
+        debug_assert!(ptr > start_ptr);
+        debug_assert!(end_ptr.sub(USIZE_BYTES) >= start_ptr);
+        while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+            debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
+
+            let a = *(ptr as *const usize);
+            let b = *(ptr.add(USIZE_BYTES) as *const usize);
+            if a != vn1 || b != vn1 {
+                break;
+            }
+            ptr = ptr.add(LOOP_SIZE);
+        }
+        forward_search(start_ptr, end_ptr, ptr, confirm)
+    }
+}
+
+/// Return the last index not matching the byte `n1` in `haystack`.
+pub fn memrchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = repeat_byte(n1);
+    let confirm = |byte| byte != n1;
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    let align = USIZE_BYTES - 1;
+    let start_ptr = haystack.as_ptr();
+
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        let mut ptr = end_ptr;
+        if haystack.len() < USIZE_BYTES {
+            return reverse_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        let chunk = (ptr.sub(USIZE_BYTES) as *const usize).read_unaligned();
+        if chunk != vn1 {
+            return reverse_search(start_ptr, end_ptr, ptr, confirm);
+        }
+
+        ptr = (end_ptr as usize & !align) as *const u8;
+        debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
+        while loop_size == LOOP_SIZE && ptr >= start_ptr.add(loop_size) {
+            debug_assert_eq!(0, (ptr as usize) % USIZE_BYTES);
+
+            let a = *(ptr.sub(2 * USIZE_BYTES) as *const usize);
+            let b = *(ptr.sub(1 * USIZE_BYTES) as *const usize);
+            if a != vn1 || b != vn1 {
+                break;
+            }
+            ptr = ptr.sub(loop_size);
+        }
+        reverse_search(start_ptr, end_ptr, ptr, confirm)
+    }
+}
+
+/// Return the first index of a byte in the inclusive range `[lo, hi]` in
+/// `haystack`.
+///
+/// There is no cheap way to test a range on each byte of a word at a time,
+/// so this is just a simple byte-at-a-time loop.
+pub fn memchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        forward_search(start_ptr, end_ptr, start_ptr, |b| lo <= b && b <= hi)
+    }
+}
+
+/// Return a mask with bit `i` set when `haystack[i] == n1`, for the first 64
+/// bytes of `haystack`.
+pub fn memchr_mask(n1: u8, haystack: &[u8]) -> u64 {
+    haystack
+        .iter()
+        .take(64)
+        .enumerate()
+        .fold(0, |mask, (i, &b)| mask | (u64::from(b == n1) << i))
+}
+
+/// Return the first index of a byte in `set` in `haystack`.
+pub fn memchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        forward_search(start_ptr, end_ptr, start_ptr, |b| set.contains(b))
+    }
+}
+
+/// Return the last index of a byte in `set` in `haystack`.
+pub fn memrchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        reverse_search(start_ptr, end_ptr, end_ptr, |b| set.contains(b))
+    }
+}
+
+/// Return the last index of a byte in the inclusive range `[lo, hi]` in
+/// `haystack`.
+pub fn memrchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        reverse_search(start_ptr, end_ptr, end_ptr, |b| lo <= b && b <= hi)
+    }
+}
+
+/// Return the first index of a byte outside the inclusive range `[lo, hi]` in
+/// `haystack`.
+pub fn memchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        forward_search(start_ptr, end_ptr, start_ptr, |b| b < lo || b > hi)
+    }
+}
+
+/// Return the last index of a byte outside the inclusive range `[lo, hi]` in
+/// `haystack`.
+pub fn memrchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        reverse_search(start_ptr, end_ptr, end_ptr, |b| b < lo || b > hi)
+    }
+}
+
+/// Return the first index of an ASCII whitespace byte in `haystack`.
+pub fn memchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        forward_search(start_ptr, end_ptr, start_ptr, |b| {
+            b.is_ascii_whitespace()
+        })
+    }
+}
+
+/// Return the last index of an ASCII whitespace byte in `haystack`.
+pub fn memrchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        reverse_search(start_ptr, end_ptr, end_ptr, |b| {
+            b.is_ascii_whitespace()
+        })
+    }
+}
+
+/// Return the first index of a byte that isn't ASCII whitespace in `haystack`.
+pub fn memchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        forward_search(start_ptr, end_ptr, start_ptr, |b| {
+            !b.is_ascii_whitespace()
+        })
+    }
+}
+
+/// Return the last index of a byte that isn't ASCII whitespace in `haystack`.
+pub fn memrchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        reverse_search(start_ptr, end_ptr, end_ptr, |b| {
+            !b.is_ascii_whitespace()
+        })
+    }
+}
+
+/// Return the first index of an ASCII control byte in `haystack`.
+pub fn find_ascii_control(haystack: &[u8]) -> Option<usize> {
+    let start_ptr = haystack.as_ptr();
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        forward_search(start_ptr, end_ptr, start_ptr, |b| b.is_ascii_control())
+    }
+}
+
+/// Return the first index at which `a` and `b` differ. Both must have the
+/// same length.
+pub fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    debug_assert_eq!(a.len(), b.len());
+    let mut at = 0;
+    // Compare a word at a time, and then find the differing byte (if any)
+    // in the first word that differs or in the tail.
+    while at + USIZE_BYTES <= a.len() {
+        let (x, y) = unsafe {
+            (
+                (a.as_ptr().add(at) as *const usize).read_unaligned(),
+                (b.as_ptr().add(at) as *const usize).read_unaligned(),
+            )
+        };
+        if x != y {
+            break;
+        }
+        at += USIZE_BYTES;
+    }
+    a[at..].iter().zip(&b[at..]).position(|(x, y)| x != y).map(|i| at + i)
+}
+
+/// Return the index of the nth (starting at 0) occurrence of `n1` in
+/// `haystack`.
+pub fn memchr_nth(mut n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+    let mut at = 0;
+    loop {
+        let i = at + memchr(n1, &haystack[at..])?;
+        if n == 0 {
+            return Some(i);
+        }
+        n -= 1;
+        at = i + 1;
+    }
+}
+
+/// Return the index of the nth (starting at 0) occurrence of `n1` in
+/// `haystack`, starting from the end.
+pub fn memrchr_nth(mut n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+    let mut end = haystack.len();
+    loop {
+        let i = memrchr(n1, &haystack[..end])?;
+        if n == 0 {
+            return Some(i);
+        }
+        n -= 1;
+        end = i;
+    }
+}
+
+/// Write the indices of all occurrences of `n1` in `haystack` to `out`, in
+/// ascending order, until `out` is full. The number of indices written is
+/// returned.
+pub fn memchr_all_into(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+    let mut written = 0;
+    let mut at = 0;
+    while written < out.len() {
+        let i = match memchr(n1, &haystack[at..]) {
+            None => break,
+            Some(i) => at + i,
+        };
+        out[written] = i;
+        written += 1;
+        at = i + 1;
+    }
+    written
+}
+
+/// Write the indices of all occurrences of `n1` in `haystack` to `out`, in
+/// descending order, until `out` is full. The number of indices written is
+/// returned.
+pub fn memrchr_all_into(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+    let mut written = 0;
+    let mut end = haystack.len();
+    while written < out.len() {
+        let i = match memrchr(n1, &haystack[..end]) {
+            None => break,
+            Some(i) => i,
+        };
+        out[written] = i;
+        written += 1;
+        end = i;
+    }
+    written
+}
+
+/// Return the number of occurrences of `n1` in `haystack`.
+pub fn count(n1: u8, haystack: &[u8]) -> usize {
+    let vn1 = repeat_byte(n1);
+    let start_ptr = haystack.as_ptr();
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        if haystack.len() >= USIZE_BYTES {
+            while ptr <= end_ptr.sub(USIZE_BYTES) {
+                let chunk = (ptr as *const usize).read_unaligned();
+                count += count_unset_high_bits(nonzero_bytes(chunk ^ vn1));
+                ptr = ptr.add(USIZE_BYTES);
+            }
+        }
+        while ptr < end_ptr {
+            if *ptr == n1 {
+                count += 1;
+            }
+            ptr = ptr.offset(1);
+        }
+    }
+    count
+}
+
+/// Return the number of bytes in `haystack` equal to `n1` or `n2`.
+pub fn count2(n1: u8, n2: u8, haystack: &[u8]) -> usize {
+    let vn1 = repeat_byte(n1);
+    let vn2 = repeat_byte(n2);
+    let start_ptr = haystack.as_ptr();
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        if haystack.len() >= USIZE_BYTES {
+            while ptr <= end_ptr.sub(USIZE_BYTES) {
+                let chunk = (ptr as *const usize).read_unaligned();
+                // A byte matches if it is zero in either XOR, i.e., if it
+                // isn't non-zero in both. This never counts a byte twice.
+                let nonzero =
+                    nonzero_bytes(chunk ^ vn1) & nonzero_bytes(chunk ^ vn2);
+                count += count_unset_high_bits(nonzero);
+                ptr = ptr.add(USIZE_BYTES);
+            }
+        }
+        while ptr < end_ptr {
+            if *ptr == n1 || *ptr == n2 {
+                count += 1;
+            }
+            ptr = ptr.offset(1);
+        }
+    }
+    count
+}
+
+/// Return the number of bytes in `haystack` equal to `n1`, `n2` or `n3`.
+pub fn count3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> usize {
+    let vn1 = repeat_byte(n1);
+    let vn2 = repeat_byte(n2);
+    let vn3 = repeat_byte(n3);
+    let start_ptr = haystack.as_ptr();
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    unsafe {
+        let end_ptr = start_ptr.add(haystack.len());
+        if haystack.len() >= USIZE_BYTES {
+            while ptr <= end_ptr.sub(USIZE_BYTES) {
+                let chunk = (ptr as *const usize).read_unaligned();
+                let nonzero = nonzero_bytes(chunk ^ vn1)
+                    & nonzero_bytes(chunk ^ vn2)
+                    & nonzero_bytes(chunk ^ vn3);
+                count += count_unset_high_bits(nonzero);
+                ptr = ptr.add(USIZE_BYTES);
+            }
+        }
+        while ptr < end_ptr {
+            if *ptr == n1 || *ptr == n2 || *ptr == n3 {
+                count += 1;
+            }
+            ptr = ptr.offset(1);
+        }
+    }
+    count
+}
+
 #[inline(always)]
 unsafe fn forward_search<F: Fn(u8) -> bool>(
     start_ptr: *const u8,
@@ -327,3 +735,151 @@ fn sub(a: *const u8, b: *const u8) -> usize {
     debug_assert!(a >= b);
     (a as usize) - (b as usize)
 }
+
+// The u16 routines below use the same zero detection trick as
+// `contains_zero_byte`, but on four 16-bit lanes packed into a `u64`. The
+// lanes are packed with shifts (instead of reading the slice's memory as a
+// `u64`), so that lanes never straddle code units regardless of endianness.
+
+const LO_U16X4: u64 = 0x0001_0001_0001_0001;
+const HI_U16X4: u64 = 0x8000_8000_8000_8000;
+
+/// Return `true` if `x` contains any zero 16-bit lane.
+#[inline(always)]
+fn contains_zero_u16(x: u64) -> bool {
+    x.wrapping_sub(LO_U16X4) & !x & HI_U16X4 != 0
+}
+
+/// Repeat the given code unit into each 16-bit lane of a `u64`.
+#[inline(always)]
+fn repeat_u16(n: u16) -> u64 {
+    (n as u64) * LO_U16X4
+}
+
+/// Pack exactly four code units into the lanes of a `u64`.
+#[inline(always)]
+fn pack_u16(chunk: &[u16]) -> u64 {
+    debug_assert_eq!(4, chunk.len());
+    (chunk[0] as u64)
+        | (chunk[1] as u64) << 16
+        | (chunk[2] as u64) << 32
+        | (chunk[3] as u64) << 48
+}
+
+/// Search for the first code unit satisfying `confirm`, where `candidate`
+/// reports whether any lane of four packed code units satisfies it.
+#[inline(always)]
+fn forward_search_u16<F: Fn(u64) -> bool, G: Fn(u16) -> bool>(
+    haystack: &[u16],
+    candidate: F,
+    confirm: G,
+) -> Option<usize> {
+    let mut chunks = haystack.chunks_exact(4);
+    for (i, chunk) in (&mut chunks).enumerate() {
+        if candidate(pack_u16(chunk)) {
+            if let Some(j) = chunk.iter().position(|&u| confirm(u)) {
+                return Some(i * 4 + j);
+            }
+        }
+    }
+    let rest = chunks.remainder();
+    let at = haystack.len() - rest.len();
+    rest.iter().position(|&u| confirm(u)).map(|j| at + j)
+}
+
+/// Search for the last code unit satisfying `confirm`, where `candidate`
+/// reports whether any lane of four packed code units satisfies it.
+#[inline(always)]
+fn reverse_search_u16<F: Fn(u64) -> bool, G: Fn(u16) -> bool>(
+    haystack: &[u16],
+    candidate: F,
+    confirm: G,
+) -> Option<usize> {
+    let mut chunks = haystack.rchunks_exact(4);
+    for (i, chunk) in (&mut chunks).enumerate() {
+        if candidate(pack_u16(chunk)) {
+            if let Some(j) = chunk.iter().rposition(|&u| confirm(u)) {
+                return Some(haystack.len() - (i + 1) * 4 + j);
+            }
+        }
+    }
+    chunks.remainder().iter().rposition(|&u| confirm(u))
+}
+
+/// Return the first index matching the code unit `n1` in `haystack`.
+pub fn memchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    let vn1 = repeat_u16(n1);
+    forward_search_u16(haystack, |x| contains_zero_u16(x ^ vn1), |u| u == n1)
+}
+
+/// Like `memchr_u16`, but searches for either `n1` or `n2`.
+pub fn memchr2_u16(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+    let (vn1, vn2) = (repeat_u16(n1), repeat_u16(n2));
+    forward_search_u16(
+        haystack,
+        |x| contains_zero_u16(x ^ vn1) || contains_zero_u16(x ^ vn2),
+        |u| u == n1 || u == n2,
+    )
+}
+
+/// Like `memchr_u16`, but searches for `n1`, `n2` or `n3`.
+pub fn memchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    let (vn1, vn2, vn3) = (repeat_u16(n1), repeat_u16(n2), repeat_u16(n3));
+    forward_search_u16(
+        haystack,
+        |x| {
+            contains_zero_u16(x ^ vn1)
+                || contains_zero_u16(x ^ vn2)
+                || contains_zero_u16(x ^ vn3)
+        },
+        |u| u == n1 || u == n2 || u == n3,
+    )
+}
+
+/// Return the last index matching the code unit `n1` in `haystack`.
+pub fn memrchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    let vn1 = repeat_u16(n1);
+    reverse_search_u16(haystack, |x| contains_zero_u16(x ^ vn1), |u| u == n1)
+}
+
+/// Like `memrchr_u16`, but searches for either `n1` or `n2`.
+pub fn memrchr2_u16(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+    let (vn1, vn2) = (repeat_u16(n1), repeat_u16(n2));
+    reverse_search_u16(
+        haystack,
+        |x| contains_zero_u16(x ^ vn1) || contains_zero_u16(x ^ vn2),
+        |u| u == n1 || u == n2,
+    )
+}
+
+/// Like `memrchr_u16`, but searches for `n1`, `n2` or `n3`.
+pub fn memrchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    let (vn1, vn2, vn3) = (repeat_u16(n1), repeat_u16(n2), repeat_u16(n3));
+    reverse_search_u16(
+        haystack,
+        |x| {
+            contains_zero_u16(x ^ vn1)
+                || contains_zero_u16(x ^ vn2)
+                || contains_zero_u16(x ^ vn3)
+        },
+        |u| u == n1 || u == n2 || u == n3,
+    )
+}
+
+/// Return the first index matching the element `n1` in `haystack`.
+///
+/// Only two 32-bit lanes fit in a `u64`, which isn't enough to make the zero
+/// lane trick used for `u16` worth it. So this is just a simple loop.
+pub fn memchr_u32(n1: u32, haystack: &[u32]) -> Option<usize> {
+    haystack.iter().position(|&u| u == n1)
+}
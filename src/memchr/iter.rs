@@ -1,4 +1,8 @@
-use crate::{memchr, memchr2, memchr3, memrchr, memrchr2, memrchr3};
+use crate::{
+    count, memchr, memchr2, memchr2_count, memchr3, memchr3_count, memchr_not,
+    memchr_set, memrchr, memrchr2, memrchr3, memrchr_not, memrchr_set,
+    ByteSet,
+};
 
 macro_rules! iter_next {
     // Common code for the memchr iterators:
@@ -31,6 +35,10 @@ macro_rules! iter_next_back {
 }
 
 /// An iterator for `memchr`.
+///
+/// This iterator is double ended. Matches may be consumed from the front
+/// (with `next`), from the back (with `next_back`) or alternately from both,
+/// and each match is reported exactly once.
 pub struct Memchr<'a> {
     needle: u8,
     // The haystack to iterate over
@@ -41,10 +49,63 @@ pub struct Memchr<'a> {
 
 impl<'a> Memchr<'a> {
     /// Creates a new iterator that yields all positions of needle in haystack.
+    ///
+    /// This is a `const fn`, so an iterator over a `'static` haystack can be
+    /// stored in a `const` or `static`.
     #[inline]
-    pub fn new(needle: u8, haystack: &[u8]) -> Memchr<'_> {
+    pub const fn new(needle: u8, haystack: &[u8]) -> Memchr<'_> {
         Memchr { needle: needle, haystack: haystack, position: 0 }
     }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack following the last match
+    /// returned by `next`, and preceding the last match returned by
+    /// `next_back`. Neither of those matches are included. This is useful for
+    /// resuming processing of a haystack after iteration stops early. Note
+    /// that once the iterator is exhausted, the remainder is whatever is left
+    /// between the last matches found (and contains no matches).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::Memchr;
+    ///
+    /// let mut it = Memchr::new(b':', b"key: value\nrest");
+    /// assert_eq!(Some(3), it.next());
+    /// assert_eq!(b" value\nrest", it.remainder());
+    /// ```
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.haystack
+    }
+
+    /// Returns the number of matches this iterator has yet to yield, without
+    /// consuming it.
+    ///
+    /// Unlike `Iterator::count`, this takes `&self`, so it can be used to
+    /// size a buffer before collecting the remaining matches. Only the
+    /// [`remainder`](Memchr::remainder) is searched, so matches that have
+    /// already been yielded (from either end) are not counted. The search uses
+    /// the same vectorized routine as [`count`](crate::count).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::Memchr;
+    ///
+    /// let mut it = Memchr::new(b',', b"a,b,c,d");
+    /// assert_eq!(Some(1), it.next());
+    /// let mut rest = Vec::with_capacity(it.count_remaining());
+    /// rest.extend(it);
+    /// assert_eq!(vec![3, 5], rest);
+    /// assert_eq!(2, rest.capacity());
+    /// ```
+    #[inline]
+    pub fn count_remaining(&self) -> usize {
+        count(self.needle, self.haystack)
+    }
 }
 
 impl<'a> Iterator for Memchr<'a> {
@@ -68,7 +129,75 @@ impl<'a> DoubleEndedIterator for Memchr<'a> {
     }
 }
 
+/// A reverse iterator for `memchr`.
+///
+/// This yields the same matches as `Memchr`, but starting from the end of the
+/// haystack. Unlike `memrchr_iter`, which returns a `Rev<Memchr>`, this gives
+/// access to the [`remainder`](MemchrRev::remainder) of the haystack, which
+/// makes it convenient for parsing a buffer from the back.
+///
+/// This iterator is double ended. Calling `next_back` yields matches from the
+/// start of the haystack.
+pub struct MemchrRev<'a>(Memchr<'a>);
+
+impl<'a> MemchrRev<'a> {
+    /// Creates a new iterator that yields all positions of needle in
+    /// haystack, in reverse.
+    #[inline]
+    pub const fn new(needle: u8, haystack: &[u8]) -> MemchrRev<'_> {
+        MemchrRev(Memchr::new(needle, haystack))
+    }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack preceding the last match
+    /// returned by `next`, and following the last match returned by
+    /// `next_back`. Neither of those matches are included. In particular, if
+    /// only `next` has been called, then this is the unsearched head of the
+    /// haystack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::MemchrRev;
+    ///
+    /// let mut it = MemchrRev::new(b'/', b"usr/local/bin");
+    /// assert_eq!(Some(9), it.next());
+    /// assert_eq!(b"usr/local", it.remainder());
+    /// ```
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.0.remainder()
+    }
+}
+
+impl<'a> Iterator for MemchrRev<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        self.0.next_back()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for MemchrRev<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
 /// An iterator for `memchr2`.
+///
+/// This iterator is double ended. Matches may be consumed from the front
+/// (with `next`), from the back (with `next_back`) or alternately from both,
+/// and each match is reported exactly once.
 pub struct Memchr2<'a> {
     needle1: u8,
     needle2: u8,
@@ -80,8 +209,14 @@ pub struct Memchr2<'a> {
 
 impl<'a> Memchr2<'a> {
     /// Creates a new iterator that yields all positions of needle in haystack.
+    ///
+    /// This is a `const fn`, like [`Memchr::new`].
     #[inline]
-    pub fn new(needle1: u8, needle2: u8, haystack: &[u8]) -> Memchr2<'_> {
+    pub const fn new(
+        needle1: u8,
+        needle2: u8,
+        haystack: &[u8],
+    ) -> Memchr2<'_> {
         Memchr2 {
             needle1: needle1,
             needle2: needle2,
@@ -89,6 +224,39 @@ impl<'a> Memchr2<'a> {
             position: 0,
         }
     }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack following the last match
+    /// returned by `next`, and preceding the last match returned by
+    /// `next_back`. Neither of those matches are included. This is useful for
+    /// resuming processing of a haystack after iteration stops early. Note
+    /// that once the iterator is exhausted, the remainder is whatever is left
+    /// between the last matches found (and contains no matches).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::Memchr2;
+    ///
+    /// let mut it = Memchr2::new(b':', b'=', b"key: value\nrest");
+    /// assert_eq!(Some(3), it.next());
+    /// assert_eq!(b" value\nrest", it.remainder());
+    /// ```
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.haystack
+    }
+
+    /// Returns the number of matches this iterator has yet to yield, without
+    /// consuming it.
+    ///
+    /// See [`Memchr::count_remaining`] for more details.
+    #[inline]
+    pub fn count_remaining(&self) -> usize {
+        memchr2_count(self.needle1, self.needle2, self.haystack)
+    }
 }
 
 impl<'a> Iterator for Memchr2<'a> {
@@ -116,6 +284,10 @@ impl<'a> DoubleEndedIterator for Memchr2<'a> {
 }
 
 /// An iterator for `memchr3`.
+///
+/// This iterator is double ended. Matches may be consumed from the front
+/// (with `next`), from the back (with `next_back`) or alternately from both,
+/// and each match is reported exactly once.
 pub struct Memchr3<'a> {
     needle1: u8,
     needle2: u8,
@@ -128,8 +300,10 @@ pub struct Memchr3<'a> {
 
 impl<'a> Memchr3<'a> {
     /// Create a new `Memchr3` that's initialized to zero with a haystack
+    ///
+    /// This is a `const fn`, like [`Memchr::new`].
     #[inline]
-    pub fn new(
+    pub const fn new(
         needle1: u8,
         needle2: u8,
         needle3: u8,
@@ -143,6 +317,39 @@ impl<'a> Memchr3<'a> {
             position: 0,
         }
     }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack following the last match
+    /// returned by `next`, and preceding the last match returned by
+    /// `next_back`. Neither of those matches are included. This is useful for
+    /// resuming processing of a haystack after iteration stops early. Note
+    /// that once the iterator is exhausted, the remainder is whatever is left
+    /// between the last matches found (and contains no matches).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::Memchr3;
+    ///
+    /// let mut it = Memchr3::new(b':', b'=', b'\t', b"key: value\nrest");
+    /// assert_eq!(Some(3), it.next());
+    /// assert_eq!(b" value\nrest", it.remainder());
+    /// ```
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.haystack
+    }
+
+    /// Returns the number of matches this iterator has yet to yield, without
+    /// consuming it.
+    ///
+    /// See [`Memchr::count_remaining`] for more details.
+    #[inline]
+    pub fn count_remaining(&self) -> usize {
+        memchr3_count(self.needle1, self.needle2, self.needle3, self.haystack)
+    }
 }
 
 impl<'a> Iterator for Memchr3<'a> {
@@ -171,3 +378,255 @@ impl<'a> DoubleEndedIterator for Memchr3<'a> {
         )
     }
 }
+
+/// An iterator for `memchr_set`.
+///
+/// This yields the position of every byte in the haystack that belongs to the
+/// set. Like `Memchr`, this iterator is double ended, and each match is
+/// reported exactly once.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, MemchrSet};
+///
+/// let delimiters = ByteSet::from_bytes(b",;");
+///
+/// let mut it = MemchrSet::new(&delimiters, b"a,b;c,d");
+/// assert_eq!(Some(1), it.next());
+/// assert_eq!(Some(5), it.next_back());
+/// assert_eq!(b"b;c", it.remainder());
+/// ```
+pub struct MemchrSet<'a> {
+    set: ByteSet,
+    haystack: &'a [u8],
+    position: usize,
+}
+
+impl<'a> MemchrSet<'a> {
+    /// Creates a new iterator that yields all positions of bytes in the set
+    /// in haystack.
+    #[inline]
+    pub const fn new(set: &ByteSet, haystack: &'a [u8]) -> MemchrSet<'a> {
+        MemchrSet { set: *set, haystack, position: 0 }
+    }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack following the last match
+    /// returned by `next`, and preceding the last match returned by
+    /// `next_back`. Neither of those matches are included.
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.haystack
+    }
+}
+
+impl<'a> Iterator for MemchrSet<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        iter_next!(self, memchr_set(&self.set, self.haystack))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.haystack.len()))
+    }
+}
+
+impl<'a> DoubleEndedIterator for MemchrSet<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        iter_next_back!(self, memrchr_set(&self.set, self.haystack))
+    }
+}
+
+/// A reverse iterator for `memchr_set`.
+///
+/// This yields the same matches as `MemchrSet`, but starting from the end of
+/// the haystack, and gives access to the
+/// [`remainder`](MemchrSetRev::remainder) of the haystack. Calling
+/// `next_back` yields matches from the start of the haystack.
+///
+/// # Example
+///
+/// ```
+/// use memchr::{ByteSet, MemchrSetRev};
+///
+/// let separators = ByteSet::from_bytes(b"/\\");
+///
+/// let mut it = MemchrSetRev::new(&separators, b"C:\\dir/file");
+/// assert_eq!(Some(6), it.next());
+/// assert_eq!(b"C:\\dir", it.remainder());
+/// ```
+pub struct MemchrSetRev<'a>(MemchrSet<'a>);
+
+impl<'a> MemchrSetRev<'a> {
+    /// Creates a new iterator that yields all positions of bytes in the set
+    /// in haystack, in reverse.
+    #[inline]
+    pub const fn new(set: &ByteSet, haystack: &'a [u8]) -> MemchrSetRev<'a> {
+        MemchrSetRev(MemchrSet::new(set, haystack))
+    }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack preceding the last match
+    /// returned by `next`, and following the last match returned by
+    /// `next_back`. Neither of those matches are included.
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.0.remainder()
+    }
+}
+
+impl<'a> Iterator for MemchrSetRev<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        self.0.next_back()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for MemchrSetRev<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// An iterator for `memchr_not`.
+///
+/// This yields the position of every byte in the haystack that is not equal
+/// to the needle. It is useful for visiting the data in a sparse buffer that
+/// is mostly padding, since runs of padding are skipped with the same
+/// vectorized routine as [`memchr_not`](crate::memchr_not). Like `Memchr`,
+/// this iterator is double ended, and each position is reported exactly once.
+///
+/// # Example
+///
+/// ```
+/// use memchr::MemchrNot;
+///
+/// let mut it = MemchrNot::new(b'\x00', b"\x00\x00a\x00\x00b\x00c\x00");
+/// assert_eq!(Some(2), it.next());
+/// assert_eq!(Some(7), it.next_back());
+/// assert_eq!(b"\x00\x00b\x00", it.remainder());
+/// ```
+pub struct MemchrNot<'a> {
+    needle: u8,
+    haystack: &'a [u8],
+    position: usize,
+}
+
+impl<'a> MemchrNot<'a> {
+    /// Creates a new iterator that yields all positions in haystack whose
+    /// byte is not equal to needle.
+    #[inline]
+    pub const fn new(needle: u8, haystack: &[u8]) -> MemchrNot<'_> {
+        MemchrNot { needle, haystack, position: 0 }
+    }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack following the last position
+    /// returned by `next`, and preceding the last position returned by
+    /// `next_back`. Neither of those positions are included.
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.haystack
+    }
+}
+
+impl<'a> Iterator for MemchrNot<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        iter_next!(self, memchr_not(self.needle, self.haystack))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.haystack.len()))
+    }
+}
+
+impl<'a> DoubleEndedIterator for MemchrNot<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        iter_next_back!(self, memrchr_not(self.needle, self.haystack))
+    }
+}
+
+/// A reverse iterator for `memchr_not`.
+///
+/// This yields the same positions as `MemchrNot`, but starting from the end
+/// of the haystack, and gives access to the
+/// [`remainder`](MemchrNotRev::remainder) of the haystack. Calling
+/// `next_back` yields positions from the start of the haystack.
+///
+/// # Example
+///
+/// This shows how to find where the trailing padding of a buffer begins.
+///
+/// ```
+/// use memchr::MemchrNotRev;
+///
+/// let mut it = MemchrNotRev::new(b' ', b"name  ab   ");
+/// assert_eq!(Some(7), it.next());
+/// assert_eq!(b"name  a", it.remainder());
+/// ```
+pub struct MemchrNotRev<'a>(MemchrNot<'a>);
+
+impl<'a> MemchrNotRev<'a> {
+    /// Creates a new iterator that yields all positions in haystack whose
+    /// byte is not equal to needle, in reverse.
+    #[inline]
+    pub const fn new(needle: u8, haystack: &[u8]) -> MemchrNotRev<'_> {
+        MemchrNotRev(MemchrNot::new(needle, haystack))
+    }
+
+    /// Returns the portion of the haystack that has not yet been consumed by
+    /// this iterator.
+    ///
+    /// This is the part of the original haystack preceding the last position
+    /// returned by `next`, and following the last position returned by
+    /// `next_back`. Neither of those positions are included.
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.0.remainder()
+    }
+}
+
+impl<'a> Iterator for MemchrNotRev<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        self.0.next_back()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for MemchrNotRev<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
@@ -0,0 +1,189 @@
+use core::iter::Rev;
+
+use crate::{memchr, memrchr};
+
+/// Returns an iterator over the lines of a haystack, without their line
+/// terminators.
+///
+/// A line is terminated by either `\n` or `\r\n`, and neither is included in
+/// the lines yielded. A `\r` that is not immediately followed by `\n` is
+/// kept. The final line does not need a terminator, and a haystack ending
+/// with a terminator does not yield a trailing empty line. This matches the
+/// behavior of `str::lines`, but works on arbitrary bytes and finds each
+/// terminator with [`memchr`](crate::memchr).
+///
+/// # Example
+///
+/// ```
+/// use memchr::lines;
+///
+/// let haystack = b"foo\r\nbar\n\nbaz";
+/// let got: Vec<&[u8]> = lines(haystack).collect();
+/// assert_eq!(got, vec![&b"foo"[..], b"bar", b"", b"baz"]);
+/// ```
+#[inline]
+pub fn lines(haystack: &[u8]) -> Lines<'_> {
+    Lines(LinesWithTerminator::new(haystack))
+}
+
+/// Returns an iterator over the lines of a haystack in reverse order, without
+/// their line terminators.
+///
+/// This yields the same lines as [`lines`], but from last to first, finding
+/// each terminator with [`memrchr`](crate::memrchr). A haystack ending with a
+/// terminator does not yield an empty line first. This is useful for reading
+/// the end of a log file without scanning it from the start.
+///
+/// # Example
+///
+/// ```
+/// use memchr::rlines;
+///
+/// let got: Vec<&[u8]> = rlines(b"a\r\nb\nc\n").collect();
+/// assert_eq!(got, vec![&b"c"[..], b"b", b"a"]);
+/// ```
+#[inline]
+pub fn rlines(haystack: &[u8]) -> Rev<Lines<'_>> {
+    lines(haystack).rev()
+}
+
+/// Returns an iterator over the lines of a haystack, including their line
+/// terminators.
+///
+/// This is like [`lines`], except each line is yielded with its trailing
+/// `\n` (or `\r\n`) intact. The final line has no terminator if the haystack
+/// does not end with `\n`. Concatenating every line yielded gives back the
+/// original haystack.
+///
+/// # Example
+///
+/// ```
+/// use memchr::lines_with_terminator;
+///
+/// let haystack = b"foo\r\nbar\nbaz";
+/// let got: Vec<&[u8]> = lines_with_terminator(haystack).collect();
+/// assert_eq!(got, vec![&b"foo\r\n"[..], b"bar\n", b"baz"]);
+/// ```
+#[inline]
+pub fn lines_with_terminator(haystack: &[u8]) -> LinesWithTerminator<'_> {
+    LinesWithTerminator::new(haystack)
+}
+
+/// An iterator over the lines of a haystack, without their line terminators.
+///
+/// This is created by [`lines`]. It is double ended, so lines may also be
+/// consumed from the back, as [`rlines`] does.
+#[derive(Clone, Debug)]
+pub struct Lines<'a>(LinesWithTerminator<'a>);
+
+impl<'a> Lines<'a> {
+    /// Returns the portion of the haystack whose lines have not yet been
+    /// yielded.
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.0.remainder()
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        self.0.next().map(trim_terminator)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Lines<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+        self.0.next_back().map(trim_terminator)
+    }
+}
+
+/// An iterator over the lines of a haystack, including their line
+/// terminators.
+///
+/// This is created by [`lines_with_terminator`]. It is double ended, so lines
+/// may also be consumed from the back.
+#[derive(Clone, Debug)]
+pub struct LinesWithTerminator<'a> {
+    haystack: &'a [u8],
+}
+
+impl<'a> LinesWithTerminator<'a> {
+    #[inline]
+    fn new(haystack: &'a [u8]) -> LinesWithTerminator<'a> {
+        LinesWithTerminator { haystack }
+    }
+
+    /// Returns the portion of the haystack whose lines have not yet been
+    /// yielded.
+    #[inline]
+    pub fn remainder(&self) -> &'a [u8] {
+        self.haystack
+    }
+}
+
+impl<'a> Iterator for LinesWithTerminator<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.haystack.is_empty() {
+            return None;
+        }
+        let end = match memchr(b'\n', self.haystack) {
+            None => self.haystack.len(),
+            Some(i) => i + 1,
+        };
+        let (line, rest) = self.haystack.split_at(end);
+        self.haystack = rest;
+        Some(line)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.haystack.is_empty() {
+            (0, Some(0))
+        } else {
+            (1, Some(self.haystack.len()))
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for LinesWithTerminator<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a [u8]> {
+        if self.haystack.is_empty() {
+            return None;
+        }
+        // The last byte is either the final line's terminator or part of an
+        // unterminated final line, so it is excluded from the search.
+        let end = self.haystack.len() - 1;
+        let start = match memrchr(b'\n', &self.haystack[..end]) {
+            None => 0,
+            Some(i) => i + 1,
+        };
+        let (rest, line) = self.haystack.split_at(start);
+        self.haystack = rest;
+        Some(line)
+    }
+}
+
+/// Removes a trailing `\n` or `\r\n` from the given line, if present.
+#[inline]
+fn trim_terminator(line: &[u8]) -> &[u8] {
+    if line.ends_with(b"\r\n") {
+        &line[..line.len() - 2]
+    } else if line.ends_with(b"\n") {
+        &line[..line.len() - 1]
+    } else {
+        line
+    }
+}
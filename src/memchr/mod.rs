@@ -1,16 +1,39 @@
-use core::iter::Rev;
+use core::{cmp, iter::Rev, ops::Range};
 
-pub use self::iter::{Memchr, Memchr2, Memchr3};
+pub use self::{
+    byteset::ByteSet,
+    config::Config,
+    elem::{find_elem, SimdElement},
+    iter::{
+        Memchr, Memchr2, Memchr3, MemchrNot, MemchrNotRev, MemchrRev,
+        MemchrSet, MemchrSetRev,
+    },
+    lines::{
+        lines, lines_with_terminator, rlines, Lines, LinesWithTerminator,
+    },
+    range::RangeError,
+    trim::{
+        trim, trim_any, trim_end, trim_end_any, trim_start, trim_start_any,
+    },
+};
 
 // N.B. If you're looking for the cfg knobs for libc, see build.rs.
+mod byteset;
 #[cfg(memchr_libc)]
 mod c;
+mod config;
+mod elem;
 #[allow(dead_code)]
 pub mod fallback;
 mod iter;
+mod lines;
 pub mod naive;
+mod range;
+mod trim;
+#[cfg(all(not(miri), target_arch = "wasm32", memchr_runtime_wasm128))]
+mod wasm;
 #[cfg(all(not(miri), target_arch = "x86_64", memchr_runtime_simd))]
-mod x86;
+pub(crate) mod x86;
 
 /// An iterator over all occurrences of the needle in a haystack.
 #[inline]
@@ -97,9 +120,18 @@ pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
         x86::memchr(n1, haystack)
     }
 
+    #[cfg(all(target_arch = "wasm32", memchr_runtime_wasm128, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        // SAFETY: The simd128 target feature is enabled at compile time by
+        // virtue of the memchr_runtime_wasm128 cfg.
+        unsafe { wasm::memchr(n1, haystack) }
+    }
+
     #[cfg(all(
         memchr_libc,
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri),
     ))]
     #[inline(always)]
@@ -110,6 +142,7 @@ pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
     #[cfg(all(
         not(memchr_libc),
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri),
     ))]
     #[inline(always)]
@@ -124,6 +157,109 @@ pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
     }
 }
 
+/// Returns the implementation of [`memchr`] selected for the current CPU.
+///
+/// The function returned behaves exactly like [`memchr`], and is the same
+/// implementation that [`memchr`] itself would use. On targets where the
+/// implementation is selected at runtime (e.g., `x86_64` with the `std`
+/// feature enabled), CPU feature detection is performed by this call if it
+/// hasn't been already. Calling through the returned pointer then skips
+/// [`memchr`]'s own dispatch on every call.
+///
+/// This is an escape hatch for hot loops in which that dispatch has been
+/// measured to matter. In most cases, calling [`memchr`] directly is just as
+/// fast. On targets where the implementation is selected at compile time,
+/// this simply returns [`memchr`].
+///
+/// There is no equivalent for substring search, since a
+/// [`memmem::Finder`](crate::memmem::Finder) already selects its
+/// implementation once, when it is built.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_fn;
+///
+/// let find = memchr_fn();
+/// let lines: &[&[u8]] = &[b"a=1", b"", b"bb=22"];
+/// let got: Vec<Option<usize>> =
+///     lines.iter().map(|line| find(b'=', line)).collect();
+/// assert_eq!(got, vec![Some(1), None, Some(2)]);
+/// ```
+#[inline]
+pub fn memchr_fn() -> fn(u8, &[u8]) -> Option<usize> {
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp() -> fn(u8, &[u8]) -> Option<usize> {
+        x86::memchr_fn()
+    }
+
+    // Every other implementation is selected at compile time, so `memchr`
+    // already calls it directly.
+    #[cfg(not(all(target_arch = "x86_64", memchr_runtime_simd, not(miri))))]
+    #[inline(always)]
+    fn imp() -> fn(u8, &[u8]) -> Option<usize> {
+        memchr
+    }
+
+    imp()
+}
+
+/// Search for the first occurrence of a byte in each of the given haystacks.
+///
+/// One result is appended to `out` for each haystack, in order, where each
+/// result is what [`memchr`] would return for that haystack. `out` is not
+/// cleared first, so it can be reused across calls by clearing it.
+///
+/// This is useful when searching a large number of small haystacks, such as
+/// the fields of records. Calling [`memchr`] in a loop selects the
+/// implementation to use (e.g., based on the CPU features detected at
+/// runtime) on every call. This selects it once for the whole batch instead.
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_batch;
+///
+/// let records = ["a=1", "bb=22", "ccc"];
+/// let mut out = vec![];
+/// memchr_batch(b'=', records.iter().map(|r| r.as_bytes()), &mut out);
+/// assert_eq!(out, vec![Some(1), Some(2), None]);
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn memchr_batch<'a, I: IntoIterator<Item = &'a [u8]>>(
+    needle: u8,
+    haystacks: I,
+    out: &mut Vec<Option<usize>>,
+) {
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp<'a>(
+        n1: u8,
+        haystacks: impl Iterator<Item = &'a [u8]>,
+        out: &mut Vec<Option<usize>>,
+    ) {
+        x86::memchr_batch(n1, haystacks, out)
+    }
+
+    // Every other implementation is selected at compile time, so there's
+    // nothing to amortize.
+    #[cfg(not(all(target_arch = "x86_64", memchr_runtime_simd, not(miri))))]
+    #[inline(always)]
+    fn imp<'a>(
+        n1: u8,
+        haystacks: impl Iterator<Item = &'a [u8]>,
+        out: &mut Vec<Option<usize>>,
+    ) {
+        out.extend(haystacks.map(|h| memchr(n1, h)))
+    }
+
+    imp(needle, haystacks.into_iter(), out)
+}
+
 /// Like `memchr`, but searches for either of two bytes instead of just one.
 ///
 /// This returns the index corresponding to the first occurrence of `needle1`
@@ -161,8 +297,17 @@ pub fn memchr2(needle1: u8, needle2: u8, haystack: &[u8]) -> Option<usize> {
         x86::memchr2(n1, n2, haystack)
     }
 
+    #[cfg(all(target_arch = "wasm32", memchr_runtime_wasm128, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+        // SAFETY: The simd128 target feature is enabled at compile time by
+        // virtue of the memchr_runtime_wasm128 cfg.
+        unsafe { wasm::memchr2(n1, n2, haystack) }
+    }
+
     #[cfg(all(
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri),
     ))]
     #[inline(always)]
@@ -219,8 +364,17 @@ pub fn memchr3(
         x86::memchr3(n1, n2, n3, haystack)
     }
 
+    #[cfg(all(target_arch = "wasm32", memchr_runtime_wasm128, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
+        // SAFETY: The simd128 target feature is enabled at compile time by
+        // virtue of the memchr_runtime_wasm128 cfg.
+        unsafe { wasm::memchr3(n1, n2, n3, haystack) }
+    }
+
     #[cfg(all(
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri),
     ))]
     #[inline(always)]
@@ -270,10 +424,19 @@ pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
         x86::memrchr(n1, haystack)
     }
 
+    #[cfg(all(target_arch = "wasm32", memchr_runtime_wasm128, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        // SAFETY: The simd128 target feature is enabled at compile time by
+        // virtue of the memchr_runtime_wasm128 cfg.
+        unsafe { wasm::memrchr(n1, haystack) }
+    }
+
     #[cfg(all(
         memchr_libc,
         target_os = "linux",
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri)
     ))]
     #[inline(always)]
@@ -284,6 +447,7 @@ pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
     #[cfg(all(
         not(all(memchr_libc, target_os = "linux")),
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri),
     ))]
     #[inline(always)]
@@ -335,8 +499,17 @@ pub fn memrchr2(needle1: u8, needle2: u8, haystack: &[u8]) -> Option<usize> {
         x86::memrchr2(n1, n2, haystack)
     }
 
+    #[cfg(all(target_arch = "wasm32", memchr_runtime_wasm128, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+        // SAFETY: The simd128 target feature is enabled at compile time by
+        // virtue of the memchr_runtime_wasm128 cfg.
+        unsafe { wasm::memrchr2(n1, n2, haystack) }
+    }
+
     #[cfg(all(
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri),
     ))]
     #[inline(always)]
@@ -393,8 +566,17 @@ pub fn memrchr3(
         x86::memrchr3(n1, n2, n3, haystack)
     }
 
+    #[cfg(all(target_arch = "wasm32", memchr_runtime_wasm128, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
+        // SAFETY: The simd128 target feature is enabled at compile time by
+        // virtue of the memchr_runtime_wasm128 cfg.
+        unsafe { wasm::memrchr3(n1, n2, n3, haystack) }
+    }
+
     #[cfg(all(
         not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(all(target_arch = "wasm32", memchr_runtime_wasm128)),
         not(miri),
     ))]
     #[inline(always)]
@@ -408,3 +590,1845 @@ pub fn memrchr3(
         imp(needle1, needle2, needle3, haystack)
     }
 }
+
+/// Like [`memchr2`], but also returns the byte that matched.
+///
+/// This returns the index of the first occurrence of either needle along
+/// with the value of the byte at that index, which is equal to `needle1` or
+/// `needle2`. This is convenient when the caller needs to branch on which
+/// needle was found, e.g., in a lexer.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr2_which;
+///
+/// let haystack = b"key: \"value\"";
+/// assert_eq!(memchr2_which(b'"', b':', haystack), Some((3, b':')));
+/// assert_eq!(memchr2_which(b'"', b'\'', &haystack[4..]), Some((1, b'"')));
+/// assert_eq!(memchr2_which(b'x', b'z', haystack), None);
+/// ```
+#[inline]
+pub fn memchr2_which(
+    needle1: u8,
+    needle2: u8,
+    haystack: &[u8],
+) -> Option<(usize, u8)> {
+    memchr2(needle1, needle2, haystack).map(|i| (i, haystack[i]))
+}
+
+/// Like [`memchr3`], but also returns the byte that matched.
+///
+/// This returns the index of the first occurrence of any of the needles
+/// along with the value of the byte at that index, which is equal to one of
+/// `needle1`, `needle2` or `needle3`. This is convenient when the caller needs
+/// to branch on which needle was found, e.g., in a lexer.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr3_which;
+///
+/// let haystack = b"a + (b * c)";
+/// match memchr3_which(b'+', b'*', b'(', haystack) {
+///     Some((i, b'+')) => assert_eq!(i, 2),
+///     other => panic!("unexpected: {:?}", other),
+/// }
+/// ```
+#[inline]
+pub fn memchr3_which(
+    needle1: u8,
+    needle2: u8,
+    needle3: u8,
+    haystack: &[u8],
+) -> Option<(usize, u8)> {
+    memchr3(needle1, needle2, needle3, haystack).map(|i| (i, haystack[i]))
+}
+
+/// Search for the first occurrence of a byte in a sub-range of a slice.
+///
+/// This is like [`memchr`], except that only `haystack[range]` is searched,
+/// and the index returned is relative to the start of `haystack` instead of
+/// the start of the range. This is useful when tracking absolute offsets into
+/// a buffer, since there is no need to add `range.start` back to the result.
+///
+/// # Panics
+///
+/// This panics if `range` is out of bounds for `haystack`, or if its start
+/// is greater than its end. This is the same condition under which
+/// `&haystack[range]` panics.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_in;
+///
+/// let haystack = b"key=value;key=other";
+/// assert_eq!(memchr_in(b'=', haystack, 9..haystack.len()), Some(13));
+/// assert_eq!(memchr_in(b'=', haystack, 4..9), None);
+/// ```
+#[inline]
+pub fn memchr_in(
+    needle: u8,
+    haystack: &[u8],
+    range: Range<usize>,
+) -> Option<usize> {
+    let start = range.start;
+    memchr(needle, &haystack[range]).map(|i| start + i)
+}
+
+/// Search for the last occurrence of a byte in a sub-range of a slice.
+///
+/// This is like [`memrchr`], except that only `haystack[range]` is searched,
+/// and the index returned is relative to the start of `haystack` instead of
+/// the start of the range. It is the reverse counterpart of [`memchr_in`].
+///
+/// # Panics
+///
+/// This panics if `range` is out of bounds for `haystack`, or if its start
+/// is greater than its end. This is the same condition under which
+/// `&haystack[range]` panics.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memrchr_in;
+///
+/// let haystack = b"key=value;key=other";
+/// assert_eq!(memrchr_in(b'=', haystack, 0..9), Some(3));
+/// assert_eq!(memrchr_in(b'=', haystack, 4..13), None);
+/// ```
+#[inline]
+pub fn memrchr_in(
+    needle: u8,
+    haystack: &[u8],
+    range: Range<usize>,
+) -> Option<usize> {
+    let start = range.start;
+    memrchr(needle, &haystack[range]).map(|i| start + i)
+}
+
+/// Search for the first occurrence of a byte in a sub-range of a slice,
+/// without panicking on an invalid range.
+///
+/// This is like [`memchr_in`], except that an error is returned instead of
+/// panicking when `range` is out of bounds for `haystack`, or when its start
+/// is greater than its end. This is useful when ranges come from untrusted
+/// input.
+///
+/// # Example
+///
+/// ```
+/// use memchr::try_memchr_in;
+///
+/// let haystack = b"key=value;key=other";
+/// assert_eq!(Ok(Some(13)), try_memchr_in(b'=', haystack, 9..19));
+/// assert!(try_memchr_in(b'=', haystack, 9..20).is_err());
+/// assert!(try_memchr_in(b'=', haystack, 9..4).is_err());
+/// ```
+#[inline]
+pub fn try_memchr_in(
+    needle: u8,
+    haystack: &[u8],
+    range: Range<usize>,
+) -> Result<Option<usize>, RangeError> {
+    RangeError::check(&range, haystack.len())?;
+    Ok(memchr_in(needle, haystack, range))
+}
+
+/// Search for the last occurrence of a byte in a sub-range of a slice,
+/// without panicking on an invalid range.
+///
+/// This is like [`memrchr_in`], except that an error is returned instead of
+/// panicking when `range` is out of bounds for `haystack`, or when its start
+/// is greater than its end. It is the reverse counterpart of
+/// [`try_memchr_in`].
+///
+/// # Example
+///
+/// ```
+/// use memchr::try_memrchr_in;
+///
+/// let haystack = b"key=value;key=other";
+/// assert_eq!(Ok(Some(3)), try_memrchr_in(b'=', haystack, 0..9));
+/// assert!(try_memrchr_in(b'=', haystack, 0..usize::MAX).is_err());
+/// ```
+#[inline]
+pub fn try_memrchr_in(
+    needle: u8,
+    haystack: &[u8],
+    range: Range<usize>,
+) -> Result<Option<usize>, RangeError> {
+    RangeError::check(&range, haystack.len())?;
+    Ok(memrchr_in(needle, haystack, range))
+}
+
+/// Search for the occurrence of a byte closest to the given position.
+///
+/// This searches outward from `pivot` in both directions, and returns the
+/// index of the occurrence of `needle` whose distance from `pivot` is
+/// smallest. An occurrence at `pivot` itself has distance zero. When the
+/// closest occurrences on either side are equally far away, the one to the
+/// left (i.e., the smaller index) is returned.
+///
+/// The search to the right of `pivot` runs first, and scans up to the nearest
+/// match on the right. The search to the left is then limited to the bytes
+/// that could still be closer, which is at most as many bytes again. When
+/// there is no match on the right, though, the entire right side is scanned
+/// and then the left side is searched without a limit. So in the worst case,
+/// this scans all of `haystack`.
+///
+/// # Panics
+///
+/// This panics if `pivot` is greater than `haystack.len()`.
+///
+/// # Example
+///
+/// This shows how to find the space nearest to a cursor position.
+///
+/// ```
+/// use memchr::memchr_nearest;
+///
+/// let haystack = b"foo bar  baz";
+/// assert_eq!(Some(7), memchr_nearest(b' ', haystack, 6));
+/// assert_eq!(Some(8), memchr_nearest(b' ', haystack, 10));
+/// // Spaces at 3 and 7 are both two bytes away from 5, so the left one wins.
+/// assert_eq!(Some(3), memchr_nearest(b' ', b"foo bar baz", 5));
+/// ```
+#[inline]
+pub fn memchr_nearest(
+    needle: u8,
+    haystack: &[u8],
+    pivot: usize,
+) -> Option<usize> {
+    match memchr(needle, &haystack[pivot..]) {
+        None => memrchr(needle, &haystack[..pivot]),
+        Some(distance) => {
+            // Ties go left, so an occurrence on the left that is exactly
+            // `distance` away from the pivot must still be considered.
+            let start = pivot.saturating_sub(distance);
+            Some(
+                memrchr_in(needle, haystack, start..pivot)
+                    .unwrap_or(pivot + distance),
+            )
+        }
+    }
+}
+
+/// Search for the first byte in a slice that belongs to the given set.
+///
+/// This returns the index corresponding to the first byte in `haystack` that
+/// is in `set`, or `None` if there is no such byte (which is always the case
+/// for an empty set). If an index is returned, it is guaranteed to be less
+/// than `usize::MAX`.
+///
+/// This is like [`memchr3`], but for any number of needles. For one, two or
+/// three needles, the dedicated routines are faster. On `x86_64`, this uses a
+/// vectorized membership test that works for sets of any size.
+///
+/// # Example
+///
+/// This shows how to find the end of a token.
+///
+/// ```
+/// use memchr::{memchr_set, ByteSet};
+///
+/// let mut delimiters = ByteSet::new();
+/// for &b in b" \t\n,;(){}" {
+///     delimiters.insert(b);
+/// }
+/// assert_eq!(memchr_set(&delimiters, b"foo(bar, baz)"), Some(3));
+/// assert_eq!(memchr_set(&delimiters, b"foo"), None);
+/// ```
+#[inline]
+pub fn memchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+        naive::memchr_set(set, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+        x86::memchr_set(set, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+        fallback::memchr_set(set, haystack)
+    }
+
+    imp(set, haystack)
+}
+
+/// Returns a bitmask of the positions of a byte in a short slice.
+///
+/// Bit `i` of the mask returned is set if and only if `haystack[i] == needle`.
+/// Only the first 64 bytes of `haystack` are examined, so for longer
+/// haystacks, this returns the mask of the first 64 positions and ignores
+/// the rest. Bits at or beyond `haystack.len()` are never set.
+///
+/// This is intended for parsers that process small fixed-size blocks and
+/// want to consume the raw comparison result, e.g., by counting or
+/// iterating over bits, instead of searching for one match at a time. On
+/// `x86_64`, the mask is computed with four SSE2 comparisons.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr_mask;
+///
+/// let mask = memchr_mask(b',', b"a,b,,c");
+/// assert_eq!(0b011010, mask);
+/// assert_eq!(3, mask.count_ones());
+/// assert_eq!(1, mask.trailing_zeros());
+///
+/// // Only the first 64 bytes are examined.
+/// let long = [b','; 100];
+/// assert_eq!(u64::MAX, memchr_mask(b',', &long));
+/// ```
+#[inline]
+pub fn memchr_mask(needle: u8, haystack: &[u8]) -> u64 {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> u64 {
+        naive::memchr_mask(n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> u64 {
+        x86::memchr_mask(n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> u64 {
+        fallback::memchr_mask(n1, haystack)
+    }
+
+    imp(needle, haystack)
+}
+
+/// Search for the last byte in a slice that belongs to the given set.
+///
+/// This returns the index corresponding to the last byte in `haystack` that
+/// is in `set`, or `None` if there is no such byte (which is always the case
+/// for an empty set). If an index is returned, it is guaranteed to be less
+/// than `usize::MAX`.
+///
+/// This is the reverse counterpart of [`memchr_set`], and uses the same
+/// vectorized membership test.
+///
+/// # Example
+///
+/// This shows how to find the start of the last token.
+///
+/// ```
+/// use memchr::{memrchr_set, ByteSet};
+///
+/// let delimiters = ByteSet::from_bytes(b" ,;()");
+///
+/// let haystack = b"foo(bar, baz)";
+/// assert_eq!(memrchr_set(&delimiters, &haystack[..12]), Some(8));
+/// assert_eq!(memrchr_set(&delimiters, b"foo"), None);
+/// ```
+#[inline]
+pub fn memrchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+        naive::memrchr_set(set, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+        x86::memrchr_set(set, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+        fallback::memrchr_set(set, haystack)
+    }
+
+    imp(set, haystack)
+}
+
+/// Splits a haystack on the first occurrence of a byte.
+///
+/// This returns the parts of the haystack before and after the first
+/// occurrence of `needle`, neither of which include `needle` itself. If
+/// `needle` does not occur in the haystack, then `None` is returned. This is
+/// like `str::split_once` with a single byte delimiter.
+///
+/// # Example
+///
+/// ```
+/// use memchr::split_once;
+///
+/// assert_eq!(split_once(b'=', b"key=a=b"), Some((&b"key"[..], &b"a=b"[..])));
+/// assert_eq!(split_once(b'=', b"key="), Some((&b"key"[..], &b""[..])));
+/// assert_eq!(split_once(b'=', b"key"), None);
+/// ```
+#[inline]
+pub fn split_once(needle: u8, haystack: &[u8]) -> Option<(&[u8], &[u8])> {
+    let i = memchr(needle, haystack)?;
+    Some((&haystack[..i], &haystack[i + 1..]))
+}
+
+/// Splits a haystack on the last occurrence of a byte.
+///
+/// This returns the parts of the haystack before and after the last
+/// occurrence of `needle`, neither of which include `needle` itself. If
+/// `needle` does not occur in the haystack, then `None` is returned. This is
+/// like `str::rsplit_once` with a single byte delimiter.
+///
+/// # Example
+///
+/// ```
+/// use memchr::rsplit_once;
+///
+/// assert_eq!(
+///     rsplit_once(b'/', b"usr/local/bin"),
+///     Some((&b"usr/local"[..], &b"bin"[..])),
+/// );
+/// assert_eq!(rsplit_once(b'/', b"bin"), None);
+/// ```
+#[inline]
+pub fn rsplit_once(needle: u8, haystack: &[u8]) -> Option<(&[u8], &[u8])> {
+    let i = memrchr(needle, haystack)?;
+    Some((&haystack[..i], &haystack[i + 1..]))
+}
+
+/// Search for the first byte in a slice that is not equal to the given byte.
+///
+/// This returns the index corresponding to the first byte in `haystack` that
+/// is not equal to `needle`, or `None` if every byte is equal to `needle` (or
+/// if `haystack` is empty). If an index is returned, it is guaranteed to be
+/// less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().position(|&b| b != needle)`, `memchr_not` will use a
+/// highly optimized routine that can be up to an order of magnitude faster in
+/// some cases. This is useful for quickly skipping over a run of identical
+/// bytes, such as padding.
+///
+/// # Example
+///
+/// This shows how to skip over leading zero padding.
+///
+/// ```
+/// use memchr::memchr_not;
+///
+/// let haystack = b"\x00\x00\x00\x00payload";
+/// assert_eq!(memchr_not(b'\x00', haystack), Some(4));
+/// assert_eq!(memchr_not(b'\x00', b"\x00\x00\x00"), None);
+/// ```
+#[inline]
+pub fn memchr_not(needle: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memchr_not(n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memchr_not(n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memchr_not(n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle, haystack)
+    }
+}
+
+/// Search for the last byte in a slice that is not equal to the given byte.
+///
+/// This returns the index corresponding to the last byte in `haystack` that
+/// is not equal to `needle`, or `None` if every byte is equal to `needle` (or
+/// if `haystack` is empty). If an index is returned, it is guaranteed to be
+/// less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().rposition(|&b| b != needle)`, `memrchr_not` will use a
+/// highly optimized routine that can be up to an order of magnitude faster in
+/// some cases.
+///
+/// # Example
+///
+/// This shows how to strip trailing padding.
+///
+/// ```
+/// use memchr::memrchr_not;
+///
+/// let haystack = b"payload    ";
+/// assert_eq!(memrchr_not(b' ', haystack), Some(6));
+/// ```
+#[inline]
+pub fn memrchr_not(needle: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memrchr_not(n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memrchr_not(n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memrchr_not(n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle, haystack)
+    }
+}
+
+/// Search for the first byte in a slice that falls in an inclusive range.
+///
+/// This returns the index corresponding to the first byte `b` in `haystack`
+/// such that `lo <= b && b <= hi`, or `None` if one is not found. When
+/// `lo > hi`, the range is empty and this always returns `None`. If an index
+/// is returned, it is guaranteed to be less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().position(|&b| lo <= b && b <= hi)`, `memchr_range` will
+/// use a highly optimized routine that can be up to an order of magnitude
+/// faster in some cases.
+///
+/// # Example
+///
+/// This shows how to find the first non-ASCII byte in a byte string.
+///
+/// ```
+/// use memchr::memchr_range;
+///
+/// let haystack = "foo☃bar".as_bytes();
+/// assert_eq!(memchr_range(0x80, 0xFF, haystack), Some(3));
+/// ```
+#[inline]
+pub fn memchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memchr_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memchr_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memchr_range(lo, hi, haystack)
+    }
+
+    if haystack.is_empty() || lo > hi {
+        None
+    } else {
+        imp(lo, hi, haystack)
+    }
+}
+
+/// Search for the last byte in a slice that falls in an inclusive range.
+///
+/// This returns the index corresponding to the last byte `b` in `haystack`
+/// such that `lo <= b && b <= hi`, or `None` if one is not found. When
+/// `lo > hi`, the range is empty and this always returns `None`. If an index
+/// is returned, it is guaranteed to be less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().rposition(|&b| lo <= b && b <= hi)`, `memrchr_range` will
+/// use a highly optimized routine that can be up to an order of magnitude
+/// faster in some cases.
+///
+/// # Example
+///
+/// This shows how to find the last ASCII digit in a byte string.
+///
+/// ```
+/// use memchr::memrchr_range;
+///
+/// let haystack = b"abc123xyz";
+/// assert_eq!(memrchr_range(b'0', b'9', haystack), Some(5));
+/// ```
+#[inline]
+pub fn memrchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memrchr_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memrchr_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memrchr_range(lo, hi, haystack)
+    }
+
+    if haystack.is_empty() || lo > hi {
+        None
+    } else {
+        imp(lo, hi, haystack)
+    }
+}
+
+/// Search for the first byte in a slice that falls outside an inclusive
+/// range.
+///
+/// This returns the index corresponding to the first byte `b` in `haystack`
+/// such that `b < lo || b > hi`, or `None` if every byte in `haystack` lies in
+/// `[lo, hi]`. When `lo > hi`, the range is empty and so this returns
+/// `Some(0)` for any non-empty haystack. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().position(|&b| b < lo || b > hi)`, `memchr_not_range` will
+/// use a highly optimized routine that can be up to an order of magnitude
+/// faster in some cases.
+///
+/// # Example
+///
+/// This shows how to find the end of a leading run of ASCII digits.
+///
+/// ```
+/// use memchr::memchr_not_range;
+///
+/// let haystack = b"12345abc";
+/// assert_eq!(memchr_not_range(b'0', b'9', haystack), Some(5));
+/// assert_eq!(memchr_not_range(b'0', b'9', b"12345"), None);
+/// ```
+#[inline]
+pub fn memchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memchr_not_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memchr_not_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memchr_not_range(lo, hi, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(lo, hi, haystack)
+    }
+}
+
+/// Search for the last byte in a slice that falls outside an inclusive range.
+///
+/// This returns the index corresponding to the last byte `b` in `haystack`
+/// such that `b < lo || b > hi`, or `None` if every byte in `haystack` lies in
+/// `[lo, hi]`. When `lo > hi`, the range is empty and so this returns the
+/// last index of any non-empty haystack. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().rposition(|&b| b < lo || b > hi)`, `memrchr_not_range`
+/// will use a highly optimized routine that can be up to an order of
+/// magnitude faster in some cases.
+///
+/// # Example
+///
+/// This shows how to find where a trailing run of ASCII digits begins.
+///
+/// ```
+/// use memchr::memrchr_not_range;
+///
+/// let haystack = b"abc12345";
+/// assert_eq!(memrchr_not_range(b'0', b'9', haystack), Some(2));
+/// ```
+#[inline]
+pub fn memrchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memrchr_not_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memrchr_not_range(lo, hi, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memrchr_not_range(lo, hi, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(lo, hi, haystack)
+    }
+}
+
+/// Search for the first ASCII whitespace byte in a slice.
+///
+/// ASCII whitespace is defined exactly as in `u8::is_ascii_whitespace`. That
+/// is, a byte is whitespace if it is one of a space (`\x20`), a horizontal
+/// tab (`\t`), a line feed (`\n`), a form feed (`\x0C`) or a carriage
+/// return (`\r`). Notably, a vertical tab (`\x0B`) is _not_ considered
+/// whitespace.
+///
+/// This returns the index corresponding to the first whitespace byte in
+/// `haystack`, or `None` if one is not found. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// # Example
+///
+/// This shows how to find the end of the first word in a byte string.
+///
+/// ```
+/// use memchr::memchr_ascii_whitespace;
+///
+/// let haystack = b"foo\tbar baz";
+/// assert_eq!(memchr_ascii_whitespace(haystack), Some(3));
+/// ```
+#[inline]
+pub fn memchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        naive::memchr_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        x86::memchr_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        fallback::memchr_ascii_whitespace(haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(haystack)
+    }
+}
+
+/// Search for the last ASCII whitespace byte in a slice.
+///
+/// ASCII whitespace is defined exactly as in `u8::is_ascii_whitespace`. See
+/// [`memchr_ascii_whitespace`] for more details.
+///
+/// This returns the index corresponding to the last whitespace byte in
+/// `haystack`, or `None` if one is not found. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memrchr_ascii_whitespace;
+///
+/// let haystack = b"foo\tbar baz";
+/// assert_eq!(memrchr_ascii_whitespace(haystack), Some(7));
+/// ```
+#[inline]
+pub fn memrchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        naive::memrchr_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        x86::memrchr_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        fallback::memrchr_ascii_whitespace(haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(haystack)
+    }
+}
+
+/// Search for the first byte in a slice that is not ASCII whitespace.
+///
+/// ASCII whitespace is defined exactly as in `u8::is_ascii_whitespace`. See
+/// [`memchr_ascii_whitespace`] for more details.
+///
+/// This returns the index corresponding to the first non-whitespace byte in
+/// `haystack`, or `None` if `haystack` consists entirely of whitespace. If an
+/// index is returned, it is guaranteed to be less than `usize::MAX`.
+///
+/// # Example
+///
+/// This shows how to trim leading whitespace from a byte string.
+///
+/// ```
+/// use memchr::memchr_not_ascii_whitespace;
+///
+/// let haystack = b" \r\n\tfoo";
+/// let start = memchr_not_ascii_whitespace(haystack).unwrap();
+/// assert_eq!(&haystack[start..], b"foo");
+/// ```
+#[inline]
+pub fn memchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        naive::memchr_not_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        x86::memchr_not_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        fallback::memchr_not_ascii_whitespace(haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(haystack)
+    }
+}
+
+/// Search for the last byte in a slice that is not ASCII whitespace.
+///
+/// ASCII whitespace is defined exactly as in `u8::is_ascii_whitespace`. See
+/// [`memchr_ascii_whitespace`] for more details.
+///
+/// This returns the index corresponding to the last non-whitespace byte in
+/// `haystack`, or `None` if `haystack` consists entirely of whitespace. If an
+/// index is returned, it is guaranteed to be less than `usize::MAX`.
+///
+/// # Example
+///
+/// This shows how to trim trailing whitespace from a byte string.
+///
+/// ```
+/// use memchr::memrchr_not_ascii_whitespace;
+///
+/// let haystack = b"foo \r\n";
+/// let end = memrchr_not_ascii_whitespace(haystack).unwrap();
+/// assert_eq!(&haystack[..end + 1], b"foo");
+/// ```
+#[inline]
+pub fn memrchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        naive::memrchr_not_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        x86::memrchr_not_ascii_whitespace(haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        fallback::memrchr_not_ascii_whitespace(haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(haystack)
+    }
+}
+
+/// Search for the first ASCII control character in a slice.
+///
+/// ASCII control characters are defined exactly as in `u8::is_ascii_control`.
+/// That is, a byte is a control character if it is less than `\x20` or if it
+/// is `\x7F` (delete). Notably, this includes whitespace like `\t`, `\n` and
+/// `\r`. Use [`find_ascii_control_except`] to allow some of them.
+///
+/// This returns the index corresponding to the first control character in
+/// `haystack`, or `None` if one is not found. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `haystack.iter().position(|b| b.is_ascii_control())`, this will use a
+/// vectorized routine that can be up to an order of magnitude faster in some
+/// cases.
+///
+/// # Example
+///
+/// This shows how to find an escape sequence in text that is about to be
+/// written to a terminal.
+///
+/// ```
+/// use memchr::find_ascii_control;
+///
+/// assert_eq!(find_ascii_control(b"hi \x1b[2J"), Some(3));
+/// assert_eq!(find_ascii_control(b"plain text"), None);
+/// ```
+#[inline]
+pub fn find_ascii_control(haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        naive::find_ascii_control(haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        x86::find_ascii_control(haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(haystack: &[u8]) -> Option<usize> {
+        fallback::find_ascii_control(haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(haystack)
+    }
+}
+
+/// Search for the first ASCII control character in a slice that is not in
+/// the given set of allowed bytes.
+///
+/// This is like [`find_ascii_control`], except that control characters in
+/// `allowed` are skipped over. Bytes in `allowed` that aren't control
+/// characters have no effect. This is useful for permitting whitespace such
+/// as `\t` and `\n`, which are usually fine in text.
+///
+/// Each allowed byte that is found restarts the vectorized search just after
+/// it, so this is fastest when allowed bytes are not too frequent.
+///
+/// # Example
+///
+/// ```
+/// use memchr::find_ascii_control_except;
+///
+/// let haystack = b"line 1\n\tline 2\x07\n";
+/// assert_eq!(find_ascii_control_except(b"\t\n", haystack), Some(14));
+/// assert_eq!(find_ascii_control_except(b"\t\n\x07", haystack), None);
+/// ```
+#[inline]
+pub fn find_ascii_control_except(
+    allowed: &[u8],
+    haystack: &[u8],
+) -> Option<usize> {
+    let mut at = 0;
+    while let Some(i) = find_ascii_control(&haystack[at..]) {
+        let i = at + i;
+        if !allowed.contains(&haystack[i]) {
+            return Some(i);
+        }
+        at = i + 1;
+    }
+    None
+}
+
+/// Returns the index of the first byte at which two slices differ.
+///
+/// This returns the smallest `i` such that `a[i] != b[i]`. If one slice is a
+/// strict prefix of the other, then they differ at the length of the shorter
+/// slice, which is returned. If the slices are equal, then `None` is
+/// returned.
+///
+/// While this is operationally the same as something like
+/// `a.iter().zip(b).position(|(x, y)| x != y)` (plus the check for unequal
+/// lengths), `mismatch` compares a vector of bytes at a time, which makes it
+/// much faster when the common prefix is long. This is useful for diffing
+/// buffers or checking whether a block has changed.
+///
+/// # Example
+///
+/// ```
+/// use memchr::mismatch;
+///
+/// assert_eq!(mismatch(b"foobar", b"foobaz"), Some(5));
+/// assert_eq!(mismatch(b"foo", b"foobar"), Some(3));
+/// assert_eq!(mismatch(b"foo", b"foo"), None);
+/// ```
+#[inline]
+pub fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(a: &[u8], b: &[u8]) -> Option<usize> {
+        naive::mismatch(a, b)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(a: &[u8], b: &[u8]) -> Option<usize> {
+        x86::mismatch(a, b)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(a: &[u8], b: &[u8]) -> Option<usize> {
+        fallback::mismatch(a, b)
+    }
+
+    let len = cmp::min(a.len(), b.len());
+    match imp(&a[..len], &b[..len]) {
+        None if a.len() != b.len() => Some(len),
+        result => result,
+    }
+}
+
+/// Search for the nth occurrence of a byte in a slice, starting at `0`.
+///
+/// This returns the index corresponding to the nth occurrence of `needle` in
+/// `haystack`, where `n = 0` corresponds to the first occurrence. If there are
+/// `n` or fewer occurrences of `needle`, then `None` is returned. If an index
+/// is returned, it is guaranteed to be less than `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `memchr_iter(needle, haystack).nth(n)`, `memchr_nth` avoids computing the
+/// position of every preceding match. Namely, it counts matches a vector at a
+/// time and only computes a position once it finds the vector containing the
+/// nth occurrence.
+///
+/// # Example
+///
+/// This shows how to find the position of the third line terminator.
+///
+/// ```
+/// use memchr::memchr_nth;
+///
+/// let haystack = b"foo\nbar\nbaz\nquux\n";
+/// assert_eq!(memchr_nth(2, b'\n', haystack), Some(11));
+/// assert_eq!(memchr_nth(4, b'\n', haystack), None);
+/// ```
+#[inline]
+pub fn memchr_nth(n: usize, needle: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memchr_nth(n, n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memchr_nth(n, n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memchr_nth(n, n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(n, needle, haystack)
+    }
+}
+
+/// Search for the nth occurrence of a byte in a slice, starting at `0` from
+/// the end of the slice.
+///
+/// This returns the index corresponding to the nth-to-last occurrence of
+/// `needle` in `haystack`, where `n = 0` corresponds to the last occurrence.
+/// If there are `n` or fewer occurrences of `needle`, then `None` is
+/// returned. If an index is returned, it is guaranteed to be less than
+/// `usize::MAX`.
+///
+/// While this is operationally the same as something like
+/// `memrchr_iter(needle, haystack).nth(n)`, `memrchr_nth` avoids computing
+/// the position of every intervening match.
+///
+/// # Example
+///
+/// This shows how to find the start of the last two lines.
+///
+/// ```
+/// use memchr::memrchr_nth;
+///
+/// let haystack = b"foo\nbar\nbaz\nquux";
+/// assert_eq!(memrchr_nth(1, b'\n', haystack), Some(7));
+/// ```
+#[inline]
+pub fn memrchr_nth(n: usize, needle: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+        naive::memrchr_nth(n, n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+        x86::memrchr_nth(n, n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+        fallback::memrchr_nth(n, n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(n, needle, haystack)
+    }
+}
+
+/// Write the positions of all occurrences of a byte in a slice to a buffer.
+///
+/// This writes the index of each occurrence of `needle` in `haystack` to
+/// `out`, in ascending order, and returns the number of indices written. No
+/// heap allocation is performed.
+///
+/// If `out` isn't big enough to hold every match, then this stops as soon as
+/// `out` is full and returns `out.len()`. Note that a return value equal to
+/// `out.len()` does not necessarily mean that more matches remain: the number
+/// of matches may be exactly `out.len()`. To find any remaining matches,
+/// search again starting just after the last position written, i.e., at
+/// `out[out.len() - 1] + 1`, and add that offset to the positions written by
+/// the subsequent search. The example below shows how to do this.
+///
+/// This is operationally the same as something like
+/// `memchr_iter(needle, haystack)` zipped with `out`, but it avoids the
+/// overhead of the iterator by writing every match in a vector at once.
+///
+/// # Example
+///
+/// This shows how to process all matches in fixed size chunks.
+///
+/// ```
+/// use memchr::memchr_all_into;
+///
+/// let haystack = b"a,b,c,d,e";
+/// let mut buf = [0; 2];
+/// let mut found = vec![];
+/// let mut at = 0;
+/// loop {
+///     let n = memchr_all_into(b',', &haystack[at..], &mut buf);
+///     found.extend(buf[..n].iter().map(|&i| at + i));
+///     if n < buf.len() {
+///         break;
+///     }
+///     // The buffer is full, so there may be more matches after the last
+///     // one we found.
+///     at += buf[n - 1] + 1;
+/// }
+/// assert_eq!(found, vec![1, 3, 5, 7]);
+/// ```
+#[inline]
+pub fn memchr_all_into(
+    needle: u8,
+    haystack: &[u8],
+    out: &mut [usize],
+) -> usize {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+        naive::memchr_all_into(n1, out, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+        x86::memchr_all_into(n1, out, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+        fallback::memchr_all_into(n1, out, haystack)
+    }
+
+    if haystack.is_empty() || out.is_empty() {
+        0
+    } else {
+        imp(needle, out, haystack)
+    }
+}
+
+/// Write the positions of all occurrences of a byte in a slice to a buffer,
+/// starting from the end of the slice.
+///
+/// This writes the index of each occurrence of `needle` in `haystack` to
+/// `out`, in _descending_ order (so that `out[0]` is the position of the last
+/// match), and returns the number of indices written. No heap allocation is
+/// performed.
+///
+/// If `out` isn't big enough to hold every match, then this stops as soon as
+/// `out` is full and returns `out.len()`. As with [`memchr_all_into`], a
+/// return value equal to `out.len()` means that more matches _may_ remain.
+/// To find them, search again in `haystack[..out[out.len() - 1]]`.
+///
+/// # Example
+///
+/// This shows how to find the positions of the last two line terminators.
+///
+/// ```
+/// use memchr::memrchr_all_into;
+///
+/// let haystack = b"foo\nbar\nbaz\nquux\n";
+/// let mut buf = [0; 2];
+/// assert_eq!(memrchr_all_into(b'\n', haystack, &mut buf), 2);
+/// assert_eq!(buf, [16, 11]);
+/// ```
+#[inline]
+pub fn memrchr_all_into(
+    needle: u8,
+    haystack: &[u8],
+    out: &mut [usize],
+) -> usize {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+        naive::memrchr_all_into(n1, out, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+        x86::memrchr_all_into(n1, out, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+        fallback::memrchr_all_into(n1, out, haystack)
+    }
+
+    if haystack.is_empty() || out.is_empty() {
+        0
+    } else {
+        imp(needle, out, haystack)
+    }
+}
+
+/// Returns the number of occurrences of a byte in a slice.
+///
+/// This is semantically equivalent to
+/// `memchr_iter(needle, haystack).count()`, but is usually much faster. In
+/// particular, since the position of each match never needs to be computed,
+/// the vectorized implementations can simply count the number of matching
+/// lanes in each vector and keep going.
+///
+/// # Example
+///
+/// This shows how to count the number of lines in a byte string.
+///
+/// ```
+/// use memchr::count;
+///
+/// let haystack = b"the quick\nbrown fox\njumps over\n";
+/// assert_eq!(count(b'\n', haystack), 3);
+/// ```
+#[inline]
+pub fn count(needle: u8, haystack: &[u8]) -> usize {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> usize {
+        naive::count(n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> usize {
+        x86::count(n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, haystack: &[u8]) -> usize {
+        fallback::count(n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        0
+    } else {
+        imp(needle, haystack)
+    }
+}
+
+/// Like `count`, but counts the bytes equal to either of two bytes.
+///
+/// This is semantically equivalent to
+/// `memchr2_iter(needle1, needle2, haystack).count()`. In particular, when
+/// `needle1 == needle2`, each matching byte is still only counted once.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr2_count;
+///
+/// let haystack = b"'single' and \"double\"";
+/// assert_eq!(memchr2_count(b'\'', b'"', haystack), 4);
+/// ```
+#[inline]
+pub fn memchr2_count(needle1: u8, needle2: u8, haystack: &[u8]) -> usize {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, haystack: &[u8]) -> usize {
+        naive::count2(n1, n2, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, haystack: &[u8]) -> usize {
+        x86::count2(n1, n2, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, haystack: &[u8]) -> usize {
+        fallback::count2(n1, n2, haystack)
+    }
+
+    if haystack.is_empty() {
+        0
+    } else {
+        imp(needle1, needle2, haystack)
+    }
+}
+
+/// Like `count`, but counts the bytes equal to any of three bytes.
+///
+/// This is semantically equivalent to
+/// `memchr3_iter(needle1, needle2, needle3, haystack).count()`. In
+/// particular, a byte equal to more than one of the needles is still only
+/// counted once.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memchr3_count;
+///
+/// let haystack = b"'single', \"double\" and `back`";
+/// assert_eq!(memchr3_count(b'\'', b'"', b'`', haystack), 6);
+/// ```
+#[inline]
+pub fn memchr3_count(
+    needle1: u8,
+    needle2: u8,
+    needle3: u8,
+    haystack: &[u8],
+) -> usize {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> usize {
+        naive::count3(n1, n2, n3, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> usize {
+        x86::count3(n1, n2, n3, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> usize {
+        fallback::count3(n1, n2, n3, haystack)
+    }
+
+    if haystack.is_empty() {
+        0
+    } else {
+        imp(needle1, needle2, needle3, haystack)
+    }
+}
+
+/// Search for the first occurrence of a 16-bit code unit in a slice.
+///
+/// This returns the index corresponding to the first occurrence of `needle` in
+/// `haystack`, or `None` if one is not found. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// Code units are compared as whole `u16` values, so this is useful for
+/// searching UTF-16 text or any other array of 16-bit values. While this is
+/// operationally the same as something like
+/// `haystack.iter().position(|&u| u == needle)`, `memchr_u16` will use a
+/// vectorized routine on targets that support it.
+///
+/// # Example
+///
+/// This shows how to find a character in a UTF-16 string. Note that only whole
+/// code units match: the byte `0x0A` in the high half of `'\u{0A00}'` is not
+/// a match for `'\n'`.
+///
+/// ```
+/// use memchr::memchr_u16;
+///
+/// let haystack: Vec<u16> = "\u{0A00} foo\nbar".encode_utf16().collect();
+/// assert_eq!(memchr_u16(b'\n' as u16, &haystack), Some(5));
+/// ```
+#[inline]
+pub fn memchr_u16(needle: u16, haystack: &[u16]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u16, haystack: &[u16]) -> Option<usize> {
+        naive::memchr_u16(n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u16, haystack: &[u16]) -> Option<usize> {
+        x86::memchr_u16(n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u16, haystack: &[u16]) -> Option<usize> {
+        fallback::memchr_u16(n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle, haystack)
+    }
+}
+
+/// Like `memchr_u16`, but searches for either of two code units.
+///
+/// This returns the index corresponding to the first occurrence of `needle1`
+/// or the first occurrence of `needle2` in `haystack` (whichever occurs
+/// earlier), or `None` if neither one is found. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// Code units are compared as whole `u16` values, so this is useful for
+/// searching UTF-16 text or any other array of 16-bit values. While this is
+/// operationally the same as something like
+/// `haystack.iter().position(|&u| u == needle1 || u == needle2)`,
+/// `memchr2_u16` will use a vectorized routine on targets that support it.
+///
+/// # Example
+///
+/// This shows how to find the first of two characters in a UTF-16 string.
+///
+/// ```
+/// use memchr::memchr2_u16;
+///
+/// let haystack: Vec<u16> = "the quick brown fox".encode_utf16().collect();
+/// assert_eq!(memchr2_u16(b'k' as u16, b'q' as u16, &haystack), Some(4));
+/// ```
+#[inline]
+pub fn memchr2_u16(
+    needle1: u16,
+    needle2: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+        naive::memchr2_u16(n1, n2, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+        x86::memchr2_u16(n1, n2, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+        fallback::memchr2_u16(n1, n2, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle1, needle2, haystack)
+    }
+}
+
+/// Like `memchr_u16`, but searches for any of three code units.
+///
+/// This returns the index corresponding to the first occurrence of `needle1`,
+/// the first occurrence of `needle2`, or the first occurrence of `needle3` in
+/// `haystack` (whichever occurs earliest), or `None` if none are found. If an
+/// index is returned, it is guaranteed to be less than `usize::MAX`.
+///
+/// Code units are compared as whole `u16` values, so this is useful for
+/// searching UTF-16 text or any other array of 16-bit values. While this is
+/// operationally the same as something like
+/// `haystack.iter().position(|&u| u == needle1 || u == needle2 ||
+/// u == needle3)`, `memchr3_u16` will use a vectorized routine on targets
+/// that support it.
+///
+/// # Example
+///
+/// This shows how to find the first of three characters in a UTF-16 string.
+///
+/// ```
+/// use memchr::memchr3_u16;
+///
+/// let haystack: Vec<u16> = "the quick brown fox".encode_utf16().collect();
+/// let (k, q, e) = (b'k' as u16, b'q' as u16, b'e' as u16);
+/// assert_eq!(memchr3_u16(k, q, e, &haystack), Some(2));
+/// ```
+#[inline]
+pub fn memchr3_u16(
+    needle1: u16,
+    needle2: u16,
+    needle3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, n3: u16, haystack: &[u16]) -> Option<usize> {
+        naive::memchr3_u16(n1, n2, n3, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, n3: u16, haystack: &[u16]) -> Option<usize> {
+        x86::memchr3_u16(n1, n2, n3, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, n3: u16, haystack: &[u16]) -> Option<usize> {
+        fallback::memchr3_u16(n1, n2, n3, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle1, needle2, needle3, haystack)
+    }
+}
+
+/// Search for the last occurrence of a 16-bit code unit in a slice.
+///
+/// This returns the index corresponding to the last occurrence of `needle` in
+/// `haystack`, or `None` if one is not found. If an index is returned, it is
+/// guaranteed to be less than `usize::MAX`.
+///
+/// Code units are compared as whole `u16` values, so this is useful for
+/// searching UTF-16 text or any other array of 16-bit values. While this is
+/// operationally the same as something like
+/// `haystack.iter().rposition(|&u| u == needle)`, `memrchr_u16` will use a
+/// vectorized routine on targets that support it.
+///
+/// # Example
+///
+/// This shows how to find the last position of a character in a UTF-16
+/// string.
+///
+/// ```
+/// use memchr::memrchr_u16;
+///
+/// let haystack: Vec<u16> = "the quick brown fox".encode_utf16().collect();
+/// assert_eq!(memrchr_u16(b'o' as u16, &haystack), Some(17));
+/// ```
+#[inline]
+pub fn memrchr_u16(needle: u16, haystack: &[u16]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u16, haystack: &[u16]) -> Option<usize> {
+        naive::memrchr_u16(n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u16, haystack: &[u16]) -> Option<usize> {
+        x86::memrchr_u16(n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u16, haystack: &[u16]) -> Option<usize> {
+        fallback::memrchr_u16(n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle, haystack)
+    }
+}
+
+/// Like `memrchr_u16`, but searches for either of two code units.
+///
+/// This returns the index corresponding to the last occurrence of `needle1` or
+/// the last occurrence of `needle2` in `haystack` (whichever occurs later), or
+/// `None` if neither one is found. If an index is returned, it is guaranteed
+/// to be less than `usize::MAX`.
+///
+/// Code units are compared as whole `u16` values, so this is useful for
+/// searching UTF-16 text or any other array of 16-bit values. While this is
+/// operationally the same as something like
+/// `haystack.iter().rposition(|&u| u == needle1 || u == needle2)`,
+/// `memrchr2_u16` will use a vectorized routine on targets that support it.
+///
+/// # Example
+///
+/// This shows how to find the last of two characters in a UTF-16 string.
+///
+/// ```
+/// use memchr::memrchr2_u16;
+///
+/// let haystack: Vec<u16> = "the quick brown fox".encode_utf16().collect();
+/// assert_eq!(memrchr2_u16(b'k' as u16, b'q' as u16, &haystack), Some(8));
+/// ```
+#[inline]
+pub fn memrchr2_u16(
+    needle1: u16,
+    needle2: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+        naive::memrchr2_u16(n1, n2, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+        x86::memrchr2_u16(n1, n2, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+        fallback::memrchr2_u16(n1, n2, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle1, needle2, haystack)
+    }
+}
+
+/// Like `memrchr_u16`, but searches for any of three code units.
+///
+/// This returns the index corresponding to the last occurrence of `needle1`,
+/// the last occurrence of `needle2`, or the last occurrence of `needle3` in
+/// `haystack` (whichever occurs later), or `None` if none are found. If an
+/// index is returned, it is guaranteed to be less than `usize::MAX`.
+///
+/// Code units are compared as whole `u16` values, so this is useful for
+/// searching UTF-16 text or any other array of 16-bit values. While this is
+/// operationally the same as something like
+/// `haystack.iter().rposition(|&u| u == needle1 || u == needle2 ||
+/// u == needle3)`, `memrchr3_u16` will use a vectorized routine on targets
+/// that support it.
+///
+/// # Example
+///
+/// This shows how to find the last of three characters in a UTF-16 string.
+///
+/// ```
+/// use memchr::memrchr3_u16;
+///
+/// let haystack: Vec<u16> = "the quick brown fox".encode_utf16().collect();
+/// let (k, q, e) = (b'k' as u16, b'q' as u16, b'e' as u16);
+/// assert_eq!(memrchr3_u16(k, q, e, &haystack), Some(8));
+/// ```
+#[inline]
+pub fn memrchr3_u16(
+    needle1: u16,
+    needle2: u16,
+    needle3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, n3: u16, haystack: &[u16]) -> Option<usize> {
+        naive::memrchr3_u16(n1, n2, n3, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, n3: u16, haystack: &[u16]) -> Option<usize> {
+        x86::memrchr3_u16(n1, n2, n3, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u16, n2: u16, n3: u16, haystack: &[u16]) -> Option<usize> {
+        fallback::memrchr3_u16(n1, n2, n3, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle1, needle2, needle3, haystack)
+    }
+}
+
+/// Search for the first occurrence of a 32-bit element in a slice.
+///
+/// This is not exported directly. Instead, it is used by
+/// [`find_elem`](crate::find_elem) for 32-bit element types.
+#[inline]
+pub(crate) fn memchr_u32(needle: u32, haystack: &[u32]) -> Option<usize> {
+    #[cfg(miri)]
+    #[inline(always)]
+    fn imp(n1: u32, haystack: &[u32]) -> Option<usize> {
+        naive::memchr_u32(n1, haystack)
+    }
+
+    #[cfg(all(target_arch = "x86_64", memchr_runtime_simd, not(miri)))]
+    #[inline(always)]
+    fn imp(n1: u32, haystack: &[u32]) -> Option<usize> {
+        x86::memchr_u32(n1, haystack)
+    }
+
+    #[cfg(all(
+        not(all(target_arch = "x86_64", memchr_runtime_simd)),
+        not(miri),
+    ))]
+    #[inline(always)]
+    fn imp(n1: u32, haystack: &[u32]) -> Option<usize> {
+        fallback::memchr_u32(n1, haystack)
+    }
+
+    if haystack.is_empty() {
+        None
+    } else {
+        imp(needle, haystack)
+    }
+}
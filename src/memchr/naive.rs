@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+use super::ByteSet;
+
 pub fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
     haystack.iter().position(|&b| b == n1)
 }
@@ -23,3 +25,148 @@ pub fn memrchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
 pub fn memrchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
     haystack.iter().rposition(|&b| b == n1 || b == n2 || b == n3)
 }
+
+pub fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b != n1)
+}
+
+pub fn memrchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| b != n1)
+}
+
+pub fn memchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| lo <= b && b <= hi)
+}
+
+pub fn memchr_mask(n1: u8, haystack: &[u8]) -> u64 {
+    let mut mask = 0;
+    for (i, &b) in haystack.iter().take(64).enumerate() {
+        if b == n1 {
+            mask |= 1 << i;
+        }
+    }
+    mask
+}
+
+pub fn memchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| set.contains(b))
+}
+
+pub fn memrchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| set.contains(b))
+}
+
+pub fn memrchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| lo <= b && b <= hi)
+}
+
+pub fn memchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b < lo || b > hi)
+}
+
+pub fn memrchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| b < lo || b > hi)
+}
+
+pub fn memchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|b| b.is_ascii_whitespace())
+}
+
+pub fn memrchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|b| b.is_ascii_whitespace())
+}
+
+pub fn memchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|b| !b.is_ascii_whitespace())
+}
+
+pub fn memrchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|b| !b.is_ascii_whitespace())
+}
+
+pub fn find_ascii_control(haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|b| b.is_ascii_control())
+}
+
+pub fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter().zip(b).position(|(x, y)| x != y)
+}
+
+pub fn memchr_nth(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+    haystack
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == n1)
+        .nth(n)
+        .map(|(i, _)| i)
+}
+
+pub fn memrchr_nth(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+    haystack
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|&(_, &b)| b == n1)
+        .nth(n)
+        .map(|(i, _)| i)
+}
+
+pub fn memchr_all_into(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+    let it = haystack.iter().enumerate().filter(|&(_, &b)| b == n1);
+    out.iter_mut().zip(it).map(|(slot, (i, _))| *slot = i).count()
+}
+
+pub fn memrchr_all_into(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+    let it = haystack.iter().enumerate().rev().filter(|&(_, &b)| b == n1);
+    out.iter_mut().zip(it).map(|(slot, (i, _))| *slot = i).count()
+}
+
+pub fn count(n1: u8, haystack: &[u8]) -> usize {
+    haystack.iter().filter(|&&b| b == n1).count()
+}
+
+pub fn count2(n1: u8, n2: u8, haystack: &[u8]) -> usize {
+    haystack.iter().filter(|&&b| b == n1 || b == n2).count()
+}
+
+pub fn count3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> usize {
+    haystack.iter().filter(|&&b| b == n1 || b == n2 || b == n3).count()
+}
+
+pub fn memchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    haystack.iter().position(|&u| u == n1)
+}
+
+pub fn memchr2_u16(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+    haystack.iter().position(|&u| u == n1 || u == n2)
+}
+
+pub fn memchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    haystack.iter().position(|&u| u == n1 || u == n2 || u == n3)
+}
+
+pub fn memrchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    haystack.iter().rposition(|&u| u == n1)
+}
+
+pub fn memrchr2_u16(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+    haystack.iter().rposition(|&u| u == n1 || u == n2)
+}
+
+pub fn memrchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    haystack.iter().rposition(|&u| u == n1 || u == n2 || u == n3)
+}
+
+pub fn memchr_u32(n1: u32, haystack: &[u32]) -> Option<usize> {
+    haystack.iter().position(|&u| u == n1)
+}
@@ -0,0 +1,76 @@
+use core::{fmt, ops::Range};
+
+/// An error that occurs when a range is not valid for a haystack.
+///
+/// This is returned by fallible routines like
+/// [`try_memchr_in`](crate::try_memchr_in) when given a range whose start is
+/// greater than its end, or whose end is greater than the length of the
+/// haystack. These are exactly the ranges for which `&haystack[range]`
+/// panics.
+///
+/// When the `std` feature is enabled, this implements
+/// `std::error::Error`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RangeError {
+    range: Range<usize>,
+    haystack_len: usize,
+}
+
+impl RangeError {
+    /// Returns `Ok` when `range` is a valid range for a haystack of the given
+    /// length, and an error describing it otherwise.
+    #[inline]
+    pub(crate) fn check(
+        range: &Range<usize>,
+        haystack_len: usize,
+    ) -> Result<(), RangeError> {
+        if range.start <= range.end && range.end <= haystack_len {
+            Ok(())
+        } else {
+            Err(RangeError { range: range.clone(), haystack_len })
+        }
+    }
+
+    /// Returns the range that was rejected.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the length of the haystack that the range was rejected for.
+    #[inline]
+    pub fn haystack_len(&self) -> usize {
+        self.haystack_len
+    }
+
+    /// Returns true if and only if the range was rejected because its start
+    /// is greater than its end.
+    ///
+    /// Otherwise, the range was rejected because its end is greater than the
+    /// length of the haystack.
+    #[inline]
+    pub fn is_inverted(&self) -> bool {
+        self.range.start > self.range.end
+    }
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_inverted() {
+            write!(
+                f,
+                "range start {} is greater than range end {}",
+                self.range.start, self.range.end,
+            )
+        } else {
+            write!(
+                f,
+                "range end {} is out of bounds for haystack of length {}",
+                self.range.end, self.haystack_len,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RangeError {}
@@ -0,0 +1,146 @@
+use crate::{memchr_not, memrchr_not};
+
+/// Returns the given haystack with every leading occurrence of `byte`
+/// removed.
+///
+/// If every byte in `haystack` is equal to `byte`, then an empty slice is
+/// returned. This uses [`memchr_not`](crate::memchr_not) to find the first
+/// byte to keep, so long runs are skipped quickly.
+///
+/// # Example
+///
+/// This shows how to strip leading NUL padding.
+///
+/// ```
+/// use memchr::trim_start;
+///
+/// assert_eq!(trim_start(b'\x00', b"\x00\x00abc\x00"), b"abc\x00");
+/// assert_eq!(trim_start(b'\x00', b"\x00\x00\x00"), b"");
+/// ```
+#[inline]
+pub fn trim_start(byte: u8, haystack: &[u8]) -> &[u8] {
+    match memchr_not(byte, haystack) {
+        None => &haystack[haystack.len()..],
+        Some(i) => &haystack[i..],
+    }
+}
+
+/// Returns the given haystack with every trailing occurrence of `byte`
+/// removed.
+///
+/// If every byte in `haystack` is equal to `byte`, then an empty slice is
+/// returned. This uses [`memrchr_not`](crate::memrchr_not) to find the last
+/// byte to keep, so long runs are skipped quickly.
+///
+/// # Example
+///
+/// ```
+/// use memchr::trim_end;
+///
+/// assert_eq!(trim_end(b' ', b"  abc   "), b"  abc");
+/// ```
+#[inline]
+pub fn trim_end(byte: u8, haystack: &[u8]) -> &[u8] {
+    match memrchr_not(byte, haystack) {
+        None => &haystack[..0],
+        Some(i) => &haystack[..i + 1],
+    }
+}
+
+/// Returns the given haystack with every leading and trailing occurrence of
+/// `byte` removed.
+///
+/// This is equivalent to `trim_end(byte, trim_start(byte, haystack))`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::trim;
+///
+/// assert_eq!(trim(b'-', b"--a-b--"), b"a-b");
+/// ```
+#[inline]
+pub fn trim(byte: u8, haystack: &[u8]) -> &[u8] {
+    trim_end(byte, trim_start(byte, haystack))
+}
+
+/// Returns the given haystack with every leading byte that is in `set`
+/// removed.
+///
+/// If every byte in `haystack` is in `set`, then an empty slice is returned.
+/// If `set` is empty, then `haystack` is returned unchanged.
+///
+/// # Example
+///
+/// This shows how to strip leading ASCII whitespace.
+///
+/// ```
+/// use memchr::trim_start_any;
+///
+/// assert_eq!(trim_start_any(b" \t\r\n", b"\r\n\t foo \n"), b"foo \n");
+/// ```
+#[inline]
+pub fn trim_start_any<'h>(set: &[u8], haystack: &'h [u8]) -> &'h [u8] {
+    let set = ByteSet::new(set);
+    match haystack.iter().position(|&b| !set.contains(b)) {
+        None => &haystack[haystack.len()..],
+        Some(i) => &haystack[i..],
+    }
+}
+
+/// Returns the given haystack with every trailing byte that is in `set`
+/// removed.
+///
+/// If every byte in `haystack` is in `set`, then an empty slice is returned.
+/// If `set` is empty, then `haystack` is returned unchanged.
+///
+/// # Example
+///
+/// ```
+/// use memchr::trim_end_any;
+///
+/// assert_eq!(trim_end_any(b"\r\n", b"line\r\n\r\n"), b"line");
+/// ```
+#[inline]
+pub fn trim_end_any<'h>(set: &[u8], haystack: &'h [u8]) -> &'h [u8] {
+    let set = ByteSet::new(set);
+    match haystack.iter().rposition(|&b| !set.contains(b)) {
+        None => &haystack[..0],
+        Some(i) => &haystack[..i + 1],
+    }
+}
+
+/// Returns the given haystack with every leading and trailing byte that is
+/// in `set` removed.
+///
+/// This is equivalent to `trim_end_any(set, trim_start_any(set, haystack))`.
+///
+/// # Example
+///
+/// ```
+/// use memchr::trim_any;
+///
+/// assert_eq!(trim_any(b"\x00 ", b"\x00 a b \x00\x00"), b"a b");
+/// ```
+#[inline]
+pub fn trim_any<'h>(set: &[u8], haystack: &'h [u8]) -> &'h [u8] {
+    trim_end_any(set, trim_start_any(set, haystack))
+}
+
+/// A set of bytes, represented as a 256-bit bitset.
+struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    fn new(bytes: &[u8]) -> ByteSet {
+        let mut set = ByteSet([0; 4]);
+        for &b in bytes {
+            set.0[(b >> 6) as usize] |= 1 << (b & 63);
+        }
+        set
+    }
+
+    #[inline(always)]
+    fn contains(&self, byte: u8) -> bool {
+        self.0[(byte >> 6) as usize] & (1 << (byte & 63)) != 0
+    }
+}
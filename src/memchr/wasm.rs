@@ -0,0 +1,519 @@
+use core::{arch::wasm32::*, mem::size_of};
+
+use crate::arch::{record_backend, Backend};
+
+const VECTOR_SIZE: usize = size_of::<v128>();
+
+// The number of bytes to loop at in one iteration of memchr/memrchr.
+const LOOP_SIZE: usize = 4 * VECTOR_SIZE;
+
+// The number of bytes to loop at in one iteration of memchr2/memrchr2 and
+// memchr3/memrchr3.
+const LOOP_SIZE2: usize = 2 * VECTOR_SIZE;
+
+// The routines below follow the same structure as the SSE2 routines in
+// x86/sse2.rs, so see the comments there for a description of the algorithm.
+// The main difference is that wasm has no notion of aligned loads, so we
+// don't bother aligning the pointer before entering the main loop.
+
+#[target_feature(enable = "simd128")]
+pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = u8x16_splat(n1);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        record_backend(Backend::Scalar);
+        while ptr < end_ptr {
+            if *ptr == n1 {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+    record_backend(Backend::Simd128);
+
+    while sub(end_ptr, ptr) >= LOOP_SIZE {
+        let a = v128_load(ptr as *const v128);
+        let b = v128_load(ptr.add(VECTOR_SIZE) as *const v128);
+        let c = v128_load(ptr.add(2 * VECTOR_SIZE) as *const v128);
+        let d = v128_load(ptr.add(3 * VECTOR_SIZE) as *const v128);
+        let eqa = u8x16_eq(vn1, a);
+        let eqb = u8x16_eq(vn1, b);
+        let eqc = u8x16_eq(vn1, c);
+        let eqd = u8x16_eq(vn1, d);
+        let or1 = v128_or(eqa, eqb);
+        let or2 = v128_or(eqc, eqd);
+        if v128_any_true(v128_or(or1, or2)) {
+            let mut at = sub(ptr, start_ptr);
+            let mask = u8x16_bitmask(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = u8x16_bitmask(eqb);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = u8x16_bitmask(eqc);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = u8x16_bitmask(eqd);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(LOOP_SIZE);
+    }
+    while sub(end_ptr, ptr) >= VECTOR_SIZE {
+        if let Some(i) = forward_search1(start_ptr, end_ptr, ptr, vn1) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        ptr = end_ptr.sub(VECTOR_SIZE);
+        return forward_search1(start_ptr, end_ptr, ptr, vn1);
+    }
+    None
+}
+
+#[target_feature(enable = "simd128")]
+pub unsafe fn memchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = u8x16_splat(n1);
+    let vn2 = u8x16_splat(n2);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if *ptr == n1 || *ptr == n2 {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    while sub(end_ptr, ptr) >= LOOP_SIZE2 {
+        let a = v128_load(ptr as *const v128);
+        let b = v128_load(ptr.add(VECTOR_SIZE) as *const v128);
+        let eqa = v128_or(u8x16_eq(vn1, a), u8x16_eq(vn2, a));
+        let eqb = v128_or(u8x16_eq(vn1, b), u8x16_eq(vn2, b));
+        if v128_any_true(v128_or(eqa, eqb)) {
+            let at = sub(ptr, start_ptr);
+            let mask = u8x16_bitmask(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+            let mask = u8x16_bitmask(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + VECTOR_SIZE + forward_pos(mask));
+        }
+        ptr = ptr.add(LOOP_SIZE2);
+    }
+    while sub(end_ptr, ptr) >= VECTOR_SIZE {
+        if let Some(i) = forward_search2(start_ptr, end_ptr, ptr, vn1, vn2) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        ptr = end_ptr.sub(VECTOR_SIZE);
+        return forward_search2(start_ptr, end_ptr, ptr, vn1, vn2);
+    }
+    None
+}
+
+#[target_feature(enable = "simd128")]
+pub unsafe fn memchr3(
+    n1: u8,
+    n2: u8,
+    n3: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    let vn1 = u8x16_splat(n1);
+    let vn2 = u8x16_splat(n2);
+    let vn3 = u8x16_splat(n3);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if *ptr == n1 || *ptr == n2 || *ptr == n3 {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    while sub(end_ptr, ptr) >= LOOP_SIZE2 {
+        let a = v128_load(ptr as *const v128);
+        let b = v128_load(ptr.add(VECTOR_SIZE) as *const v128);
+        let eqa = eq3(vn1, vn2, vn3, a);
+        let eqb = eq3(vn1, vn2, vn3, b);
+        if v128_any_true(v128_or(eqa, eqb)) {
+            let at = sub(ptr, start_ptr);
+            let mask = u8x16_bitmask(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+            let mask = u8x16_bitmask(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + VECTOR_SIZE + forward_pos(mask));
+        }
+        ptr = ptr.add(LOOP_SIZE2);
+    }
+    while sub(end_ptr, ptr) >= VECTOR_SIZE {
+        if let Some(i) =
+            forward_search3(start_ptr, end_ptr, ptr, vn1, vn2, vn3)
+        {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        ptr = end_ptr.sub(VECTOR_SIZE);
+        return forward_search3(start_ptr, end_ptr, ptr, vn1, vn2, vn3);
+    }
+    None
+}
+
+#[target_feature(enable = "simd128")]
+pub unsafe fn memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = u8x16_splat(n1);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        record_backend(Backend::Scalar);
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if *ptr == n1 {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+    record_backend(Backend::Simd128);
+
+    while sub(ptr, start_ptr) >= LOOP_SIZE {
+        ptr = ptr.sub(LOOP_SIZE);
+        let a = v128_load(ptr as *const v128);
+        let b = v128_load(ptr.add(VECTOR_SIZE) as *const v128);
+        let c = v128_load(ptr.add(2 * VECTOR_SIZE) as *const v128);
+        let d = v128_load(ptr.add(3 * VECTOR_SIZE) as *const v128);
+        let eqa = u8x16_eq(vn1, a);
+        let eqb = u8x16_eq(vn1, b);
+        let eqc = u8x16_eq(vn1, c);
+        let eqd = u8x16_eq(vn1, d);
+        let or1 = v128_or(eqa, eqb);
+        let or2 = v128_or(eqc, eqd);
+        if v128_any_true(v128_or(or1, or2)) {
+            let mut at = sub(ptr.add(3 * VECTOR_SIZE), start_ptr);
+            let mask = u8x16_bitmask(eqd);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = u8x16_bitmask(eqc);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = u8x16_bitmask(eqb);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = u8x16_bitmask(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while sub(ptr, start_ptr) >= VECTOR_SIZE {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) = reverse_search1(start_ptr, end_ptr, ptr, vn1) {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        return reverse_search1(start_ptr, end_ptr, start_ptr, vn1);
+    }
+    None
+}
+
+#[target_feature(enable = "simd128")]
+pub unsafe fn memrchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = u8x16_splat(n1);
+    let vn2 = u8x16_splat(n2);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if *ptr == n1 || *ptr == n2 {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+
+    while sub(ptr, start_ptr) >= LOOP_SIZE2 {
+        ptr = ptr.sub(LOOP_SIZE2);
+        let a = v128_load(ptr as *const v128);
+        let b = v128_load(ptr.add(VECTOR_SIZE) as *const v128);
+        let eqa = v128_or(u8x16_eq(vn1, a), u8x16_eq(vn2, a));
+        let eqb = v128_or(u8x16_eq(vn1, b), u8x16_eq(vn2, b));
+        if v128_any_true(v128_or(eqa, eqb)) {
+            let at = sub(ptr, start_ptr);
+            let mask = u8x16_bitmask(eqb);
+            if mask != 0 {
+                return Some(at + VECTOR_SIZE + reverse_pos(mask));
+            }
+            let mask = u8x16_bitmask(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while sub(ptr, start_ptr) >= VECTOR_SIZE {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) = reverse_search2(start_ptr, end_ptr, ptr, vn1, vn2) {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        return reverse_search2(start_ptr, end_ptr, start_ptr, vn1, vn2);
+    }
+    None
+}
+
+#[target_feature(enable = "simd128")]
+pub unsafe fn memrchr3(
+    n1: u8,
+    n2: u8,
+    n3: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    let vn1 = u8x16_splat(n1);
+    let vn2 = u8x16_splat(n2);
+    let vn3 = u8x16_splat(n3);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if *ptr == n1 || *ptr == n2 || *ptr == n3 {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+
+    while sub(ptr, start_ptr) >= LOOP_SIZE2 {
+        ptr = ptr.sub(LOOP_SIZE2);
+        let a = v128_load(ptr as *const v128);
+        let b = v128_load(ptr.add(VECTOR_SIZE) as *const v128);
+        let eqa = eq3(vn1, vn2, vn3, a);
+        let eqb = eq3(vn1, vn2, vn3, b);
+        if v128_any_true(v128_or(eqa, eqb)) {
+            let at = sub(ptr, start_ptr);
+            let mask = u8x16_bitmask(eqb);
+            if mask != 0 {
+                return Some(at + VECTOR_SIZE + reverse_pos(mask));
+            }
+            let mask = u8x16_bitmask(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while sub(ptr, start_ptr) >= VECTOR_SIZE {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) =
+            reverse_search3(start_ptr, end_ptr, ptr, vn1, vn2, vn3)
+        {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        return reverse_search3(start_ptr, end_ptr, start_ptr, vn1, vn2, vn3);
+    }
+    None
+}
+
+#[target_feature(enable = "simd128")]
+unsafe fn forward_search1(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: v128,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = v128_load(ptr as *const v128);
+    let mask = u8x16_bitmask(u8x16_eq(chunk, vn1));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "simd128")]
+unsafe fn forward_search2(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: v128,
+    vn2: v128,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = v128_load(ptr as *const v128);
+    let eq = v128_or(u8x16_eq(chunk, vn1), u8x16_eq(chunk, vn2));
+    let mask = u8x16_bitmask(eq);
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "simd128")]
+unsafe fn forward_search3(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: v128,
+    vn2: v128,
+    vn3: v128,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = v128_load(ptr as *const v128);
+    let mask = u8x16_bitmask(eq3(vn1, vn2, vn3, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "simd128")]
+unsafe fn reverse_search1(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: v128,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = v128_load(ptr as *const v128);
+    let mask = u8x16_bitmask(u8x16_eq(vn1, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "simd128")]
+unsafe fn reverse_search2(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: v128,
+    vn2: v128,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = v128_load(ptr as *const v128);
+    let eq = v128_or(u8x16_eq(chunk, vn1), u8x16_eq(chunk, vn2));
+    let mask = u8x16_bitmask(eq);
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "simd128")]
+unsafe fn reverse_search3(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: v128,
+    vn2: v128,
+    vn3: v128,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = v128_load(ptr as *const v128);
+    let mask = u8x16_bitmask(eq3(vn1, vn2, vn3, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+/// Returns a vector whose lanes are all ones wherever the corresponding lane
+/// in `chunk` is equal to any of `vn1`, `vn2` or `vn3`.
+#[target_feature(enable = "simd128")]
+unsafe fn eq3(vn1: v128, vn2: v128, vn3: v128, chunk: v128) -> v128 {
+    let or = v128_or(u8x16_eq(chunk, vn1), u8x16_eq(chunk, vn2));
+    v128_or(or, u8x16_eq(chunk, vn3))
+}
+
+/// Compute the position of the first matching byte from the given mask. The
+/// position returned is always in the range [0, 15].
+///
+/// The mask given is expected to be the result of u8x16_bitmask.
+fn forward_pos(mask: u16) -> usize {
+    mask.trailing_zeros() as usize
+}
+
+/// Compute the position of the last matching byte from the given mask. The
+/// position returned is always in the range [0, 15].
+///
+/// The mask given is expected to be the result of u8x16_bitmask.
+fn reverse_pos(mask: u16) -> usize {
+    VECTOR_SIZE - (mask.leading_zeros() as usize) - 1
+}
+
+/// Subtract `b` from `a` and return the difference. `a` should be greater than
+/// or equal to `b`.
+fn sub(a: *const u8, b: *const u8) -> usize {
+    debug_assert!(a >= b);
+    (a as usize) - (b as usize)
+}
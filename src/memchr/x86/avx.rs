@@ -1,6 +1,10 @@
 use core::{arch::x86_64::*, cmp, mem::size_of};
 
-use super::sse2;
+use super::{
+    sse2,
+    wide::{self, Vector},
+};
+use crate::arch::{record_backend, Backend};
 
 const VECTOR_SIZE: usize = size_of::<__m256i>();
 const VECTOR_ALIGN: usize = VECTOR_SIZE - 1;
@@ -71,6 +75,7 @@ pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
         // suggests this completely avoids touching the AVX vectors.
         return sse2::memchr(n1, haystack);
     }
+    record_backend(Backend::Avx2);
 
     let vn1 = _mm256_set1_epi8(n1 as i8);
     let loop_size = cmp::min(LOOP_SIZE, haystack.len());
@@ -312,6 +317,7 @@ pub unsafe fn memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
     let mut ptr = end_ptr;
 
     if haystack.len() < VECTOR_SIZE {
+        record_backend(Backend::Scalar);
         while ptr > start_ptr {
             ptr = ptr.offset(-1);
             if *ptr == n1 {
@@ -320,6 +326,7 @@ pub unsafe fn memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
         }
         return None;
     }
+    record_backend(Backend::Avx2);
 
     ptr = ptr.sub(VECTOR_SIZE);
     if let Some(i) = reverse_search1(start_ptr, end_ptr, ptr, vn1) {
@@ -529,6 +536,1303 @@ pub unsafe fn memrchr3(
     None
 }
 
+#[target_feature(enable = "avx2")]
+pub unsafe fn count(n1: u8, haystack: &[u8]) -> usize {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The avx implementation here is the same, but with
+    // 256-bit vectors instead of 128-bit vectors.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    if haystack.len() < VECTOR_SIZE {
+        // For small haystacks, defer to the SSE2 implementation. Codegen
+        // suggests this completely avoids touching the AVX vectors.
+        return sse2::count(n1, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+        let a = _mm256_loadu_si256(ptr as *const __m256i);
+        let b = _mm256_loadu_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let c = _mm256_loadu_si256(ptr.add(2 * VECTOR_SIZE) as *const __m256i);
+        let d = _mm256_loadu_si256(ptr.add(3 * VECTOR_SIZE) as *const __m256i);
+        let eqa = _mm256_cmpeq_epi8(vn1, a);
+        let eqb = _mm256_cmpeq_epi8(vn1, b);
+        let eqc = _mm256_cmpeq_epi8(vn1, c);
+        let eqd = _mm256_cmpeq_epi8(vn1, d);
+        count += count_mask(_mm256_movemask_epi8(eqa));
+        count += count_mask(_mm256_movemask_epi8(eqb));
+        count += count_mask(_mm256_movemask_epi8(eqc));
+        count += count_mask(_mm256_movemask_epi8(eqd));
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let eq = _mm256_cmpeq_epi8(vn1, chunk);
+        count += count_mask(_mm256_movemask_epi8(eq));
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        let already = VECTOR_SIZE - sub(end_ptr, ptr);
+        ptr = ptr.sub(already);
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        count += count_mask(((mask as u32) >> already) as i32);
+    }
+    count
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn count2(n1: u8, n2: u8, haystack: &[u8]) -> usize {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The avx implementation here is the same, but with
+    // 256-bit vectors instead of 128-bit vectors.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    if haystack.len() < VECTOR_SIZE {
+        // For small haystacks, defer to the SSE2 implementation. Codegen
+        // suggests this completely avoids touching the AVX vectors.
+        return sse2::count2(n1, n2, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let vn2 = _mm256_set1_epi8(n2 as i8);
+    let loop_size = cmp::min(LOOP_SIZE2, haystack.len());
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        let a = _mm256_loadu_si256(ptr as *const __m256i);
+        let b = _mm256_loadu_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = _mm256_or_si256(
+            _mm256_cmpeq_epi8(vn1, a),
+            _mm256_cmpeq_epi8(vn2, a),
+        );
+        let eqb = _mm256_or_si256(
+            _mm256_cmpeq_epi8(vn1, b),
+            _mm256_cmpeq_epi8(vn2, b),
+        );
+        count += count_mask(_mm256_movemask_epi8(eqa));
+        count += count_mask(_mm256_movemask_epi8(eqb));
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let eq = _mm256_or_si256(
+            _mm256_cmpeq_epi8(vn1, chunk),
+            _mm256_cmpeq_epi8(vn2, chunk),
+        );
+        count += count_mask(_mm256_movemask_epi8(eq));
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        let already = VECTOR_SIZE - sub(end_ptr, ptr);
+        ptr = ptr.sub(already);
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_or_si256(
+            _mm256_cmpeq_epi8(vn1, chunk),
+            _mm256_cmpeq_epi8(vn2, chunk),
+        ));
+        count += count_mask(((mask as u32) >> already) as i32);
+    }
+    count
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn count3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> usize {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The avx implementation here is the same, but with
+    // 256-bit vectors instead of 128-bit vectors.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+    let mut count = 0;
+
+    if haystack.len() < VECTOR_SIZE {
+        // For small haystacks, defer to the SSE2 implementation. Codegen
+        // suggests this completely avoids touching the AVX vectors.
+        return sse2::count3(n1, n2, n3, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let vn2 = _mm256_set1_epi8(n2 as i8);
+    let vn3 = _mm256_set1_epi8(n3 as i8);
+    let loop_size = cmp::min(LOOP_SIZE2, haystack.len());
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        let a = _mm256_loadu_si256(ptr as *const __m256i);
+        let b = _mm256_loadu_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = _mm256_or_si256(
+            _mm256_or_si256(
+                _mm256_cmpeq_epi8(vn1, a),
+                _mm256_cmpeq_epi8(vn2, a),
+            ),
+            _mm256_cmpeq_epi8(vn3, a),
+        );
+        let eqb = _mm256_or_si256(
+            _mm256_or_si256(
+                _mm256_cmpeq_epi8(vn1, b),
+                _mm256_cmpeq_epi8(vn2, b),
+            ),
+            _mm256_cmpeq_epi8(vn3, b),
+        );
+        count += count_mask(_mm256_movemask_epi8(eqa));
+        count += count_mask(_mm256_movemask_epi8(eqb));
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let eq = _mm256_or_si256(
+            _mm256_or_si256(
+                _mm256_cmpeq_epi8(vn1, chunk),
+                _mm256_cmpeq_epi8(vn2, chunk),
+            ),
+            _mm256_cmpeq_epi8(vn3, chunk),
+        );
+        count += count_mask(_mm256_movemask_epi8(eq));
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        let already = VECTOR_SIZE - sub(end_ptr, ptr);
+        ptr = ptr.sub(already);
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_or_si256(
+            _mm256_or_si256(
+                _mm256_cmpeq_epi8(vn1, chunk),
+                _mm256_cmpeq_epi8(vn2, chunk),
+            ),
+            _mm256_cmpeq_epi8(vn3, chunk),
+        ));
+        count += count_mask(((mask as u32) >> already) as i32);
+    }
+    count
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if *ptr != n1 {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    if let Some(i) = forward_search_not(start_ptr, end_ptr, ptr, vn1) {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = neq_lanes(vn1, a);
+        let eqb = neq_lanes(vn1, b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr, start_ptr);
+            let mask = _mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        if let Some(i) = forward_search_not(start_ptr, end_ptr, ptr, vn1) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_not(start_ptr, end_ptr, ptr, vn1);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if *ptr != n1 {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+
+    ptr = ptr.sub(VECTOR_SIZE);
+    if let Some(i) = reverse_search_not(start_ptr, end_ptr, ptr, vn1) {
+        return Some(i);
+    }
+
+    ptr = (end_ptr as usize & !VECTOR_ALIGN) as *const u8;
+    debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
+    while loop_size == LOOP_SIZE2 && ptr >= start_ptr.add(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        ptr = ptr.sub(loop_size);
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = neq_lanes(vn1, a);
+        let eqb = neq_lanes(vn1, b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr.add(VECTOR_SIZE), start_ptr);
+            let mask = _mm256_movemask_epi8(eqb);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while ptr >= start_ptr.add(VECTOR_SIZE) {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) = reverse_search_not(start_ptr, end_ptr, ptr, vn1) {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        debug_assert!(sub(ptr, start_ptr) < VECTOR_SIZE);
+        return reverse_search_not(start_ptr, end_ptr, start_ptr, vn1);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    let vlo = _mm256_set1_epi8(lo as i8);
+    let vhi = _mm256_set1_epi8(hi as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if lo <= *ptr && *ptr <= hi {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    if let Some(i) = forward_search_range(start_ptr, end_ptr, ptr, vlo, vhi) {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = range_lanes(vlo, vhi, a);
+        let eqb = range_lanes(vlo, vhi, b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr, start_ptr);
+            let mask = _mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        if let Some(i) =
+            forward_search_range(start_ptr, end_ptr, ptr, vlo, vhi)
+        {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_range(start_ptr, end_ptr, ptr, vlo, vhi);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    let vlo = _mm256_set1_epi8(lo as i8);
+    let vhi = _mm256_set1_epi8(hi as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if lo <= *ptr && *ptr <= hi {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+
+    ptr = ptr.sub(VECTOR_SIZE);
+    if let Some(i) = reverse_search_range(start_ptr, end_ptr, ptr, vlo, vhi) {
+        return Some(i);
+    }
+
+    ptr = (end_ptr as usize & !VECTOR_ALIGN) as *const u8;
+    debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
+    while loop_size == LOOP_SIZE2 && ptr >= start_ptr.add(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        ptr = ptr.sub(loop_size);
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = range_lanes(vlo, vhi, a);
+        let eqb = range_lanes(vlo, vhi, b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr.add(VECTOR_SIZE), start_ptr);
+            let mask = _mm256_movemask_epi8(eqb);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while ptr >= start_ptr.add(VECTOR_SIZE) {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) =
+            reverse_search_range(start_ptr, end_ptr, ptr, vlo, vhi)
+        {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        debug_assert!(sub(ptr, start_ptr) < VECTOR_SIZE);
+        return reverse_search_range(start_ptr, end_ptr, start_ptr, vlo, vhi);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_not_range(
+    lo: u8,
+    hi: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    let vlo = _mm256_set1_epi8(lo as i8);
+    let vhi = _mm256_set1_epi8(hi as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if *ptr < lo || *ptr > hi {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    if let Some(i) =
+        forward_search_not_range(start_ptr, end_ptr, ptr, vlo, vhi)
+    {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = not_range_lanes(vlo, vhi, a);
+        let eqb = not_range_lanes(vlo, vhi, b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr, start_ptr);
+            let mask = _mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        if let Some(i) =
+            forward_search_not_range(start_ptr, end_ptr, ptr, vlo, vhi)
+        {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_not_range(start_ptr, end_ptr, ptr, vlo, vhi);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_not_range(
+    lo: u8,
+    hi: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    let vlo = _mm256_set1_epi8(lo as i8);
+    let vhi = _mm256_set1_epi8(hi as i8);
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if *ptr < lo || *ptr > hi {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+
+    ptr = ptr.sub(VECTOR_SIZE);
+    if let Some(i) =
+        reverse_search_not_range(start_ptr, end_ptr, ptr, vlo, vhi)
+    {
+        return Some(i);
+    }
+
+    ptr = (end_ptr as usize & !VECTOR_ALIGN) as *const u8;
+    debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
+    while loop_size == LOOP_SIZE2 && ptr >= start_ptr.add(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        ptr = ptr.sub(loop_size);
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = not_range_lanes(vlo, vhi, a);
+        let eqb = not_range_lanes(vlo, vhi, b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr.add(VECTOR_SIZE), start_ptr);
+            let mask = _mm256_movemask_epi8(eqb);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while ptr >= start_ptr.add(VECTOR_SIZE) {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) =
+            reverse_search_not_range(start_ptr, end_ptr, ptr, vlo, vhi)
+        {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        debug_assert!(sub(ptr, start_ptr) < VECTOR_SIZE);
+        return reverse_search_not_range(
+            start_ptr, end_ptr, start_ptr, vlo, vhi,
+        );
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if (*ptr).is_ascii_whitespace() {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    if let Some(i) = forward_search_whitespace(start_ptr, end_ptr, ptr) {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = whitespace_lanes(a);
+        let eqb = whitespace_lanes(b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr, start_ptr);
+            let mask = _mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        if let Some(i) = forward_search_whitespace(start_ptr, end_ptr, ptr) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_whitespace(start_ptr, end_ptr, ptr);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if (*ptr).is_ascii_whitespace() {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+
+    ptr = ptr.sub(VECTOR_SIZE);
+    if let Some(i) = reverse_search_whitespace(start_ptr, end_ptr, ptr) {
+        return Some(i);
+    }
+
+    ptr = (end_ptr as usize & !VECTOR_ALIGN) as *const u8;
+    debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
+    while loop_size == LOOP_SIZE2 && ptr >= start_ptr.add(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        ptr = ptr.sub(loop_size);
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = whitespace_lanes(a);
+        let eqb = whitespace_lanes(b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr.add(VECTOR_SIZE), start_ptr);
+            let mask = _mm256_movemask_epi8(eqb);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while ptr >= start_ptr.add(VECTOR_SIZE) {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) = reverse_search_whitespace(start_ptr, end_ptr, ptr) {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        debug_assert!(sub(ptr, start_ptr) < VECTOR_SIZE);
+        return reverse_search_whitespace(start_ptr, end_ptr, start_ptr);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if !(*ptr).is_ascii_whitespace() {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    if let Some(i) = forward_search_not_whitespace(start_ptr, end_ptr, ptr) {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = not_whitespace_lanes(a);
+        let eqb = not_whitespace_lanes(b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr, start_ptr);
+            let mask = _mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        if let Some(i) = forward_search_not_whitespace(start_ptr, end_ptr, ptr)
+        {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_not_whitespace(start_ptr, end_ptr, ptr);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr > start_ptr {
+            ptr = ptr.offset(-1);
+            if !(*ptr).is_ascii_whitespace() {
+                return Some(sub(ptr, start_ptr));
+            }
+        }
+        return None;
+    }
+
+    ptr = ptr.sub(VECTOR_SIZE);
+    if let Some(i) = reverse_search_not_whitespace(start_ptr, end_ptr, ptr) {
+        return Some(i);
+    }
+
+    ptr = (end_ptr as usize & !VECTOR_ALIGN) as *const u8;
+    debug_assert!(start_ptr <= ptr && ptr <= end_ptr);
+    while loop_size == LOOP_SIZE2 && ptr >= start_ptr.add(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        ptr = ptr.sub(loop_size);
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = not_whitespace_lanes(a);
+        let eqb = not_whitespace_lanes(b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr.add(VECTOR_SIZE), start_ptr);
+            let mask = _mm256_movemask_epi8(eqb);
+            if mask != 0 {
+                return Some(at + reverse_pos(mask));
+            }
+
+            at -= VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqa);
+            debug_assert!(mask != 0);
+            return Some(at + reverse_pos(mask));
+        }
+    }
+    while ptr >= start_ptr.add(VECTOR_SIZE) {
+        ptr = ptr.sub(VECTOR_SIZE);
+        if let Some(i) = reverse_search_not_whitespace(start_ptr, end_ptr, ptr)
+        {
+            return Some(i);
+        }
+    }
+    if ptr > start_ptr {
+        debug_assert!(sub(ptr, start_ptr) < VECTOR_SIZE);
+        return reverse_search_not_whitespace(start_ptr, end_ptr, start_ptr);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn find_ascii_control(haystack: &[u8]) -> Option<usize> {
+    let len = haystack.len();
+    let loop_size = cmp::min(LOOP_SIZE2, len);
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        while ptr < end_ptr {
+            if (*ptr).is_ascii_control() {
+                return Some(sub(ptr, start_ptr));
+            }
+            ptr = ptr.offset(1);
+        }
+        return None;
+    }
+
+    if let Some(i) = forward_search_control(start_ptr, end_ptr, ptr) {
+        return Some(i);
+    }
+
+    ptr = ptr.add(VECTOR_SIZE - (start_ptr as usize & VECTOR_ALIGN));
+    debug_assert!(ptr > start_ptr && end_ptr.sub(VECTOR_SIZE) >= start_ptr);
+    while loop_size == LOOP_SIZE2 && ptr <= end_ptr.sub(loop_size) {
+        debug_assert_eq!(0, (ptr as usize) % VECTOR_SIZE);
+
+        let a = _mm256_load_si256(ptr as *const __m256i);
+        let b = _mm256_load_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let eqa = control_lanes(a);
+        let eqb = control_lanes(b);
+        if _mm256_movemask_epi8(_mm256_or_si256(eqa, eqb)) != 0 {
+            let mut at = sub(ptr, start_ptr);
+            let mask = _mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+
+            at += VECTOR_SIZE;
+            let mask = _mm256_movemask_epi8(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + forward_pos(mask));
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        if let Some(i) = forward_search_control(start_ptr, end_ptr, ptr) {
+            return Some(i);
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        ptr = ptr.sub(VECTOR_SIZE - sub(end_ptr, ptr));
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        return forward_search_control(start_ptr, end_ptr, ptr);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    // For a high level explanation of how this works, see the sse2
+    // implementation.
+    debug_assert_eq!(a.len(), b.len());
+    let len = a.len();
+    if len < VECTOR_SIZE {
+        return sse2::mismatch(a, b);
+    }
+
+    let (a, b) = (a.as_ptr(), b.as_ptr());
+    let mut at = 0;
+    while at + LOOP_SIZE2 <= len {
+        let eqa = eq_lanes(a.add(at), b.add(at));
+        let eqb = eq_lanes(a.add(at + VECTOR_SIZE), b.add(at + VECTOR_SIZE));
+        if _mm256_movemask_epi8(_mm256_and_si256(eqa, eqb)) != -1 {
+            let mask = !_mm256_movemask_epi8(eqa);
+            if mask != 0 {
+                return Some(at + forward_pos(mask));
+            }
+            let mask = !_mm256_movemask_epi8(eqb);
+            debug_assert!(mask != 0);
+            return Some(at + VECTOR_SIZE + forward_pos(mask));
+        }
+        at += LOOP_SIZE2;
+    }
+    while at + VECTOR_SIZE <= len {
+        if let Some(i) = forward_search_mismatch(a, b, at) {
+            return Some(i);
+        }
+        at += VECTOR_SIZE;
+    }
+    if at < len {
+        return forward_search_mismatch(a, b, len - VECTOR_SIZE);
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_nth(
+    mut n: usize,
+    n1: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The avx implementation here is the same, but with
+    // 256-bit vectors instead of 128-bit vectors.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        return sse2::memchr_nth(n, n1, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+        let a = _mm256_loadu_si256(ptr as *const __m256i);
+        let b = _mm256_loadu_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let c = _mm256_loadu_si256(ptr.add(2 * VECTOR_SIZE) as *const __m256i);
+        let d = _mm256_loadu_si256(ptr.add(3 * VECTOR_SIZE) as *const __m256i);
+        let count =
+            count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, a)))
+                + count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, b)))
+                + count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, c)))
+                + count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, d)));
+        if n < count {
+            // The match is in one of these four vectors, which the loop
+            // below will find.
+            break;
+        }
+        n -= count;
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let count = count_mask(mask);
+        if n < count {
+            return Some(sub(ptr, start_ptr) + forward_nth_pos(mask, n));
+        }
+        n -= count;
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        let already = VECTOR_SIZE - sub(end_ptr, ptr);
+        ptr = ptr.sub(already);
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let mask = (((mask as u32) >> already) << already) as i32;
+        if n < count_mask(mask) {
+            return Some(sub(ptr, start_ptr) + forward_nth_pos(mask, n));
+        }
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_nth(
+    mut n: usize,
+    n1: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The avx implementation here is the same, but with
+    // 256-bit vectors instead of 128-bit vectors.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+
+    if haystack.len() < VECTOR_SIZE {
+        return sse2::memrchr_nth(n, n1, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    while loop_size == LOOP_SIZE && ptr >= start_ptr.add(loop_size) {
+        let p = ptr.sub(loop_size);
+        let a = _mm256_loadu_si256(p as *const __m256i);
+        let b = _mm256_loadu_si256(p.add(VECTOR_SIZE) as *const __m256i);
+        let c = _mm256_loadu_si256(p.add(2 * VECTOR_SIZE) as *const __m256i);
+        let d = _mm256_loadu_si256(p.add(3 * VECTOR_SIZE) as *const __m256i);
+        let count =
+            count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, a)))
+                + count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, b)))
+                + count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, c)))
+                + count_mask(_mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, d)));
+        if n < count {
+            break;
+        }
+        n -= count;
+        ptr = p;
+    }
+    while ptr >= start_ptr.add(VECTOR_SIZE) {
+        ptr = ptr.sub(VECTOR_SIZE);
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let count = count_mask(mask);
+        if n < count {
+            return Some(sub(ptr, start_ptr) + reverse_nth_pos(mask, n));
+        }
+        n -= count;
+    }
+    if ptr > start_ptr {
+        let remaining = sub(ptr, start_ptr);
+        debug_assert!(remaining < VECTOR_SIZE);
+
+        let chunk = _mm256_loadu_si256(start_ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let mask = ((mask as u32) & ((1 << remaining) - 1)) as i32;
+        if n < count_mask(mask) {
+            return Some(reverse_nth_pos(mask, n));
+        }
+    }
+    None
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_all_into(
+    n1: u8,
+    out: &mut [usize],
+    haystack: &[u8],
+) -> usize {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The avx implementation here is the same, but with
+    // 256-bit vectors instead of 128-bit vectors.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+    let mut written = 0;
+
+    if out.is_empty() {
+        return 0;
+    }
+    if haystack.len() < VECTOR_SIZE {
+        return sse2::memchr_all_into(n1, out, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    while loop_size == LOOP_SIZE && ptr <= end_ptr.sub(loop_size) {
+        let a = _mm256_loadu_si256(ptr as *const __m256i);
+        let b = _mm256_loadu_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let c = _mm256_loadu_si256(ptr.add(2 * VECTOR_SIZE) as *const __m256i);
+        let d = _mm256_loadu_si256(ptr.add(3 * VECTOR_SIZE) as *const __m256i);
+        let eqa = _mm256_cmpeq_epi8(vn1, a);
+        let eqb = _mm256_cmpeq_epi8(vn1, b);
+        let eqc = _mm256_cmpeq_epi8(vn1, c);
+        let eqd = _mm256_cmpeq_epi8(vn1, d);
+        let or1 = _mm256_or_si256(eqa, eqb);
+        let or2 = _mm256_or_si256(eqc, eqd);
+        if _mm256_movemask_epi8(_mm256_or_si256(or1, or2)) != 0 {
+            let at = sub(ptr, start_ptr);
+            written =
+                forward_write(_mm256_movemask_epi8(eqa), at, out, written);
+            written = forward_write(
+                _mm256_movemask_epi8(eqb),
+                at + VECTOR_SIZE,
+                out,
+                written,
+            );
+            written = forward_write(
+                _mm256_movemask_epi8(eqc),
+                at + 2 * VECTOR_SIZE,
+                out,
+                written,
+            );
+            written = forward_write(
+                _mm256_movemask_epi8(eqd),
+                at + 3 * VECTOR_SIZE,
+                out,
+                written,
+            );
+            if written == out.len() {
+                return written;
+            }
+        }
+        ptr = ptr.add(loop_size);
+    }
+    while ptr <= end_ptr.sub(VECTOR_SIZE) {
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let at = sub(ptr, start_ptr);
+        written = forward_write(mask, at, out, written);
+        if written == out.len() {
+            return written;
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        debug_assert!(sub(end_ptr, ptr) < VECTOR_SIZE);
+        let already = VECTOR_SIZE - sub(end_ptr, ptr);
+        ptr = ptr.sub(already);
+        debug_assert_eq!(sub(end_ptr, ptr), VECTOR_SIZE);
+
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let mask = (((mask as u32) >> already) << already) as i32;
+        written = forward_write(mask, sub(ptr, start_ptr), out, written);
+    }
+    written
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_all_into(
+    n1: u8,
+    out: &mut [usize],
+    haystack: &[u8],
+) -> usize {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The avx implementation here is the same, but with
+    // 256-bit vectors instead of 128-bit vectors.
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+    let mut written = 0;
+
+    if out.is_empty() {
+        return 0;
+    }
+    if haystack.len() < VECTOR_SIZE {
+        return sse2::memrchr_all_into(n1, out, haystack);
+    }
+
+    let vn1 = _mm256_set1_epi8(n1 as i8);
+    let loop_size = cmp::min(LOOP_SIZE, haystack.len());
+    while loop_size == LOOP_SIZE && ptr >= start_ptr.add(loop_size) {
+        ptr = ptr.sub(loop_size);
+        let a = _mm256_loadu_si256(ptr as *const __m256i);
+        let b = _mm256_loadu_si256(ptr.add(VECTOR_SIZE) as *const __m256i);
+        let c = _mm256_loadu_si256(ptr.add(2 * VECTOR_SIZE) as *const __m256i);
+        let d = _mm256_loadu_si256(ptr.add(3 * VECTOR_SIZE) as *const __m256i);
+        let eqa = _mm256_cmpeq_epi8(vn1, a);
+        let eqb = _mm256_cmpeq_epi8(vn1, b);
+        let eqc = _mm256_cmpeq_epi8(vn1, c);
+        let eqd = _mm256_cmpeq_epi8(vn1, d);
+        let or1 = _mm256_or_si256(eqa, eqb);
+        let or2 = _mm256_or_si256(eqc, eqd);
+        if _mm256_movemask_epi8(_mm256_or_si256(or1, or2)) != 0 {
+            let at = sub(ptr, start_ptr);
+            written = reverse_write(
+                _mm256_movemask_epi8(eqd),
+                at + 3 * VECTOR_SIZE,
+                out,
+                written,
+            );
+            written = reverse_write(
+                _mm256_movemask_epi8(eqc),
+                at + 2 * VECTOR_SIZE,
+                out,
+                written,
+            );
+            written = reverse_write(
+                _mm256_movemask_epi8(eqb),
+                at + VECTOR_SIZE,
+                out,
+                written,
+            );
+            written =
+                reverse_write(_mm256_movemask_epi8(eqa), at, out, written);
+            if written == out.len() {
+                return written;
+            }
+        }
+    }
+    while ptr >= start_ptr.add(VECTOR_SIZE) {
+        ptr = ptr.sub(VECTOR_SIZE);
+        let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let at = sub(ptr, start_ptr);
+        written = reverse_write(mask, at, out, written);
+        if written == out.len() {
+            return written;
+        }
+    }
+    if ptr > start_ptr {
+        let remaining = sub(ptr, start_ptr);
+        debug_assert!(remaining < VECTOR_SIZE);
+
+        let chunk = _mm256_loadu_si256(start_ptr as *const __m256i);
+        let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vn1, chunk));
+        let mask = ((mask as u32) & ((1 << remaining) - 1)) as i32;
+        written = reverse_write(mask, 0, out, written);
+    }
+    written
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    if haystack.len() < <__m256i as Vector<u16>>::LANES {
+        return sse2::memchr_u16(n1, haystack);
+    }
+    wide::forward(wide::One(__m256i::splat(n1)), haystack)
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr2_u16(
+    n1: u16,
+    n2: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    if haystack.len() < <__m256i as Vector<u16>>::LANES {
+        return sse2::memchr2_u16(n1, n2, haystack);
+    }
+    wide::forward(wide::Two(__m256i::splat(n1), __m256i::splat(n2)), haystack)
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    if haystack.len() < <__m256i as Vector<u16>>::LANES {
+        return sse2::memchr3_u16(n1, n2, n3, haystack);
+    }
+    wide::forward(
+        wide::Three(
+            __m256i::splat(n1),
+            __m256i::splat(n2),
+            __m256i::splat(n3),
+        ),
+        haystack,
+    )
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    if haystack.len() < <__m256i as Vector<u16>>::LANES {
+        return sse2::memrchr_u16(n1, haystack);
+    }
+    wide::reverse(wide::One(__m256i::splat(n1)), haystack)
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr2_u16(
+    n1: u16,
+    n2: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    if haystack.len() < <__m256i as Vector<u16>>::LANES {
+        return sse2::memrchr2_u16(n1, n2, haystack);
+    }
+    wide::reverse(wide::Two(__m256i::splat(n1), __m256i::splat(n2)), haystack)
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memrchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    if haystack.len() < <__m256i as Vector<u16>>::LANES {
+        return sse2::memrchr3_u16(n1, n2, n3, haystack);
+    }
+    wide::reverse(
+        wide::Three(
+            __m256i::splat(n1),
+            __m256i::splat(n2),
+            __m256i::splat(n3),
+        ),
+        haystack,
+    )
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn memchr_u32(n1: u32, haystack: &[u32]) -> Option<usize> {
+    if haystack.len() < <__m256i as Vector<u32>>::LANES {
+        return sse2::memchr_u32(n1, haystack);
+    }
+    wide::forward(wide::One(__m256i::splat(n1)), haystack)
+}
+
 #[target_feature(enable = "avx2")]
 unsafe fn forward_search1(
     start_ptr: *const u8,
@@ -673,6 +1977,390 @@ unsafe fn reverse_search3(
     }
 }
 
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_not(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: __m256i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(neq_lanes(vn1, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn reverse_search_not(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vn1: __m256i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(neq_lanes(vn1, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+/// Return a vector where each lane is `0xFF` if the corresponding byte in
+/// `chunk` is not equal to the corresponding byte in `vn1`, and `0x00`
+/// otherwise.
+#[target_feature(enable = "avx2")]
+unsafe fn neq_lanes(vn1: __m256i, chunk: __m256i) -> __m256i {
+    _mm256_andnot_si256(_mm256_cmpeq_epi8(vn1, chunk), _mm256_set1_epi8(-1))
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_range(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vlo: __m256i,
+    vhi: __m256i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(range_lanes(vlo, vhi, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn reverse_search_range(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vlo: __m256i,
+    vhi: __m256i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(range_lanes(vlo, vhi, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+/// Return a vector where each lane is `0xFF` if the corresponding byte in
+/// `chunk` is in the inclusive range given by the corresponding bytes in
+/// `vlo` and `vhi`, and `0x00` otherwise.
+///
+/// There are no unsigned byte comparisons, so we emulate `x >= lo` with
+/// `max(x, lo) == x` and `x <= hi` with `min(x, hi) == x`, using unsigned
+/// min/max. When `lo > hi`, no byte can satisfy both, so every lane is
+/// `0x00`.
+#[target_feature(enable = "avx2")]
+unsafe fn range_lanes(vlo: __m256i, vhi: __m256i, chunk: __m256i) -> __m256i {
+    let ge_lo = _mm256_cmpeq_epi8(_mm256_max_epu8(vlo, chunk), chunk);
+    let le_hi = _mm256_cmpeq_epi8(_mm256_min_epu8(vhi, chunk), chunk);
+    _mm256_and_si256(ge_lo, le_hi)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_not_range(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vlo: __m256i,
+    vhi: __m256i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(not_range_lanes(vlo, vhi, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn reverse_search_not_range(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+    vlo: __m256i,
+    vhi: __m256i,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(not_range_lanes(vlo, vhi, chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+/// Return a vector where each lane is `0xFF` if the corresponding byte in
+/// `chunk` is outside the inclusive range given by the corresponding bytes
+/// in `vlo` and `vhi`, and `0x00` otherwise. This is the inverse of
+/// `range_lanes`.
+#[target_feature(enable = "avx2")]
+unsafe fn not_range_lanes(
+    vlo: __m256i,
+    vhi: __m256i,
+    chunk: __m256i,
+) -> __m256i {
+    _mm256_andnot_si256(range_lanes(vlo, vhi, chunk), _mm256_set1_epi8(-1))
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_whitespace(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(whitespace_lanes(chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn reverse_search_whitespace(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(whitespace_lanes(chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+/// Return a vector where each lane is `0xFF` if the corresponding byte in
+/// `chunk` is ASCII whitespace (as defined by `u8::is_ascii_whitespace`),
+/// and `0x00` otherwise.
+#[target_feature(enable = "avx2")]
+unsafe fn whitespace_lanes(chunk: __m256i) -> __m256i {
+    // See the sse2 implementation for an explanation.
+    let space = _mm256_cmpeq_epi8(_mm256_set1_epi8(b' ' as i8), chunk);
+    let vt = _mm256_cmpeq_epi8(_mm256_set1_epi8(0x0B), chunk);
+    let range =
+        range_lanes(_mm256_set1_epi8(0x09), _mm256_set1_epi8(0x0D), chunk);
+    _mm256_or_si256(space, _mm256_andnot_si256(vt, range))
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_control(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(control_lanes(chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn control_lanes(chunk: __m256i) -> __m256i {
+    // See the sse2 implementation for an explanation.
+    let del = _mm256_cmpeq_epi8(_mm256_set1_epi8(0x7F), chunk);
+    let low = _mm256_cmpeq_epi8(
+        _mm256_min_epu8(_mm256_set1_epi8(0x1F), chunk),
+        chunk,
+    );
+    _mm256_or_si256(del, low)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_mismatch(
+    a: *const u8,
+    b: *const u8,
+    at: usize,
+) -> Option<usize> {
+    let mask = !_mm256_movemask_epi8(eq_lanes(a.add(at), b.add(at)));
+    if mask != 0 {
+        Some(at + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+/// Return a vector where each lane is `0xFF` if the corresponding bytes at
+/// `a` and `b` are equal, and `0x00` otherwise.
+#[target_feature(enable = "avx2")]
+unsafe fn eq_lanes(a: *const u8, b: *const u8) -> __m256i {
+    _mm256_cmpeq_epi8(
+        _mm256_loadu_si256(a as *const __m256i),
+        _mm256_loadu_si256(b as *const __m256i),
+    )
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn forward_search_not_whitespace(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(not_whitespace_lanes(chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + forward_pos(mask))
+    } else {
+        None
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn reverse_search_not_whitespace(
+    start_ptr: *const u8,
+    end_ptr: *const u8,
+    ptr: *const u8,
+) -> Option<usize> {
+    debug_assert!(sub(end_ptr, start_ptr) >= VECTOR_SIZE);
+    debug_assert!(start_ptr <= ptr);
+    debug_assert!(ptr <= end_ptr.sub(VECTOR_SIZE));
+
+    let chunk = _mm256_loadu_si256(ptr as *const __m256i);
+    let mask = _mm256_movemask_epi8(not_whitespace_lanes(chunk));
+    if mask != 0 {
+        Some(sub(ptr, start_ptr) + reverse_pos(mask))
+    } else {
+        None
+    }
+}
+
+/// Return a vector where each lane is `0xFF` if the corresponding byte in
+/// `chunk` is not ASCII whitespace, and `0x00` otherwise. This is the
+/// inverse of `whitespace_lanes`.
+#[target_feature(enable = "avx2")]
+unsafe fn not_whitespace_lanes(chunk: __m256i) -> __m256i {
+    _mm256_andnot_si256(whitespace_lanes(chunk), _mm256_set1_epi8(-1))
+}
+
+/// Compute the number of matching bytes from the given mask. The count
+/// returned is always in the range [0, 32].
+///
+/// The mask given is expected to be the result of _mm256_movemask_epi8.
+fn count_mask(mask: i32) -> usize {
+    mask.count_ones() as usize
+}
+
+/// Compute the position of the nth (starting at 0) matching byte from the
+/// given mask. The position returned is always in the range [0, 31].
+///
+/// The mask given must have more than `n` bits set.
+fn forward_nth_pos(mask: i32, n: usize) -> usize {
+    let mut mask = mask as u32;
+    for _ in 0..n {
+        // Clear the least significant bit that is set.
+        mask &= mask - 1;
+    }
+    forward_pos(mask as i32)
+}
+
+/// Compute the position of the nth (starting at 0) matching byte from the
+/// end of the given mask. The position returned is always in the range
+/// [0, 31].
+///
+/// The mask given must have more than `n` bits set.
+fn reverse_nth_pos(mask: i32, n: usize) -> usize {
+    let mut mask = mask as u32;
+    for _ in 0..n {
+        // Clear the most significant bit that is set.
+        mask &= !(1 << (31 - mask.leading_zeros()));
+    }
+    reverse_pos(mask as i32)
+}
+
+/// Write the position of each matching byte in the given mask, offset by
+/// `at`, to `out[written..]` in ascending order, stopping when `out` is full.
+/// The new number of positions written is returned.
+///
+/// The mask given is expected to be the result of a movemask.
+fn forward_write(
+    mask: i32,
+    at: usize,
+    out: &mut [usize],
+    mut written: usize,
+) -> usize {
+    let mut mask = mask as u32;
+    while mask != 0 && written < out.len() {
+        out[written] = at + forward_pos(mask as i32);
+        written += 1;
+        // Clear the least significant bit that is set.
+        mask &= mask - 1;
+    }
+    written
+}
+
+/// Like `forward_write`, but writes positions in descending order.
+fn reverse_write(
+    mask: i32,
+    at: usize,
+    out: &mut [usize],
+    mut written: usize,
+) -> usize {
+    let mut mask = mask as u32;
+    while mask != 0 && written < out.len() {
+        let pos = reverse_pos(mask as i32);
+        out[written] = at + pos;
+        written += 1;
+        mask &= !(1 << pos);
+    }
+    written
+}
+
 /// Compute the position of the first matching byte from the given mask. The
 /// position returned is always in the range [0, 31].
 ///
@@ -0,0 +1,258 @@
+use core::{arch::x86_64::*, mem::size_of};
+
+use super::avx;
+use crate::arch::{record_backend, Backend};
+
+// Only the routines that search for one, two or three bytes have dedicated
+// AVX-512 implementations. Everything else uses the AVX2 routines, which are
+// always available when AVX-512BW is.
+pub use super::avx::{
+    count, count2, count3, find_ascii_control, memchr2_u16, memchr3_u16,
+    memchr_all_into, memchr_ascii_whitespace, memchr_not,
+    memchr_not_ascii_whitespace, memchr_not_range, memchr_nth, memchr_range,
+    memchr_u16, memchr_u32, memrchr2_u16, memrchr3_u16, memrchr_all_into,
+    memrchr_ascii_whitespace, memrchr_not, memrchr_not_ascii_whitespace,
+    memrchr_not_range, memrchr_nth, memrchr_range, memrchr_u16, mismatch,
+};
+
+const VECTOR_SIZE: usize = size_of::<__m512i>();
+
+// The number of bytes to loop at in one iteration. Unlike the SSE2 and AVX2
+// routines, there is no need to OR the comparisons together before extracting
+// a mask, since the comparisons produce mask registers directly.
+const LOOP_SIZE: usize = 2 * VECTOR_SIZE;
+
+/// Returns true if and only if the routines in this module can be called on
+/// the current CPU.
+pub fn is_available() -> bool {
+    is_x86_feature_detected!("avx2")
+        && is_x86_feature_detected!("avx512f")
+        && is_x86_feature_detected!("avx512bw")
+}
+
+#[target_feature(enable = "avx2,avx512f,avx512bw")]
+pub unsafe fn memchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    // For a high level explanation for how this algorithm works, see the
+    // sse2 implementation. The algorithm here is the same, but with 512-bit
+    // vectors and without aligned loads. Haystacks shorter than a single
+    // vector are handed off to the AVX2 routine.
+    if haystack.len() < VECTOR_SIZE {
+        return avx::memchr(n1, haystack);
+    }
+    record_backend(Backend::Avx512);
+    forward(One(_mm512_set1_epi8(n1 as i8)), haystack)
+}
+
+#[target_feature(enable = "avx2,avx512f,avx512bw")]
+pub unsafe fn memchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+    if haystack.len() < VECTOR_SIZE {
+        return avx::memchr2(n1, n2, haystack);
+    }
+    record_backend(Backend::Avx512);
+    let m = Two(_mm512_set1_epi8(n1 as i8), _mm512_set1_epi8(n2 as i8));
+    forward(m, haystack)
+}
+
+#[target_feature(enable = "avx2,avx512f,avx512bw")]
+pub unsafe fn memchr3(
+    n1: u8,
+    n2: u8,
+    n3: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    if haystack.len() < VECTOR_SIZE {
+        return avx::memchr3(n1, n2, n3, haystack);
+    }
+    record_backend(Backend::Avx512);
+    let m = Three(
+        _mm512_set1_epi8(n1 as i8),
+        _mm512_set1_epi8(n2 as i8),
+        _mm512_set1_epi8(n3 as i8),
+    );
+    forward(m, haystack)
+}
+
+#[target_feature(enable = "avx2,avx512f,avx512bw")]
+pub unsafe fn memrchr(n1: u8, haystack: &[u8]) -> Option<usize> {
+    if haystack.len() < VECTOR_SIZE {
+        return avx::memrchr(n1, haystack);
+    }
+    record_backend(Backend::Avx512);
+    reverse(One(_mm512_set1_epi8(n1 as i8)), haystack)
+}
+
+#[target_feature(enable = "avx2,avx512f,avx512bw")]
+pub unsafe fn memrchr2(n1: u8, n2: u8, haystack: &[u8]) -> Option<usize> {
+    if haystack.len() < VECTOR_SIZE {
+        return avx::memrchr2(n1, n2, haystack);
+    }
+    record_backend(Backend::Avx512);
+    let m = Two(_mm512_set1_epi8(n1 as i8), _mm512_set1_epi8(n2 as i8));
+    reverse(m, haystack)
+}
+
+#[target_feature(enable = "avx2,avx512f,avx512bw")]
+pub unsafe fn memrchr3(
+    n1: u8,
+    n2: u8,
+    n3: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    if haystack.len() < VECTOR_SIZE {
+        return avx::memrchr3(n1, n2, n3, haystack);
+    }
+    record_backend(Backend::Avx512);
+    let m = Three(
+        _mm512_set1_epi8(n1 as i8),
+        _mm512_set1_epi8(n2 as i8),
+        _mm512_set1_epi8(n3 as i8),
+    );
+    reverse(m, haystack)
+}
+
+/// Describes how to compare a single vector of haystack bytes with the
+/// needle bytes.
+///
+/// # Safety
+///
+/// Like the `Vector` trait in the memmem module, implementations are marked
+/// `#[inline(always)]` instead of using `#[target_feature]`. Callers must
+/// ensure that they are only called from functions with the avx512f and
+/// avx512bw target features enabled.
+trait Matcher: Copy {
+    /// Returns a mask where bit `i` is set if and only if byte `i` in
+    /// `chunk` matches.
+    unsafe fn mask(self, chunk: __m512i) -> u64;
+}
+
+#[derive(Clone, Copy)]
+struct One(__m512i);
+
+#[derive(Clone, Copy)]
+struct Two(__m512i, __m512i);
+
+#[derive(Clone, Copy)]
+struct Three(__m512i, __m512i, __m512i);
+
+impl Matcher for One {
+    #[inline(always)]
+    unsafe fn mask(self, chunk: __m512i) -> u64 {
+        _mm512_cmpeq_epi8_mask(self.0, chunk)
+    }
+}
+
+impl Matcher for Two {
+    #[inline(always)]
+    unsafe fn mask(self, chunk: __m512i) -> u64 {
+        _mm512_cmpeq_epi8_mask(self.0, chunk)
+            | _mm512_cmpeq_epi8_mask(self.1, chunk)
+    }
+}
+
+impl Matcher for Three {
+    #[inline(always)]
+    unsafe fn mask(self, chunk: __m512i) -> u64 {
+        _mm512_cmpeq_epi8_mask(self.0, chunk)
+            | _mm512_cmpeq_epi8_mask(self.1, chunk)
+            | _mm512_cmpeq_epi8_mask(self.2, chunk)
+    }
+}
+
+/// Find the position of the first match in the given haystack, which must be
+/// at least `VECTOR_SIZE` bytes long.
+#[inline(always)]
+unsafe fn forward<M: Matcher>(m: M, haystack: &[u8]) -> Option<usize> {
+    debug_assert!(haystack.len() >= VECTOR_SIZE);
+
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = start_ptr;
+    while sub(end_ptr, ptr) >= LOOP_SIZE {
+        let maska = m.mask(_mm512_loadu_si512(ptr.cast()));
+        let maskb = m.mask(_mm512_loadu_si512(ptr.add(VECTOR_SIZE).cast()));
+        if (maska | maskb) != 0 {
+            let at = sub(ptr, start_ptr);
+            if maska != 0 {
+                return Some(at + forward_pos(maska));
+            }
+            return Some(at + VECTOR_SIZE + forward_pos(maskb));
+        }
+        ptr = ptr.add(LOOP_SIZE);
+    }
+    while sub(end_ptr, ptr) >= VECTOR_SIZE {
+        let mask = m.mask(_mm512_loadu_si512(ptr.cast()));
+        if mask != 0 {
+            return Some(sub(ptr, start_ptr) + forward_pos(mask));
+        }
+        ptr = ptr.add(VECTOR_SIZE);
+    }
+    if ptr < end_ptr {
+        // Search the last vector's worth of bytes, which may overlap with
+        // bytes we've already searched. Since those bytes didn't match, the
+        // first match in this vector is still the first match overall.
+        ptr = end_ptr.sub(VECTOR_SIZE);
+        let mask = m.mask(_mm512_loadu_si512(ptr.cast()));
+        if mask != 0 {
+            return Some(sub(ptr, start_ptr) + forward_pos(mask));
+        }
+    }
+    None
+}
+
+/// Find the position of the last match in the given haystack, which must be
+/// at least `VECTOR_SIZE` bytes long.
+#[inline(always)]
+unsafe fn reverse<M: Matcher>(m: M, haystack: &[u8]) -> Option<usize> {
+    debug_assert!(haystack.len() >= VECTOR_SIZE);
+
+    let start_ptr = haystack.as_ptr();
+    let end_ptr = start_ptr.add(haystack.len());
+    let mut ptr = end_ptr;
+    while sub(ptr, start_ptr) >= LOOP_SIZE {
+        ptr = ptr.sub(LOOP_SIZE);
+        let maska = m.mask(_mm512_loadu_si512(ptr.cast()));
+        let maskb = m.mask(_mm512_loadu_si512(ptr.add(VECTOR_SIZE).cast()));
+        if (maska | maskb) != 0 {
+            let at = sub(ptr, start_ptr);
+            if maskb != 0 {
+                return Some(at + VECTOR_SIZE + reverse_pos(maskb));
+            }
+            return Some(at + reverse_pos(maska));
+        }
+    }
+    while sub(ptr, start_ptr) >= VECTOR_SIZE {
+        ptr = ptr.sub(VECTOR_SIZE);
+        let mask = m.mask(_mm512_loadu_si512(ptr.cast()));
+        if mask != 0 {
+            return Some(sub(ptr, start_ptr) + reverse_pos(mask));
+        }
+    }
+    if ptr > start_ptr {
+        // As in the forward case, this may overlap with bytes we've already
+        // searched, which is fine since none of them matched.
+        let mask = m.mask(_mm512_loadu_si512(start_ptr.cast()));
+        if mask != 0 {
+            return Some(reverse_pos(mask));
+        }
+    }
+    None
+}
+
+/// Compute the position of the first matching byte from the given mask. The
+/// position returned is always in the range [0, 63].
+fn forward_pos(mask: u64) -> usize {
+    mask.trailing_zeros() as usize
+}
+
+/// Compute the position of the last matching byte from the given mask. The
+/// position returned is always in the range [0, 63].
+fn reverse_pos(mask: u64) -> usize {
+    VECTOR_SIZE - mask.leading_zeros() as usize - 1
+}
+
+/// Subtract `b` from `a` and return the difference. `a` should be greater than
+/// or equal to `b`.
+fn sub(a: *const u8, b: *const u8) -> usize {
+    debug_assert!(a >= b);
+    (a as usize) - (b as usize)
+}
@@ -0,0 +1,323 @@
+// This module provides the vectorized routines for searching for any byte in
+// a `ByteSet`. They are written once over a small `Vector` trait and
+// instantiated with SSSE3 and AVX2 vectors, since both provide the byte
+// shuffle that the membership test is built on.
+//
+// The membership test works as follows. A `ByteSet` is a pair of 16 byte
+// tables indexed by the low nibble of a byte, where bit `k` of an entry says
+// whether the byte with high nibble `k` (in the first table) or `k + 8` (in
+// the second table) is a member. For each byte in a vector, we look up its
+// entry in both tables with a shuffle. Shuffles produce zero for indices with
+// their most significant bit set, so indexing with the byte's low nibble and
+// its most significant bit selects the entry from the first table only for
+// bytes less than 128, and flipping that bit selects the entry from the second
+// table only for the other bytes. ORing the two gives the right entry. A third
+// shuffle, indexed by the high nibble, produces the bit to test in that entry.
+
+use core::arch::x86_64::*;
+
+use crate::memchr::ByteSet;
+
+/// A vector of bytes.
+///
+/// # Safety
+///
+/// Like the `Vector` trait in the `wide` module, implementations are marked
+/// `#[inline(always)]` instead of using `#[target_feature]`. Callers must
+/// ensure that they are only called from functions with the corresponding
+/// target feature (ssse3 or avx2) enabled.
+trait Vector: Copy {
+    /// The number of bytes in this vector.
+    const BYTES: usize;
+
+    unsafe fn splat(byte: u8) -> Self;
+    /// Load the given table into every 128-bit lane of a vector.
+    unsafe fn load_table(table: &[u8; 16]) -> Self;
+    unsafe fn load_unaligned(ptr: *const u8) -> Self;
+    unsafe fn and(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    /// Shuffle the bytes of this vector within each 128-bit lane, as with
+    /// `pshufb`.
+    unsafe fn shuffle(self, indices: Self) -> Self;
+    /// Shift each 16-bit lane right by four bits.
+    unsafe fn shift_right4(self) -> Self;
+    unsafe fn cmpeq(self, other: Self) -> Self;
+    unsafe fn movemask(self) -> u32;
+}
+
+impl Vector for __m128i {
+    const BYTES: usize = 16;
+
+    #[inline(always)]
+    unsafe fn splat(byte: u8) -> __m128i {
+        _mm_set1_epi8(byte as i8)
+    }
+
+    #[inline(always)]
+    unsafe fn load_table(table: &[u8; 16]) -> __m128i {
+        _mm_loadu_si128(table.as_ptr() as *const __m128i)
+    }
+
+    #[inline(always)]
+    unsafe fn load_unaligned(ptr: *const u8) -> __m128i {
+        _mm_loadu_si128(ptr as *const __m128i)
+    }
+
+    #[inline(always)]
+    unsafe fn and(self, other: __m128i) -> __m128i {
+        _mm_and_si128(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn or(self, other: __m128i) -> __m128i {
+        _mm_or_si128(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: __m128i) -> __m128i {
+        _mm_xor_si128(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn shuffle(self, indices: __m128i) -> __m128i {
+        _mm_shuffle_epi8(self, indices)
+    }
+
+    #[inline(always)]
+    unsafe fn shift_right4(self) -> __m128i {
+        _mm_srli_epi16(self, 4)
+    }
+
+    #[inline(always)]
+    unsafe fn cmpeq(self, other: __m128i) -> __m128i {
+        _mm_cmpeq_epi8(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn movemask(self) -> u32 {
+        _mm_movemask_epi8(self) as u32
+    }
+}
+
+impl Vector for __m256i {
+    const BYTES: usize = 32;
+
+    #[inline(always)]
+    unsafe fn splat(byte: u8) -> __m256i {
+        _mm256_set1_epi8(byte as i8)
+    }
+
+    #[inline(always)]
+    unsafe fn load_table(table: &[u8; 16]) -> __m256i {
+        _mm256_broadcastsi128_si256(__m128i::load_table(table))
+    }
+
+    #[inline(always)]
+    unsafe fn load_unaligned(ptr: *const u8) -> __m256i {
+        _mm256_loadu_si256(ptr as *const __m256i)
+    }
+
+    #[inline(always)]
+    unsafe fn and(self, other: __m256i) -> __m256i {
+        _mm256_and_si256(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn or(self, other: __m256i) -> __m256i {
+        _mm256_or_si256(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: __m256i) -> __m256i {
+        _mm256_xor_si256(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn shuffle(self, indices: __m256i) -> __m256i {
+        _mm256_shuffle_epi8(self, indices)
+    }
+
+    #[inline(always)]
+    unsafe fn shift_right4(self) -> __m256i {
+        _mm256_srli_epi16(self, 4)
+    }
+
+    #[inline(always)]
+    unsafe fn cmpeq(self, other: __m256i) -> __m256i {
+        _mm256_cmpeq_epi8(self, other)
+    }
+
+    #[inline(always)]
+    unsafe fn movemask(self) -> u32 {
+        _mm256_movemask_epi8(self) as u32
+    }
+}
+
+/// The membership test for a particular set, with every table loaded into a
+/// vector.
+#[derive(Clone, Copy)]
+struct Matcher<V> {
+    lo: V,
+    hi: V,
+    bits: V,
+    low_nibble_and_msb: V,
+    msb: V,
+    low_nibble: V,
+}
+
+/// For each possible high nibble `k`, the bit that is set in a table entry
+/// when the byte with that high nibble is a member.
+const BITS: [u8; 16] =
+    [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+
+impl<V: Vector> Matcher<V> {
+    #[inline(always)]
+    unsafe fn new(set: &ByteSet) -> Matcher<V> {
+        let (lo, hi) = set.tables();
+        Matcher {
+            lo: V::load_table(lo),
+            hi: V::load_table(hi),
+            bits: V::load_table(&BITS),
+            low_nibble_and_msb: V::splat(0x8F),
+            msb: V::splat(0x80),
+            low_nibble: V::splat(0x0F),
+        }
+    }
+
+    /// Returns a mask with one bit set for each byte in `chunk` that is in
+    /// the set.
+    #[inline(always)]
+    unsafe fn matches(&self, chunk: V) -> u32 {
+        let index_lo = chunk.and(self.low_nibble_and_msb);
+        let index_hi = chunk.xor(self.msb).and(self.low_nibble_and_msb);
+        let entry = self.lo.shuffle(index_lo).or(self.hi.shuffle(index_hi));
+        let bit = self.bits.shuffle(chunk.shift_right4().and(self.low_nibble));
+        entry.and(bit).cmpeq(bit).movemask()
+    }
+}
+
+/// Find the index of the first byte in the given haystack that is in the
+/// set. The haystack must contain at least `V::BYTES` bytes.
+#[inline(always)]
+unsafe fn forward<V: Vector>(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    debug_assert!(haystack.len() >= V::BYTES);
+
+    let m = Matcher::<V>::new(set);
+    let start_ptr = haystack.as_ptr();
+    let end = haystack.len();
+    let mut i = 0;
+    while end - i >= V::BYTES {
+        let mask = m.matches(V::load_unaligned(start_ptr.add(i)));
+        if mask != 0 {
+            return Some(i + mask.trailing_zeros() as usize);
+        }
+        i += V::BYTES;
+    }
+    if i < end {
+        // Search the last vector's worth of bytes, which may overlap with
+        // bytes we've already searched. Since those didn't match, the first
+        // match in this vector is still the first match overall.
+        i = end - V::BYTES;
+        let mask = m.matches(V::load_unaligned(start_ptr.add(i)));
+        if mask != 0 {
+            return Some(i + mask.trailing_zeros() as usize);
+        }
+    }
+    None
+}
+
+/// Find the index of the last byte in the given haystack that is in the set.
+/// The haystack must contain at least `V::BYTES` bytes.
+#[inline(always)]
+unsafe fn reverse<V: Vector>(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    debug_assert!(haystack.len() >= V::BYTES);
+
+    let m = Matcher::<V>::new(set);
+    let start_ptr = haystack.as_ptr();
+    let mut i = haystack.len();
+    while i >= V::BYTES {
+        i -= V::BYTES;
+        let mask = m.matches(V::load_unaligned(start_ptr.add(i)));
+        if mask != 0 {
+            return Some(i + (31 - mask.leading_zeros() as usize));
+        }
+    }
+    if i > 0 {
+        // As in the forward case, this may overlap with bytes we've already
+        // searched, which is fine since none of them matched.
+        let mask = m.matches(V::load_unaligned(start_ptr));
+        if mask != 0 {
+            return Some(31 - mask.leading_zeros() as usize);
+        }
+    }
+    None
+}
+
+/// Find the index of the first byte in the given haystack that is in the
+/// set, using SSSE3 vectors. The haystack must contain at least 16 bytes.
+///
+/// # Safety
+///
+/// Callers must ensure that the ssse3 CPU feature is enabled in the current
+/// environment.
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn memchr_set_ssse3(
+    set: &ByteSet,
+    haystack: &[u8],
+) -> Option<usize> {
+    forward::<__m128i>(set, haystack)
+}
+
+/// Find the index of the first byte in the given haystack that is in the
+/// set, using AVX2 vectors. Haystacks shorter than 32 bytes are searched
+/// with SSSE3 vectors, and must contain at least 16 bytes.
+///
+/// # Safety
+///
+/// Callers must ensure that the avx2 CPU feature is enabled in the current
+/// environment.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn memchr_set_avx2(
+    set: &ByteSet,
+    haystack: &[u8],
+) -> Option<usize> {
+    if haystack.len() < __m256i::BYTES {
+        return forward::<__m128i>(set, haystack);
+    }
+    forward::<__m256i>(set, haystack)
+}
+
+/// Find the index of the last byte in the given haystack that is in the set,
+/// using SSSE3 vectors. The haystack must contain at least 16 bytes.
+///
+/// # Safety
+///
+/// Callers must ensure that the ssse3 CPU feature is enabled in the current
+/// environment.
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn memrchr_set_ssse3(
+    set: &ByteSet,
+    haystack: &[u8],
+) -> Option<usize> {
+    reverse::<__m128i>(set, haystack)
+}
+
+/// Find the index of the last byte in the given haystack that is in the set,
+/// using AVX2 vectors. Haystacks shorter than 32 bytes are searched with
+/// SSSE3 vectors, and must contain at least 16 bytes.
+///
+/// # Safety
+///
+/// Callers must ensure that the avx2 CPU feature is enabled in the current
+/// environment.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn memrchr_set_avx2(
+    set: &ByteSet,
+    haystack: &[u8],
+) -> Option<usize> {
+    if haystack.len() < __m256i::BYTES {
+        return reverse::<__m128i>(set, haystack);
+    }
+    reverse::<__m256i>(set, haystack)
+}
@@ -1,10 +1,51 @@
-use super::fallback;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use super::{fallback, ByteSet};
+use crate::arch::Backend;
 
 // We only use AVX when we can detect at runtime whether it's available, which
 // requires std.
 #[cfg(feature = "std")]
 mod avx;
+// AVX-512 can cause some CPUs to downclock, so it's opt-in.
+#[cfg(memchr_runtime_avx512)]
+pub(crate) mod avx512;
+mod byteset;
 mod sse2;
+mod wide;
+
+/// The backend chosen by the most recent CPU feature detection performed by
+/// `unsafe_ifunc`, stored as its discriminant. Zero means that no detection
+/// has been performed yet.
+#[cfg(feature = "std")]
+static DETECTED: AtomicU8 = AtomicU8::new(0);
+
+/// Returns the backend selected for single byte search.
+///
+/// If a search hasn't been executed yet, then one is executed to trigger CPU
+/// feature detection. This way, the result always reflects the cached choice
+/// made by `unsafe_ifunc` instead of probing the CPU again.
+#[cfg(feature = "std")]
+pub(crate) fn detected() -> Backend {
+    let mut byte = DETECTED.load(Ordering::Relaxed);
+    if byte == 0 {
+        memchr(0, &[0]);
+        byte = DETECTED.load(Ordering::Relaxed);
+    }
+    Backend::from_u8(byte)
+}
+
+/// Returns the backend selected for single byte search. Without std, this is
+/// always known at compile time.
+#[cfg(not(feature = "std"))]
+pub(crate) fn detected() -> Backend {
+    if cfg!(memchr_runtime_sse2) {
+        Backend::Sse2
+    } else {
+        Backend::Scalar
+    }
+}
 
 /// This macro employs a gcc-like "ifunc" trick where by upon first calling
 /// `memchr` (for example), CPU feature detection will be performed at runtime
@@ -31,31 +72,71 @@ mod sse2;
 /// # Safety
 ///
 /// Callers must ensure that fnty is function pointer type.
+///
+/// By default, the routine is assumed to return an `Option<usize>`. Routines
+/// with a different return type (such as `count`) must spell it out after
+/// the function pointer type, e.g., `fn(u8, &[u8]) -> usize => usize`.
+/// Similarly, every argument preceding the haystack is assumed to be a `u8`.
+/// Arguments of any other type (such as the `n` in `memchr_nth`) must be
+/// given along with their type, e.g., `n: usize`. Likewise, the haystack is
+/// assumed to be a `&[u8]` unless its type is given, e.g., `haystack: &[u16]`.
 #[cfg(feature = "std")]
 macro_rules! unsafe_ifunc {
-    ($fnty:ty, $name:ident, $haystack:ident, $($needle:ident),+) => {{
+    ($fnty:ty, $name:ident, $haystack:ident $(, $needle:ident)*) => {
+        unsafe_ifunc!(
+            $fnty => Option<usize>, $name, $haystack $(, $needle: u8)*
+        )
+    };
+    ($fnty:ty => $ret:ty, $name:ident, $haystack:ident $(, $needle:ident)+) => {
+        unsafe_ifunc!($fnty => $ret, $name, $haystack $(, $needle: u8)*)
+    };
+    (
+        $fnty:ty => $ret:ty,
+        $name:ident,
+        $haystack:ident
+        $(, $needle:ident: $nty:ty)*
+    ) => {
+        unsafe_ifunc!(
+            $fnty => $ret, $name, $haystack: &[u8] $(, $needle: $nty)*
+        )
+    };
+    (
+        $fnty:ty => $ret:ty,
+        $name:ident,
+        $haystack:ident: $hty:ty
+        $(, $needle:ident: $nty:ty)*
+    ) => {{
         use std::{mem, sync::atomic::{AtomicPtr, Ordering}};
 
         type FnRaw = *mut ();
 
         static FN: AtomicPtr<()> = AtomicPtr::new(detect as FnRaw);
 
-        fn detect($($needle: u8),+, haystack: &[u8]) -> Option<usize> {
-            let fun =
+        fn detect($($needle: $nty,)* haystack: $hty) -> $ret {
+            let (fun, backend) =
                 if cfg!(memchr_runtime_avx) && is_x86_feature_detected!("avx2") {
-                    avx::$name as FnRaw
+                    (avx::$name as FnRaw, Backend::Avx2)
                 } else if cfg!(memchr_runtime_sse2) {
-                    sse2::$name as FnRaw
+                    (sse2::$name as FnRaw, Backend::Sse2)
                 } else {
-                    fallback::$name as FnRaw
+                    (fallback::$name as FnRaw, Backend::Scalar)
                 };
+            #[cfg(memchr_runtime_avx512)]
+            let (fun, backend) =
+                if cfg!(memchr_runtime_avx) && avx512::is_available() {
+                    (avx512::$name as FnRaw, Backend::Avx512)
+                } else {
+                    (fun, backend)
+                };
+            DETECTED.store(backend as u8, Ordering::Relaxed);
             FN.store(fun as FnRaw, Ordering::Relaxed);
             // SAFETY: By virtue of the caller contract, $fnty is a function
             // pointer, which is always safe to transmute with a *mut ().
-            // Also, if 'fun is the AVX routine, then it is guaranteed to be
-            // supported since we checked the avx2 feature.
+            // Also, if 'fun is the AVX or AVX-512 routine, then it is
+            // guaranteed to be supported since we checked the avx2 feature
+            // (and the avx512f and avx512bw features).
             unsafe {
-                mem::transmute::<FnRaw, $fnty>(fun)($($needle),+, haystack)
+                mem::transmute::<FnRaw, $fnty>(fun)($($needle,)* haystack)
             }
         }
 
@@ -65,7 +146,7 @@ macro_rules! unsafe_ifunc {
         // we checked the avx2 feature.
         unsafe {
             let fun = FN.load(Ordering::Relaxed);
-            mem::transmute::<FnRaw, $fnty>(fun)($($needle),+, $haystack)
+            mem::transmute::<FnRaw, $fnty>(fun)($($needle,)* $haystack)
         }
     }}
 }
@@ -82,11 +163,34 @@ macro_rules! unsafe_ifunc {
 /// or the SSE routine, which is always safe to call on x86_64.
 #[cfg(not(feature = "std"))]
 macro_rules! unsafe_ifunc {
-    ($fnty:ty, $name:ident, $haystack:ident, $($needle:ident),+) => {{
+    ($fnty:ty, $name:ident, $haystack:ident $(, $needle:ident)*) => {
+        unsafe_ifunc!(
+            $fnty => Option<usize>, $name, $haystack $(, $needle: u8)*
+        )
+    };
+    ($fnty:ty => $ret:ty, $name:ident, $haystack:ident $(, $needle:ident)+) => {
+        unsafe_ifunc!($fnty => $ret, $name, $haystack $(, $needle: u8)*)
+    };
+    (
+        $fnty:ty => $ret:ty,
+        $name:ident,
+        $haystack:ident
+        $(, $needle:ident: $nty:ty)*
+    ) => {
+        unsafe_ifunc!(
+            $fnty => $ret, $name, $haystack: &[u8] $(, $needle: $nty)*
+        )
+    };
+    (
+        $fnty:ty => $ret:ty,
+        $name:ident,
+        $haystack:ident: $hty:ty
+        $(, $needle:ident: $nty:ty)*
+    ) => {{
         if cfg!(memchr_runtime_sse2) {
-            unsafe { sse2::$name($($needle),+, $haystack) }
+            unsafe { sse2::$name($($needle,)* $haystack) }
         } else {
-            fallback::$name($($needle),+, $haystack)
+            fallback::$name($($needle,)* $haystack)
         }
     }}
 }
@@ -146,3 +250,412 @@ pub fn memrchr3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> Option<usize> {
         n3
     )
 }
+
+#[inline(always)]
+pub fn memchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(fn(u8, &[u8]) -> Option<usize>, memchr_not, haystack, n1)
+}
+
+#[inline(always)]
+pub fn memrchr_not(n1: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(fn(u8, &[u8]) -> Option<usize>, memrchr_not, haystack, n1)
+}
+
+#[inline(always)]
+pub fn memchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u8, u8, &[u8]) -> Option<usize>,
+        memchr_range,
+        haystack,
+        lo,
+        hi
+    )
+}
+
+#[inline(always)]
+pub fn memrchr_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u8, u8, &[u8]) -> Option<usize>,
+        memrchr_range,
+        haystack,
+        lo,
+        hi
+    )
+}
+
+#[inline(always)]
+pub fn memchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u8, u8, &[u8]) -> Option<usize>,
+        memchr_not_range,
+        haystack,
+        lo,
+        hi
+    )
+}
+
+#[inline(always)]
+pub fn memrchr_not_range(lo: u8, hi: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u8, u8, &[u8]) -> Option<usize>,
+        memrchr_not_range,
+        haystack,
+        lo,
+        hi
+    )
+}
+
+#[inline(always)]
+pub fn memchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(&[u8]) -> Option<usize>,
+        memchr_ascii_whitespace,
+        haystack
+    )
+}
+
+#[inline(always)]
+pub fn memrchr_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(&[u8]) -> Option<usize>,
+        memrchr_ascii_whitespace,
+        haystack
+    )
+}
+
+#[inline(always)]
+pub fn memchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(&[u8]) -> Option<usize>,
+        memchr_not_ascii_whitespace,
+        haystack
+    )
+}
+
+#[inline(always)]
+pub fn memrchr_not_ascii_whitespace(haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(&[u8]) -> Option<usize>,
+        memrchr_not_ascii_whitespace,
+        haystack
+    )
+}
+
+#[inline(always)]
+pub fn find_ascii_control(haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(fn(&[u8]) -> Option<usize>, find_ascii_control, haystack)
+}
+
+#[inline(always)]
+pub fn mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(&[u8], &[u8]) -> Option<usize> => Option<usize>,
+        mismatch,
+        b,
+        a: &[u8]
+    )
+}
+
+#[inline(always)]
+pub fn memchr_nth(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(usize, u8, &[u8]) -> Option<usize> => Option<usize>,
+        memchr_nth,
+        haystack,
+        n: usize,
+        n1: u8
+    )
+}
+
+#[inline(always)]
+pub fn memrchr_nth(n: usize, n1: u8, haystack: &[u8]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(usize, u8, &[u8]) -> Option<usize> => Option<usize>,
+        memrchr_nth,
+        haystack,
+        n: usize,
+        n1: u8
+    )
+}
+
+#[inline(always)]
+pub fn memchr_all_into(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+    unsafe_ifunc!(
+        fn(u8, &mut [usize], &[u8]) -> usize => usize,
+        memchr_all_into,
+        haystack,
+        n1: u8,
+        out: &mut [usize]
+    )
+}
+
+#[inline(always)]
+pub fn memrchr_all_into(n1: u8, out: &mut [usize], haystack: &[u8]) -> usize {
+    unsafe_ifunc!(
+        fn(u8, &mut [usize], &[u8]) -> usize => usize,
+        memrchr_all_into,
+        haystack,
+        n1: u8,
+        out: &mut [usize]
+    )
+}
+
+#[inline(always)]
+pub fn count(n1: u8, haystack: &[u8]) -> usize {
+    unsafe_ifunc!(fn(u8, &[u8]) -> usize => usize, count, haystack, n1)
+}
+
+#[inline(always)]
+pub fn count2(n1: u8, n2: u8, haystack: &[u8]) -> usize {
+    unsafe_ifunc!(
+        fn(u8, u8, &[u8]) -> usize => usize,
+        count2,
+        haystack,
+        n1,
+        n2
+    )
+}
+
+#[inline(always)]
+pub fn count3(n1: u8, n2: u8, n3: u8, haystack: &[u8]) -> usize {
+    unsafe_ifunc!(
+        fn(u8, u8, u8, &[u8]) -> usize => usize,
+        count3,
+        haystack,
+        n1,
+        n2,
+        n3
+    )
+}
+
+#[inline(always)]
+pub fn memchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u16, &[u16]) -> Option<usize> => Option<usize>,
+        memchr_u16,
+        haystack: &[u16],
+        n1: u16
+    )
+}
+
+#[inline(always)]
+pub fn memchr2_u16(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u16, u16, &[u16]) -> Option<usize> => Option<usize>,
+        memchr2_u16,
+        haystack: &[u16],
+        n1: u16,
+        n2: u16
+    )
+}
+
+#[inline(always)]
+pub fn memchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u16, u16, u16, &[u16]) -> Option<usize> => Option<usize>,
+        memchr3_u16,
+        haystack: &[u16],
+        n1: u16,
+        n2: u16,
+        n3: u16
+    )
+}
+
+#[inline(always)]
+pub fn memrchr_u16(n1: u16, haystack: &[u16]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u16, &[u16]) -> Option<usize> => Option<usize>,
+        memrchr_u16,
+        haystack: &[u16],
+        n1: u16
+    )
+}
+
+#[inline(always)]
+pub fn memrchr2_u16(n1: u16, n2: u16, haystack: &[u16]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u16, u16, &[u16]) -> Option<usize> => Option<usize>,
+        memrchr2_u16,
+        haystack: &[u16],
+        n1: u16,
+        n2: u16
+    )
+}
+
+#[inline(always)]
+pub fn memrchr3_u16(
+    n1: u16,
+    n2: u16,
+    n3: u16,
+    haystack: &[u16],
+) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u16, u16, u16, &[u16]) -> Option<usize> => Option<usize>,
+        memrchr3_u16,
+        haystack: &[u16],
+        n1: u16,
+        n2: u16,
+        n3: u16
+    )
+}
+
+#[inline(always)]
+pub fn memchr_u32(n1: u32, haystack: &[u32]) -> Option<usize> {
+    unsafe_ifunc!(
+        fn(u32, &[u32]) -> Option<usize> => Option<usize>,
+        memchr_u32,
+        haystack: &[u32],
+        n1: u32
+    )
+}
+
+/// Search each of the given haystacks for `n1`, appending one result per
+/// haystack to `out`.
+///
+/// Unlike calling `memchr` for each haystack, this selects the backend once
+/// and then calls its routine directly for every haystack.
+#[cfg(feature = "std")]
+pub fn memchr_batch<'a, I: Iterator<Item = &'a [u8]>>(
+    n1: u8,
+    haystacks: I,
+    out: &mut Vec<Option<usize>>,
+) {
+    #[inline(always)]
+    fn batch<'a, I: Iterator<Item = &'a [u8]>>(
+        haystacks: I,
+        out: &mut Vec<Option<usize>>,
+        find: impl Fn(&[u8]) -> Option<usize>,
+    ) {
+        out.extend(
+            haystacks.map(|h| if h.is_empty() { None } else { find(h) }),
+        );
+    }
+
+    // SAFETY: `detected` only reports a backend after checking that the
+    // current CPU supports it.
+    match detected() {
+        #[cfg(memchr_runtime_avx512)]
+        Backend::Avx512 => {
+            batch(haystacks, out, |h| unsafe { avx512::memchr(n1, h) })
+        }
+        Backend::Avx2 => {
+            batch(haystacks, out, |h| unsafe { avx::memchr(n1, h) })
+        }
+        Backend::Sse2 => {
+            batch(haystacks, out, |h| unsafe { sse2::memchr(n1, h) })
+        }
+        _ => batch(haystacks, out, |h| fallback::memchr(n1, h)),
+    }
+}
+
+/// Returns the index of the first byte in `set` in `haystack`.
+///
+/// The membership test needs a byte shuffle, which SSE2 lacks. So when the
+/// selected backend is SSE2, this uses SSSE3 if the CPU supports it, and the
+/// fallback routine otherwise. AVX-512 isn't used, but implies AVX2.
+pub fn memchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    if haystack.len() < 16 {
+        return fallback::memchr_set(set, haystack);
+    }
+    // SAFETY: `detected` only reports a backend after checking that the
+    // current CPU supports it, and the AVX-512 backend requires AVX2.
+    match detected() {
+        Backend::Avx512 | Backend::Avx2 => unsafe {
+            byteset::memchr_set_avx2(set, haystack)
+        },
+        Backend::Sse2 if ssse3_available() => unsafe {
+            byteset::memchr_set_ssse3(set, haystack)
+        },
+        _ => fallback::memchr_set(set, haystack),
+    }
+}
+
+/// Returns the index of the last byte in `set` in `haystack`.
+///
+/// The implementation is selected in the same way as for `memchr_set`.
+pub fn memrchr_set(set: &ByteSet, haystack: &[u8]) -> Option<usize> {
+    if haystack.len() < 16 {
+        return fallback::memrchr_set(set, haystack);
+    }
+    // SAFETY: `detected` only reports a backend after checking that the
+    // current CPU supports it, and the AVX-512 backend requires AVX2.
+    match detected() {
+        Backend::Avx512 | Backend::Avx2 => unsafe {
+            byteset::memrchr_set_avx2(set, haystack)
+        },
+        Backend::Sse2 if ssse3_available() => unsafe {
+            byteset::memrchr_set_ssse3(set, haystack)
+        },
+        _ => fallback::memrchr_set(set, haystack),
+    }
+}
+
+/// Returns a mask of the positions of `n1` in the first 64 bytes of
+/// `haystack`. SSE2 is always used when available, since four comparisons
+/// of 16 bytes each are enough to cover the whole mask.
+pub fn memchr_mask(n1: u8, haystack: &[u8]) -> u64 {
+    if cfg!(memchr_runtime_sse2) {
+        unsafe { sse2::memchr_mask(n1, haystack) }
+    } else {
+        fallback::memchr_mask(n1, haystack)
+    }
+}
+
+/// Returns true if the SSSE3 CPU feature is available. Without std, this is
+/// only true if it's enabled at compile time.
+fn ssse3_available() -> bool {
+    #[cfg(feature = "std")]
+    {
+        is_x86_feature_detected!("ssse3")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "ssse3")
+    }
+}
+
+/// Returns the routine that `memchr` uses on the current CPU.
+///
+/// Unlike the routines in the `sse2`, `avx` and `avx512` modules, the
+/// returned function accepts empty haystacks.
+pub fn memchr_fn() -> fn(u8, &[u8]) -> Option<usize> {
+    // SAFETY: `detected` only reports a backend after checking that the
+    // current CPU supports it.
+    match detected() {
+        #[cfg(memchr_runtime_avx512)]
+        Backend::Avx512 => |n1, h| {
+            if h.is_empty() {
+                None
+            } else {
+                unsafe { avx512::memchr(n1, h) }
+            }
+        },
+        #[cfg(feature = "std")]
+        Backend::Avx2 => |n1, h| {
+            if h.is_empty() {
+                None
+            } else {
+                unsafe { avx::memchr(n1, h) }
+            }
+        },
+        Backend::Sse2 => |n1, h| {
+            if h.is_empty() {
+                None
+            } else {
+                unsafe { sse2::memchr(n1, h) }
+            }
+        },
+        _ => |n1, h| {
+            if h.is_empty() {
+                None
+            } else {
+                fallback::memchr(n1, h)
+            }
+        },
+    }
+}
@@ -0,0 +1,248 @@
+// This module provides the vectorized search routines for 16-bit and 32-bit
+// elements (e.g., `memchr_u16`) that are shared by the SSE2 and AVX2
+// implementations. Unlike the byte oriented routines, which are hand-tuned for
+// each vector size, these are written once over a small `Vector` trait and
+// instantiated from the `sse2` and `avx` modules, inside functions that enable
+// the corresponding target feature.
+//
+// Elements are compared with a lane comparison of the same width. This means
+// a needle can only match a whole element: a needle whose bytes happen to
+// straddle two elements, or that equals the high byte of some other element,
+// never matches.
+
+use core::{arch::x86_64::*, mem::size_of};
+
+/// A vector of lanes of type `T`, which is either `u16` or `u32`.
+///
+/// # Safety
+///
+/// Like the `Vector` trait in the memmem module, implementations are marked
+/// `#[inline(always)]` instead of using `#[target_feature]`. Callers must
+/// ensure that they are only called from functions with the corresponding
+/// target feature (sse2 or avx2) enabled.
+pub(crate) trait Vector<T>: Copy {
+    /// The number of lanes in this vector.
+    const LANES: usize;
+
+    unsafe fn splat(n: T) -> Self;
+    unsafe fn load_unaligned(ptr: *const T) -> Self;
+    unsafe fn cmpeq(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    /// Returns a mask with one bit set for each byte whose bits are all set,
+    /// as produced by `cmpeq`. So for lanes of `k` bytes, the bits for lane
+    /// `i` are `k*i` through `k*i+k-1`.
+    unsafe fn movemask(self) -> u32;
+}
+
+macro_rules! impl_vector {
+    (
+        $vector:ty, $elem:ty, $lanes:expr,
+        $splat:ident, $load:ident, $cmpeq:ident, $or:ident, $movemask:ident
+    ) => {
+        impl Vector<$elem> for $vector {
+            const LANES: usize = $lanes;
+
+            #[inline(always)]
+            unsafe fn splat(n: $elem) -> $vector {
+                $splat(n as _)
+            }
+
+            #[inline(always)]
+            unsafe fn load_unaligned(ptr: *const $elem) -> $vector {
+                $load(ptr as *const $vector)
+            }
+
+            #[inline(always)]
+            unsafe fn cmpeq(self, other: $vector) -> $vector {
+                $cmpeq(self, other)
+            }
+
+            #[inline(always)]
+            unsafe fn or(self, other: $vector) -> $vector {
+                $or(self, other)
+            }
+
+            #[inline(always)]
+            unsafe fn movemask(self) -> u32 {
+                $movemask(self) as u32
+            }
+        }
+    };
+}
+
+impl_vector!(
+    __m128i,
+    u16,
+    8,
+    _mm_set1_epi16,
+    _mm_loadu_si128,
+    _mm_cmpeq_epi16,
+    _mm_or_si128,
+    _mm_movemask_epi8
+);
+impl_vector!(
+    __m128i,
+    u32,
+    4,
+    _mm_set1_epi32,
+    _mm_loadu_si128,
+    _mm_cmpeq_epi32,
+    _mm_or_si128,
+    _mm_movemask_epi8
+);
+impl_vector!(
+    __m256i,
+    u16,
+    16,
+    _mm256_set1_epi16,
+    _mm256_loadu_si256,
+    _mm256_cmpeq_epi16,
+    _mm256_or_si256,
+    _mm256_movemask_epi8
+);
+impl_vector!(
+    __m256i,
+    u32,
+    8,
+    _mm256_set1_epi32,
+    _mm256_loadu_si256,
+    _mm256_cmpeq_epi32,
+    _mm256_or_si256,
+    _mm256_movemask_epi8
+);
+
+/// Describes how to compare a vector of haystack elements with the needles.
+pub(crate) trait Matcher<T, V: Vector<T>>: Copy {
+    /// Returns a vector whose lanes are all ones where the corresponding lane
+    /// in `chunk` matches, and all zeros otherwise.
+    unsafe fn matches(self, chunk: V) -> V;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct One<V>(pub(crate) V);
+
+#[derive(Clone, Copy)]
+pub(crate) struct Two<V>(pub(crate) V, pub(crate) V);
+
+#[derive(Clone, Copy)]
+pub(crate) struct Three<V>(pub(crate) V, pub(crate) V, pub(crate) V);
+
+impl<T, V: Vector<T>> Matcher<T, V> for One<V> {
+    #[inline(always)]
+    unsafe fn matches(self, chunk: V) -> V {
+        self.0.cmpeq(chunk)
+    }
+}
+
+impl<T, V: Vector<T>> Matcher<T, V> for Two<V> {
+    #[inline(always)]
+    unsafe fn matches(self, chunk: V) -> V {
+        self.0.cmpeq(chunk).or(self.1.cmpeq(chunk))
+    }
+}
+
+impl<T, V: Vector<T>> Matcher<T, V> for Three<V> {
+    #[inline(always)]
+    unsafe fn matches(self, chunk: V) -> V {
+        self.0.cmpeq(chunk).or(self.1.cmpeq(chunk)).or(self.2.cmpeq(chunk))
+    }
+}
+
+/// Find the index of the first matching element in the given haystack, which
+/// must contain at least `V::LANES` elements.
+#[inline(always)]
+pub(crate) unsafe fn forward<T, V: Vector<T>, M: Matcher<T, V>>(
+    m: M,
+    haystack: &[T],
+) -> Option<usize> {
+    debug_assert!(haystack.len() >= V::LANES);
+
+    let lanes = V::LANES;
+    let start_ptr = haystack.as_ptr();
+    let end = haystack.len();
+    let mut i = 0;
+    while end - i >= 2 * lanes {
+        let a = m.matches(V::load_unaligned(start_ptr.add(i)));
+        let b = m.matches(V::load_unaligned(start_ptr.add(i + lanes)));
+        if a.or(b).movemask() != 0 {
+            let mask = a.movemask();
+            if mask != 0 {
+                return Some(i + forward_pos::<T>(mask));
+            }
+            return Some(i + lanes + forward_pos::<T>(b.movemask()));
+        }
+        i += 2 * lanes;
+    }
+    while end - i >= lanes {
+        let mask = m.matches(V::load_unaligned(start_ptr.add(i))).movemask();
+        if mask != 0 {
+            return Some(i + forward_pos::<T>(mask));
+        }
+        i += lanes;
+    }
+    if i < end {
+        // Search the last vector's worth of elements, which may overlap
+        // with elements we've already searched. Since those didn't match,
+        // the first match in this vector is still the first match overall.
+        i = end - lanes;
+        let mask = m.matches(V::load_unaligned(start_ptr.add(i))).movemask();
+        if mask != 0 {
+            return Some(i + forward_pos::<T>(mask));
+        }
+    }
+    None
+}
+
+/// Find the index of the last matching element in the given haystack, which
+/// must contain at least `V::LANES` elements.
+#[inline(always)]
+pub(crate) unsafe fn reverse<T, V: Vector<T>, M: Matcher<T, V>>(
+    m: M,
+    haystack: &[T],
+) -> Option<usize> {
+    debug_assert!(haystack.len() >= V::LANES);
+
+    let lanes = V::LANES;
+    let start_ptr = haystack.as_ptr();
+    let mut i = haystack.len();
+    while i >= 2 * lanes {
+        i -= 2 * lanes;
+        let a = m.matches(V::load_unaligned(start_ptr.add(i)));
+        let b = m.matches(V::load_unaligned(start_ptr.add(i + lanes)));
+        if a.or(b).movemask() != 0 {
+            let mask = b.movemask();
+            if mask != 0 {
+                return Some(i + lanes + reverse_pos::<T>(mask));
+            }
+            return Some(i + reverse_pos::<T>(a.movemask()));
+        }
+    }
+    while i >= lanes {
+        i -= lanes;
+        let mask = m.matches(V::load_unaligned(start_ptr.add(i))).movemask();
+        if mask != 0 {
+            return Some(i + reverse_pos::<T>(mask));
+        }
+    }
+    if i > 0 {
+        // As in the forward case, this may overlap with elements we've
+        // already searched, which is fine since none of them matched.
+        let mask = m.matches(V::load_unaligned(start_ptr)).movemask();
+        if mask != 0 {
+            return Some(reverse_pos::<T>(mask));
+        }
+    }
+    None
+}
+
+/// Compute the lane of the first match from the given mask, for lanes of type
+/// `T`.
+fn forward_pos<T>(mask: u32) -> usize {
+    mask.trailing_zeros() as usize / size_of::<T>()
+}
+
+/// Compute the lane of the last match from the given mask, for lanes of type
+/// `T`.
+fn reverse_pos<T>(mask: u32) -> usize {
+    (31 - mask.leading_zeros() as usize) / size_of::<T>()
+}
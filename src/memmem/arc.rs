@@ -0,0 +1,80 @@
+use crate::memmem::{FindIter, Finder};
+
+/// A substring searcher that can be cheaply cloned and shared across threads.
+///
+/// This is created by [`Finder::into_arc`]. It owns its needle, and cloning
+/// it only increments a reference count, so all clones share one searcher.
+/// This is useful for searching for one needle from many threads. `ArcFinder`
+/// is `Send` and `Sync`, and since searching only requires a shared
+/// reference, a single `ArcFinder` may also be used by several threads at
+/// once.
+///
+/// Other search routines are available via [`ArcFinder::finder`].
+///
+/// This is only available when the `std` feature is enabled.
+#[derive(Clone, Debug)]
+pub struct ArcFinder(std::sync::Arc<Finder<'static>>);
+
+impl ArcFinder {
+    pub(crate) fn new(finder: Finder<'static>) -> ArcFinder {
+        ArcFinder(std::sync::Arc::new(finder))
+    }
+
+    /// Returns the index of the first occurrence of this needle in the given
+    /// haystack.
+    ///
+    /// This is the same as [`Finder::find`].
+    #[inline]
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.0.find(haystack)
+    }
+
+    /// Returns an iterator over all non-overlapping occurrences of this
+    /// needle in the given haystack.
+    ///
+    /// This is the same as [`Finder::find_iter`].
+    #[inline]
+    pub fn find_iter<'a, 'h>(
+        &'a self,
+        haystack: &'h [u8],
+    ) -> FindIter<'h, 'a> {
+        self.0.find_iter(haystack)
+    }
+
+    /// Returns the shared finder.
+    #[inline]
+    pub fn finder(&self) -> &Finder<'static> {
+        &self.0
+    }
+
+    /// Returns the needle that this finder searches for.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        self.0.needle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn arc_finder() {
+        use crate::memmem::{ArcFinder, Finder};
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ArcFinder>();
+
+        let needle = String::from("foo");
+        let finder = Finder::new(&needle).into_arc();
+        drop(needle);
+        let clone = finder.clone();
+        assert!(core::ptr::eq(finder.finder(), clone.finder()));
+        assert!(core::ptr::eq(finder.needle(), clone.needle()));
+        assert_eq!(b"foo", clone.needle());
+        assert_eq!(Some(4), clone.find(b"bar foo foo"));
+        assert_eq!(
+            vec![4, 8],
+            clone.find_iter(b"bar foo foo").collect::<Vec<_>>()
+        );
+        assert_eq!(None, finder.find(b"bar"));
+    }
+}
@@ -0,0 +1,56 @@
+use core::convert::TryInto;
+
+/// A byte array that can be used as the needle of
+/// [`find_n`](crate::memmem::find_n).
+///
+/// This is implemented for byte arrays of every length from 0 to 32. Since
+/// the length of an array is part of its type, `find_n` is compiled
+/// separately for each length, which lets the compiler unroll comparisons
+/// against the needle.
+///
+/// This trait is sealed, so it cannot be implemented outside of this crate.
+pub trait NeedleArray: private::Sealed {}
+
+macro_rules! impl_needle_array {
+    ($($len:expr),*) => {
+        $(
+            impl NeedleArray for [u8; $len] {}
+
+            impl private::Sealed for [u8; $len] {
+                const LEN: usize = $len;
+
+                #[inline(always)]
+                fn as_slice(&self) -> &[u8] {
+                    self
+                }
+
+                #[inline(always)]
+                fn is_window(&self, window: &[u8]) -> bool {
+                    // Comparing arrays instead of slices lets the comparison
+                    // be unrolled instead of calling memcmp.
+                    let window: &[u8; $len] = window.try_into().unwrap();
+                    window == self
+                }
+            }
+        )*
+    };
+}
+
+impl_needle_array!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+    21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
+);
+
+pub(crate) mod private {
+    pub trait Sealed {
+        /// The length of the needle.
+        const LEN: usize;
+
+        /// Returns the needle as a slice.
+        fn as_slice(&self) -> &[u8];
+
+        /// Returns true if and only if the given window, which must have a
+        /// length of `LEN`, is equal to the needle.
+        fn is_window(&self, window: &[u8]) -> bool;
+    }
+}
@@ -0,0 +1,198 @@
+/*!
+Ready-made byte frequency heuristics for substring search.
+
+Each type in this module is a zero sized implementation of
+[`HeuristicFrequencyRank`], which can be given to
+[`FinderBuilder::build_heuristic`](crate::memmem::FinderBuilder::build_heuristic)
+and friends. Picking a heuristic that matches the data being searched lets
+prefilters look for bytes that are actually rare in it.
+
+# Example
+
+```
+use memchr::memmem::{freq, FinderBuilder};
+
+let finder = FinderBuilder::new()
+    .build_heuristic(b"\x00\x00\xdd\xdd", freq::X86Executable);
+assert_eq!(Some(1), finder.find(b"\x00\x00\x00\xdd\xdd"));
+```
+*/
+
+use core::cmp;
+
+use crate::memmem::{
+    byte_frequencies::BYTE_FREQUENCIES, HeuristicFrequencyRank,
+};
+
+/// The heuristic used by default, which is derived from a corpus of source
+/// code, natural language text and binary data. It is a good choice for
+/// most inputs, and especially for text.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Text;
+
+impl HeuristicFrequencyRank for Text {
+    fn rank(&self, byte: u8) -> u8 {
+        BYTE_FREQUENCIES[byte as usize]
+    }
+}
+
+/// A heuristic derived from x86 and x86_64 executables. In this kind of
+/// data, bytes like `\x00` and `\xFF` are very common, while many printable
+/// ASCII bytes are not.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct X86Executable;
+
+impl HeuristicFrequencyRank for X86Executable {
+    fn rank(&self, byte: u8) -> u8 {
+        X86_EXECUTABLE_FREQUENCIES[byte as usize]
+    }
+}
+
+/// A heuristic that considers every byte equally common.
+///
+/// This makes no assumptions about the data being searched, so prefilters
+/// look for the first distinct bytes in the needle. This avoids the worst
+/// case of the other heuristics, where a byte believed to be rare is in fact
+/// common in the haystack, at the cost of rarely picking the best bytes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Uniform;
+
+impl HeuristicFrequencyRank for Uniform {
+    fn rank(&self, _: u8) -> u8 {
+        128
+    }
+}
+
+/// A heuristic learned from a sample of the data that will be searched.
+///
+/// The rank of each byte is proportional to the number of times it occurs in
+/// the samples. The most frequent byte has rank `255`, every other byte that
+/// occurs has a rank of at least `1` (rounding up), and bytes that never
+/// occur have rank `0`. The ranks only depend on the byte counts, so the same
+/// samples always produce the same table.
+///
+/// # Example
+///
+/// ```
+/// use memchr::memmem::{freq::SampleTable, FinderBuilder};
+///
+/// let table = SampleTable::from_samples(&[b"\x00\x00\x00\x01", b"\x00\x02"]);
+/// let finder = FinderBuilder::new().build_heuristic(b"\x00\x01", &table);
+/// assert_eq!(Some(2), finder.find(b"\x00\x00\x00\x01"));
+/// ```
+#[derive(Clone)]
+pub struct SampleTable {
+    ranks: [u8; 256],
+}
+
+impl SampleTable {
+    /// Build a table by counting the bytes in the given samples.
+    pub fn from_samples(samples: &[&[u8]]) -> SampleTable {
+        let mut counts = [0u64; 256];
+        for sample in samples {
+            for &b in sample.iter() {
+                counts[b as usize] += 1;
+            }
+        }
+        // Clamping to 1 leaves every rank at 0 when there are no samples.
+        let max = cmp::max(1, counts.iter().copied().max().unwrap_or(0));
+        let max = max as u128;
+        let mut ranks = [0u8; 256];
+        // `div_ceil` isn't available on our MSRV.
+        #[allow(clippy::manual_div_ceil)]
+        for (rank, &count) in ranks.iter_mut().zip(counts.iter()) {
+            // This can't overflow since count <= max and the result is at
+            // most 255.
+            *rank = ((count as u128 * 255 + max - 1) / max) as u8;
+        }
+        SampleTable { ranks }
+    }
+
+    /// Returns the rank of every byte, indexed by byte.
+    ///
+    /// This can be used to embed a learned table in source code.
+    pub fn ranks(&self) -> &[u8; 256] {
+        &self.ranks
+    }
+}
+
+// This can't be derived on our MSRV, since arrays longer than 32 elements
+// only implement Debug as of Rust 1.47.
+impl core::fmt::Debug for SampleTable {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("SampleTable").field("ranks", &&self.ranks[..]).finish()
+    }
+}
+
+impl HeuristicFrequencyRank for SampleTable {
+    fn rank(&self, byte: u8) -> u8 {
+        self.ranks[byte as usize]
+    }
+}
+
+const X86_EXECUTABLE_FREQUENCIES: [u8; 256] = [
+    255, 128, 61, 43, 50, 41, 27, 28, 57, 15, 21, 13, 24, 17, 17, 89, 58, 16,
+    11, 7, 14, 23, 7, 6, 24, 9, 6, 5, 9, 4, 7, 16, 68, 11, 9, 6, 88, 7, 4, 4,
+    23, 9, 4, 8, 8, 5, 10, 4, 30, 11, 9, 24, 11, 5, 5, 5, 19, 11, 6, 17, 9, 9,
+    6, 8, 48, 58, 11, 14, 53, 40, 9, 9, 254, 35, 3, 6, 52, 23, 6, 6, 27, 4, 7,
+    11, 14, 13, 10, 11, 11, 5, 2, 10, 16, 12, 6, 19, 19, 20, 5, 14, 16, 31,
+    19, 7, 14, 20, 4, 4, 19, 8, 18, 20, 24, 1, 25, 19, 58, 29, 10, 5, 15, 20,
+    2, 2, 9, 4, 3, 5, 51, 11, 4, 53, 23, 39, 6, 4, 13, 81, 4, 186, 5, 67, 3,
+    2, 15, 0, 0, 1, 3, 2, 0, 0, 5, 0, 0, 0, 2, 0, 0, 0, 12, 2, 1, 1, 3, 1, 1,
+    1, 6, 1, 2, 1, 3, 1, 1, 2, 9, 1, 1, 0, 2, 2, 4, 4, 11, 6, 7, 3, 6, 9, 4,
+    5, 46, 18, 8, 18, 17, 3, 8, 20, 16, 10, 3, 7, 175, 4, 6, 7, 13, 3, 7, 3,
+    3, 1, 3, 3, 10, 3, 1, 5, 2, 0, 1, 2, 16, 3, 5, 1, 6, 1, 1, 2, 58, 20, 3,
+    14, 12, 2, 1, 3, 16, 3, 5, 8, 3, 1, 8, 6, 17, 6, 5, 3, 8, 6, 13, 175,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memmem::FinderBuilder;
+
+    #[test]
+    fn sample_table_skewed() {
+        let mut sample = [0u8; 10_000];
+        sample[10] = b'a';
+        sample[20] = b'b';
+        sample[30] = b'b';
+        let table = SampleTable::from_samples(&[&sample[..], b"bb"]);
+        assert_eq!(255, table.rank(0));
+        assert_eq!(1, table.rank(b'a'));
+        assert_eq!(1, table.rank(b'b'));
+        assert_eq!(0, table.rank(b'z'));
+
+        // 'x' occurs 4 times and 'y' occurs 6 times, so 'x' ranks at two
+        // thirds of the maximum.
+        let table = SampleTable::from_samples(&[b"xxyy", b"yyyyxx"]);
+        assert_eq!(255, table.rank(b'y'));
+        assert_eq!(170, table.rank(b'x'));
+
+        let table = SampleTable::from_samples(&[]);
+        assert!(table.ranks().iter().all(|&r| r == 0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn every_table_finds_the_same_matches() {
+        let haystack = b"\x00\x00\x00\xdd\xdd foo bar \x00\xdd foo";
+        for needle in [&b"\x00\xdd"[..], b"foo", b"o b", b"\x00\x00\xdd\xdd"] {
+            let expected: Vec<usize> = FinderBuilder::new()
+                .build_forward(needle)
+                .find_iter(haystack)
+                .collect();
+            let b = FinderBuilder::new();
+            let sampled = SampleTable::from_samples(&[haystack]);
+            let finders = [
+                b.build_heuristic(needle, &sampled),
+                b.build_heuristic(needle, Text),
+                b.build_heuristic(needle, X86Executable),
+                b.build_heuristic(needle, Uniform),
+            ];
+            for finder in finders.iter() {
+                let got: Vec<usize> = finder.find_iter(haystack).collect();
+                assert_eq!(expected, got);
+            }
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use core::mem::size_of;
 
-use crate::memmem::{util::memcmp, vector::Vector, NeedleInfo};
+use crate::memmem::{
+    util::memcmp,
+    vector::{Vector, PREFETCH_DISTANCE, PREFETCH_MIN_HAYSTACK_LEN},
+    NeedleInfo, Verifier,
+};
 
 /// The minimum length of a needle required for this algorithm. The minimum
 /// is 2 since a length of 1 should just use memchr and a length of 0 isn't
@@ -34,6 +38,14 @@ pub(crate) const MIN_NEEDLE_LEN: usize = 2;
 /// worse. So... 32 it is?
 pub(crate) const MAX_NEEDLE_LEN: usize = 32;
 
+/// The minimum length of a needle for which candidates are verified with
+/// vector comparisons when [`Verifier::Simd`] is selected.
+///
+/// This is the size of the vector used for verification. Shorter needles are
+/// always verified with `memcmp`, which only needs one or two loads for them
+/// anyway.
+const SIMD_VERIFY_MIN_NEEDLE_LEN: usize = 16;
+
 /// The implementation of the forward vector accelerated substring search.
 ///
 /// This is extremely similar to the prefilter vector module by the same name.
@@ -56,12 +68,19 @@ pub(crate) const MAX_NEEDLE_LEN: usize = 32;
 pub(crate) struct Forward {
     rare1i: u8,
     rare2i: u8,
+    /// Whether candidates are verified with vector comparisons instead of
+    /// `memcmp`.
+    simd_verify: bool,
 }
 
 impl Forward {
     /// Create a new "generic simd" forward searcher. If one could not be
     /// created from the given inputs, then None is returned.
-    pub(crate) fn new(ninfo: &NeedleInfo, needle: &[u8]) -> Option<Forward> {
+    pub(crate) fn new(
+        ninfo: &NeedleInfo,
+        needle: &[u8],
+        verifier: Verifier,
+    ) -> Option<Forward> {
         let (rare1i, rare2i) = ninfo.rarebytes.as_rare_ordered_u8();
         // If the needle is too short or too long, give up. Also, give up
         // if the rare bytes detected are at the same position. (It likely
@@ -73,7 +92,9 @@ impl Forward {
         {
             return None;
         }
-        Some(Forward { rare1i, rare2i })
+        let simd_verify = verifier == Verifier::Simd
+            && needle.len() >= SIMD_VERIFY_MIN_NEEDLE_LEN;
+        Some(Forward { rare1i, rare2i, simd_verify })
     }
 
     /// Returns the minimum length of haystack that is needed for this searcher
@@ -88,6 +109,10 @@ impl Forward {
 /// Searches the given haystack for the given needle. The needle given should
 /// be the same as the needle that this searcher was initialized with.
 ///
+/// `V` is the vector used to find candidates, while `W` is the 16 byte vector
+/// used to verify them. They are distinct so that searches with 32 byte
+/// vectors can still verify needles shorter than 32 bytes with vectors.
+///
 /// # Panics
 ///
 /// When the given haystack has a length smaller than `min_haystack_len`.
@@ -100,7 +125,7 @@ impl Forward {
 /// supports the vector functions that this function is specialized for. (For
 /// the specific vector functions used, see the Vector trait implementations.)
 #[inline(always)]
-pub(crate) unsafe fn fwd_find<V: Vector>(
+pub(crate) unsafe fn fwd_find<V: Vector, W: Vector>(
     fwd: &Forward,
     haystack: &[u8],
     needle: &[u8],
@@ -141,14 +166,31 @@ pub(crate) unsafe fn fwd_find<V: Vector>(
     // marginally faster while the quadruple unroll was unambiguously slower.
     // In the end, I decided the complexity from unrolling wasn't worth it. I
     // used the memmem/krate/prebuilt/huge-en/ benchmarks to compare.
-    while ptr <= max_ptr {
-        let m = fwd_find_in_chunk(
-            fwd, needle, ptr, end_ptr, rare1chunk, rare2chunk, !0,
-        );
-        if let Some(chunki) = m {
-            return Some(matched(start_ptr, ptr, chunki));
+    //
+    // The loop is duplicated so that only large haystacks pay for the
+    // prefetch. Checking the length inside a single loop instead measurably
+    // slows down searches of small haystacks.
+    if haystack.len() >= PREFETCH_MIN_HAYSTACK_LEN {
+        while ptr <= max_ptr {
+            V::prefetch(ptr.wrapping_add(PREFETCH_DISTANCE));
+            let m = fwd_find_in_chunk::<V, W>(
+                fwd, needle, ptr, end_ptr, rare1chunk, rare2chunk, !0,
+            );
+            if let Some(chunki) = m {
+                return Some(matched(start_ptr, ptr, chunki));
+            }
+            ptr = ptr.add(size_of::<V>());
+        }
+    } else {
+        while ptr <= max_ptr {
+            let m = fwd_find_in_chunk::<V, W>(
+                fwd, needle, ptr, end_ptr, rare1chunk, rare2chunk, !0,
+            );
+            if let Some(chunki) = m {
+                return Some(matched(start_ptr, ptr, chunki));
+            }
+            ptr = ptr.add(size_of::<V>());
         }
-        ptr = ptr.add(size_of::<V>());
     }
     if ptr < end_ptr {
         let remaining = diff(end_ptr, ptr);
@@ -184,7 +226,7 @@ pub(crate) unsafe fn fwd_find<V: Vector>(
         // ignored.
         let mask = !((1 << overlap) - 1);
         ptr = max_ptr;
-        let m = fwd_find_in_chunk(
+        let m = fwd_find_in_chunk::<V, W>(
             fwd, needle, ptr, end_ptr, rare1chunk, rare2chunk, mask,
         );
         if let Some(chunki) = m {
@@ -214,7 +256,7 @@ pub(crate) unsafe fn fwd_find<V: Vector>(
 /// (ptr + rare1i) and (ptr + rare2i). It must also be safe to do unaligned
 /// loads on ptr up to (end_ptr - needle.len()).
 #[inline(always)]
-unsafe fn fwd_find_in_chunk<V: Vector>(
+unsafe fn fwd_find_in_chunk<V: Vector, W: Vector>(
     fwd: &Forward,
     needle: &[u8],
     ptr: *const u8,
@@ -237,7 +279,12 @@ unsafe fn fwd_find_in_chunk<V: Vector>(
             return None;
         }
         let chunk = core::slice::from_raw_parts(ptr, needle.len());
-        if memcmp(needle, chunk) {
+        let verified = if fwd.simd_verify {
+            memcmp_vector::<W>(needle, chunk)
+        } else {
+            memcmp(needle, chunk)
+        };
+        if verified {
             return Some(offset);
         }
         match_offsets &= match_offsets - 1;
@@ -245,6 +292,38 @@ unsafe fn fwd_find_in_chunk<V: Vector>(
     None
 }
 
+/// Returns true if and only if `x == y`, comparing a vector of bytes at a
+/// time.
+///
+/// `x` and `y` must have the same length, which must be at least the size of
+/// `V`. The last comparison overlaps with the previous one when the length
+/// isn't a multiple of the size of `V`.
+///
+/// # Safety
+///
+/// Callers must ensure that the target features required by `V` are enabled.
+#[inline(always)]
+unsafe fn memcmp_vector<V: Vector>(x: &[u8], y: &[u8]) -> bool {
+    debug_assert_eq!(x.len(), y.len());
+    debug_assert!(x.len() >= size_of::<V>());
+
+    let all = (1u64 << size_of::<V>()) - 1;
+    let eq = |at: usize| {
+        let vx = V::load_unaligned(x.as_ptr().add(at));
+        let vy = V::load_unaligned(y.as_ptr().add(at));
+        u64::from(vx.cmpeq(vy).movemask()) == all
+    };
+    let last = x.len() - size_of::<V>();
+    let mut at = 0;
+    while at < last {
+        if !eq(at) {
+            return false;
+        }
+        at += size_of::<V>();
+    }
+    eq(last)
+}
+
 /// Accepts a chunk-relative offset and returns a haystack relative offset
 /// after updating the prefilter state.
 ///
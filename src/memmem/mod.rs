@@ -66,7 +66,20 @@ assert_eq!(None, finder.find(b"quux baz bar"));
 ```
 */
 
-pub use self::prefilter::Prefilter;
+#[cfg(all(feature = "std", feature = "tokio"))]
+pub use self::stream::find_async_reader;
+#[cfg(feature = "std")]
+pub use self::{
+    arc::ArcFinder,
+    set::FinderSet,
+    stream::{find_reader, StreamFindIter, StreamFinder, StreamMatch},
+};
+pub use self::{
+    array::NeedleArray,
+    prefilter::{Prefilter, PrefilterInfo, PrefilterKind},
+    verifier::Verifier,
+    wildcard::WildcardFinder,
+};
 
 use crate::{
     cow::CowBytes,
@@ -145,24 +158,40 @@ macro_rules! define_memmem_simple_tests {
     };
 }
 
+#[cfg(feature = "std")]
+mod arc;
+mod array;
 mod byte_frequencies;
+pub mod freq;
 #[cfg(memchr_runtime_simd)]
 mod genericsimd;
-mod prefilter;
-mod rabinkarp;
-mod rarebytes;
-mod twoway;
+pub(crate) mod prefilter;
+pub(crate) mod rabinkarp;
+pub(crate) mod rarebytes;
+#[cfg(feature = "std")]
+mod set;
+#[cfg(feature = "std")]
+mod stream;
+pub(crate) mod twoway;
 mod util;
-#[cfg(memchr_runtime_simd)]
+#[cfg(any(memchr_runtime_simd, memchr_runtime_neon))]
 mod vector;
+mod verifier;
 #[cfg(all(memchr_runtime_wasm128))]
 mod wasm;
+mod wildcard;
 #[cfg(all(not(miri), target_arch = "x86_64", memchr_runtime_simd))]
 mod x86;
 
 /// Returns an iterator over all non-overlapping occurrences of a substring in
 /// a haystack.
 ///
+/// An empty needle matches at every position in the haystack, including at
+/// its end. So for an empty needle, this yields every offset in
+/// `0..=haystack.len()`, exactly once each. This is like `str::match_indices`
+/// with an empty pattern, except that every position is a match instead of
+/// only the positions at valid UTF-8 boundaries.
+///
 /// # Complexity
 ///
 /// This routine is guaranteed to have worst case linear time complexity
@@ -197,6 +226,13 @@ pub fn find_iter<'h, 'n, N: 'n + ?Sized + AsRef<[u8]>>(
 /// Returns a reverse iterator over all non-overlapping occurrences of a
 /// substring in a haystack.
 ///
+/// Occurrences are reported by their starting offset from the beginning of
+/// the haystack, in descending order. Since occurrences are found from the
+/// end of the haystack, a needle that can overlap with itself may produce a
+/// different set of occurrences than [`find_iter`]. For example, searching
+/// `aaa` for `aa` yields `0` forwards but `1` in reverse. Otherwise, both
+/// report the same set of occurrences.
+///
 /// # Complexity
 ///
 /// This routine is guaranteed to have worst case linear time complexity
@@ -230,6 +266,9 @@ pub fn rfind_iter<'h, 'n, N: 'n + ?Sized + AsRef<[u8]>>(
 
 /// Returns the index of the first occurrence of the given needle.
 ///
+/// An empty needle always matches at offset `0`, even in an empty haystack.
+/// This is consistent with `str::find`.
+///
 /// Note that if you're are searching for the same needle in many different
 /// small haystacks, it may be faster to initialize a [`Finder`] once,
 /// and reuse it for each search.
@@ -301,170 +340,2182 @@ pub fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     }
 }
 
-/// An iterator over non-overlapping substring matches.
+/// Returns the index of the first occurrence of the given needle within a
+/// sub-range of the haystack.
 ///
-/// Matches are reported by the byte offset at which they begin.
+/// This is like [`find`], except that only `haystack[range]` is searched, and
+/// the index returned is relative to the start of `haystack` instead of the
+/// start of the range. An occurrence is only reported if it lies entirely
+/// within the range.
 ///
-/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
-/// needle.
-#[derive(Debug)]
-pub struct FindIter<'h, 'n> {
-    haystack: &'h [u8],
-    prestate: PrefilterState,
-    finder: Finder<'n>,
-    pos: usize,
+/// # Panics
+///
+/// This panics if `range` is out of bounds for `haystack`, or if its start
+/// is greater than its end. This is the same condition under which
+/// `&haystack[range]` panics.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let haystack = b"foo bar foo baz";
+/// assert_eq!(Some(8), memmem::find_in(haystack, b"foo", 1..haystack.len()));
+/// // The occurrence at 8 does not fit in the range.
+/// assert_eq!(None, memmem::find_in(haystack, b"foo", 1..10));
+/// ```
+#[inline]
+pub fn find_in(
+    haystack: &[u8],
+    needle: &[u8],
+    range: core::ops::Range<usize>,
+) -> Option<usize> {
+    let start = range.start;
+    find(&haystack[range], needle).map(|i| start + i)
 }
 
-impl<'h, 'n> FindIter<'h, 'n> {
-    #[inline(always)]
-    pub(crate) fn new(
-        haystack: &'h [u8],
-        finder: Finder<'n>,
-    ) -> FindIter<'h, 'n> {
-        let prestate = finder.searcher.prefilter_state();
-        FindIter { haystack, prestate, finder, pos: 0 }
-    }
-
-    /// Convert this iterator into its owned variant, such that it no longer
-    /// borrows the finder and needle.
-    ///
-    /// If this is already an owned iterator, then this is a no-op. Otherwise,
-    /// this copies the needle.
-    ///
-    /// This is only available when the `std` feature is enabled.
-    #[cfg(feature = "std")]
-    #[inline]
-    pub fn into_owned(self) -> FindIter<'h, 'static> {
-        FindIter {
-            haystack: self.haystack,
-            prestate: self.prestate,
-            finder: self.finder.into_owned(),
-            pos: self.pos,
-        }
-    }
+/// Returns the index of the first occurrence of the given needle within a
+/// sub-range of the haystack, without panicking on an invalid range.
+///
+/// This is like [`find_in`], except that an error is returned instead of
+/// panicking when `range` is out of bounds for `haystack`, or when its start
+/// is greater than its end. This is useful when ranges come from untrusted
+/// input.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let haystack = b"foo bar foo baz";
+/// assert_eq!(Ok(Some(8)), memmem::try_find_in(haystack, b"foo", 1..15));
+///
+/// let err = memmem::try_find_in(haystack, b"foo", 1..16).unwrap_err();
+/// assert_eq!(1..16, err.range());
+/// assert!(!err.is_inverted());
+/// ```
+#[inline]
+pub fn try_find_in(
+    haystack: &[u8],
+    needle: &[u8],
+    range: core::ops::Range<usize>,
+) -> Result<Option<usize>, crate::RangeError> {
+    crate::RangeError::check(&range, haystack.len())?;
+    Ok(find_in(haystack, needle, range))
 }
 
-impl<'h, 'n> Iterator for FindIter<'h, 'n> {
-    type Item = usize;
+/// Returns the index of the first occurrence of a needle whose length is
+/// known at compile time.
+///
+/// This is like [`find`], but is specialized on the length of the needle,
+/// which makes it a good fit for fixed delimiters like `b"\r\n"`. The needle
+/// must be a byte array of at most 32 bytes, as described by
+/// [`NeedleArray`]. Short haystacks are searched with a loop that compares
+/// `N` bytes at a time, where `N` is the length of the needle, which the
+/// compiler can unroll since `N` is a constant. [`find`] doesn't build a
+/// searcher for haystacks this short either, but uses Rabin-Karp, and
+/// updating its rolling hash at every position costs more than comparing a
+/// short window directly. Longer haystacks are searched with [`find`].
+///
+/// # Complexity
+///
+/// For short haystacks, this runs in `O(N * haystack.len())` time, and
+/// otherwise has the same guarantees as [`find`]. Since the loop is only
+/// used on haystacks of bounded length, this is linear time in the
+/// haystack for any fixed `N`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let haystack = b"GET / HTTP/1.1\r\nHost: example.com\r\n";
+/// assert_eq!(Some(14), memmem::find_n(haystack, b"\r\n"));
+/// assert_eq!(None, memmem::find_n(haystack, b"\n\n"));
+/// ```
+#[inline]
+pub fn find_n<A: NeedleArray>(haystack: &[u8], needle: &A) -> Option<usize> {
+    // Below this, `find` uses Rabin-Karp. For short needles, comparing N
+    // bytes at each position is faster than updating and checking its
+    // rolling hash. Above it, `find` builds a full searcher.
+    const MAX_LOOP_LEN: usize = 64;
 
-    fn next(&mut self) -> Option<usize> {
-        if self.pos > self.haystack.len() {
-            return None;
-        }
-        let result = self
-            .finder
-            .searcher
-            .find(&mut self.prestate, &self.haystack[self.pos..]);
-        match result {
-            None => None,
-            Some(i) => {
-                let pos = self.pos + i;
-                self.pos = pos + core::cmp::max(1, self.finder.needle().len());
-                Some(pos)
-            }
-        }
+    let n = A::LEN;
+    if n <= 1 || haystack.len() >= MAX_LOOP_LEN {
+        return find(haystack, needle.as_slice());
+    }
+    if haystack.len() < n {
+        return None;
     }
+    (0..=haystack.len() - n).find(|&i| needle.is_window(&haystack[i..i + n]))
 }
 
-/// An iterator over non-overlapping substring matches in reverse.
+/// Returns true if and only if the given needle occurs in the haystack.
 ///
-/// Matches are reported by the byte offset at which they begin.
+/// This is equivalent to `find(haystack, needle).is_some()`.
 ///
-/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
-/// needle.
-#[derive(Debug)]
-pub struct FindRevIter<'h, 'n> {
-    haystack: &'h [u8],
-    finder: FinderRev<'n>,
-    /// When searching with an empty needle, this gets set to `None` after
-    /// we've yielded the last element at `0`.
-    pos: Option<usize>,
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the needle and the haystack. That is, this runs
+/// in `O(needle.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// assert!(memmem::contains(b"foo bar baz", b"bar"));
+/// assert!(!memmem::contains(b"foo bar baz", b"quux"));
+/// ```
+#[inline]
+pub fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    find(haystack, needle).is_some()
 }
 
-impl<'h, 'n> FindRevIter<'h, 'n> {
-    #[inline(always)]
-    pub(crate) fn new(
-        haystack: &'h [u8],
-        finder: FinderRev<'n>,
-    ) -> FindRevIter<'h, 'n> {
-        let pos = Some(haystack.len());
-        FindRevIter { haystack, finder, pos }
-    }
-
-    /// Convert this iterator into its owned variant, such that it no longer
-    /// borrows the finder and needle.
-    ///
-    /// If this is already an owned iterator, then this is a no-op. Otherwise,
-    /// this copies the needle.
-    ///
-    /// This is only available when the `std` feature is enabled.
-    #[cfg(feature = "std")]
-    #[inline]
-    pub fn into_owned(self) -> FindRevIter<'h, 'static> {
-        FindRevIter {
-            haystack: self.haystack,
-            finder: self.finder.into_owned(),
-            pos: self.pos,
-        }
-    }
+/// Returns the number of non-overlapping occurrences of a needle in a
+/// haystack.
+///
+/// This is equivalent to `Finder::new(needle).count(haystack)`. In
+/// particular, an empty needle matches at every position in the haystack,
+/// including at its end, so the count for an empty needle is
+/// `haystack.len() + 1`.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the needle and the haystack. That is, this runs
+/// in `O(needle.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// assert_eq!(3, memmem::count(b"foo bar foo baz foo", b"foo"));
+/// assert_eq!(2, memmem::count(b"aaaa", b"aa"));
+/// ```
+#[inline]
+pub fn count(haystack: &[u8], needle: &[u8]) -> usize {
+    Finder::new(needle).count(haystack)
 }
 
-impl<'h, 'n> Iterator for FindRevIter<'h, 'n> {
-    type Item = usize;
-
-    fn next(&mut self) -> Option<usize> {
-        let pos = match self.pos {
-            None => return None,
-            Some(pos) => pos,
-        };
-        let result = self.finder.rfind(&self.haystack[..pos]);
-        match result {
-            None => None,
-            Some(i) => {
-                if pos == i {
-                    self.pos = pos.checked_sub(1);
-                } else {
-                    self.pos = Some(i);
-                }
-                Some(i)
-            }
-        }
-    }
+/// Returns the number of occurrences of a needle in a haystack, including
+/// occurrences that overlap.
+///
+/// This is equivalent to
+/// `Finder::new(needle).find_overlapping_iter(haystack).count()`.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the needle and the haystack. That is, this runs
+/// in `O(needle.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// assert_eq!(3, memmem::count_overlapping(b"aaaa", b"aa"));
+/// assert_eq!(1, memmem::count_overlapping(b"abab", b"bab"));
+/// ```
+#[inline]
+pub fn count_overlapping(haystack: &[u8], needle: &[u8]) -> usize {
+    Finder::new(needle).find_overlapping_iter(haystack).count()
 }
 
-/// A single substring searcher fixed to a particular needle.
+/// Returns the index of the first ASCII case insensitive occurrence of the
+/// given needle.
 ///
-/// The purpose of this type is to permit callers to construct a substring
-/// searcher that can be used to search haystacks without the overhead of
-/// constructing the searcher in the first place. This is a somewhat niche
-/// concern when it's necessary to re-use the same needle to search multiple
-/// different haystacks with as little overhead as possible. In general, using
-/// [`find`] is good enough, but `Finder` is useful when you can meaningfully
-/// observe searcher construction time in a profile.
+/// Only the ASCII letters `A-Z` and `a-z` are folded. All other bytes,
+/// including non-ASCII bytes, must match exactly. In particular, no Unicode
+/// case folding is performed.
 ///
-/// When the `std` feature is enabled, then this type has an `into_owned`
-/// version which permits building a `Finder` that is not connected to
-/// the lifetime of its needle.
-#[derive(Clone, Debug)]
-pub struct Finder<'n> {
-    searcher: Searcher<'n>,
+/// Note that if you're are searching for the same needle in many different
+/// haystacks, it may be faster to build a [`Finder`] once with
+/// [`FinderBuilder::build_forward_ascii_ignore_case`], and reuse it for each
+/// search.
+///
+/// # Complexity
+///
+/// Unlike [`find`], this routine does not guarantee linear time complexity.
+/// In the worst case, it runs in `O(needle.len() * haystack.len())` time.
+///
+/// This routine is guaranteed to have worst case constant space complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let haystack = b"Foo BAR baz";
+/// assert_eq!(Some(0), memmem::find_ascii_ignore_case(haystack, b"foo"));
+/// assert_eq!(Some(4), memmem::find_ascii_ignore_case(haystack, b"bar"));
+/// assert_eq!(None, memmem::find_ascii_ignore_case(haystack, b"quux"));
+/// ```
+#[inline]
+pub fn find_ascii_ignore_case(
+    haystack: &[u8],
+    needle: &[u8],
+) -> Option<usize> {
+    FinderBuilder::new().build_forward_ascii_ignore_case(needle).find(haystack)
 }
 
-impl<'n> Finder<'n> {
-    /// Create a new finder for the given needle.
-    #[inline]
-    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &'n B) -> Finder<'n> {
-        FinderBuilder::new().build_forward(needle)
-    }
-
-    /// Returns the index of the first occurrence of this needle in the given
-    /// haystack.
-    ///
-    /// # Complexity
-    ///
-    /// This routine is guaranteed to have worst case linear time complexity
-    /// with respect to both the needle and the haystack. That is, this runs
-    /// in `O(needle.len() + haystack.len())` time.
+/// Returns a copy of the haystack with every non-overlapping occurrence of
+/// the needle replaced by the given replacement.
+///
+/// Occurrences are found and replaced from left to right, exactly as they
+/// are reported by [`find_iter`]. All bytes outside of an occurrence are
+/// copied verbatim.
+///
+/// If the needle is empty, then no replacements are made and a copy of the
+/// haystack is returned unchanged.
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to the needle, the haystack and the returned bytes.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let replaced = memmem::replace_all(b"foo bar foo", b"foo", b"quux");
+/// assert_eq!(&b"quux bar quux"[..], &*replaced);
+///
+/// // Occurrences never overlap.
+/// assert_eq!(&b"bb"[..], &*memmem::replace_all(b"aaaa", b"aa", b"b"));
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn replace_all(
+    haystack: &[u8],
+    needle: &[u8],
+    replacement: &[u8],
+) -> Vec<u8> {
+    Finder::new(needle).replace_all(haystack, replacement)
+}
+
+/// Like [`replace_all`], but borrows the haystack instead of copying it when
+/// there is nothing to replace.
+///
+/// This returns `Cow::Borrowed(haystack)` when the needle does not occur in
+/// the haystack (or is empty), and otherwise returns an owned copy of the
+/// haystack with every non-overlapping occurrence replaced. So a rewrite
+/// that rarely matches only allocates when it actually changes something.
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to the needle, the haystack and the returned bytes.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use memchr::memmem;
+///
+/// let replaced = memmem::replace_all_cow(b"foo bar", b"foo", b"quux");
+/// assert_eq!(&b"quux bar"[..], &*replaced);
+///
+/// let unchanged = memmem::replace_all_cow(b"bar baz", b"foo", b"quux");
+/// assert!(matches!(unchanged, Cow::Borrowed(b"bar baz")));
+/// ```
+#[cfg(feature = "std")]
+#[inline]
+pub fn replace_all_cow<'h>(
+    haystack: &'h [u8],
+    needle: &[u8],
+    replacement: &[u8],
+) -> std::borrow::Cow<'h, [u8]> {
+    Finder::new(needle).replace_all_cow(haystack, replacement)
+}
+
+/// Returns the position of the first occurrence of a needle in a haystack
+/// that is split into segments.
+///
+/// The haystack is the concatenation of `segments`, in order, as with the
+/// buffers of a vectored read. The position is returned as the index of the
+/// segment in which the occurrence starts, along with the offset of its
+/// start within that segment. Occurrences that straddle the boundaries
+/// between segments are found, including ones that span several segments,
+/// without copying the segments into a contiguous buffer. Only the bytes
+/// that could begin an occurrence straddling the next boundary are copied.
+///
+/// An empty needle never matches, which is consistent with
+/// [`StreamFinder`].
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to the haystack, and `O(needle.len())` time per segment.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let segments: &[&[u8]] = &[b"foo b", b"a", b"r baz"];
+/// assert_eq!(Some((1, 0)), memmem::find_segments(segments, b"ar"));
+/// assert_eq!(Some((0, 4)), memmem::find_segments(segments, b"bar"));
+/// assert_eq!(None, memmem::find_segments(segments, b"quux"));
+/// ```
+#[cfg(feature = "std")]
+pub fn find_segments(
+    segments: &[&[u8]],
+    needle: &[u8],
+) -> Option<(usize, usize)> {
+    let mut stream = StreamFinder::new(needle);
+    let m = segments.iter().find_map(|segment| stream.push(segment))?;
+    // The start of the occurrence is in the haystack, so it fits in a usize.
+    let mut start = m.start() as usize;
+    for (i, segment) in segments.iter().enumerate() {
+        if start < segment.len() {
+            return Some((i, start));
+        }
+        start -= segment.len();
+    }
+    unreachable!("occurrence must start within a segment")
+}
+
+/// Returns an iterator over the subslices of a haystack that are separated by
+/// non-overlapping occurrences of a delimiter.
+///
+/// This has the same semantics as `str::split`. In particular, if the
+/// haystack starts or ends with the delimiter, then an empty slice is
+/// yielded first or last, respectively. And an empty haystack always yields
+/// a single empty slice.
+///
+/// An empty delimiter matches at every position in the haystack, including
+/// at its start and end. Splitting on an empty delimiter therefore yields
+/// an empty slice, followed by every byte of the haystack as its own slice,
+/// followed by another empty slice. This mirrors what `str::split` does with
+/// an empty string pattern.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the delimiter and the haystack. That is, this runs
+/// in `O(delimiter.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let mut it = memmem::split(b"a\r\nb\r\n", b"\r\n");
+/// assert_eq!(Some(&b"a"[..]), it.next());
+/// assert_eq!(Some(&b"b"[..]), it.next());
+/// assert_eq!(Some(&b""[..]), it.next());
+/// assert_eq!(None, it.next());
+/// ```
+#[inline]
+pub fn split<'h, 'n, N: 'n + ?Sized + AsRef<[u8]>>(
+    haystack: &'h [u8],
+    delimiter: &'n N,
+) -> Split<'h, 'n> {
+    Split::new(haystack, Finder::new(delimiter))
+}
+
+/// Returns a reverse iterator over the subslices of a haystack that are
+/// separated by non-overlapping occurrences of a delimiter.
+///
+/// This has the same semantics as `str::rsplit`. That is, it yields the same
+/// slices as [`split`] when the delimiter can't overlap with itself, but
+/// starting from the end of the haystack. In particular, if the haystack ends
+/// with the delimiter, then an empty slice is yielded first.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the delimiter and the haystack. That is, this runs
+/// in `O(delimiter.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let mut it = memmem::rsplit(b"a/b/c/", b"/");
+/// assert_eq!(Some(&b""[..]), it.next());
+/// assert_eq!(Some(&b"c"[..]), it.next());
+/// assert_eq!(Some(&b"b"[..]), it.next());
+/// assert_eq!(Some(&b"a"[..]), it.next());
+/// assert_eq!(None, it.next());
+/// ```
+#[inline]
+pub fn rsplit<'h, 'n, N: 'n + ?Sized + AsRef<[u8]>>(
+    haystack: &'h [u8],
+    delimiter: &'n N,
+) -> RSplit<'h, 'n> {
+    RSplit::new(haystack, FinderRev::new(delimiter))
+}
+
+/// Returns an iterator over at most `limit` subslices of a haystack that are
+/// separated by non-overlapping occurrences of a delimiter.
+///
+/// This has the same semantics as `str::splitn`. That is, it yields the same
+/// slices as [`split`], except that once `limit - 1` slices have been
+/// yielded, the rest of the haystack is yielded as the last slice, including
+/// any delimiters it contains. If `limit` is `0`, then nothing is yielded.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the delimiter and the haystack. That is, this runs
+/// in `O(delimiter.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let mut it = memmem::splitn(2, b"key=value=with=equals", b"=");
+/// assert_eq!(Some(&b"key"[..]), it.next());
+/// assert_eq!(Some(&b"value=with=equals"[..]), it.next());
+/// assert_eq!(None, it.next());
+/// ```
+#[inline]
+pub fn splitn<'h, 'n, N: 'n + ?Sized + AsRef<[u8]>>(
+    limit: usize,
+    haystack: &'h [u8],
+    delimiter: &'n N,
+) -> SplitN<'h, 'n> {
+    SplitN::new(limit, Split::new(haystack, Finder::new(delimiter)))
+}
+
+/// Returns a reverse iterator over at most `limit` subslices of a haystack
+/// that are separated by non-overlapping occurrences of a delimiter.
+///
+/// This has the same semantics as `str::rsplitn`. That is, it yields the
+/// same slices as [`rsplit`], except that once `limit - 1` slices have been
+/// yielded, the rest of the haystack is yielded as the last slice, including
+/// any delimiters it contains. If `limit` is `0`, then nothing is yielded.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the delimiter and the haystack. That is, this runs
+/// in `O(delimiter.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let mut it = memmem::rsplitn(2, b"archive.tar.gz", b".");
+/// assert_eq!(Some(&b"gz"[..]), it.next());
+/// assert_eq!(Some(&b"archive.tar"[..]), it.next());
+/// assert_eq!(None, it.next());
+/// ```
+#[inline]
+pub fn rsplitn<'h, 'n, N: 'n + ?Sized + AsRef<[u8]>>(
+    limit: usize,
+    haystack: &'h [u8],
+    delimiter: &'n N,
+) -> RSplitN<'h, 'n> {
+    RSplitN::new(limit, RSplit::new(haystack, FinderRev::new(delimiter)))
+}
+
+/// Splits a haystack on the first occurrence of a delimiter.
+///
+/// This has the same semantics as `str::split_once`. That is, this returns
+/// the parts of the haystack before and after the first occurrence of the
+/// delimiter, neither of which include the delimiter itself. If the delimiter
+/// does not occur in the haystack, then `None` is returned.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the delimiter and the haystack. That is, this runs
+/// in `O(delimiter.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let (head, body) = memmem::split_once(b"k: v\r\n\r\nbody", b"\r\n\r\n")
+///     .unwrap();
+/// assert_eq!(&b"k: v"[..], head);
+/// assert_eq!(&b"body"[..], body);
+/// assert_eq!(None, memmem::split_once(b"k: v", b"\r\n\r\n"));
+/// ```
+#[inline]
+pub fn split_once<'h, N: ?Sized + AsRef<[u8]>>(
+    haystack: &'h [u8],
+    delimiter: &N,
+) -> Option<(&'h [u8], &'h [u8])> {
+    let delimiter = delimiter.as_ref();
+    let i = find(haystack, delimiter)?;
+    Some((&haystack[..i], &haystack[i + delimiter.len()..]))
+}
+
+/// Splits a haystack on the last occurrence of a delimiter.
+///
+/// This has the same semantics as `str::rsplit_once`. That is, this returns
+/// the parts of the haystack before and after the last occurrence of the
+/// delimiter, neither of which include the delimiter itself. If the delimiter
+/// does not occur in the haystack, then `None` is returned.
+///
+/// # Complexity
+///
+/// This routine is guaranteed to have worst case linear time complexity
+/// with respect to both the delimiter and the haystack. That is, this runs
+/// in `O(delimiter.len() + haystack.len())` time.
+///
+/// This routine is also guaranteed to have worst case constant space
+/// complexity.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let (stem, ext) = memmem::rsplit_once(b"archive.tar.gz", b".").unwrap();
+/// assert_eq!(&b"archive.tar"[..], stem);
+/// assert_eq!(&b"gz"[..], ext);
+/// ```
+#[inline]
+pub fn rsplit_once<'h, N: ?Sized + AsRef<[u8]>>(
+    haystack: &'h [u8],
+    delimiter: &N,
+) -> Option<(&'h [u8], &'h [u8])> {
+    let delimiter = delimiter.as_ref();
+    let i = rfind(haystack, delimiter)?;
+    Some((&haystack[..i], &haystack[i + delimiter.len()..]))
+}
+
+/// An iterator over non-overlapping substring matches.
+///
+/// Matches are reported by the byte offset at which they begin.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// needle.
+#[derive(Debug)]
+pub struct FindIter<'h, 'n> {
+    haystack: &'h [u8],
+    prestate: PrefilterState,
+    finder: Finder<'n>,
+    pos: usize,
+}
+
+impl<'h, 'n> FindIter<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: Finder<'n>,
+    ) -> FindIter<'h, 'n> {
+        let prestate = finder.searcher.prefilter_state();
+        FindIter { haystack, prestate, finder, pos: 0 }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and needle.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> FindIter<'h, 'static> {
+        FindIter {
+            haystack: self.haystack,
+            prestate: self.prestate,
+            finder: self.finder.into_owned(),
+            pos: self.pos,
+        }
+    }
+}
+
+impl<'h, 'n> Iterator for FindIter<'h, 'n> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.pos > self.haystack.len() {
+            return None;
+        }
+        // An empty needle matches at every position, including the end of
+        // the haystack, so there's no need to run the searcher.
+        if self.finder.needle().is_empty() {
+            let pos = self.pos;
+            self.pos += 1;
+            return Some(pos);
+        }
+        let result = self
+            .finder
+            .searcher
+            .find(&mut self.prestate, &self.haystack[self.pos..]);
+        match result {
+            None => None,
+            Some(i) => {
+                let pos = self.pos + i;
+                self.pos = pos + self.finder.needle().len();
+                Some(pos)
+            }
+        }
+    }
+}
+
+/// An iterator over the ranges of non-overlapping substring matches.
+///
+/// This reports the same matches as [`FindIter`], but each match is reported
+/// as the range of bytes it spans in the haystack instead of just its
+/// starting offset.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// needle.
+#[derive(Debug)]
+pub struct FindRangesIter<'h, 'n> {
+    it: FindIter<'h, 'n>,
+}
+
+impl<'h, 'n> FindRangesIter<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: Finder<'n>,
+    ) -> FindRangesIter<'h, 'n> {
+        FindRangesIter { it: FindIter::new(haystack, finder) }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and needle.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> FindRangesIter<'h, 'static> {
+        FindRangesIter { it: self.it.into_owned() }
+    }
+}
+
+impl<'h, 'n> Iterator for FindRangesIter<'h, 'n> {
+    type Item = core::ops::Range<usize>;
+
+    fn next(&mut self) -> Option<core::ops::Range<usize>> {
+        let start = self.it.next()?;
+        Some(start..start + self.it.finder.needle().len())
+    }
+}
+
+/// An iterator over overlapping substring matches.
+///
+/// Unlike [`FindIter`], which resumes its search after the end of each match,
+/// this iterator resumes its search one byte after the start of each match.
+/// Matches may therefore overlap. For example, the needle `aa` is found at
+/// offsets `0`, `1` and `2` in the haystack `aaaa`.
+///
+/// Matches are reported by the byte offset at which they begin.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// needle.
+#[derive(Debug)]
+pub struct FindOverlappingIter<'h, 'n> {
+    haystack: &'h [u8],
+    prestate: PrefilterState,
+    finder: Finder<'n>,
+    pos: usize,
+}
+
+impl<'h, 'n> FindOverlappingIter<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: Finder<'n>,
+    ) -> FindOverlappingIter<'h, 'n> {
+        let prestate = finder.searcher.prefilter_state();
+        FindOverlappingIter { haystack, prestate, finder, pos: 0 }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and needle.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> FindOverlappingIter<'h, 'static> {
+        FindOverlappingIter {
+            haystack: self.haystack,
+            prestate: self.prestate,
+            finder: self.finder.into_owned(),
+            pos: self.pos,
+        }
+    }
+}
+
+impl<'h, 'n> Iterator for FindOverlappingIter<'h, 'n> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.pos > self.haystack.len() {
+            return None;
+        }
+        let result = self
+            .finder
+            .searcher
+            .find(&mut self.prestate, &self.haystack[self.pos..]);
+        match result {
+            None => None,
+            Some(i) => {
+                let pos = self.pos + i;
+                self.pos = pos + 1;
+                Some(pos)
+            }
+        }
+    }
+}
+
+/// An iterator over non-overlapping substring matches in reverse.
+///
+/// Matches are reported by the byte offset at which they begin.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// needle.
+#[derive(Debug)]
+pub struct FindRevIter<'h, 'n> {
+    haystack: &'h [u8],
+    prestate: PrefilterState,
+    finder: FinderRev<'n>,
+    /// When searching with an empty needle, this gets set to `None` after
+    /// we've yielded the last element at `0`.
+    pos: Option<usize>,
+}
+
+impl<'h, 'n> FindRevIter<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: FinderRev<'n>,
+    ) -> FindRevIter<'h, 'n> {
+        let prestate = finder.searcher.prefilter_state();
+        let pos = Some(haystack.len());
+        FindRevIter { haystack, prestate, finder, pos }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and needle.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> FindRevIter<'h, 'static> {
+        FindRevIter {
+            haystack: self.haystack,
+            prestate: self.prestate,
+            finder: self.finder.into_owned(),
+            pos: self.pos,
+        }
+    }
+}
+
+impl<'h, 'n> Iterator for FindRevIter<'h, 'n> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let pos = match self.pos {
+            None => return None,
+            Some(pos) => pos,
+        };
+        let result = self
+            .finder
+            .searcher
+            .rfind(&mut self.prestate, &self.haystack[..pos]);
+        match result {
+            None => None,
+            Some(i) => {
+                if pos == i {
+                    self.pos = pos.checked_sub(1);
+                } else {
+                    self.pos = Some(i);
+                }
+                Some(i)
+            }
+        }
+    }
+}
+
+/// An iterator over the subslices of a haystack separated by a delimiter.
+///
+/// This iterator has the same semantics as `str::split`. See [`split`] for
+/// more details.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// delimiter.
+#[derive(Debug)]
+pub struct Split<'h, 'n> {
+    it: FindIter<'h, 'n>,
+    /// The start of the next slice to yield, or `None` once the last slice
+    /// has been yielded.
+    last: Option<usize>,
+}
+
+impl<'h, 'n> Split<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: Finder<'n>,
+    ) -> Split<'h, 'n> {
+        Split { it: FindIter::new(haystack, finder), last: Some(0) }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and delimiter.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the delimiter.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> Split<'h, 'static> {
+        Split { it: self.it.into_owned(), last: self.last }
+    }
+}
+
+impl<'h, 'n> Iterator for Split<'h, 'n> {
+    type Item = &'h [u8];
+
+    fn next(&mut self) -> Option<&'h [u8]> {
+        let last = self.last?;
+        let haystack = self.it.haystack;
+        match self.it.next() {
+            None => {
+                self.last = None;
+                Some(&haystack[last..])
+            }
+            Some(start) => {
+                self.last = Some(start + self.it.finder.needle().len());
+                Some(&haystack[last..start])
+            }
+        }
+    }
+}
+
+/// A part of a haystack reported by [`Finder::find_iter_with_gaps`].
+///
+/// `'h` is the lifetime of the haystack.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Segment<'h> {
+    /// The bytes between two occurrences of the needle, or between an
+    /// occurrence and the start or end of the haystack. This may be empty.
+    Gap(&'h [u8]),
+    /// The offset of an occurrence of the needle in the haystack.
+    Match(usize),
+}
+
+/// An iterator over the occurrences of a needle in a haystack, along with the
+/// gaps between them.
+///
+/// See [`Finder::find_iter_with_gaps`] for more details.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// needle.
+#[derive(Debug)]
+pub struct FindIterWithGaps<'h, 'n> {
+    it: FindIter<'h, 'n>,
+    /// The start of the next gap to yield, or `None` once the last gap has
+    /// been yielded.
+    last: Option<usize>,
+    /// An occurrence to yield after the gap preceding it.
+    pending: Option<usize>,
+}
+
+impl<'h, 'n> FindIterWithGaps<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: Finder<'n>,
+    ) -> FindIterWithGaps<'h, 'n> {
+        FindIterWithGaps {
+            it: FindIter::new(haystack, finder),
+            last: Some(0),
+            pending: None,
+        }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and needle.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> FindIterWithGaps<'h, 'static> {
+        FindIterWithGaps {
+            it: self.it.into_owned(),
+            last: self.last,
+            pending: self.pending,
+        }
+    }
+}
+
+impl<'h, 'n> Iterator for FindIterWithGaps<'h, 'n> {
+    type Item = Segment<'h>;
+
+    fn next(&mut self) -> Option<Segment<'h>> {
+        if let Some(start) = self.pending.take() {
+            return Some(Segment::Match(start));
+        }
+        let last = self.last?;
+        let haystack = self.it.haystack;
+        match self.it.next() {
+            None => {
+                self.last = None;
+                Some(Segment::Gap(&haystack[last..]))
+            }
+            Some(start) => {
+                self.last = Some(start + self.it.finder.needle().len());
+                self.pending = Some(start);
+                Some(Segment::Gap(&haystack[last..start]))
+            }
+        }
+    }
+}
+
+/// An iterator over pairs of consecutive occurrences of a needle in a
+/// haystack.
+///
+/// See [`Finder::match_gaps`] for more details.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// needle.
+#[derive(Debug)]
+pub struct MatchGaps<'h, 'n> {
+    it: FindIter<'h, 'n>,
+    /// The start of the most recent occurrence, or `None` before the first
+    /// occurrence has been found.
+    prev: Option<usize>,
+}
+
+impl<'h, 'n> MatchGaps<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: Finder<'n>,
+    ) -> MatchGaps<'h, 'n> {
+        MatchGaps { it: FindIter::new(haystack, finder), prev: None }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and needle.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> MatchGaps<'h, 'static> {
+        MatchGaps { it: self.it.into_owned(), prev: self.prev }
+    }
+}
+
+impl<'h, 'n> Iterator for MatchGaps<'h, 'n> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let prev = match self.prev {
+            Some(prev) => prev,
+            None => self.it.next()?,
+        };
+        let next = self.it.next()?;
+        self.prev = Some(next);
+        Some((prev, next))
+    }
+}
+
+/// The result of a search with a byte budget, as reported by
+/// [`Finder::find_within`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FindWithin {
+    /// An occurrence of the needle starts at this offset.
+    Found(usize),
+    /// The needle does not occur anywhere in the haystack.
+    NotFound,
+    /// The needle does not start anywhere within the budget, but the budget
+    /// ran out before the end of the haystack. So it may still occur later
+    /// in the haystack.
+    BudgetExhausted,
+}
+
+/// Statistics about a search, as reported by [`Finder::find_iter_profiled`].
+///
+/// A search alternates between asking its prefilter for the next candidate
+/// position and verifying whether the needle occurs at that candidate. These
+/// statistics count each step, which makes it possible to judge how well the
+/// prefilter suits a particular needle and haystack. When a finder has no
+/// prefilter, every position in the haystack is a candidate.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SearchStats {
+    candidates: usize,
+    verifications: usize,
+    matches: usize,
+}
+
+impl SearchStats {
+    /// Returns the number of candidate positions that were produced.
+    pub fn candidates(&self) -> usize {
+        self.candidates
+    }
+
+    /// Returns the number of candidates that were verified by comparing the
+    /// needle with the haystack.
+    ///
+    /// This is at most the number of candidates. It's smaller when a
+    /// candidate is too close to the end of the haystack for the needle to
+    /// fit, in which case it's rejected without a comparison.
+    pub fn verifications(&self) -> usize {
+        self.verifications
+    }
+
+    /// Returns the number of verified candidates that were confirmed as
+    /// occurrences of the needle.
+    pub fn matches(&self) -> usize {
+        self.matches
+    }
+
+    /// Returns the number of candidates that failed verification or were
+    /// rejected without one.
+    ///
+    /// The ratio of this to [`SearchStats::candidates`] is the false positive
+    /// rate of the prefilter.
+    pub fn false_positives(&self) -> usize {
+        self.candidates - self.matches
+    }
+}
+
+/// A reverse iterator over the subslices of a haystack separated by a
+/// delimiter.
+///
+/// This iterator has the same semantics as `str::rsplit`. See [`rsplit`] for
+/// more details.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// delimiter.
+#[derive(Debug)]
+pub struct RSplit<'h, 'n> {
+    it: FindRevIter<'h, 'n>,
+    /// The end of the next slice to yield, or `None` once the first slice
+    /// has been yielded.
+    last: Option<usize>,
+}
+
+impl<'h, 'n> RSplit<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(
+        haystack: &'h [u8],
+        finder: FinderRev<'n>,
+    ) -> RSplit<'h, 'n> {
+        RSplit {
+            it: FindRevIter::new(haystack, finder),
+            last: Some(haystack.len()),
+        }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and delimiter.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the delimiter.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> RSplit<'h, 'static> {
+        RSplit { it: self.it.into_owned(), last: self.last }
+    }
+}
+
+impl<'h, 'n> Iterator for RSplit<'h, 'n> {
+    type Item = &'h [u8];
+
+    fn next(&mut self) -> Option<&'h [u8]> {
+        let last = self.last?;
+        let haystack = self.it.haystack;
+        match self.it.next() {
+            None => {
+                self.last = None;
+                Some(&haystack[..last])
+            }
+            Some(start) => {
+                self.last = Some(start);
+                Some(&haystack[start + self.it.finder.needle().len()..last])
+            }
+        }
+    }
+}
+
+/// An iterator over a limited number of subslices of a haystack separated by
+/// a delimiter.
+///
+/// This iterator has the same semantics as `str::splitn`. See [`splitn`] for
+/// more details.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// delimiter.
+#[derive(Debug)]
+pub struct SplitN<'h, 'n> {
+    split: Split<'h, 'n>,
+    /// The number of slices that may still be yielded.
+    limit: usize,
+}
+
+impl<'h, 'n> SplitN<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(limit: usize, split: Split<'h, 'n>) -> SplitN<'h, 'n> {
+        SplitN { split, limit }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and delimiter.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the delimiter.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> SplitN<'h, 'static> {
+        SplitN { split: self.split.into_owned(), limit: self.limit }
+    }
+}
+
+impl<'h, 'n> Iterator for SplitN<'h, 'n> {
+    type Item = &'h [u8];
+
+    fn next(&mut self) -> Option<&'h [u8]> {
+        match self.limit {
+            0 => None,
+            1 => {
+                self.limit = 0;
+                let last = self.split.last.take()?;
+                Some(&self.split.it.haystack[last..])
+            }
+            _ => {
+                self.limit -= 1;
+                self.split.next()
+            }
+        }
+    }
+}
+
+/// A reverse iterator over a limited number of subslices of a haystack
+/// separated by a delimiter.
+///
+/// This iterator has the same semantics as `str::rsplitn`. See [`rsplitn`]
+/// for more details.
+///
+/// `'h` is the lifetime of the haystack while `'n` is the lifetime of the
+/// delimiter.
+#[derive(Debug)]
+pub struct RSplitN<'h, 'n> {
+    split: RSplit<'h, 'n>,
+    /// The number of slices that may still be yielded.
+    limit: usize,
+}
+
+impl<'h, 'n> RSplitN<'h, 'n> {
+    #[inline(always)]
+    pub(crate) fn new(limit: usize, split: RSplit<'h, 'n>) -> RSplitN<'h, 'n> {
+        RSplitN { split, limit }
+    }
+
+    /// Convert this iterator into its owned variant, such that it no longer
+    /// borrows the finder and delimiter.
+    ///
+    /// If this is already an owned iterator, then this is a no-op. Otherwise,
+    /// this copies the delimiter.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_owned(self) -> RSplitN<'h, 'static> {
+        RSplitN { split: self.split.into_owned(), limit: self.limit }
+    }
+}
+
+impl<'h, 'n> Iterator for RSplitN<'h, 'n> {
+    type Item = &'h [u8];
+
+    fn next(&mut self) -> Option<&'h [u8]> {
+        match self.limit {
+            0 => None,
+            1 => {
+                self.limit = 0;
+                let last = self.split.last.take()?;
+                Some(&self.split.it.haystack[..last])
+            }
+            _ => {
+                self.limit -= 1;
+                self.split.next()
+            }
+        }
+    }
+}
+
+/// A single substring searcher fixed to a particular needle.
+///
+/// The purpose of this type is to permit callers to construct a substring
+/// searcher that can be used to search haystacks without the overhead of
+/// constructing the searcher in the first place. This is a somewhat niche
+/// concern when it's necessary to re-use the same needle to search multiple
+/// different haystacks with as little overhead as possible. In general, using
+/// [`find`] is good enough, but `Finder` is useful when you can meaningfully
+/// observe searcher construction time in a profile.
+///
+/// Building a `Finder` never allocates. It borrows its needle, and all of
+/// its search state has a fixed size, so it works in `no_std` environments
+/// without an allocator.
+///
+/// When the `std` feature is enabled, then this type has an `into_owned`
+/// version which permits building a `Finder` that is not connected to
+/// the lifetime of its needle.
+#[derive(Clone)]
+pub struct Finder<'n> {
+    searcher: Searcher<'n>,
+}
+
+/// Formats the needle and the kind of prefilter selected.
+///
+/// The needle is formatted like a string if it's valid UTF-8, and like a
+/// byte string literal otherwise. For example,
+/// `Finder { needle: "foo", prefilter: None }`.
+impl<'n> core::fmt::Debug for Finder<'n> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Finder")
+            .field("needle", &util::DebugNeedle(self.needle()))
+            .field("prefilter", &self.prefilter_info().kind())
+            .finish()
+    }
+}
+
+/// Two finders are equal when they search for the same needle in the same
+/// way.
+///
+/// That is, their needles are equal and they were built with the same
+/// configuration, such as whether a prefilter is used, whether ASCII case is
+/// ignored and the SIMD threshold. A byte frequency heuristic can't be
+/// compared directly, so instead the bytes of the needle that it picked as
+/// the rarest are compared. Two different heuristics that pick the same rare
+/// bytes result in finders that search identically, and so compare equal.
+///
+/// This never allocates.
+impl<'n> PartialEq for Finder<'n> {
+    fn eq(&self, other: &Finder<'n>) -> bool {
+        self.searcher.same_search(&other.searcher)
+    }
+}
+
+impl<'n> Eq for Finder<'n> {}
+
+impl<'n> Finder<'n> {
+    /// Create a new finder for the given needle.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &'n B) -> Finder<'n> {
+        FinderBuilder::new().build_forward(needle)
+    }
+
+    /// Returns the index of the first occurrence of this needle in the given
+    /// haystack.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let haystack = b"foo bar baz";
+    /// assert_eq!(Some(0), Finder::new("foo").find(haystack));
+    /// assert_eq!(Some(4), Finder::new("bar").find(haystack));
+    /// assert_eq!(None, Finder::new("quux").find(haystack));
+    /// ```
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.searcher.find(&mut self.searcher.prefilter_state(), haystack)
+    }
+
+    /// Returns the index of the first occurrence of this needle in the given
+    /// haystack that starts at or after `from`.
+    ///
+    /// The index returned is relative to the start of `haystack`, not to
+    /// `from`, which makes this convenient for advancing a cursor through a
+    /// haystack. An occurrence that starts before `from` is never returned,
+    /// even if it ends after `from`. If `from` is greater than the length of
+    /// the haystack, then `None` is returned.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("ab");
+    /// let haystack = b"ab ab ab";
+    /// let mut cursor = 1;
+    /// let mut found = vec![];
+    /// while let Some(i) = finder.find_from(haystack, cursor) {
+    ///     found.push(i);
+    ///     cursor = i + 1;
+    /// }
+    /// assert_eq!(vec![3, 6], found);
+    /// ```
+    #[inline]
+    pub fn find_from(&self, haystack: &[u8], from: usize) -> Option<usize> {
+        self.find(haystack.get(from..)?).map(|i| from + i)
+    }
+
+    /// Returns the index of the first occurrence of this needle in the given
+    /// haystack that starts within its first `max_bytes` bytes.
+    ///
+    /// This is useful for bounding the time spent searching an untrusted
+    /// haystack. Only occurrences starting before `max_bytes` are considered,
+    /// but unlike searching `&haystack[..max_bytes]`, an occurrence that
+    /// starts before `max_bytes` and ends after it is still found. So at most
+    /// `max_bytes + needle.len() - 1` bytes of the haystack are examined.
+    ///
+    /// If no occurrence is found, then this distinguishes between the case
+    /// where the budget covered every position at which the needle could
+    /// occur ([`FindWithin::NotFound`]) and the case where it did not
+    /// ([`FindWithin::BudgetExhausted`]). An empty needle is always found at
+    /// position `0`, even when `max_bytes` is `0`.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and `max_bytes`. That is, this runs
+    /// in `O(needle.len() + max_bytes)` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::{Finder, FindWithin};
+    ///
+    /// let finder = Finder::new("foo");
+    /// let haystack = b"xxxxfooxxxxfoo";
+    /// assert_eq!(FindWithin::Found(4), finder.find_within(haystack, 5));
+    /// assert_eq!(FindWithin::BudgetExhausted, finder.find_within(haystack, 4));
+    /// assert_eq!(FindWithin::NotFound, finder.find_within(b"xxxx", 2));
+    /// ```
+    #[inline]
+    pub fn find_within(
+        &self,
+        haystack: &[u8],
+        max_bytes: usize,
+    ) -> FindWithin {
+        // Just past the end of an occurrence starting at `max_bytes - 1`.
+        let end =
+            max_bytes.saturating_add(self.needle().len()).saturating_sub(1);
+        if end >= haystack.len() {
+            match self.find(haystack) {
+                Some(i) => FindWithin::Found(i),
+                None => FindWithin::NotFound,
+            }
+        } else {
+            match self.find(&haystack[..end]) {
+                Some(i) => FindWithin::Found(i),
+                None => FindWithin::BudgetExhausted,
+            }
+        }
+    }
+
+    /// Returns the index of the first occurrence of this needle within the
+    /// last `suffix_len` bytes of the given haystack.
+    ///
+    /// Only occurrences that lie entirely within the suffix are found, but
+    /// the index returned is relative to the start of `haystack`, not to the
+    /// start of the suffix. If `suffix_len` is greater than the length of the
+    /// haystack, then the whole haystack is searched.
+    ///
+    /// This is useful when searching a stream one chunk at a time, where
+    /// only the end of a chunk needs to be searched again once it has been
+    /// joined with the start of the next chunk.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and `suffix_len`. That is, this runs
+    /// in `O(needle.len() + suffix_len)` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("ab");
+    /// let haystack = b"ab xx ab";
+    /// assert_eq!(Some(6), finder.find_in_suffix(haystack, 3));
+    /// assert_eq!(None, finder.find_in_suffix(haystack, 1));
+    /// assert_eq!(Some(0), finder.find_in_suffix(haystack, 100));
+    /// ```
+    #[inline]
+    pub fn find_in_suffix(
+        &self,
+        haystack: &[u8],
+        suffix_len: usize,
+    ) -> Option<usize> {
+        let start = haystack.len().saturating_sub(suffix_len);
+        self.find(&haystack[start..]).map(|i| start + i)
+    }
+
+    /// Returns true if and only if this needle occurs in the given haystack.
+    ///
+    /// This is equivalent to `self.find(haystack).is_some()`, and stops
+    /// searching as soon as the first occurrence is found.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("ERROR");
+    /// let lines: &[&[u8]] = &[b"INFO: ok", b"ERROR: bad", b"ERROR: worse"];
+    /// assert_eq!(2, lines.iter().filter(|l| finder.contains(l)).count());
+    /// ```
+    #[inline]
+    pub fn contains(&self, haystack: &[u8]) -> bool {
+        self.find(haystack).is_some()
+    }
+
+    /// Returns true if and only if the given haystack starts with this
+    /// needle.
+    ///
+    /// This returns `false` without comparing any bytes when the haystack is
+    /// shorter than the needle. An empty needle is a prefix of every
+    /// haystack. If this finder was built to ignore ASCII case, then the
+    /// prefix is compared ignoring ASCII case too.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new(b"\x89PNG");
+    /// assert!(finder.starts_with(b"\x89PNG\r\n\x1a\n"));
+    /// assert!(!finder.starts_with(b"GIF89a"));
+    /// ```
+    #[inline]
+    pub fn starts_with(&self, haystack: &[u8]) -> bool {
+        let len = self.needle().len();
+        len <= haystack.len() && self.searcher.is_needle(&haystack[..len])
+    }
+
+    /// Returns true if and only if the given haystack ends with this needle.
+    ///
+    /// This returns `false` without comparing any bytes when the haystack is
+    /// shorter than the needle. An empty needle is a suffix of every
+    /// haystack. If this finder was built to ignore ASCII case, then the
+    /// suffix is compared ignoring ASCII case too.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("\r\n\r\n");
+    /// assert!(finder.ends_with(b"GET / HTTP/1.1\r\n\r\n"));
+    /// assert!(!finder.ends_with(b"GET / HTTP/1.1\r\n"));
+    /// ```
+    #[inline]
+    pub fn ends_with(&self, haystack: &[u8]) -> bool {
+        let len = self.needle().len();
+        len <= haystack.len()
+            && self.searcher.is_needle(&haystack[haystack.len() - len..])
+    }
+
+    /// Returns an iterator over all occurrences of a substring in a haystack.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let haystack = b"foo bar foo baz foo";
+    /// let finder = Finder::new(b"foo");
+    /// let mut it = finder.find_iter(haystack);
+    /// assert_eq!(Some(0), it.next());
+    /// assert_eq!(Some(8), it.next());
+    /// assert_eq!(Some(16), it.next());
+    /// assert_eq!(None, it.next());
+    /// ```
+    #[inline]
+    pub fn find_iter<'a, 'h>(
+        &'a self,
+        haystack: &'h [u8],
+    ) -> FindIter<'h, 'a> {
+        FindIter::new(haystack, self.as_ref())
+    }
+
+    /// Returns an iterator over the ranges of all non-overlapping occurrences
+    /// of a substring in a haystack.
+    ///
+    /// This reports the same matches as [`Finder::find_iter`], but each match
+    /// is reported as a `start..end` range of the haystack, where `end` is
+    /// the exclusive end of the match. This is convenient when a match needs
+    /// to be sliced out of the haystack or highlighted.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let haystack = b"foo bar foo";
+    /// let finder = Finder::new("foo");
+    /// let mut it = finder.find_ranges_iter(haystack);
+    /// assert_eq!(Some(0..3), it.next());
+    /// assert_eq!(Some(8..11), it.next());
+    /// assert_eq!(None, it.next());
+    /// ```
+    #[inline]
+    pub fn find_ranges_iter<'a, 'h>(
+        &'a self,
+        haystack: &'h [u8],
+    ) -> FindRangesIter<'h, 'a> {
+        FindRangesIter::new(haystack, self.as_ref())
+    }
+
+    /// Returns an iterator over all occurrences of a substring in a haystack,
+    /// including occurrences that overlap with one another.
+    ///
+    /// This differs from [`Finder::find_iter`] in where the search resumes
+    /// after a match. `find_iter` resumes at the end of the previous match,
+    /// so it never reports a match that overlaps with an earlier one. This
+    /// iterator resumes one byte after the start of the previous match, so it
+    /// reports every offset at which the needle occurs. For an empty needle,
+    /// both iterators report every position in the haystack.
+    ///
+    /// # Complexity
+    ///
+    /// Each call to `next` is guaranteed to have worst case linear time
+    /// complexity with respect to both the needle and the remainder of
+    /// the haystack. Since matches may overlap, exhausting this iterator
+    /// may take `O(needle.len() * haystack.len())` time in the worst case.
+    ///
+    /// This routine is guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new(b"aa");
+    /// let overlapping: Vec<usize> =
+    ///     finder.find_overlapping_iter(b"aaaa").collect();
+    /// assert_eq!(vec![0, 1, 2], overlapping);
+    ///
+    /// let non_overlapping: Vec<usize> = finder.find_iter(b"aaaa").collect();
+    /// assert_eq!(vec![0, 2], non_overlapping);
+    /// ```
+    #[inline]
+    pub fn find_overlapping_iter<'a, 'h>(
+        &'a self,
+        haystack: &'h [u8],
+    ) -> FindOverlappingIter<'h, 'a> {
+        FindOverlappingIter::new(haystack, self.as_ref())
+    }
+
+    /// Returns an iterator over the subslices of a haystack that are
+    /// separated by non-overlapping occurrences of this needle.
+    ///
+    /// This has the same semantics as `str::split`. See [`split`] for more
+    /// details, including the behavior of an empty needle.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new(", ");
+    /// let mut it = finder.split(b"foo, bar, baz");
+    /// assert_eq!(Some(&b"foo"[..]), it.next());
+    /// assert_eq!(Some(&b"bar"[..]), it.next());
+    /// assert_eq!(Some(&b"baz"[..]), it.next());
+    /// assert_eq!(None, it.next());
+    /// ```
+    #[inline]
+    pub fn split<'a, 'h>(&'a self, haystack: &'h [u8]) -> Split<'h, 'a> {
+        Split::new(haystack, self.as_ref())
+    }
+
+    /// Returns an iterator over the non-overlapping occurrences of this
+    /// needle in a haystack, interleaved with the gaps between them.
+    ///
+    /// Gaps and occurrences always alternate, starting and ending with a
+    /// gap, so a haystack with `n` occurrences produces `n + 1` gaps. Gaps
+    /// may be empty, e.g., when the haystack starts with an occurrence. The
+    /// gaps are exactly the slices that [`Finder::split`] yields, and the
+    /// occurrences are the offsets that [`Finder::find_iter`] yields. This
+    /// makes it possible to process delimiters and the content between them
+    /// in one pass.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::{Finder, Segment};
+    ///
+    /// let finder = Finder::new("--");
+    /// let segments: Vec<Segment> =
+    ///     finder.find_iter_with_gaps(b"a--bc--").collect();
+    /// assert_eq!(
+    ///     segments,
+    ///     vec![
+    ///         Segment::Gap(b"a"),
+    ///         Segment::Match(1),
+    ///         Segment::Gap(b"bc"),
+    ///         Segment::Match(5),
+    ///         Segment::Gap(b""),
+    ///     ],
+    /// );
+    /// ```
+    #[inline]
+    pub fn find_iter_with_gaps<'a, 'h>(
+        &'a self,
+        haystack: &'h [u8],
+    ) -> FindIterWithGaps<'h, 'a> {
+        FindIterWithGaps::new(haystack, self.as_ref())
+    }
+
+    /// Returns an iterator over pairs of consecutive non-overlapping
+    /// occurrences of this needle in a haystack.
+    ///
+    /// Each pair is `(start, next_start)`, where `start` is the offset of an
+    /// occurrence and `next_start` is the offset of the occurrence after it.
+    /// So `next_start - start` is the distance between them, which is useful
+    /// for measuring the width of delimited fields. Each occurrence other
+    /// than the first and last appears in two pairs, and a haystack with
+    /// fewer than two occurrences yields no pairs.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("|");
+    /// let gaps: Vec<(usize, usize)> =
+    ///     finder.match_gaps(b"|ab|c|def|").collect();
+    /// assert_eq!(gaps, vec![(0, 3), (3, 5), (5, 9)]);
+    ///
+    /// let widths: Vec<usize> = finder
+    ///     .match_gaps(b"|ab|c|def|")
+    ///     .map(|(start, next)| next - start - finder.needle().len())
+    ///     .collect();
+    /// assert_eq!(widths, vec![2, 1, 3]);
+    /// ```
+    #[inline]
+    pub fn match_gaps<'a, 'h>(
+        &'a self,
+        haystack: &'h [u8],
+    ) -> MatchGaps<'h, 'a> {
+        MatchGaps::new(haystack, self.as_ref())
+    }
+
+    /// Returns a reverse iterator over the subslices of a haystack that are
+    /// separated by non-overlapping occurrences of this needle.
+    ///
+    /// This has the same semantics as `str::rsplit`. See [`rsplit`] for more
+    /// details.
+    ///
+    /// Since a `Finder` can only search forwards, this builds a [`FinderRev`]
+    /// for this needle with the same configuration as this finder. In
+    /// particular, a finder that ignores ASCII case splits on the needle
+    /// ignoring ASCII case in both directions. If you need to split many
+    /// haystacks from the end, it is better to build a `FinderRev` once and
+    /// use [`FinderRev::rsplit`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("::");
+    /// let mut it = finder.rsplit(b"std::io::Read");
+    /// assert_eq!(Some(&b"Read"[..]), it.next());
+    /// assert_eq!(Some(&b"io"[..]), it.next());
+    /// assert_eq!(Some(&b"std"[..]), it.next());
+    /// assert_eq!(None, it.next());
+    /// ```
+    #[inline]
+    pub fn rsplit<'a, 'h>(&'a self, haystack: &'h [u8]) -> RSplit<'h, 'a> {
+        RSplit::new(haystack, FinderRev { searcher: self.searcher.reverse() })
+    }
+
+    /// Returns an iterator over at most `limit` subslices of a haystack that
+    /// are separated by non-overlapping occurrences of this needle.
+    ///
+    /// This has the same semantics as `str::splitn`. See [`splitn`] for more
+    /// details.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new(": ");
+    /// let mut it = finder.splitn(2, b"Host: example.com: 80");
+    /// assert_eq!(Some(&b"Host"[..]), it.next());
+    /// assert_eq!(Some(&b"example.com: 80"[..]), it.next());
+    /// assert_eq!(None, it.next());
+    /// ```
+    #[inline]
+    pub fn splitn<'a, 'h>(
+        &'a self,
+        limit: usize,
+        haystack: &'h [u8],
+    ) -> SplitN<'h, 'a> {
+        SplitN::new(limit, self.split(haystack))
+    }
+
+    /// Returns a reverse iterator over at most `limit` subslices of a
+    /// haystack that are separated by non-overlapping occurrences of this
+    /// needle.
+    ///
+    /// This has the same semantics as `str::rsplitn`. See [`rsplitn`] for
+    /// more details.
+    ///
+    /// Like [`Finder::rsplit`], this builds a [`FinderRev`] for this needle.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("/");
+    /// let mut it = finder.rsplitn(2, b"a/b/c");
+    /// assert_eq!(Some(&b"c"[..]), it.next());
+    /// assert_eq!(Some(&b"a/b"[..]), it.next());
+    /// assert_eq!(None, it.next());
+    /// ```
+    #[inline]
+    pub fn rsplitn<'a, 'h>(
+        &'a self,
+        limit: usize,
+        haystack: &'h [u8],
+    ) -> RSplitN<'h, 'a> {
+        RSplitN::new(limit, self.rsplit(haystack))
+    }
+
+    /// Returns a copy of the haystack with every non-overlapping occurrence of
+    /// this needle replaced by the given replacement.
+    ///
+    /// Occurrences are found and replaced from left to right, exactly as they
+    /// are reported by [`Finder::find_iter`]. All bytes outside of an
+    /// occurrence are copied verbatim.
+    ///
+    /// If the needle is empty, then no replacements are made and a copy of
+    /// the haystack is returned unchanged.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("foo");
+    /// let replaced = finder.replace_all(b"foo bar foo", b"quux");
+    /// assert_eq!(&b"quux bar quux"[..], &*replaced);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn replace_all(&self, haystack: &[u8], replacement: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(haystack.len());
+        self.replace_all_into(haystack, replacement, &mut out);
+        out
+    }
+
+    /// Like [`Finder::replace_all`], but borrows the haystack instead of
+    /// copying it when there is nothing to replace.
+    ///
+    /// This returns `Cow::Borrowed(haystack)` when this needle does not occur
+    /// in the haystack (or is empty). Otherwise, an owned copy of the
+    /// haystack with every occurrence replaced is returned. This is true even
+    /// if the replacement is equal to the needle.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("\r\n");
+    /// let lines = [&b"a\r\nb"[..], b"c\nd"];
+    /// let fixed: Vec<Cow<[u8]>> =
+    ///     lines.iter().map(|l| finder.replace_all_cow(l, b"\n")).collect();
+    /// assert_eq!(&b"a\nb"[..], &*fixed[0]);
+    /// assert!(matches!(fixed[1], Cow::Borrowed(_)));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn replace_all_cow<'h>(
+        &self,
+        haystack: &'h [u8],
+        replacement: &[u8],
+    ) -> std::borrow::Cow<'h, [u8]> {
+        use std::borrow::Cow;
+
+        if self.needle().is_empty() {
+            return Cow::Borrowed(haystack);
+        }
+        let first = match self.find(haystack) {
+            None => return Cow::Borrowed(haystack),
+            Some(first) => first,
+        };
+        // Resuming the search after the first occurrence finds the same
+        // occurrences as `find_iter`, since they never overlap.
+        let rest = first + self.needle().len();
+        let mut out = Vec::with_capacity(haystack.len());
+        out.extend_from_slice(&haystack[..first]);
+        out.extend_from_slice(replacement);
+        self.extend_replaced(&haystack[rest..], &mut out, |_, out| {
+            out.extend_from_slice(replacement)
+        });
+        Cow::Owned(out)
+    }
+
+    /// Like [`Finder::replace_all`], but writes the result into the given
+    /// buffer instead of returning a new one.
+    ///
+    /// The buffer is cleared first, so when this returns, it contains exactly
+    /// the haystack with every occurrence replaced. Any capacity the buffer
+    /// already has is reused. This makes it possible to avoid allocating a new
+    /// buffer for every haystack by reusing one buffer across calls.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("\t");
+    /// let mut out = vec![];
+    /// for (line, expected) in [("a\tb", "a    b"), ("\t", "    ")] {
+    ///     finder.replace_all_into(line.as_bytes(), b"    ", &mut out);
+    ///     assert_eq!(expected.as_bytes(), &*out);
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn replace_all_into(
+        &self,
+        haystack: &[u8],
+        replacement: &[u8],
+        out: &mut Vec<u8>,
+    ) {
+        out.clear();
+        out.reserve(haystack.len());
+        self.extend_replaced(haystack, out, |_, out| {
+            out.extend_from_slice(replacement)
+        });
+    }
+
+    /// Returns a copy of the haystack where every non-overlapping occurrence
+    /// of this needle is replaced by whatever the given closure writes.
+    ///
+    /// The closure is called once for each occurrence, in ascending order,
+    /// with the starting offset of the occurrence in the haystack and the
+    /// output buffer. Any bytes the closure appends to the buffer take the
+    /// place of the occurrence. All bytes outside of an occurrence are
+    /// copied verbatim.
+    ///
+    /// If the needle is empty, then the closure is never called and a copy of
+    /// the haystack is returned unchanged.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// This example replaces each placeholder with an incrementing index:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("{}");
+    /// let mut index = 0;
+    /// let replaced = finder.replace_all_with(b"{}, {} and {}", |_, out| {
+    ///     out.extend_from_slice(index.to_string().as_bytes());
+    ///     index += 1;
+    /// });
+    /// assert_eq!(&b"0, 1 and 2"[..], &*replaced);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn replace_all_with<F: FnMut(usize, &mut Vec<u8>)>(
+        &self,
+        haystack: &[u8],
+        f: F,
+    ) -> Vec<u8> {
+        let mut out = Vec::with_capacity(haystack.len());
+        self.extend_replaced(haystack, &mut out, f);
+        out
+    }
+
+    /// Appends the haystack to `out`, with every non-overlapping occurrence
+    /// of this needle replaced by whatever `f` writes.
+    #[cfg(feature = "std")]
+    fn extend_replaced<F: FnMut(usize, &mut Vec<u8>)>(
+        &self,
+        haystack: &[u8],
+        out: &mut Vec<u8>,
+        mut f: F,
+    ) {
+        if self.needle().is_empty() {
+            out.extend_from_slice(haystack);
+            return;
+        }
+        let mut last = 0;
+        for start in self.find_iter(haystack) {
+            out.extend_from_slice(&haystack[last..start]);
+            f(start, out);
+            last = start + self.needle().len();
+        }
+        out.extend_from_slice(&haystack[last..]);
+    }
+
+    /// Returns an iterator over all non-overlapping occurrences of this
+    /// needle in the bytes read from the given reader.
+    ///
+    /// The iterator yields the offset, from the start of the reader, of each
+    /// occurrence. Reads are buffered internally, and occurrences that span
+    /// two reads are found.
+    ///
+    /// If the reader returns an error, other than
+    /// [`std::io::ErrorKind::Interrupted`], then the iterator yields it. The
+    /// next call to `next` reads from the reader again.
+    ///
+    /// An empty needle never matches, which is consistent with
+    /// [`StreamFinder`].
+    ///
+    /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("foo");
+    /// let reader = &b"foo bar foo baz foo"[..];
+    /// let offsets = finder
+    ///     .stream_find_iter(reader)
+    ///     .collect::<std::io::Result<Vec<u64>>>()?;
+    /// assert_eq!(vec![0, 8, 16], offsets);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn stream_find_iter<'a, R: std::io::Read>(
+        &'a self,
+        reader: R,
+    ) -> StreamFindIter<'a, R> {
+        StreamFindIter::new(StreamFinder::from_finder(self.as_ref()), reader)
+    }
+
+    /// Returns the number of non-overlapping occurrences of this needle in
+    /// the given haystack.
+    ///
+    /// This always returns the same value as
+    /// `self.find_iter(haystack).count()`, but avoids the overhead of an
+    /// iterator. In particular, an empty needle matches at every position in
+    /// the haystack, including at its end, so the count for an empty needle
+    /// is `haystack.len() + 1`.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
     ///
     /// This routine is also guaranteed to have worst case constant space
     /// complexity.
@@ -476,16 +2527,35 @@ impl<'n> Finder<'n> {
     /// ```
     /// use memchr::memmem::Finder;
     ///
-    /// let haystack = b"foo bar baz";
-    /// assert_eq!(Some(0), Finder::new("foo").find(haystack));
-    /// assert_eq!(Some(4), Finder::new("bar").find(haystack));
-    /// assert_eq!(None, Finder::new("quux").find(haystack));
+    /// let haystack = b"foo bar foo baz foo";
+    /// assert_eq!(3, Finder::new("foo").count(haystack));
+    /// assert_eq!(0, Finder::new("quux").count(haystack));
+    /// // Matches never overlap.
+    /// assert_eq!(2, Finder::new("aa").count(b"aaaaa"));
     /// ```
-    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
-        self.searcher.find(&mut self.searcher.prefilter_state(), haystack)
+    pub fn count(&self, haystack: &[u8]) -> usize {
+        let mut prestate = self.searcher.prefilter_state();
+        let step = core::cmp::max(1, self.needle().len());
+        let (mut count, mut pos) = (0, 0);
+        while pos <= haystack.len() {
+            match self.searcher.find(&mut prestate, &haystack[pos..]) {
+                None => break,
+                Some(i) => {
+                    count += 1;
+                    pos += i + step;
+                }
+            }
+        }
+        count
     }
 
-    /// Returns an iterator over all occurrences of a substring in a haystack.
+    /// Returns the index of the `n`th non-overlapping occurrence of this
+    /// needle in the given haystack, where `n` is zero-indexed.
+    ///
+    /// This always returns the same value as
+    /// `self.find_iter(haystack).nth(n)`, but avoids the overhead of an
+    /// iterator. If there are fewer than `n + 1` occurrences, then `None` is
+    /// returned.
     ///
     /// # Complexity
     ///
@@ -503,20 +2573,128 @@ impl<'n> Finder<'n> {
     /// ```
     /// use memchr::memmem::Finder;
     ///
+    /// let finder = Finder::new("foo");
     /// let haystack = b"foo bar foo baz foo";
-    /// let finder = Finder::new(b"foo");
-    /// let mut it = finder.find_iter(haystack);
-    /// assert_eq!(Some(0), it.next());
-    /// assert_eq!(Some(8), it.next());
-    /// assert_eq!(Some(16), it.next());
-    /// assert_eq!(None, it.next());
+    /// assert_eq!(Some(0), finder.find_nth(0, haystack));
+    /// assert_eq!(Some(16), finder.find_nth(2, haystack));
+    /// assert_eq!(None, finder.find_nth(3, haystack));
     /// ```
-    #[inline]
-    pub fn find_iter<'a, 'h>(
-        &'a self,
-        haystack: &'h [u8],
-    ) -> FindIter<'h, 'a> {
-        FindIter::new(haystack, self.as_ref())
+    pub fn find_nth(&self, n: usize, haystack: &[u8]) -> Option<usize> {
+        let step = core::cmp::max(1, self.needle().len());
+        self.find_nth_imp(n, haystack, step)
+    }
+
+    /// Returns the index of the `n`th occurrence of this needle in the given
+    /// haystack, where `n` is zero-indexed and occurrences may overlap.
+    ///
+    /// This always returns the same value as
+    /// `self.find_overlapping_iter(haystack).nth(n)`. If there are fewer than
+    /// `n + 1` occurrences, then `None` is returned.
+    ///
+    /// # Complexity
+    ///
+    /// Since occurrences may overlap, this may take
+    /// `O(needle.len() * haystack.len())` time in the worst case.
+    ///
+    /// This routine is guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("aa");
+    /// assert_eq!(Some(1), finder.find_nth_overlapping(1, b"aaaa"));
+    /// assert_eq!(Some(2), finder.find_nth(1, b"aaaa"));
+    /// ```
+    pub fn find_nth_overlapping(
+        &self,
+        n: usize,
+        haystack: &[u8],
+    ) -> Option<usize> {
+        self.find_nth_imp(n, haystack, 1)
+    }
+
+    /// Returns the index of the `n`th occurrence of this needle, where the
+    /// search resumes `step` bytes after the start of each occurrence.
+    fn find_nth_imp(
+        &self,
+        mut n: usize,
+        haystack: &[u8],
+        step: usize,
+    ) -> Option<usize> {
+        let mut prestate = self.searcher.prefilter_state();
+        let mut pos = 0;
+        while pos <= haystack.len() {
+            let i =
+                pos + self.searcher.find(&mut prestate, &haystack[pos..])?;
+            if n == 0 {
+                return Some(i);
+            }
+            n -= 1;
+            pos = i + step;
+        }
+        None
+    }
+
+    /// Returns the index of the first occurrence of this needle in the given
+    /// haystack that starts at a multiple of `align`.
+    ///
+    /// Occurrences at any other offset are skipped. When one is found, the
+    /// search resumes at the next multiple of `align`, so the haystack
+    /// between the two is never searched again. An `align` of `0` or `1`
+    /// places no constraint on the offset, which makes this equivalent to
+    /// [`Finder::find`].
+    ///
+    /// This is useful for finding records in a binary format made of
+    /// fixed-size blocks, where only occurrences at the start of a block are
+    /// meaningful.
+    ///
+    /// # Complexity
+    ///
+    /// Since skipped occurrences may overlap with the bytes following the
+    /// next multiple of `align`, this may take
+    /// `O(needle.len() * haystack.len())` time in the worst case.
+    ///
+    /// This routine is guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("REC");
+    /// let haystack = b"xREC....RECxxxxREC";
+    /// assert_eq!(Some(1), finder.find(haystack));
+    /// assert_eq!(Some(8), finder.find_aligned(haystack, 4));
+    /// assert_eq!(None, finder.find_aligned(haystack, 16));
+    /// ```
+    pub fn find_aligned(
+        &self,
+        haystack: &[u8],
+        align: usize,
+    ) -> Option<usize> {
+        if align <= 1 {
+            return self.find(haystack);
+        }
+        let mut prestate = self.searcher.prefilter_state();
+        let mut pos = 0;
+        while pos <= haystack.len() {
+            let i =
+                pos + self.searcher.find(&mut prestate, &haystack[pos..])?;
+            if i % align == 0 {
+                return Some(i);
+            }
+            // Resume at the next multiple of `align` after `i`.
+            pos = (i / align).checked_add(1)?.checked_mul(align)?;
+        }
+        None
     }
 
     /// Convert this finder into its owned variant, such that it no longer
@@ -526,6 +2704,26 @@ impl<'n> Finder<'n> {
     /// this copies the needle.
     ///
     /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// This shows how to store a finder built from a temporary needle:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// struct Matcher {
+    ///     finder: Finder<'static>,
+    /// }
+    ///
+    /// fn matcher(word: &str) -> Matcher {
+    ///     let needle = format!("<{}>", word);
+    ///     Matcher { finder: Finder::new(&needle).into_owned() }
+    /// }
+    ///
+    /// let m = matcher("b");
+    /// assert_eq!(Some(3), m.finder.find(b"<a><b>"));
+    /// ```
     #[cfg(feature = "std")]
     #[inline]
     pub fn into_owned(self) -> Finder<'static> {
@@ -547,6 +2745,90 @@ impl<'n> Finder<'n> {
         Finder { searcher: self.searcher.as_ref() }
     }
 
+    /// Returns information about the prefilter that this finder selected.
+    ///
+    /// This is purely observational and is mostly useful for debugging
+    /// performance problems.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::{FinderBuilder, Prefilter, PrefilterKind};
+    ///
+    /// let finder = FinderBuilder::new()
+    ///     .prefilter(Prefilter::None)
+    ///     .build_forward("foobar");
+    /// assert_eq!(PrefilterKind::None, finder.prefilter_info().kind());
+    /// ```
+    #[inline]
+    pub fn prefilter_info(&self) -> PrefilterInfo {
+        self.searcher.prefilter_info()
+    }
+
+    /// Returns all non-overlapping occurrences of this needle in a haystack,
+    /// along with statistics about how the prefilter performed while finding
+    /// them.
+    ///
+    /// The occurrences are the same as the ones reported by
+    /// [`Finder::find_iter`]. The statistics count the candidates reported by
+    /// the prefilter described by [`Finder::prefilter_info`], how many of
+    /// them were verified and how many were confirmed as matches. A high
+    /// ratio of candidates to matches suggests that the prefilter is poorly
+    /// suited to the data being searched, e.g., because the bytes that the
+    /// frequency heuristic believes are rare are actually common. See
+    /// [`SearchStats`] for details.
+    ///
+    /// This is purely observational and is meant for tuning needles and
+    /// frequency heuristics. It is considerably slower than
+    /// [`Finder::find_iter`], since it bypasses the vectorized searchers,
+    /// keeps the prefilter enabled even when it stops being effective, and
+    /// verifies every candidate with a direct comparison. In particular,
+    /// unlike every other search routine in this crate, it does not
+    /// guarantee linear time.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let finder = Finder::new("foo");
+    /// let (matches, stats) = finder.find_iter_profiled(b"foo bar foo baz");
+    /// assert_eq!(vec![0, 8], matches);
+    /// assert_eq!(2, stats.matches());
+    /// assert!(stats.candidates() >= stats.matches());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn find_iter_profiled(
+        &self,
+        haystack: &[u8],
+    ) -> (Vec<usize>, SearchStats) {
+        let needle_len = self.needle().len();
+        let mut state = self.searcher.prefilter_state();
+        let (mut matches, mut stats) = (vec![], SearchStats::default());
+        let mut pos = 0;
+        while pos <= haystack.len() {
+            let result = self.searcher.find_profiled(
+                &mut state,
+                &haystack[pos..],
+                &mut stats,
+            );
+            match result {
+                None => break,
+                Some(i) => {
+                    matches.push(pos + i);
+                    pos += i + core::cmp::max(1, needle_len);
+                }
+            }
+        }
+        (matches, stats)
+    }
+
     /// Returns the needle that this finder searches for.
     ///
     /// Note that the lifetime of the needle returned is tied to the lifetime
@@ -557,6 +2839,42 @@ impl<'n> Finder<'n> {
     pub fn needle(&self) -> &[u8] {
         self.searcher.needle()
     }
+
+    /// Convert this finder into one that can be cheaply cloned and shared.
+    ///
+    /// The needle is copied at most once, when converting. Cloning the
+    /// [`ArcFinder`] returned only increments a reference count.
+    ///
+    /// This is only available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::Finder;
+    ///
+    /// let needle = String::from("foo");
+    /// let finder = Finder::new(&needle).into_arc();
+    /// drop(needle);
+    ///
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|i| {
+    ///         let finder = finder.clone();
+    ///         std::thread::spawn(move || {
+    ///             finder.find(format!("{}foo", "x".repeat(i)).as_bytes())
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for (i, worker) in workers.into_iter().enumerate() {
+    ///     assert_eq!(Some(i), worker.join().unwrap());
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn into_arc(self) -> ArcFinder {
+        ArcFinder::new(self.into_owned())
+    }
 }
 
 /// A single substring reverse searcher fixed to a particular needle.
@@ -572,11 +2890,35 @@ impl<'n> Finder<'n> {
 /// When the `std` feature is enabled, then this type has an `into_owned`
 /// version which permits building a `FinderRev` that is not connected to
 /// the lifetime of its needle.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct FinderRev<'n> {
     searcher: SearcherRev<'n>,
 }
 
+/// Formats the needle and the kind of prefilter selected.
+///
+/// See the `Debug` impl for [`Finder`] for details on the format.
+impl<'n> core::fmt::Debug for FinderRev<'n> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("FinderRev")
+            .field("needle", &util::DebugNeedle(self.needle()))
+            .field("prefilter", &self.prefilter_info().kind())
+            .finish()
+    }
+}
+
+/// Two reverse finders are equal when they search for the same needle in
+/// the same way.
+///
+/// See the `PartialEq` impl for [`Finder`] for details.
+impl<'n> PartialEq for FinderRev<'n> {
+    fn eq(&self, other: &FinderRev<'n>) -> bool {
+        self.searcher.same_search(&other.searcher)
+    }
+}
+
+impl<'n> Eq for FinderRev<'n> {}
+
 impl<'n> FinderRev<'n> {
     /// Create a new reverse finder for the given needle.
     #[inline]
@@ -612,11 +2954,24 @@ impl<'n> FinderRev<'n> {
     /// assert_eq!(None, FinderRev::new("quux").rfind(haystack));
     /// ```
     pub fn rfind<B: AsRef<[u8]>>(&self, haystack: B) -> Option<usize> {
-        self.searcher.rfind(haystack.as_ref())
+        let mut prestate = self.searcher.prefilter_state();
+        self.searcher.rfind(&mut prestate, haystack.as_ref())
     }
 
-    /// Returns a reverse iterator over all occurrences of a substring in a
-    /// haystack.
+    /// Returns a reverse iterator over all non-overlapping occurrences of a
+    /// substring in a haystack.
+    ///
+    /// Occurrences are reported by their starting offset, in descending
+    /// order. After each match, the search continues to the left of where
+    /// the match begins, so no two matches overlap and no match is reported
+    /// twice. This is the mirror image of [`Finder::find_iter`].
+    ///
+    /// When the needle can overlap with itself (that is, when some proper
+    /// prefix of the needle is also a suffix of it, like `aa` or `abab`), the
+    /// forward and reverse iterators may choose different matches. For
+    /// example, searching `aaaaa` for `aa` yields `0, 2` forwards but `3, 1`
+    /// in reverse. Both always report the same number of matches, and when
+    /// the needle can't overlap with itself, they report the same matches.
     ///
     /// # Complexity
     ///
@@ -641,6 +2996,11 @@ impl<'n> FinderRev<'n> {
     /// assert_eq!(Some(8), it.next());
     /// assert_eq!(Some(0), it.next());
     /// assert_eq!(None, it.next());
+    ///
+    /// // Matches never overlap.
+    /// let finder = FinderRev::new(b"aa");
+    /// let matches: Vec<usize> = finder.rfind_iter(b"aaaa").collect();
+    /// assert_eq!(vec![2, 0], matches);
     /// ```
     #[inline]
     pub fn rfind_iter<'a, 'h>(
@@ -650,6 +3010,65 @@ impl<'n> FinderRev<'n> {
         FindRevIter::new(haystack, self.as_ref())
     }
 
+    /// Returns a reverse iterator over the subslices of a haystack that are
+    /// separated by non-overlapping occurrences of this needle.
+    ///
+    /// This has the same semantics as `str::rsplit`. See [`rsplit`] for more
+    /// details.
+    ///
+    /// # Complexity
+    ///
+    /// This routine is guaranteed to have worst case linear time complexity
+    /// with respect to both the needle and the haystack. That is, this runs
+    /// in `O(needle.len() + haystack.len())` time.
+    ///
+    /// This routine is also guaranteed to have worst case constant space
+    /// complexity.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::FinderRev;
+    ///
+    /// let finder = FinderRev::new("/");
+    /// assert_eq!(Some(&b"c.txt"[..]), finder.rsplit(b"a/b/c.txt").next());
+    /// ```
+    #[inline]
+    pub fn rsplit<'a, 'h>(&'a self, haystack: &'h [u8]) -> RSplit<'h, 'a> {
+        RSplit::new(haystack, self.as_ref())
+    }
+
+    /// Returns a reverse iterator over at most `limit` subslices of a
+    /// haystack that are separated by non-overlapping occurrences of this
+    /// needle.
+    ///
+    /// This has the same semantics as `str::rsplitn`. See [`rsplitn`] for
+    /// more details.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::FinderRev;
+    ///
+    /// let finder = FinderRev::new("/");
+    /// let mut it = finder.rsplitn(2, b"a/b/c");
+    /// assert_eq!(Some(&b"c"[..]), it.next());
+    /// assert_eq!(Some(&b"a/b"[..]), it.next());
+    /// assert_eq!(None, it.next());
+    /// ```
+    #[inline]
+    pub fn rsplitn<'a, 'h>(
+        &'a self,
+        limit: usize,
+        haystack: &'h [u8],
+    ) -> RSplitN<'h, 'a> {
+        RSplitN::new(limit, self.rsplit(haystack))
+    }
+
     /// Convert this finder into its owned variant, such that it no longer
     /// borrows the needle.
     ///
@@ -678,6 +3097,28 @@ impl<'n> FinderRev<'n> {
         FinderRev { searcher: self.searcher.as_ref() }
     }
 
+    /// Returns information about the prefilter that this finder selected.
+    ///
+    /// This is purely observational and is mostly useful for debugging
+    /// performance problems.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::{FinderBuilder, Prefilter, PrefilterKind};
+    ///
+    /// let finder = FinderBuilder::new()
+    ///     .prefilter(Prefilter::None)
+    ///     .build_reverse("foobar");
+    /// assert_eq!(PrefilterKind::None, finder.prefilter_info().kind());
+    /// ```
+    #[inline]
+    pub fn prefilter_info(&self) -> PrefilterInfo {
+        self.searcher.prefilter_info()
+    }
+
     /// Returns the needle that this finder searches for.
     ///
     /// Note that the lifetime of the needle returned is tied to the lifetime
@@ -708,6 +3149,8 @@ impl<'n> FinderRev<'n> {
 /// frequency table defined in `src/memmem/byte_frequencies.rs`.
 /// This is optimal for most inputs, so if you are unsure of the impact of using
 /// a custom `HeuristicFrequencyRank` you should probably just use the default.
+/// The [`freq`] module provides ready-made implementations for other kinds of
+/// data, including the table for x86 executables used in the example below.
 ///
 /// Example:
 /// ```
@@ -741,6 +3184,45 @@ pub trait HeuristicFrequencyRank {
     fn rank(&self, byte: u8) -> u8;
 }
 
+/// A byte frequency heuristic that ships with this crate.
+///
+/// Unlike an arbitrary [`HeuristicFrequencyRank`] implementation, a built-in
+/// heuristic can be named by a value, which permits it to be stored as part
+/// of a [`FinderConfig`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FrequencyTable {
+    /// The default heuristic, which is derived from a corpus of source code,
+    /// natural language text and binary data. This is what
+    /// [`FinderBuilder::build_forward`] and friends use.
+    ///
+    /// This is the same as [`freq::Text`].
+    Default,
+    /// A heuristic for searching x86 executables. This is the same as
+    /// [`freq::X86Executable`].
+    X86Executable,
+    /// A heuristic that considers every byte equally common. This is the same
+    /// as [`freq::Uniform`].
+    Uniform,
+}
+
+impl Default for FrequencyTable {
+    fn default() -> FrequencyTable {
+        FrequencyTable::Default
+    }
+}
+
+impl HeuristicFrequencyRank for FrequencyTable {
+    fn rank(&self, byte: u8) -> u8 {
+        match *self {
+            FrequencyTable::Default => freq::Text.rank(byte),
+            FrequencyTable::X86Executable => freq::X86Executable.rank(byte),
+            FrequencyTable::Uniform => freq::Uniform.rank(byte),
+        }
+    }
+}
+
 /// The default byte frequency heuristic that is good for most inputs
 pub(crate) struct DefaultHFR;
 impl HeuristicFrequencyRank for DefaultHFR {
@@ -771,10 +3253,27 @@ pub struct FinderBuilder {
 
 impl FinderBuilder {
     /// Create a new finder builder with default settings.
-    pub fn new() -> FinderBuilder {
-        FinderBuilder::default()
+    ///
+    /// This is a `const fn`, so a builder can be stored in a `const` or
+    /// `static`. Building a finder from it still happens at runtime.
+    pub const fn new() -> FinderBuilder {
+        FinderBuilder {
+            config: SearcherConfig {
+                prefilter: Prefilter::Auto,
+                simd_threshold: FinderBuilder::DEFAULT_SIMD_THRESHOLD,
+                verifier: Verifier::Simd,
+            },
+        }
     }
 
+    /// The default value of [`FinderBuilder::simd_threshold`].
+    ///
+    /// This was picked with the `sliceslice-words/words` benchmarks, whose
+    /// haystacks are English words. Larger values showed no consistent
+    /// improvement there, since almost all of those words are shorter than
+    /// 16 bytes.
+    pub const DEFAULT_SIMD_THRESHOLD: usize = 16;
+
     /// Build a forward finder using the given needle from the current
     /// settings.
     pub fn build_forward<'n, B: ?Sized + AsRef<[u8]>>(
@@ -805,23 +3304,352 @@ impl FinderBuilder {
         }
     }
 
+    /// Build a forward finder using the given needle from the current
+    /// settings that matches ASCII letters case insensitively.
+    ///
+    /// Only the ASCII letters `A-Z` and `a-z` are folded. All other bytes,
+    /// including non-ASCII bytes, must match exactly.
+    ///
+    /// Note that searches with the finder returned do not have the worst case
+    /// linear time guarantee of other finders.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::FinderBuilder;
+    ///
+    /// let finder = FinderBuilder::new().build_forward_ascii_ignore_case("foo");
+    /// assert_eq!(Some(4), finder.find(b"baz FoO quux"));
+    /// assert_eq!(b"foo", finder.needle());
+    /// ```
+    pub fn build_forward_ascii_ignore_case<'n, B: ?Sized + AsRef<[u8]>>(
+        &self,
+        needle: &'n B,
+    ) -> Finder<'n> {
+        self.build_heuristic_ascii_ignore_case(needle, DefaultHFR)
+    }
+
+    /// Build a forward ASCII case insensitive finder using the given needle
+    /// and a custom heuristic for determining the frequency of a given byte
+    /// in the dataset.
+    /// See [`FinderBuilder::build_forward_ascii_ignore_case`] and
+    /// [`HeuristicFrequencyRank`] for more details.
+    ///
+    /// As with the other finders, the heuristic is used for every decision
+    /// about which bytes in the needle are rare. In this case, the rank of an
+    /// ASCII letter is the rank of its more frequent case.
+    pub fn build_heuristic_ascii_ignore_case<
+        'n,
+        B: ?Sized + AsRef<[u8]>,
+        H: HeuristicFrequencyRank,
+    >(
+        &self,
+        needle: &'n B,
+        heuristic: H,
+    ) -> Finder<'n> {
+        Finder {
+            searcher: Searcher::new_ascii_ignore_case(
+                self.config,
+                needle.as_ref(),
+                heuristic,
+            ),
+        }
+    }
+
     /// Build a reverse finder using the given needle from the current
     /// settings.
+    ///
+    /// Reverse finders built this way never use a prefilter. On short or
+    /// adversarial haystacks, the reverse prefilter can cost several times
+    /// more than it saves, so it's only used when a heuristic is given
+    /// explicitly with [`FinderBuilder::build_heuristic_reverse`].
     pub fn build_reverse<'n, B: ?Sized + AsRef<[u8]>>(
         &self,
         needle: &'n B,
     ) -> FinderRev<'n> {
-        FinderRev { searcher: SearcherRev::new(needle.as_ref()) }
+        FinderRev { searcher: SearcherRev::new(self.config, needle.as_ref()) }
+    }
+
+    /// Build a reverse finder using the given needle and a custom heuristic
+    /// for determining the frequency of a given byte in the dataset.
+    /// See [`HeuristicFrequencyRank`] for more details.
+    ///
+    /// The heuristic is used to pick the bytes that the reverse prefilter
+    /// looks for, in the same way as [`FinderBuilder::build_heuristic`] does
+    /// for forward finders. The prefilter is only used when the prefilter
+    /// setting is [`Prefilter::Auto`] and the heuristic ranks one of the
+    /// needle's bytes as rare enough. It usually makes searching long
+    /// haystacks faster, but can slow down short haystacks and haystacks
+    /// full of the needle's rare bytes.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::{FinderBuilder, HeuristicFrequencyRank};
+    ///
+    /// // Pretend that NUL bytes are common and everything else is rare.
+    /// struct NulHeavy;
+    /// impl HeuristicFrequencyRank for NulHeavy {
+    ///     fn rank(&self, byte: u8) -> u8 {
+    ///         if byte == 0 { 255 } else { 0 }
+    ///     }
+    /// }
+    ///
+    /// let finder = FinderBuilder::new()
+    ///     .build_heuristic_reverse(b"\x00\x00\xdd\xdd", NulHeavy);
+    /// let haystack = b"\x00\x00\xdd\xdd\x00\x00\x00\xdd\xdd\x00";
+    /// assert_eq!(Some(5), finder.rfind(haystack));
+    /// ```
+    pub fn build_heuristic_reverse<
+        'n,
+        B: ?Sized + AsRef<[u8]>,
+        H: HeuristicFrequencyRank,
+    >(
+        &self,
+        needle: &'n B,
+        heuristic: H,
+    ) -> FinderRev<'n> {
+        FinderRev {
+            searcher: SearcherRev::new_heuristic(
+                self.config,
+                needle.as_ref(),
+                heuristic,
+            ),
+        }
     }
 
     /// Configure the prefilter setting for the finder.
     ///
+    /// This applies to forward and reverse finders alike. Disabling the
+    /// prefilter with [`Prefilter::None`] never changes which matches are
+    /// reported. It only removes the heuristic skip-scan, which is useful
+    /// when you know your haystacks will make the prefilter report many
+    /// false positive candidates, and you'd rather have the steady
+    /// performance of the underlying search algorithm.
+    ///
     /// See the documentation for [`Prefilter`] for more discussion on why
     /// you might want to configure this.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::{FinderBuilder, Prefilter, PrefilterKind};
+    ///
+    /// let finder = FinderBuilder::new()
+    ///     .prefilter(Prefilter::None)
+    ///     .build_forward("needle in a haystack");
+    /// assert_eq!(PrefilterKind::None, finder.prefilter_info().kind());
+    /// assert_eq!(Some(4), finder.find(b"the needle in a haystack"));
+    /// ```
     pub fn prefilter(&mut self, prefilter: Prefilter) -> &mut FinderBuilder {
         self.config.prefilter = prefilter;
         self
     }
+
+    /// Configure how candidate matches are verified by the vectorized
+    /// searchers.
+    ///
+    /// This only applies to forward finders for needles of 2 to 32 bytes.
+    /// It never changes which matches are reported. See the documentation
+    /// for [`Verifier`] for more details. The default is
+    /// [`Verifier::Simd`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::memmem::{FinderBuilder, Verifier};
+    ///
+    /// let finder = FinderBuilder::new()
+    ///     .verifier(Verifier::Scalar)
+    ///     .build_forward("a needle of 24 bytes....");
+    /// assert_eq!(Some(4), finder.find(b"and a needle of 24 bytes...."));
+    /// ```
+    pub fn verifier(&mut self, verifier: Verifier) -> &mut FinderBuilder {
+        self.config.verifier = verifier;
+        self
+    }
+
+    /// Configure the haystack length, in bytes, below which searches use a
+    /// simple scalar search instead of vectorized or Two-Way search.
+    ///
+    /// Setting up a vectorized search and handling the bytes at its end has
+    /// a fixed cost, which can outweigh comparing bytes one at a time when
+    /// the haystack is tiny. Where the crossover lies depends on the machine,
+    /// so workloads that search huge numbers of tiny haystacks may want to
+    /// tune it. Single byte needles are then searched with a byte loop, and
+    /// longer needles with Rabin-Karp.
+    ///
+    /// Since Rabin-Karp has multiplicative worst case time, setting a large
+    /// threshold weakens this crate's linear time guarantee for haystacks
+    /// below it. The default is
+    /// [`DEFAULT_SIMD_THRESHOLD`](FinderBuilder::DEFAULT_SIMD_THRESHOLD).
+    /// This doesn't apply to ASCII case insensitive searches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memchr::memmem::FinderBuilder;
+    ///
+    /// let finder = FinderBuilder::new().simd_threshold(64).build_forward("a=");
+    /// assert_eq!(Some(4), finder.find(b"key=a=b"));
+    /// ```
+    pub fn simd_threshold(&mut self, bytes: usize) -> &mut FinderBuilder {
+        self.config.simd_threshold = bytes;
+        self
+    }
+}
+
+/// An owned description of a substring searcher: its needle along with the
+/// settings used to build it.
+///
+/// Unlike a [`Finder`], a `FinderConfig` holds no search state, which makes
+/// it suitable for storing and building finders from later. When the `serde`
+/// feature is enabled, it implements `Serialize` and `Deserialize`. The
+/// needle is serialized as an array of bytes, and the prefilter setting and
+/// frequency table as enum variant names.
+///
+/// Only built-in frequency heuristics, as named by [`FrequencyTable`], can be
+/// stored in a config. A custom [`HeuristicFrequencyRank`] implementation
+/// can't be serialized, so a finder using one must be built with
+/// [`FinderBuilder::build_heuristic`] instead.
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem::{FinderConfig, Prefilter};
+///
+/// let mut config = FinderConfig::new("bar");
+/// config.prefilter(Prefilter::None);
+/// let finder = config.build_forward();
+/// assert_eq!(Some(4), finder.find(b"foo bar"));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FinderConfig {
+    #[cfg_attr(feature = "serde", serde(with = "needle_serde"))]
+    needle: Vec<u8>,
+    prefilter: Prefilter,
+    frequencies: FrequencyTable,
+}
+
+#[cfg(feature = "std")]
+impl FinderConfig {
+    /// Create a new config for the given needle with default settings.
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &B) -> FinderConfig {
+        FinderConfig {
+            needle: needle.as_ref().to_vec(),
+            prefilter: Prefilter::default(),
+            frequencies: FrequencyTable::default(),
+        }
+    }
+
+    /// Returns the needle that this config searches for.
+    pub fn needle(&self) -> &[u8] {
+        &self.needle
+    }
+
+    /// Configure the prefilter setting, as with [`FinderBuilder::prefilter`].
+    pub fn prefilter(&mut self, prefilter: Prefilter) -> &mut FinderConfig {
+        self.prefilter = prefilter;
+        self
+    }
+
+    /// Configure the byte frequency heuristic used to pick the bytes that
+    /// prefilters look for.
+    pub fn frequencies(
+        &mut self,
+        frequencies: FrequencyTable,
+    ) -> &mut FinderConfig {
+        self.frequencies = frequencies;
+        self
+    }
+
+    /// Build a forward finder for the needle in this config.
+    pub fn build_forward(&self) -> Finder<'_> {
+        self.builder().build_heuristic(&self.needle, self.frequencies)
+    }
+
+    /// Build a reverse finder for the needle in this config.
+    ///
+    /// Like [`FinderBuilder::build_reverse`], this doesn't use a prefilter
+    /// when the frequency table is [`FrequencyTable::Default`].
+    pub fn build_reverse(&self) -> FinderRev<'_> {
+        if self.frequencies == FrequencyTable::Default {
+            return self.builder().build_reverse(&self.needle);
+        }
+        self.builder().build_heuristic_reverse(&self.needle, self.frequencies)
+    }
+
+    fn builder(&self) -> FinderBuilder {
+        let mut builder = FinderBuilder::new();
+        builder.prefilter(self.prefilter);
+        builder
+    }
+}
+
+/// Serializes the needle of a `FinderConfig` as a sequence of bytes.
+///
+/// serde only implements its traits for `Vec<u8>` when its own `std` or
+/// `alloc` feature is enabled. We don't enable either, so that the `serde`
+/// feature doesn't force them on users of this crate without the standard
+/// library.
+#[cfg(all(feature = "std", feature = "serde"))]
+mod needle_serde {
+    use core::fmt;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        needle: &[u8],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(needle)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        deserializer.deserialize_seq(NeedleVisitor)
+    }
+
+    struct NeedleVisitor;
+
+    impl<'de> de::Visitor<'de> for NeedleVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence of bytes")
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Vec<u8>, A::Error> {
+            let mut needle = vec![];
+            while let Some(byte) = seq.next_element()? {
+                needle.push(byte);
+            }
+            Ok(needle)
+        }
+
+        fn visit_bytes<E: de::Error>(
+            self,
+            bytes: &[u8],
+        ) -> Result<Vec<u8>, E> {
+            Ok(bytes.to_vec())
+        }
+    }
 }
 
 /// The internal implementation of a forward substring searcher.
@@ -845,6 +3673,9 @@ struct Searcher<'n> {
     /// Some substring search implementations (like Two-Way) benefit greatly
     /// if we can quickly find candidate starting positions for a match.
     prefn: Option<PrefilterFn>,
+    /// The configuration this searcher was built with. This is kept so that
+    /// a reverse searcher with the same configuration can be built from it.
+    config: SearcherConfig,
     /// The actual substring implementation in use.
     kind: SearcherKind,
 }
@@ -879,11 +3710,21 @@ pub(crate) struct NeedleInfo {
 }
 
 /// Configuration for substring search.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 struct SearcherConfig {
     /// This permits changing the behavior of the prefilter, since it can have
     /// a variable impact on performance.
     prefilter: Prefilter,
+    /// Haystacks shorter than this are searched with a scalar search.
+    simd_threshold: usize,
+    /// How the vectorized searchers verify candidates.
+    verifier: Verifier,
+}
+
+impl Default for SearcherConfig {
+    fn default() -> SearcherConfig {
+        FinderBuilder::new().config
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -904,6 +3745,10 @@ enum SearcherKind {
     GenericSIMD128(wasm::Forward),
     #[cfg(all(not(miri), target_arch = "x86_64", memchr_runtime_simd))]
     GenericSIMD256(x86::avx::Forward),
+    /// This is used for ASCII case insensitive searches of any needle. It
+    /// uses a case folding prefilter to find candidates and then verifies
+    /// each of them.
+    AsciiIgnoreCase,
 }
 
 impl<'n> Searcher<'n> {
@@ -941,14 +3786,18 @@ impl<'n> Searcher<'n> {
         use self::SearcherKind::*;
 
         let ninfo = NeedleInfo::new(needle, &heuristic);
-        let mk = |kind: SearcherKind| {
-            let prefn = prefilter::forward(
-                &config.prefilter,
-                &ninfo.rarebytes,
-                needle,
-                heuristic,
-            );
-            Searcher { needle: CowBytes::new(needle), ninfo, prefn, kind }
+        let prefn = prefilter::forward(
+            &config.prefilter,
+            &ninfo.rarebytes,
+            needle,
+            heuristic,
+        );
+        let mk = |kind: SearcherKind| Searcher {
+            needle: CowBytes::new(needle),
+            ninfo,
+            prefn,
+            config,
+            kind,
         };
         if needle.len() == 0 {
             return mk(Empty);
@@ -958,20 +3807,70 @@ impl<'n> Searcher<'n> {
         }
         #[cfg(all(not(miri), target_arch = "x86_64", memchr_runtime_simd))]
         {
-            if let Some(fwd) = x86::avx::Forward::new(&ninfo, needle) {
-                return mk(GenericSIMD256(fwd));
-            } else if let Some(fwd) = x86::sse::Forward::new(&ninfo, needle) {
-                return mk(GenericSIMD128(fwd));
+            // The experimental PCMPESTRI prefilter is only used by Two-Way,
+            // so skip the vectorized searchers when it has been selected.
+            // Otherwise, it would never run for the short needles it was
+            // selected for.
+            let pcmpestri =
+                prefn.map(|p| p.kind()) == Some(PrefilterKind::Pcmpestri);
+            if !pcmpestri {
+                if let Some(fwd) =
+                    x86::avx::Forward::new(&ninfo, needle, config.verifier)
+                {
+                    return mk(GenericSIMD256(fwd));
+                } else if let Some(fwd) =
+                    x86::sse::Forward::new(&ninfo, needle, config.verifier)
+                {
+                    return mk(GenericSIMD128(fwd));
+                }
             }
         }
         #[cfg(all(target_arch = "wasm32", memchr_runtime_simd))]
         {
-            if let Some(fwd) = wasm::Forward::new(&ninfo, needle) {
+            if let Some(fwd) =
+                wasm::Forward::new(&ninfo, needle, config.verifier)
+            {
                 return mk(GenericSIMD128(fwd));
             }
         }
 
-        mk(TwoWay(twoway::Forward::new(needle)))
+        mk(TwoWay(twoway::Forward::new(needle)))
+    }
+
+    fn new_ascii_ignore_case<H: HeuristicFrequencyRank>(
+        config: SearcherConfig,
+        needle: &'n [u8],
+        heuristic: H,
+    ) -> Searcher<'n> {
+        let ninfo = NeedleInfo::new_ascii_ignore_case(needle, &heuristic);
+        let prefn = prefilter::forward_ascii_ignore_case(
+            &config.prefilter,
+            &ninfo.rarebytes,
+            needle,
+            heuristic,
+        );
+        Searcher {
+            needle: CowBytes::new(needle),
+            ninfo,
+            prefn,
+            config,
+            kind: SearcherKind::AsciiIgnoreCase,
+        }
+    }
+
+    /// Returns true if and only if this searcher and the one given search for
+    /// the same needle with the same configuration.
+    ///
+    /// Everything else in a searcher is derived from the needle, the rare
+    /// bytes picked for it and its configuration, so comparing those is
+    /// sufficient.
+    fn same_search(&self, other: &Searcher<'_>) -> bool {
+        self.needle() == other.needle()
+            && self.ninfo.rarebytes == other.ninfo.rarebytes
+            && self.prefn.map(|p| p.kind()) == other.prefn.map(|p| p.kind())
+            && self.config == other.config
+            && core::mem::discriminant(&self.kind)
+                == core::mem::discriminant(&other.kind)
     }
 
     /// Return a fresh prefilter state that can be used with this searcher.
@@ -995,6 +3894,100 @@ impl<'n> Searcher<'n> {
         self.needle.as_slice()
     }
 
+    /// Returns a reverse searcher for the same needle, built with the same
+    /// configuration as this searcher.
+    ///
+    /// The reverse searcher uses the default byte frequency heuristic, since
+    /// a custom heuristic isn't kept after construction. This only matters
+    /// for performance.
+    fn reverse(&self) -> SearcherRev<'_> {
+        if self.is_ascii_ignore_case() {
+            SearcherRev::new_ascii_ignore_case(self.config, self.needle())
+        } else {
+            SearcherRev::new(self.config, self.needle())
+        }
+    }
+
+    /// Returns true if and only if this searcher is an ASCII case insensitive
+    /// searcher.
+    fn is_ascii_ignore_case(&self) -> bool {
+        match self.kind {
+            SearcherKind::AsciiIgnoreCase => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if and only if the given window of a haystack is equal
+    /// to this searcher's needle, ignoring ASCII case when this searcher
+    /// does.
+    fn is_needle(&self, window: &[u8]) -> bool {
+        if self.is_ascii_ignore_case() {
+            window.eq_ignore_ascii_case(self.needle())
+        } else {
+            util::memcmp(window, self.needle())
+        }
+    }
+
+    /// Returns information about the prefilter in use by this searcher.
+    ///
+    /// A prefilter is only reported when the search strategy selected for
+    /// this searcher actually uses it.
+    fn prefilter_info(&self) -> PrefilterInfo {
+        let kind =
+            self.active_prefn().map_or(PrefilterKind::None, |p| p.kind());
+        PrefilterInfo::new(kind, &self.ninfo.rarebytes)
+    }
+
+    /// Returns the prefilter used by the search strategy selected for this
+    /// searcher, if any.
+    fn active_prefn(&self) -> Option<PrefilterFn> {
+        use self::SearcherKind::*;
+
+        match self.kind {
+            TwoWay(_) | AsciiIgnoreCase => self.prefn,
+            _ => None,
+        }
+    }
+
+    /// Like `find`, but records what the search did in `stats`.
+    ///
+    /// Candidates are reported by the active prefilter, if there is one, and
+    /// every candidate is verified by comparing the needle with the haystack
+    /// directly. Unlike `find`, the prefilter is used for the entire search,
+    /// even if it stops being effective, so that the stats describe the
+    /// prefilter instead of the heuristic that decides when to disable it.
+    /// Without a prefilter, every position is a candidate.
+    ///
+    /// This is not linear time in the worst case, and is only meant for
+    /// profiling.
+    #[cfg(feature = "std")]
+    fn find_profiled(
+        &self,
+        state: &mut PrefilterState,
+        haystack: &[u8],
+        stats: &mut SearchStats,
+    ) -> Option<usize> {
+        let needle = self.needle();
+        let prefn = self.active_prefn();
+        let mut i = 0;
+        while haystack.len() - i >= needle.len() {
+            if let Some(prefn) = prefn {
+                i += prefn.call(state, &self.ninfo, &haystack[i..], needle)?;
+            }
+            stats.candidates += 1;
+            if haystack.len() - i < needle.len() {
+                return None;
+            }
+            stats.verifications += 1;
+            if self.is_needle(&haystack[i..i + needle.len()]) {
+                stats.matches += 1;
+                return Some(i);
+            }
+            i += 1;
+        }
+        None
+    }
+
     fn as_ref(&self) -> Searcher<'_> {
         use self::SearcherKind::*;
 
@@ -1010,11 +4003,13 @@ impl<'n> Searcher<'n> {
                 memchr_runtime_simd
             ))]
             GenericSIMD256(gs) => GenericSIMD256(gs),
+            AsciiIgnoreCase => AsciiIgnoreCase,
         };
         Searcher {
             needle: CowBytes::new(self.needle()),
             ninfo: self.ninfo,
             prefn: self.prefn,
+            config: self.config,
             kind,
         }
     }
@@ -1035,11 +4030,13 @@ impl<'n> Searcher<'n> {
                 memchr_runtime_simd
             ))]
             GenericSIMD256(gs) => GenericSIMD256(gs),
+            AsciiIgnoreCase => AsciiIgnoreCase,
         };
         Searcher {
             needle: self.needle.into_owned(),
             ninfo: self.ninfo,
             prefn: self.prefn,
+            config: self.config,
             kind,
         }
     }
@@ -1059,13 +4056,15 @@ impl<'n> Searcher<'n> {
         if haystack.len() < needle.len() {
             return None;
         }
+        let short = haystack.len() < self.config.simd_threshold;
         match self.kind {
             Empty => Some(0),
+            OneByte(b) if short => haystack.iter().position(|&x| x == b),
             OneByte(b) => crate::memchr(b, haystack),
             TwoWay(ref tw) => {
                 // For very short haystacks (e.g., where the prefilter probably
                 // can't run), it's faster to just run RK.
-                if rabinkarp::is_fast(haystack, needle) {
+                if short {
                     rabinkarp::find_with(&self.ninfo.nhash, haystack, needle)
                 } else {
                     self.find_tw(tw, state, haystack, needle)
@@ -1075,7 +4074,7 @@ impl<'n> Searcher<'n> {
             GenericSIMD128(ref gs) => {
                 // The SIMD matcher can't handle particularly short haystacks,
                 // so we fall back to RK in these cases.
-                if haystack.len() < gs.min_haystack_len() {
+                if short || haystack.len() < gs.min_haystack_len() {
                     rabinkarp::find_with(&self.ninfo.nhash, haystack, needle)
                 } else {
                     gs.find(haystack, needle)
@@ -1089,12 +4088,15 @@ impl<'n> Searcher<'n> {
             GenericSIMD256(ref gs) => {
                 // The SIMD matcher can't handle particularly short haystacks,
                 // so we fall back to RK in these cases.
-                if haystack.len() < gs.min_haystack_len() {
+                if short || haystack.len() < gs.min_haystack_len() {
                     rabinkarp::find_with(&self.ninfo.nhash, haystack, needle)
                 } else {
                     gs.find(haystack, needle)
                 }
             }
+            AsciiIgnoreCase => {
+                self.find_ascii_ignore_case(state, haystack, needle)
+            }
         }
     }
 
@@ -1128,6 +4130,52 @@ impl<'n> Searcher<'n> {
         }
         tw.find(None, haystack, needle)
     }
+
+    /// Runs an ASCII case insensitive search on the given haystack/needle.
+    ///
+    /// Candidates are found with the case folding prefilter when it is
+    /// available and still effective, and are otherwise tried one position
+    /// at a time. Every candidate is then verified by comparing the needle
+    /// with the haystack while ignoring ASCII case.
+    #[inline(never)]
+    fn find_ascii_ignore_case(
+        &self,
+        state: &mut PrefilterState,
+        haystack: &[u8],
+        needle: &[u8],
+    ) -> Option<usize> {
+        if needle.len() == 0 {
+            return Some(0);
+        }
+        if needle.len() == 1 {
+            let (lower, upper) = (
+                needle[0].to_ascii_lowercase(),
+                needle[0].to_ascii_uppercase(),
+            );
+            return crate::memchr2(lower, upper, haystack);
+        }
+        let mut i = 0;
+        while haystack.len() - i >= needle.len() {
+            if let Some(prefn) = self.prefn {
+                if state.is_effective() {
+                    i += prefn.call(
+                        state,
+                        &self.ninfo,
+                        &haystack[i..],
+                        needle,
+                    )?;
+                    if haystack.len() - i < needle.len() {
+                        return None;
+                    }
+                }
+            }
+            if haystack[i..i + needle.len()].eq_ignore_ascii_case(needle) {
+                return Some(i);
+            }
+            i += 1;
+        }
+        None
+    }
 }
 
 impl NeedleInfo {
@@ -1140,21 +4188,48 @@ impl NeedleInfo {
             nhash: NeedleHash::forward(needle),
         }
     }
+
+    /// Like `new`, but computes facts for a reverse search.
+    pub(crate) fn reverse<H: HeuristicFrequencyRank>(
+        needle: &[u8],
+        heuristic: &H,
+    ) -> NeedleInfo {
+        NeedleInfo {
+            rarebytes: RareNeedleBytes::reverse(needle, heuristic),
+            nhash: NeedleHash::reverse(needle),
+        }
+    }
+
+    /// Like `new`, but computes facts for an ASCII case insensitive search.
+    pub(crate) fn new_ascii_ignore_case<H: HeuristicFrequencyRank>(
+        needle: &[u8],
+        heuristic: &H,
+    ) -> NeedleInfo {
+        NeedleInfo {
+            rarebytes: RareNeedleBytes::forward_ascii_ignore_case(
+                needle, heuristic,
+            ),
+            nhash: NeedleHash::forward(needle),
+        }
+    }
 }
 
 /// The internal implementation of a reverse substring searcher.
 ///
 /// See the forward searcher docs for more details. Currently, the reverse
-/// searcher is considerably simpler since it lacks prefilter support. This
-/// was done because it adds a lot of code, and more surface area to test. And
-/// in particular, it's not clear whether a prefilter on reverse searching is
-/// worth it. (If you have a compelling use case, please file an issue!)
+/// searcher is considerably simpler since it lacks vectorized search and only
+/// supports the memchr based fallback prefilter, which is only used when the
+/// searcher is built with an explicit heuristic.
 #[derive(Clone, Debug)]
 struct SearcherRev<'n> {
     /// The actual needle we're searching for.
     needle: CowBytes<'n>,
-    /// A Rabin-Karp hash of the needle.
-    nhash: NeedleHash,
+    /// A collection of facts computed on the needle for reverse searching.
+    ninfo: NeedleInfo,
+    /// A reverse prefilter function, if it was deemed appropriate.
+    prefn: Option<PrefilterFn>,
+    /// Haystacks shorter than this are searched with a scalar search.
+    simd_threshold: usize,
     /// The actual substring implementation in use.
     kind: SearcherRevKind,
 }
@@ -1171,12 +4246,32 @@ enum SearcherRevKind {
     /// linear time guarantee. In general, it's used when the needle is bigger
     /// than 8 bytes or so.
     TwoWay(twoway::Reverse),
+    /// This is used for ASCII case insensitive searches of any needle. Every
+    /// position is verified, since there is no reverse case folding
+    /// prefilter.
+    AsciiIgnoreCase,
 }
 
 impl<'n> SearcherRev<'n> {
-    fn new(needle: &'n [u8]) -> SearcherRev<'n> {
+    /// Like `Searcher::new`, this non-generic constructor must not be
+    /// removed. See the docs on `Searcher::new` for why.
+    ///
+    /// Unlike `new_heuristic`, this never uses a prefilter. See
+    /// `FinderBuilder::build_reverse` for why.
+    fn new(config: SearcherConfig, needle: &'n [u8]) -> SearcherRev<'n> {
+        let mut searcher = Self::new_heuristic(config, needle, DefaultHFR);
+        searcher.prefn = None;
+        searcher
+    }
+
+    fn new_heuristic<H: HeuristicFrequencyRank>(
+        config: SearcherConfig,
+        needle: &'n [u8],
+        heuristic: H,
+    ) -> SearcherRev<'n> {
         use self::SearcherRevKind::*;
 
+        let ninfo = NeedleInfo::reverse(needle, &heuristic);
         let kind = if needle.len() == 0 {
             Empty
         } else if needle.len() == 1 {
@@ -1184,17 +4279,73 @@ impl<'n> SearcherRev<'n> {
         } else {
             TwoWay(twoway::Reverse::new(needle))
         };
+        let prefn = prefilter::reverse(
+            &config.prefilter,
+            &ninfo.rarebytes,
+            needle,
+            heuristic,
+        );
         SearcherRev {
             needle: CowBytes::new(needle),
-            nhash: NeedleHash::reverse(needle),
+            ninfo,
+            prefn,
+            simd_threshold: config.simd_threshold,
             kind,
         }
     }
 
+    /// Create a reverse searcher that ignores ASCII case. This is only used
+    /// to build reverse searchers from ASCII case insensitive forward ones.
+    fn new_ascii_ignore_case(
+        config: SearcherConfig,
+        needle: &'n [u8],
+    ) -> SearcherRev<'n> {
+        SearcherRev {
+            needle: CowBytes::new(needle),
+            ninfo: NeedleInfo::reverse(needle, &DefaultHFR),
+            prefn: None,
+            simd_threshold: config.simd_threshold,
+            kind: SearcherRevKind::AsciiIgnoreCase,
+        }
+    }
+
+    /// Returns true if and only if this searcher and the one given search for
+    /// the same needle with the same configuration.
+    ///
+    /// See `Searcher::same_search` for more details.
+    fn same_search(&self, other: &SearcherRev<'_>) -> bool {
+        self.needle() == other.needle()
+            && self.ninfo.rarebytes == other.ninfo.rarebytes
+            && self.prefn.map(|p| p.kind()) == other.prefn.map(|p| p.kind())
+            && self.simd_threshold == other.simd_threshold
+            && core::mem::discriminant(&self.kind)
+                == core::mem::discriminant(&other.kind)
+    }
+
+    /// Return a fresh prefilter state that can be used with this searcher.
+    ///
+    /// See `Searcher::prefilter_state` for more details.
+    fn prefilter_state(&self) -> PrefilterState {
+        if self.prefn.is_none() {
+            PrefilterState::inert()
+        } else {
+            PrefilterState::new()
+        }
+    }
+
     fn needle(&self) -> &[u8] {
         self.needle.as_slice()
     }
 
+    /// Returns information about the prefilter in use by this searcher.
+    fn prefilter_info(&self) -> PrefilterInfo {
+        let kind = match (&self.kind, self.prefn) {
+            (&SearcherRevKind::TwoWay(_), Some(prefn)) => prefn.kind(),
+            _ => PrefilterKind::None,
+        };
+        PrefilterInfo::new(kind, &self.ninfo.rarebytes)
+    }
+
     fn as_ref(&self) -> SearcherRev<'_> {
         use self::SearcherRevKind::*;
 
@@ -1202,10 +4353,13 @@ impl<'n> SearcherRev<'n> {
             Empty => Empty,
             OneByte(b) => OneByte(b),
             TwoWay(tw) => TwoWay(tw),
+            AsciiIgnoreCase => AsciiIgnoreCase,
         };
         SearcherRev {
             needle: CowBytes::new(self.needle()),
-            nhash: self.nhash,
+            ninfo: self.ninfo,
+            prefn: self.prefn,
+            simd_threshold: self.simd_threshold,
             kind,
         }
     }
@@ -1218,52 +4372,675 @@ impl<'n> SearcherRev<'n> {
             Empty => Empty,
             OneByte(b) => OneByte(b),
             TwoWay(tw) => TwoWay(tw),
+            AsciiIgnoreCase => AsciiIgnoreCase,
         };
         SearcherRev {
             needle: self.needle.into_owned(),
-            nhash: self.nhash,
+            ninfo: self.ninfo,
+            prefn: self.prefn,
+            simd_threshold: self.simd_threshold,
             kind,
         }
     }
 
-    /// Implements reverse substring search by selecting the implementation
-    /// chosen at construction and executing it on the given haystack with the
-    /// prefilter's current state of effectiveness.
-    #[inline(always)]
-    fn rfind(&self, haystack: &[u8]) -> Option<usize> {
-        use self::SearcherRevKind::*;
+    /// Implements reverse substring search by selecting the implementation
+    /// chosen at construction and executing it on the given haystack with the
+    /// prefilter's current state of effectiveness.
+    #[inline(always)]
+    fn rfind(
+        &self,
+        state: &mut PrefilterState,
+        haystack: &[u8],
+    ) -> Option<usize> {
+        use self::SearcherRevKind::*;
+
+        let needle = self.needle();
+        if haystack.len() < needle.len() {
+            return None;
+        }
+        let short = haystack.len() < self.simd_threshold;
+        match self.kind {
+            Empty => Some(haystack.len()),
+            OneByte(b) if short => haystack.iter().rposition(|&x| x == b),
+            OneByte(b) => crate::memrchr(b, haystack),
+            TwoWay(ref tw) => {
+                // For very short haystacks (e.g., where the prefilter probably
+                // can't run), it's faster to just run RK.
+                if short {
+                    rabinkarp::rfind_with(&self.ninfo.nhash, haystack, needle)
+                } else {
+                    self.rfind_tw(tw, state, haystack, needle)
+                }
+            }
+            AsciiIgnoreCase => rfind_ascii_ignore_case(haystack, needle),
+        }
+    }
+
+    /// Calls reverse Two-Way on the given haystack/needle.
+    ///
+    /// This is marked as unlineable for the same reasons as
+    /// `Searcher::find_tw`.
+    #[inline(never)]
+    fn rfind_tw(
+        &self,
+        tw: &twoway::Reverse,
+        state: &mut PrefilterState,
+        haystack: &[u8],
+        needle: &[u8],
+    ) -> Option<usize> {
+        if let Some(prefn) = self.prefn {
+            if state.is_effective() {
+                let mut pre = Pre { state, prefn, ninfo: &self.ninfo };
+                return tw.rfind(Some(&mut pre), haystack, needle);
+            }
+        }
+        tw.rfind(None, haystack, needle)
+    }
+}
+
+/// Returns the starting index of the last occurrence of the given needle in
+/// the given haystack, ignoring ASCII case.
+///
+/// Like the forward ASCII case insensitive search, single byte needles use
+/// `memrchr2`. Otherwise, every position is checked from the end of the
+/// haystack, so this takes `O(haystack.len() * needle.len())` time in the
+/// worst case.
+fn rfind_ascii_ignore_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.len() == 0 {
+        return Some(haystack.len());
+    }
+    if needle.len() == 1 {
+        let (lower, upper) =
+            (needle[0].to_ascii_lowercase(), needle[0].to_ascii_uppercase());
+        return crate::memrchr2(lower, upper, haystack);
+    }
+    let last = haystack.len().checked_sub(needle.len())?;
+    (0..=last)
+        .rev()
+        .find(|&i| haystack[i..i + needle.len()].eq_ignore_ascii_case(needle))
+}
+
+/// This module defines some generic quickcheck properties useful for testing
+/// any substring search algorithm. It also runs those properties for the
+/// top-level public API memmem routines. (The properties are also used to
+/// test various substring search implementations more granularly elsewhere as
+/// well.)
+#[cfg(all(test, feature = "std", not(miri)))]
+mod proptests {
+    // N.B. This defines the quickcheck tests using the properties defined
+    // below. Because of macro-visibility weirdness, the actual macro is
+    // defined at the top of this file.
+    define_memmem_quickcheck_tests!(super::find, super::rfind);
+
+    quickcheck::quickcheck! {
+        fn qc_stream_finder_matches_find_iter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            sizes: Vec<u8>
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|&b| b % 3).collect();
+            let needle: Vec<u8> =
+                needle.iter().take(5).map(|&b| b % 3).collect();
+            if needle.is_empty() {
+                return true;
+            }
+            let expected: Vec<u64> = super::Finder::new(&needle)
+                .find_iter(&haystack)
+                .map(|i| i as u64)
+                .collect();
+
+            let mut stream = super::StreamFinder::new(&needle);
+            let mut got = vec![];
+            let mut rest = &haystack[..];
+            let mut sizes = sizes.iter().map(|&n| n as usize % 8 + 1).cycle();
+            while !rest.is_empty() {
+                let size = sizes.next().unwrap_or(1);
+                let (mut chunk, next) =
+                    rest.split_at(core::cmp::min(size, rest.len()));
+                while let Some(m) = stream.push(chunk) {
+                    got.push(m.start());
+                    chunk = &chunk[m.consumed()..];
+                }
+                // Empty chunks are allowed and never produce a match.
+                if stream.push(&[]).is_some() {
+                    return false;
+                }
+                rest = next;
+            }
+            expected == got
+        }
+
+        fn qc_rfind_iter_mirrors_find_iter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            // A binary alphabet makes self-overlapping needles common.
+            let haystack: Vec<u8> = haystack.iter().map(|&b| b % 2).collect();
+            let needle: Vec<u8> =
+                needle.iter().take(4).map(|&b| b % 2).collect();
+            let fwd: Vec<usize> =
+                super::Finder::new(&needle).find_iter(&haystack).collect();
+            let mut rev: Vec<usize> =
+                super::FinderRev::new(&needle).rfind_iter(&haystack).collect();
+            rev.reverse();
+            // Reverse matches are strictly descending and never overlap.
+            let disjoint = needle.is_empty()
+                || rev.windows(2).all(|w| w[0] + needle.len() <= w[1]);
+            // Greedily picking matches from either end yields the maximum
+            // number of non-overlapping matches, so the counts always agree.
+            // The matches themselves only agree in general when the needle
+            // has no border, i.e., can't overlap with itself.
+            let bordered = (1..needle.len())
+                .any(|k| needle[..k] == needle[needle.len() - k..]);
+            disjoint && fwd.len() == rev.len() && (bordered || fwd == rev)
+        }
+
+        fn qc_count_matches_find_iter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            // Use a small alphabet so that matches are frequent.
+            let haystack: Vec<u8> = haystack.iter().map(|&b| b % 3).collect();
+            let needle: Vec<u8> =
+                needle.iter().take(4).map(|&b| b % 3).collect();
+            let finder = super::Finder::new(&needle);
+            finder.count(&haystack) == finder.find_iter(&haystack).count()
+        }
+
+        fn qc_find_overlapping_iter_matches_naive(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|&b| b % 2).collect();
+            let needle: Vec<u8> =
+                needle.iter().take(4).map(|&b| b % 2).collect();
+            let expected: Vec<usize> = (0..=haystack.len())
+                .filter(|&i| haystack[i..].starts_with(&needle))
+                .collect();
+            let got: Vec<usize> = super::Finder::new(&needle)
+                .find_overlapping_iter(&haystack)
+                .collect();
+            expected == got
+        }
+
+        fn qc_split_matches_str_split(
+            haystack: Vec<u8>,
+            delimiter: Vec<u8>
+        ) -> bool {
+            let ascii = |bs: Vec<u8>| -> String {
+                bs.into_iter().map(|b| (b'a' + b % 3) as char).collect()
+            };
+            let (haystack, delimiter) = (ascii(haystack), ascii(delimiter));
+            let delimiter = &delimiter[..core::cmp::min(3, delimiter.len())];
+            let expected: Vec<&[u8]> =
+                haystack.split(delimiter).map(|s| s.as_bytes()).collect();
+            let got: Vec<&[u8]> =
+                super::split(haystack.as_bytes(), delimiter).collect();
+            expected == got
+        }
+
+        fn qc_rsplit_matches_str_rsplit(
+            haystack: Vec<u8>,
+            delimiter: Vec<u8>
+        ) -> bool {
+            let ascii = |bs: Vec<u8>| -> String {
+                bs.into_iter().map(|b| (b'a' + b % 3) as char).collect()
+            };
+            let (haystack, delimiter) = (ascii(haystack), ascii(delimiter));
+            let delimiter = &delimiter[..core::cmp::min(3, delimiter.len())];
+            let expected: Vec<&[u8]> =
+                haystack.rsplit(delimiter).map(|s| s.as_bytes()).collect();
+            let got: Vec<&[u8]> =
+                super::rsplit(haystack.as_bytes(), delimiter).collect();
+            expected == got
+        }
+
+        fn qc_splitn_matches_str_splitn(
+            limit: u8,
+            haystack: Vec<u8>,
+            delimiter: Vec<u8>
+        ) -> bool {
+            let ascii = |bs: Vec<u8>| -> String {
+                bs.into_iter().map(|b| (b'a' + b % 3) as char).collect()
+            };
+            let limit = limit as usize % 6;
+            let (haystack, delimiter) = (ascii(haystack), ascii(delimiter));
+            let delimiter = &delimiter[..core::cmp::min(2, delimiter.len())];
+            let expected: Vec<&[u8]> = haystack
+                .splitn(limit, delimiter)
+                .map(|s| s.as_bytes())
+                .collect();
+            let got: Vec<&[u8]> =
+                super::splitn(limit, haystack.as_bytes(), delimiter).collect();
+            let rexpected: Vec<&[u8]> = haystack
+                .rsplitn(limit, delimiter)
+                .map(|s| s.as_bytes())
+                .collect();
+            let rgot: Vec<&[u8]> =
+                super::rsplitn(limit, haystack.as_bytes(), delimiter)
+                    .collect();
+            expected == got && rexpected == rgot
+        }
+
+        fn qc_split_once_matches_str_split_once(
+            haystack: Vec<u8>,
+            delimiter: Vec<u8>
+        ) -> bool {
+            let ascii = |bs: Vec<u8>| -> String {
+                bs.into_iter().map(|b| (b'a' + b % 3) as char).collect()
+            };
+            let (haystack, delimiter) = (ascii(haystack), ascii(delimiter));
+            let delimiter = &delimiter[..core::cmp::min(2, delimiter.len())];
+            fn as_bytes<'a>(
+                (a, b): (&'a str, &'a str),
+            ) -> (&'a [u8], &'a [u8]) {
+                (a.as_bytes(), b.as_bytes())
+            }
+            let expected = haystack.split_once(delimiter).map(as_bytes);
+            let got = super::split_once(haystack.as_bytes(), delimiter);
+            let rexpected = haystack.rsplit_once(delimiter).map(as_bytes);
+            let rgot = super::rsplit_once(haystack.as_bytes(), delimiter);
+            expected == got && rexpected == rgot
+        }
+
+        fn qc_find_from_matches_overlapping_iter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            from: usize
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(3).map(|b| b % 2).collect();
+            let from = from % (haystack.len() + 2);
+            let finder = super::Finder::new(&needle);
+            let expected = finder
+                .find_overlapping_iter(&haystack)
+                .find(|&i| i >= from);
+            finder.find_from(&haystack, from) == expected
+        }
+
+        fn qc_find_n_matches_find(haystack: Vec<u8>, needle: u32) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let n: [u8; 4] = needle.to_le_bytes();
+            let n = [n[0] % 2, n[1] % 2, n[2] % 2, n[3] % 2];
+            let h = &haystack;
+            super::find_n(h, &[n[0]]) == super::find(h, &n[..1])
+                && super::find_n(h, &[n[0], n[1]]) == super::find(h, &n[..2])
+                && super::find_n(h, &[n[0], n[1], n[2]])
+                    == super::find(h, &n[..3])
+                && super::find_n(h, &n) == super::find(h, &n)
+        }
+
+        fn qc_find_segments_matches_find(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            cuts: Vec<usize>
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(4).map(|b| b % 2).collect();
+            let mut cuts: Vec<usize> =
+                cuts.iter().map(|c| c % (haystack.len() + 1)).collect();
+            cuts.push(0);
+            cuts.push(haystack.len());
+            cuts.sort();
+            let segments: Vec<&[u8]> =
+                cuts.windows(2).map(|w| &haystack[w[0]..w[1]]).collect();
+            let expected = if needle.is_empty() {
+                None
+            } else {
+                super::find(&haystack, &needle)
+            };
+            let got = super::find_segments(&segments, &needle)
+                .map(|(i, offset)| cuts[i] + offset);
+            got == expected
+        }
+
+        fn qc_find_within_matches_find(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            max_bytes: usize
+        ) -> bool {
+            use super::FindWithin;
+
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(3).map(|b| b % 2).collect();
+            let max_bytes = max_bytes % (haystack.len() + 2);
+            let finder = super::Finder::new(&needle);
+            let expected = match finder.find(&haystack) {
+                Some(i) if i < max_bytes || needle.is_empty() => {
+                    FindWithin::Found(i)
+                }
+                // An occurrence past the budget means that the budget
+                // didn't cover the whole haystack.
+                Some(_) => FindWithin::BudgetExhausted,
+                None if max_bytes + needle.len() <= haystack.len() => {
+                    FindWithin::BudgetExhausted
+                }
+                None => FindWithin::NotFound,
+            };
+            finder.find_within(&haystack, max_bytes) == expected
+        }
+
+        fn qc_find_in_suffix_matches_find_iter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            suffix_len: usize
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(3).map(|b| b % 2).collect();
+            let suffix_len = suffix_len % (haystack.len() + 2);
+            let start = haystack.len().saturating_sub(suffix_len);
+            let finder = super::Finder::new(&needle);
+            let expected = finder.find(&haystack[start..]).map(|i| start + i);
+            finder.find_in_suffix(&haystack, suffix_len) == expected
+        }
+
+        fn qc_replace_all_cow_matches_replace_all(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            replacement: Vec<u8>
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(3).map(|b| b % 2).collect();
+            let finder = super::Finder::new(&needle);
+            let expected = finder.replace_all(&haystack, &replacement);
+            let got = finder.replace_all_cow(&haystack, &replacement);
+            let borrowed = matches!(got, std::borrow::Cow::Borrowed(_));
+            let changed = !needle.is_empty() && finder.find(&haystack).is_some();
+            *got == *expected && borrowed != changed
+        }
+
+        fn qc_one_byte_finder_matches_memchr(
+            haystack: Vec<u8>,
+            needle: u8
+        ) -> bool {
+            // Use a small alphabet so that the needle occurs often.
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 4).collect();
+            let needle = [needle % 4];
+            let finder = super::Finder::new(&needle);
+            let finder_rev = super::FinderRev::new(&needle);
+            let expected: Vec<usize> =
+                crate::memchr_iter(needle[0], &haystack).collect();
+            let got: Vec<usize> = finder.find_iter(&haystack).collect();
+            finder.find(&haystack) == crate::memchr(needle[0], &haystack)
+                && finder_rev.rfind(&haystack)
+                    == crate::memrchr(needle[0], &haystack)
+                && got == expected
+        }
+
+        fn qc_find_iter_with_gaps_reconstructs(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            use super::Segment;
+
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(3).map(|b| b % 2).collect();
+            let finder = super::Finder::new(&needle);
+            let (mut rebuilt, mut matches) = (vec![], vec![]);
+            for segment in finder.find_iter_with_gaps(&haystack) {
+                match segment {
+                    Segment::Gap(gap) => rebuilt.extend_from_slice(gap),
+                    Segment::Match(i) => {
+                        matches.push(i);
+                        rebuilt.extend_from_slice(&needle);
+                    }
+                }
+            }
+            let expected: Vec<usize> = finder.find_iter(&haystack).collect();
+            rebuilt == haystack && matches == expected
+        }
+
+        fn qc_match_gaps_are_windows_of_find_iter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(3).map(|b| b % 2).collect();
+            let finder = super::Finder::new(&needle);
+            let matches: Vec<usize> = finder.find_iter(&haystack).collect();
+            let expected: Vec<(usize, usize)> =
+                matches.windows(2).map(|w| (w[0], w[1])).collect();
+            let got: Vec<(usize, usize)> =
+                finder.match_gaps(&haystack).collect();
+            got == expected
+        }
+
+        fn qc_find_iter_profiled_matches_find_iter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            use super::{FinderBuilder, Prefilter};
+
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 3).collect();
+            let needle: Vec<u8> = needle.iter().take(4).map(|b| b % 3).collect();
+            let finders = [
+                super::Finder::new(&needle),
+                FinderBuilder::new()
+                    .prefilter(Prefilter::None)
+                    .build_forward(&needle),
+                FinderBuilder::new()
+                    .build_forward_ascii_ignore_case(&needle),
+            ];
+            finders.iter().all(|finder| {
+                let (matches, stats) = finder.find_iter_profiled(&haystack);
+                let expected: Vec<usize> =
+                    finder.find_iter(&haystack).collect();
+                matches == expected
+                    && stats.matches() == matches.len()
+                    && stats.verifications() >= stats.matches()
+                    && stats.candidates() >= stats.verifications()
+            })
+        }
+
+        fn qc_find_aligned_matches_filter(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            align: u8
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 2).collect();
+            let needle: Vec<u8> = needle.iter().take(3).map(|b| b % 2).collect();
+            let align = usize::from(align % 8);
+            let finder = super::Finder::new(&needle);
+            let expected = finder
+                .find_overlapping_iter(&haystack)
+                .find(|&i| align <= 1 || i % align == 0);
+            finder.find_aligned(&haystack, align) == expected
+        }
+
+        fn qc_wildcard_finder_matches_naive(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            mask: Vec<bool>
+        ) -> bool {
+            // A small alphabet makes matches likely.
+            let haystack: Vec<u8> = haystack.iter().map(|b| b % 3).collect();
+            let needle: Vec<u8> = needle.iter().map(|b| b % 3).collect();
+            let mask: Vec<bool> = (0..needle.len())
+                .map(|i| mask.get(i).copied().unwrap_or(true))
+                .collect();
+            let is_match = |at: usize| {
+                (0..needle.len())
+                    .all(|i| !mask[i] || haystack[at + i] == needle[i])
+            };
+            let expected = if needle.len() > haystack.len() {
+                None
+            } else {
+                (0..=haystack.len() - needle.len()).find(|&at| is_match(at))
+            };
+            let finder = super::WildcardFinder::new(&needle, &mask);
+            finder.find(&haystack) == expected
+        }
+
+        fn qc_find_nth_matches_find_iter(
+            n: u8,
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            let n = n as usize % 8;
+            let haystack: Vec<u8> = haystack.iter().map(|&b| b % 2).collect();
+            let needle: Vec<u8> =
+                needle.iter().take(3).map(|&b| b % 2).collect();
+            let finder = super::Finder::new(&needle);
+            finder.find_nth(n, &haystack) == finder.find_iter(&haystack).nth(n)
+                && finder.find_nth_overlapping(n, &haystack)
+                    == finder.find_overlapping_iter(&haystack).nth(n)
+        }
+
+        fn qc_finder_set_matches_naive(
+            haystack: Vec<u8>,
+            needles: Vec<Vec<u8>>
+        ) -> bool {
+            let haystack: Vec<u8> = haystack.iter().map(|&b| b % 3).collect();
+            let needles: Vec<Vec<u8>> = needles
+                .iter()
+                .take(5)
+                .map(|n| n.iter().take(3).map(|&b| b % 3).collect())
+                .collect();
+            let expected = needles
+                .iter()
+                .enumerate()
+                .filter_map(|(i, n)| naive_find(&haystack, n).map(|o| (o, i)))
+                .min();
+            let refs: Vec<&[u8]> = needles.iter().map(|n| &**n).collect();
+            expected == super::FinderSet::new(&refs).find(&haystack)
+        }
+
+        fn qc_rev_heuristic_matches_naive(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            // Use a small alphabet and a haystack long enough that Two-Way
+            // and the reverse prefilter are used instead of Rabin-Karp.
+            let mut haystack: Vec<u8> =
+                haystack.iter().map(|&b| b % 3).collect();
+            haystack.resize(core::cmp::max(64, haystack.len()), 1);
+            let needle: Vec<u8> =
+                needle.iter().take(6).map(|&b| b % 3).collect();
+            let expected = naive_rfind(&haystack, &needle);
+            let expected_all: Vec<usize> = {
+                let mut all = vec![];
+                let mut end = haystack.len();
+                while let Some(i) = naive_rfind(&haystack[..end], &needle) {
+                    all.push(i);
+                    if needle.is_empty() {
+                        if i == 0 {
+                            break;
+                        }
+                        end = i - 1;
+                    } else {
+                        end = i;
+                    }
+                }
+                all
+            };
+
+            let builder = super::FinderBuilder::new();
+            let default = builder.build_reverse(&needle);
+            let custom = builder.build_heuristic_reverse(&needle, ZeroIsRare);
+            let nopre = super::FinderBuilder::new()
+                .prefilter(super::Prefilter::None)
+                .build_reverse(&needle);
+            for finder in [default, custom, nopre] {
+                if expected != finder.rfind(&haystack) {
+                    return false;
+                }
+                let got: Vec<usize> = finder.rfind_iter(&haystack).collect();
+                if expected_all != got {
+                    return false;
+                }
+            }
+            true
+        }
+
+        fn qc_no_prefilter_matches_default(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            let mut haystack: Vec<u8> =
+                haystack.iter().map(|&b| b % 4).collect();
+            haystack.resize(core::cmp::max(64, haystack.len()), 3);
+            let needle: Vec<u8> =
+                needle.iter().take(40).map(|&b| b % 4).collect();
+            let default = super::FinderBuilder::new().build_forward(&needle);
+            let nopre = super::FinderBuilder::new()
+                .prefilter(super::Prefilter::None)
+                .build_forward(&needle);
+            default.find_iter(&haystack).eq(nopre.find_iter(&haystack))
+        }
+
+        fn qc_verifiers_match_naive(
+            haystack: Vec<u8>,
+            needle: Vec<u8>,
+            at: usize
+        ) -> bool {
+            use super::{FinderBuilder, Verifier};
+
+            // Use needles long enough for vector verification, and a tiny
+            // alphabet with a copy of the needle so that they match.
+            let mut needle: Vec<u8> =
+                needle.iter().take(32).map(|&b| b % 2).collect();
+            needle.resize(core::cmp::max(16, needle.len()), 1);
+            let mut haystack: Vec<u8> =
+                haystack.iter().map(|&b| b % 2).collect();
+            haystack.resize(core::cmp::max(64, haystack.len()), 0);
+            let at = at % (haystack.len() - needle.len() + 1);
+            haystack[at..at + needle.len()].copy_from_slice(&needle);
+
+            let expected = naive_find(&haystack, &needle);
+            [Verifier::Simd, Verifier::Scalar].iter().all(|&v| {
+                let finder =
+                    FinderBuilder::new().verifier(v).build_forward(&needle);
+                finder.find(&haystack) == expected
+            })
+        }
+
+        fn qc_fwd_ascii_ignore_case_matches_naive(
+            haystack: Vec<u8>,
+            needle: Vec<u8>
+        ) -> bool {
+            // Use a tiny alphabet of mixed case letters and a non-ASCII byte
+            // so that case insensitive matches are actually likely.
+            let alphabet = b"aAbB\xDF";
+            let fold = |bs: Vec<u8>| -> Vec<u8> {
+                bs.into_iter().map(|b| alphabet[b as usize % 5]).collect()
+            };
+            let (haystack, needle) = (fold(haystack), fold(needle));
+            let expected = naive_find_ascii_ignore_case(&haystack, &needle);
+            let finder = super::FinderBuilder::new()
+                .prefilter(super::Prefilter::None)
+                .build_forward_ascii_ignore_case(&needle);
+            expected == super::find_ascii_ignore_case(&haystack, &needle)
+                && expected == finder.find(&haystack)
+        }
+    }
+
+    /// A heuristic that considers `0` to be the rarest byte and `2` to be
+    /// the most common, which is the opposite of the default heuristic.
+    struct ZeroIsRare;
 
-        let needle = self.needle();
-        if haystack.len() < needle.len() {
-            return None;
-        }
-        match self.kind {
-            Empty => Some(haystack.len()),
-            OneByte(b) => crate::memrchr(b, haystack),
-            TwoWay(ref tw) => {
-                // For very short haystacks (e.g., where the prefilter probably
-                // can't run), it's faster to just run RK.
-                if rabinkarp::is_fast(haystack, needle) {
-                    rabinkarp::rfind_with(&self.nhash, haystack, needle)
-                } else {
-                    tw.rfind(haystack, needle)
-                }
+    impl super::HeuristicFrequencyRank for ZeroIsRare {
+        fn rank(&self, byte: u8) -> u8 {
+            match byte {
+                0 => 0,
+                1 => 100,
+                _ => 200,
             }
         }
     }
-}
 
-/// This module defines some generic quickcheck properties useful for testing
-/// any substring search algorithm. It also runs those properties for the
-/// top-level public API memmem routines. (The properties are also used to
-/// test various substring search implementations more granularly elsewhere as
-/// well.)
-#[cfg(all(test, feature = "std", not(miri)))]
-mod proptests {
-    // N.B. This defines the quickcheck tests using the properties defined
-    // below. Because of macro-visibility weirdness, the actual macro is
-    // defined at the top of this file.
-    define_memmem_quickcheck_tests!(super::find, super::rfind);
+    /// Naively search forwards for the given needle in the given haystack
+    /// while ignoring ASCII case.
+    fn naive_find_ascii_ignore_case(
+        haystack: &[u8],
+        needle: &[u8],
+    ) -> Option<usize> {
+        if needle.is_empty() {
+            return Some(0);
+        }
+        haystack
+            .windows(needle.len())
+            .position(|w| w.eq_ignore_ascii_case(needle))
+    }
 
     /// Check that every prefix of the given byte string is a substring.
     pub(crate) fn prefix_is_substring(
@@ -1355,7 +5132,7 @@ mod proptests {
 /// also provides routines for easily running them on any substring search
 /// implementation.
 #[cfg(test)]
-mod testsimples {
+pub(crate) mod testsimples {
     define_memmem_simple_tests!(super::find, super::rfind);
 
     /// Each test is a (needle, haystack, expected_fwd, expected_rev) tuple.
@@ -1403,6 +5180,1051 @@ mod testsimples {
         ("\u{0}\u{1e}", "\u{1e}\u{0}", None, None),
     ];
 
+    #[test]
+    fn contains() {
+        use crate::memmem::{contains, Finder};
+
+        assert!(contains(b"foo bar baz", b"bar"));
+        assert!(contains(b"foo bar baz", b""));
+        assert!(contains(b"", b""));
+        assert!(!contains(b"", b"a"));
+        assert!(!contains(b"foo bar baz", b"bz"));
+        assert!(Finder::new("baz").contains(b"foo bar baz"));
+        assert!(!Finder::new("bazz").contains(b"foo bar baz"));
+    }
+
+    #[test]
+    fn starts_with_ends_with() {
+        use crate::memmem::Finder;
+
+        let finder = Finder::new("abcde");
+        assert!(finder.starts_with(b"abcde"));
+        assert!(finder.ends_with(b"abcde"));
+        assert!(finder.starts_with(b"abcdex"));
+        assert!(!finder.ends_with(b"abcdex"));
+        assert!(finder.ends_with(b"xabcde"));
+        assert!(!finder.starts_with(b"xabcde"));
+        assert!(!finder.starts_with(b"abcd"));
+        assert!(!finder.ends_with(b"bcde"));
+        assert!(!finder.starts_with(b"abcdf"));
+        assert!(!finder.ends_with(b"bbcde"));
+        assert!(!finder.starts_with(b""));
+
+        let finder = Finder::new("");
+        assert!(finder.starts_with(b""));
+        assert!(finder.ends_with(b""));
+        assert!(finder.starts_with(b"a"));
+        assert!(finder.ends_with(b"a"));
+    }
+
+    #[test]
+    fn starts_with_ends_with_ascii_ignore_case() {
+        use crate::memmem::FinderBuilder;
+
+        let finder =
+            FinderBuilder::new().build_forward_ascii_ignore_case("hello");
+        assert_eq!(Some(0), finder.find(b"HELLO world"));
+        assert!(finder.starts_with(b"HELLO world"));
+        assert!(finder.starts_with(b"hElLo"));
+        assert!(!finder.starts_with(b"world HELLO"));
+        assert!(finder.ends_with(b"world HELLO"));
+        assert!(finder.ends_with(b"HeLlO"));
+        assert!(!finder.ends_with(b"HELLO world"));
+        assert!(!finder.starts_with(b"HELL"));
+        // Only ASCII case is ignored.
+        let finder =
+            FinderBuilder::new().build_forward_ascii_ignore_case(b"\xE9");
+        assert!(!finder.starts_with(b"\xC9"));
+    }
+
+    #[test]
+    fn prefilter_info() {
+        use crate::memmem::{
+            FinderBuilder, FinderRev, Prefilter, PrefilterKind,
+        };
+
+        // 'q' and 'z' are the rarest bytes in this needle according to the
+        // default heuristic.
+        let needle = "azaaqaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        let info = FinderBuilder::new().build_forward(needle).prefilter_info();
+        assert_eq!(
+            PrefilterKind::None,
+            FinderBuilder::new()
+                .prefilter(Prefilter::None)
+                .build_forward(needle)
+                .prefilter_info()
+                .kind()
+        );
+        assert_eq!((4, 1), info.rare_offsets());
+        assert_ne!(PrefilterKind::None, info.kind());
+
+        // Reverse finders only use a prefilter when given a heuristic.
+        let info = FinderRev::new(needle).prefilter_info();
+        assert_eq!((4, 1), info.rare_offsets());
+        assert_eq!(PrefilterKind::None, info.kind());
+        let info = FinderBuilder::new()
+            .build_heuristic_reverse(needle, crate::memmem::freq::Text)
+            .prefilter_info();
+        assert_eq!((4, 1), info.rare_offsets());
+        assert_eq!(PrefilterKind::Memchr, info.kind());
+
+        let info = FinderBuilder::new().build_forward("a").prefilter_info();
+        assert_eq!(PrefilterKind::None, info.kind());
+        assert_eq!((0, 0), info.rare_offsets());
+    }
+
+    #[test]
+    fn finder_eq() {
+        use crate::memmem::{
+            Finder, FinderBuilder, FinderRev, FrequencyTable, Prefilter,
+            Verifier,
+        };
+
+        let needle = b"abc".to_vec();
+        assert_eq!(Finder::new(b"abc"), Finder::new(&needle));
+        assert_eq!(FinderRev::new(b"abc"), FinderRev::new(&needle));
+        assert_ne!(Finder::new(b"abc"), Finder::new(b"abd"));
+        assert_ne!(FinderRev::new(b"abc"), FinderRev::new(b"ab"));
+        assert_eq!(Finder::new(b""), Finder::new(b""));
+
+        // The default heuristic considers 'b' and 'c' rarer than 'a', while
+        // the uniform heuristic picks the first two bytes of the needle.
+        let custom = FinderBuilder::new()
+            .build_heuristic(b"abc", FrequencyTable::Uniform);
+        assert_ne!(
+            Finder::new(b"abc").prefilter_info().rare_offsets(),
+            custom.prefilter_info().rare_offsets()
+        );
+        assert_ne!(Finder::new(b"abc"), custom);
+        let custom = FinderBuilder::new()
+            .build_heuristic_reverse(b"abc", FrequencyTable::Uniform);
+        assert_ne!(FinderRev::new(b"abc"), custom);
+        // Naming the default table explicitly builds an equal finder.
+        let default = FinderBuilder::new()
+            .build_heuristic(b"abc", FrequencyTable::Default);
+        assert_eq!(Finder::new(b"abc"), default);
+
+        let none = FinderBuilder::new()
+            .prefilter(Prefilter::None)
+            .build_forward(b"Sherlock Holmes");
+        assert_ne!(Finder::new(b"Sherlock Holmes"), none);
+        let ignore_case =
+            FinderBuilder::new().build_forward_ascii_ignore_case(b"abc");
+        assert_ne!(Finder::new(b"abc"), ignore_case);
+        let threshold =
+            FinderBuilder::new().simd_threshold(0).build_forward(b"abc");
+        assert_ne!(Finder::new(b"abc"), threshold);
+        let scalar = FinderBuilder::new()
+            .verifier(Verifier::Scalar)
+            .build_forward("abc");
+        assert_ne!(Finder::new(b"abc"), scalar);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_in_absolute_offsets() {
+        use crate::memmem::find_in;
+
+        let haystack = b"foo bar foo baz foo";
+        let n = haystack.len();
+        assert_eq!(Some(0), find_in(haystack, b"foo", 0..n));
+        assert_eq!(Some(8), find_in(haystack, b"foo", 1..n));
+        assert_eq!(Some(16), find_in(haystack, b"foo", 9..n));
+        // Ranges at the very tail of the buffer.
+        assert_eq!(Some(16), find_in(haystack, b"foo", n - 3..n));
+        assert_eq!(None, find_in(haystack, b"foo", n - 2..n));
+        assert_eq!(Some(n), find_in(haystack, b"", n..n));
+        // A match must lie entirely within the range.
+        assert_eq!(None, find_in(haystack, b"foo", 9..n - 1));
+
+        let mut long = vec![b'a'; 1000];
+        long[990..].copy_from_slice(b"needle!!!!");
+        for start in 0..100 {
+            assert_eq!(Some(990), find_in(&long, b"needle", start..1000));
+            assert_eq!(None, find_in(&long, b"needle", start..995));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn find_in_out_of_bounds() {
+        crate::memmem::find_in(b"abc", b"a", 0..4);
+    }
+
+    #[test]
+    fn try_find_in_rejects_bad_ranges() {
+        use crate::memmem::try_find_in;
+
+        let haystack = b"abcabc";
+        assert_eq!(Ok(Some(3)), try_find_in(haystack, b"abc", 1..6));
+        assert_eq!(Ok(None), try_find_in(haystack, b"abc", 1..5));
+        assert_eq!(Ok(Some(6)), try_find_in(haystack, b"", 6..6));
+
+        let err = try_find_in(haystack, b"abc", 0..7).unwrap_err();
+        assert_eq!(0..7, err.range());
+        assert_eq!(6, err.haystack_len());
+        assert!(!err.is_inverted());
+        assert!(try_find_in(haystack, b"", 7..7).is_err());
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 4..1;
+        let err = try_find_in(haystack, b"abc", inverted).unwrap_err();
+        assert!(err.is_inverted());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn empty_needle_contract() {
+        use crate::memmem::{find, find_iter, rfind_iter, Finder};
+
+        for &haystack in &["", "a", "abc", "☃"] {
+            let h = haystack.as_bytes();
+            assert_eq!(Some(0), find(h, b""));
+            assert_eq!(Some(0), Finder::new("").find(h));
+            let expected: Vec<usize> = (0..=h.len()).collect();
+            assert_eq!(expected, find_iter(h, b"").collect::<Vec<usize>>());
+            let mut rev: Vec<usize> = rfind_iter(h, b"").collect();
+            rev.reverse();
+            assert_eq!(expected, rev);
+            // The standard library agrees, at least on valid UTF-8
+            // boundaries.
+            assert_eq!(haystack.find(""), find(h, b""));
+        }
+        let mut it = find_iter(b"", b"");
+        assert_eq!(Some(0), it.next());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn simd_threshold_does_not_change_matches() {
+        use crate::memmem::FinderBuilder;
+
+        for &threshold in &[0, 1, 5, 17, 64, usize::MAX] {
+            let mut b = FinderBuilder::new();
+            b.simd_threshold(threshold);
+            run_search_tests_fwd(|h, n| b.build_forward(n).find(h));
+            run_search_tests_rev(|h, n| b.build_reverse(n).rfind(h));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn rfind_iter_same_set_as_find_iter() {
+        use crate::memmem::{find_iter, rfind_iter};
+
+        let tests: &[(&str, &str)] = &[
+            ("foo bar foo baz foo", "foo"),
+            ("abcabcabc", "bc"),
+            ("xyz", "q"),
+            ("", "a"),
+            ("abcabcabc", "abcabcabc"),
+        ];
+        for &(haystack, needle) in tests {
+            let fwd: Vec<usize> =
+                find_iter(haystack.as_bytes(), needle).collect();
+            let mut rev: Vec<usize> =
+                rfind_iter(haystack.as_bytes(), needle).collect();
+            assert!(rev.windows(2).all(|w| w[0] > w[1]));
+            rev.sort();
+            assert_eq!(fwd, rev, "haystack: {:?}", haystack);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn rfind_iter_self_overlapping_needle() {
+        use crate::memmem::{find_iter, rfind_iter};
+
+        // A needle that overlaps with itself can produce different matches.
+        assert_eq!(vec![0], find_iter(b"aaa", "aa").collect::<Vec<_>>());
+        assert_eq!(vec![1], rfind_iter(b"aaa", "aa").collect::<Vec<_>>());
+        assert_eq!(vec![0, 2], find_iter(b"aaaa", "aa").collect::<Vec<_>>());
+        assert_eq!(vec![2, 0], rfind_iter(b"aaaa", "aa").collect::<Vec<_>>());
+        assert_eq!(vec![0, 2], find_iter(b"aaaaa", "aa").collect::<Vec<_>>());
+        assert_eq!(vec![3, 1], rfind_iter(b"aaaaa", "aa").collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn count_free_functions() {
+        use crate::memmem::{count, count_overlapping, Finder};
+
+        assert_eq!(2, count(b"aaaa", b"aa"));
+        assert_eq!(3, count_overlapping(b"aaaa", b"aa"));
+        assert_eq!(0, count(b"aaaa", b"b"));
+        assert_eq!(0, count_overlapping(b"aaaa", b"b"));
+        assert_eq!(0, count_overlapping(b"a", b"aa"));
+
+        let (haystack, needle) = (b"abababa", b"aba");
+        let finder = Finder::new(needle);
+        assert_eq!(finder.count(haystack), count(haystack, needle));
+        assert_eq!(
+            finder.find_overlapping_iter(haystack).count(),
+            count_overlapping(haystack, needle)
+        );
+        assert_eq!(3, count_overlapping(haystack, needle));
+        assert_eq!(
+            Finder::new("").find_overlapping_iter(b"abc").count(),
+            count_overlapping(b"abc", b"")
+        );
+        assert_eq!(4, count(b"abc", b""));
+    }
+
+    #[test]
+    fn custom_heuristic_used_everywhere() {
+        use crate::memmem::{
+            FinderBuilder, HeuristicFrequencyRank, Prefilter, PrefilterKind,
+        };
+
+        /// A heuristic where a space, which is one of the most common bytes
+        /// by default, is the rarest byte, and 'w' is the second rarest.
+        struct SpaceIsRare;
+
+        impl HeuristicFrequencyRank for SpaceIsRare {
+            fn rank(&self, byte: u8) -> u8 {
+                match byte {
+                    b' ' => 0,
+                    b'w' | b'W' => 1,
+                    _ => 200,
+                }
+            }
+        }
+
+        let needle = "hello world";
+        let b = FinderBuilder::new();
+        let default = b.build_forward(needle).prefilter_info();
+        assert_ne!(5, default.rare_offsets().0);
+
+        let infos = [
+            b.build_heuristic(needle, SpaceIsRare).prefilter_info(),
+            b.build_heuristic_reverse(needle, SpaceIsRare).prefilter_info(),
+            b.build_heuristic_ascii_ignore_case(needle, SpaceIsRare)
+                .prefilter_info(),
+        ];
+        for info in infos.iter() {
+            assert_eq!((5, 6), info.rare_offsets());
+        }
+
+        let finder = b.build_heuristic_ascii_ignore_case(needle, SpaceIsRare);
+        assert_eq!(Some(4), finder.find(b"say HELLO World"));
+
+        /// A heuristic where every byte is too common for the memchr based
+        /// prefilter to be worth using.
+        struct AllCommon;
+
+        impl HeuristicFrequencyRank for AllCommon {
+            fn rank(&self, _: u8) -> u8 {
+                255
+            }
+        }
+
+        // Given a heuristic and the default `Prefilter::Auto`, the reverse
+        // searcher uses the memchr based prefilter whenever the heuristic
+        // says the needle has a rare enough byte.
+        let info =
+            b.build_heuristic_reverse(needle, SpaceIsRare).prefilter_info();
+        assert_eq!(PrefilterKind::Memchr, info.kind());
+        let info =
+            b.build_heuristic_reverse(needle, AllCommon).prefilter_info();
+        assert_eq!(PrefilterKind::None, info.kind());
+        let mut none = FinderBuilder::new();
+        none.prefilter(Prefilter::None);
+        let info =
+            none.build_heuristic_reverse(needle, SpaceIsRare).prefilter_info();
+        assert_eq!(PrefilterKind::None, info.kind());
+        // Without a heuristic, reverse searches never use a prefilter.
+        let info = b.build_reverse(needle).prefilter_info();
+        assert_eq!(PrefilterKind::None, info.kind());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_ranges_iter() {
+        use crate::memmem::{Finder, FinderBuilder};
+
+        let ranges = |finder: &Finder<'_>, haystack: &[u8]| {
+            finder.find_ranges_iter(haystack).collect::<Vec<_>>()
+        };
+        assert_eq!(vec![0..2, 2..4], ranges(&Finder::new("aa"), b"aaaaa"));
+        assert_eq!(vec![1..4], ranges(&Finder::new("bcd"), b"abcde"));
+        assert_eq!(vec![0..0, 1..1], ranges(&Finder::new(""), b"a"));
+        assert!(ranges(&Finder::new("z"), b"abc").is_empty());
+
+        let finder = FinderBuilder::new().build_forward_ascii_ignore_case("B");
+        assert_eq!(vec![1..2, 3..4], ranges(&finder, b"abcB"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn into_owned() {
+        use crate::memmem::{Finder, FinderRev};
+
+        let haystack = b"xxabcxxabcxx";
+        let (finder, finder_rev) = {
+            let needle = String::from("abc");
+            let finder = Finder::new(&needle).into_owned();
+            let finder_rev = FinderRev::new(&needle).into_owned();
+            (finder, finder_rev)
+        };
+        assert_eq!(b"abc", finder.needle());
+        assert_eq!(Some(2), finder.find(haystack));
+        assert_eq!(vec![2, 7], finder.find_iter(haystack).collect::<Vec<_>>());
+        assert_eq!(Some(7), finder_rev.rfind(haystack));
+
+        let borrowed = Finder::new("abc");
+        let it = borrowed.find_iter(haystack).into_owned();
+        drop(borrowed);
+        assert_eq!(vec![2, 7], it.collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn debug() {
+        use crate::memmem::{FinderBuilder, FinderRev, Prefilter};
+
+        let mut builder = FinderBuilder::new();
+        builder.prefilter(Prefilter::None);
+        assert_eq!(
+            r#"Finder { needle: "foo", prefilter: None }"#,
+            format!("{:?}", builder.build_forward("foo")),
+        );
+        assert_eq!(
+            r#"Finder { needle: "a\"b", prefilter: None }"#,
+            format!("{:?}", builder.build_forward("a\"b")),
+        );
+        assert_eq!(
+            r#"Finder { needle: b"\xffa\"\n", prefilter: None }"#,
+            format!("{:?}", builder.build_forward(b"\xffa\"\n")),
+        );
+        assert_eq!(
+            r#"FinderRev { needle: "z", prefilter: None }"#,
+            format!("{:?}", FinderRev::new("z")),
+        );
+        assert_eq!(
+            r#"FinderRev { needle: "zq", prefilter: Memchr }"#,
+            format!(
+                "{:?}",
+                FinderBuilder::new()
+                    .build_heuristic_reverse("zq", crate::memmem::freq::Text)
+            ),
+        );
+    }
+
+    #[test]
+    fn count() {
+        use crate::memmem::Finder;
+
+        assert_eq!(3, Finder::new("foo").count(b"foo bar foo baz foo"));
+        assert_eq!(2, Finder::new("aa").count(b"aaaa"));
+        assert_eq!(2, Finder::new("aa").count(b"aaaaa"));
+        assert_eq!(0, Finder::new("a").count(b""));
+        assert_eq!(1, Finder::new("").count(b""));
+        assert_eq!(4, Finder::new("").count(b"abc"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_overlapping_iter() {
+        use crate::memmem::Finder;
+
+        let find = |haystack: &[u8], needle: &[u8]| -> Vec<usize> {
+            Finder::new(needle).find_overlapping_iter(haystack).collect()
+        };
+        assert_eq!(vec![0, 1, 2], find(b"aaaa", b"aa"));
+        assert_eq!(vec![0, 2, 4], find(b"abababa", b"aba"));
+        assert_eq!(vec![1, 4], find(b"xabxab", b"ab"));
+        assert_eq!(vec![0, 1, 2], find(b"ab", b""));
+        assert_eq!(Vec::<usize>::new(), find(b"a", b"aa"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn replace_all() {
+        use crate::memmem::{replace_all, Finder};
+
+        assert_eq!(
+            b"quux bar quux",
+            &*replace_all(b"foo bar foo", b"foo", b"quux")
+        );
+        assert_eq!(b"bb", &*replace_all(b"aaaa", b"aa", b"b"));
+        assert_eq!(b"bba", &*replace_all(b"aaaaa", b"aa", b"b"));
+        assert_eq!(b"xaxaxa", &*replace_all(b"aaa", b"a", b"xa"));
+        assert_eq!(b"a-c", &*replace_all(b"abc", b"b", b"-"));
+        assert_eq!(b"ac", &*replace_all(b"abc", b"b", b""));
+        assert_eq!(b"abc", &*replace_all(b"abc", b"", b"-"));
+        assert_eq!(b"abc", &*replace_all(b"abc", b"z", b"-"));
+        assert_eq!(b"", &*replace_all(b"", b"a", b"-"));
+
+        let finder = Finder::new("\r\n");
+        assert_eq!(b"a\nb\n", &*finder.replace_all(b"a\r\nb\r\n", b"\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn replace_all_into() {
+        use crate::memmem::Finder;
+
+        let finder = Finder::new("foo");
+        let mut out = Vec::with_capacity(100);
+        out.extend_from_slice(b"stale contents");
+        let ptr = out.as_ptr();
+        finder.replace_all_into(b"foo bar foo", b"quux", &mut out);
+        assert_eq!(b"quux bar quux", &*out);
+        finder.replace_all_into(b"bar", b"quux", &mut out);
+        assert_eq!(b"bar", &*out);
+        finder.replace_all_into(b"", b"quux", &mut out);
+        assert_eq!(b"", &*out);
+        // The buffer's existing allocation is reused.
+        assert_eq!(ptr, out.as_ptr());
+        assert_eq!(100, out.capacity());
+
+        // An empty needle copies the haystack, but still clears the buffer.
+        out.extend_from_slice(b"stale");
+        Finder::new("").replace_all_into(b"abc", b"-", &mut out);
+        assert_eq!(b"abc", &*out);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn replace_all_cow() {
+        use std::borrow::Cow;
+
+        use crate::memmem::{replace_all_cow, Finder};
+
+        let haystack = &b"abc"[..];
+        let got = replace_all_cow(haystack, b"z", b"-");
+        assert!(matches!(got, Cow::Borrowed(_)));
+        assert_eq!(haystack.as_ptr(), got.as_ptr());
+        assert!(matches!(replace_all_cow(b"", b"a", b"-"), Cow::Borrowed(_)));
+        assert!(matches!(
+            replace_all_cow(b"abc", b"", b"-"),
+            Cow::Borrowed(_)
+        ));
+
+        let got = replace_all_cow(b"abcabc", b"b", b"-");
+        assert!(matches!(got, Cow::Owned(_)));
+        assert_eq!(b"a-ca-c", &*got);
+        // A replacement equal to the needle still counts as a change.
+        assert!(matches!(replace_all_cow(b"abc", b"b", b"b"), Cow::Owned(_)));
+        assert_eq!(b"bba", &*replace_all_cow(b"aaaaa", b"aa", b"b"));
+
+        let finder = Finder::new("\r\n");
+        assert_eq!(b"a\nb\n", &*finder.replace_all_cow(b"a\r\nb\r\n", b"\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn replace_all_with() {
+        use crate::memmem::Finder;
+
+        let mut offsets = vec![];
+        let replaced =
+            Finder::new("ab").replace_all_with(b"abxabababyab", |i, out| {
+                offsets.push(i);
+                out.extend_from_slice(i.to_string().as_bytes());
+            });
+        assert_eq!(vec![0, 3, 5, 7, 10], offsets);
+        assert_eq!(b"0x357y10", &*replaced);
+
+        let mut called = false;
+        let replaced =
+            Finder::new("").replace_all_with(b"abc", |_, _| called = true);
+        assert!(!called);
+        assert_eq!(b"abc", &*replaced);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn split() {
+        use crate::memmem::split;
+
+        let split = |haystack: &'static [u8], delimiter: &'static [u8]| {
+            split(haystack, delimiter).collect::<Vec<&[u8]>>()
+        };
+        let empty: &[u8] = b"";
+        assert_eq!(vec![&b"a"[..], b"b", b""], split(b"a\r\nb\r\n", b"\r\n"));
+        assert_eq!(vec![empty, b"a", b"b"], split(b"\r\na\r\nb", b"\r\n"));
+        assert_eq!(vec![&b"a"[..], b"", b"b"], split(b"a,,b", b","));
+        assert_eq!(vec![&b"abc"[..]], split(b"abc", b"z"));
+        assert_eq!(vec![empty], split(b"", b"z"));
+        assert_eq!(vec![empty, b""], split(b"aa", b"aa"));
+        assert_eq!(vec![empty, b"", b"a"], split(b"aaaaa", b"aa"));
+        assert_eq!(vec![empty, b"a", b"b", b""], split(b"ab", b""));
+        assert_eq!(vec![empty, b""], split(b"", b""));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn rsplit() {
+        use crate::memmem::{rsplit, Finder};
+
+        let rsplit = |haystack: &'static str, delimiter: &'static str| {
+            let expected: Vec<&[u8]> =
+                haystack.rsplit(delimiter).map(|s| s.as_bytes()).collect();
+            let got: Vec<&[u8]> =
+                rsplit(haystack.as_bytes(), delimiter).collect();
+            assert_eq!(expected, got);
+            got
+        };
+        let empty: &[u8] = b"";
+        assert_eq!(vec![empty, b"b", b"a"], rsplit("a\r\nb\r\n", "\r\n"));
+        assert_eq!(vec![&b"c"[..], b"b", b"a", b""], rsplit("/a/b/c", "/"));
+        assert_eq!(vec![empty, b"", b"a"], rsplit("aaaaa", "aa"));
+        assert_eq!(vec![&b"abc"[..]], rsplit("abc", "z"));
+        assert_eq!(vec![empty], rsplit("", "z"));
+        assert_eq!(vec![empty, b"b", b"a", b""], rsplit("ab", ""));
+
+        let finder = Finder::new("/");
+        let got: Vec<&[u8]> = finder.rsplit(b"a/b/").collect();
+        assert_eq!(vec![empty, b"b", b"a"], got);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn rsplit_ascii_ignore_case() {
+        use crate::memmem::FinderBuilder;
+
+        let haystack = b"fooANDbarandbazAnDquux";
+        let finder =
+            FinderBuilder::new().build_forward_ascii_ignore_case("and");
+        let fwd: Vec<&[u8]> = finder.split(haystack).collect();
+        let mut rev: Vec<&[u8]> = finder.rsplit(haystack).collect();
+        rev.reverse();
+        assert_eq!(vec![&b"foo"[..], b"bar", b"baz", b"quux"], fwd);
+        assert_eq!(fwd, rev);
+
+        let rev: Vec<&[u8]> = finder.rsplitn(2, haystack).collect();
+        assert_eq!(vec![&b"quux"[..], b"fooANDbarandbaz"], rev);
+
+        let finder = FinderBuilder::new().build_forward_ascii_ignore_case("X");
+        let rev: Vec<&[u8]> = finder.rsplit(b"axbXc").collect();
+        assert_eq!(vec![&b"c"[..], b"b", b"a"], rev);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn splitn() {
+        use crate::memmem::{rsplitn, splitn};
+
+        let splitn = |limit: usize, haystack: &'static [u8]| {
+            splitn(limit, haystack, b"=").collect::<Vec<&[u8]>>()
+        };
+        let rsplitn = |limit: usize, haystack: &'static [u8]| {
+            rsplitn(limit, haystack, b"=").collect::<Vec<&[u8]>>()
+        };
+        let empty: &[u8] = b"";
+        assert_eq!(vec![&b"a"[..], b"b=c"], splitn(2, b"a=b=c"));
+        assert_eq!(vec![&b"a"[..], b"b", b"c"], splitn(3, b"a=b=c"));
+        assert_eq!(vec![&b"a"[..], b"b", b"c"], splitn(4, b"a=b=c"));
+        assert_eq!(vec![&b"a=b=c"[..]], splitn(1, b"a=b=c"));
+        assert_eq!(Vec::<&[u8]>::new(), splitn(0, b"a=b=c"));
+        assert_eq!(vec![empty, b"=b="], splitn(2, b"==b="));
+        assert_eq!(vec![empty], splitn(2, b""));
+        assert_eq!(vec![&b"c"[..], b"a=b"], rsplitn(2, b"a=b=c"));
+        assert_eq!(vec![empty, b"==b"], rsplitn(2, b"==b="));
+        assert_eq!(Vec::<&[u8]>::new(), rsplitn(0, b"a=b=c"));
+    }
+
+    #[test]
+    fn split_once() {
+        use crate::memmem::{rsplit_once, split_once};
+
+        let empty: &[u8] = b"";
+        assert_eq!(Some((&b"a"[..], &b"b=c"[..])), split_once(b"a=b=c", "="));
+        assert_eq!(Some((&b"a=b"[..], &b"c"[..])), rsplit_once(b"a=b=c", "="));
+        assert_eq!(None, split_once(b"abc", "="));
+        assert_eq!(None, rsplit_once(b"abc", "="));
+        assert_eq!(None, split_once(b"", "="));
+        // A delimiter at either end leaves an empty side.
+        assert_eq!(Some((&b"key"[..], empty)), split_once(b"key=", "="));
+        assert_eq!(Some((&b"key"[..], empty)), rsplit_once(b"key=", "="));
+        assert_eq!(Some((empty, &b"v"[..])), split_once(b"=v", "="));
+        assert_eq!(Some((empty, empty)), split_once(b"\r\n", "\r\n"));
+        // Overlapping occurrences.
+        assert_eq!(Some((empty, &b"a"[..])), split_once(b"aaa", "aa"));
+        assert_eq!(Some((&b"a"[..], empty)), rsplit_once(b"aaa", "aa"));
+        // An empty delimiter matches at the start (or end) of the haystack.
+        assert_eq!(Some((empty, &b"ab"[..])), split_once(b"ab", ""));
+        assert_eq!(Some((&b"ab"[..], empty)), rsplit_once(b"ab", ""));
+    }
+
+    #[test]
+    fn find_from() {
+        use crate::memmem::Finder;
+
+        let finder = Finder::new("abc");
+        let haystack = b"xabcxabc";
+        assert_eq!(Some(1), finder.find_from(haystack, 0));
+        assert_eq!(Some(1), finder.find_from(haystack, 1));
+        // A cursor inside an earlier occurrence skips it.
+        assert_eq!(Some(5), finder.find_from(haystack, 2));
+        assert_eq!(Some(5), finder.find_from(haystack, 3));
+        assert_eq!(Some(5), finder.find_from(haystack, 5));
+        assert_eq!(None, finder.find_from(haystack, 6));
+        assert_eq!(None, finder.find_from(haystack, haystack.len()));
+        // A cursor past the end.
+        assert_eq!(None, finder.find_from(haystack, haystack.len() + 1));
+        assert_eq!(None, finder.find_from(haystack, usize::MAX));
+
+        // The empty needle matches at the cursor, up to and including the
+        // end of the haystack.
+        let finder = Finder::new("");
+        assert_eq!(Some(3), finder.find_from(b"abcd", 3));
+        assert_eq!(Some(4), finder.find_from(b"abcd", 4));
+        assert_eq!(None, finder.find_from(b"abcd", 5));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_large_haystack() {
+        use crate::memmem::{find, Finder, FinderRev};
+
+        // Vectorized searchers use a separate loop that prefetches ahead for
+        // haystacks of at least 64KB, so check matches throughout one,
+        // including at the very end where the prefetch address is past the
+        // end of the haystack. Both a short needle and one long enough to
+        // use a prefilter are tried.
+        let len = (1 << 16) + 100;
+        let long = "0123456789abcdefghijklmnopqrstuvwxyz";
+        for &needle in &["xyz", long] {
+            let finder = Finder::new(needle);
+            let mut haystack = vec![b'a'; len];
+            assert_eq!(None, finder.find(&haystack));
+            for &at in &[0, 1000, len / 2, len - needle.len()] {
+                let mut haystack = haystack.clone();
+                haystack[at..at + needle.len()]
+                    .copy_from_slice(needle.as_bytes());
+                assert_eq!(Some(at), finder.find(&haystack));
+                assert_eq!(Some(at), find(&haystack, needle.as_bytes()));
+                assert_eq!(Some(at), FinderRev::new(needle).rfind(&haystack));
+            }
+            // Candidates that fail verification keep the search going.
+            haystack[len / 3] = needle.as_bytes()[0];
+            haystack[len - 1] = needle.as_bytes()[needle.len() - 1];
+            assert_eq!(None, finder.find(&haystack));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_iter_profiled() {
+        use crate::memmem::{Finder, FinderBuilder, Prefilter};
+
+        // Without a prefilter, every position outside of a match is a
+        // candidate, except for the last two. The needle can't fit there, so
+        // the search stops before them.
+        let haystack = b"zzzzfoozzzfoozz";
+        let finder = FinderBuilder::new()
+            .prefilter(Prefilter::None)
+            .build_forward("foo");
+        let (matches, stats) = finder.find_iter_profiled(haystack);
+        assert_eq!(vec![4, 10], matches);
+        assert_eq!(9, stats.candidates());
+        assert_eq!(9, stats.verifications());
+        assert_eq!(2, stats.matches());
+        assert_eq!(7, stats.false_positives());
+
+        // The empty needle matches everywhere, and each match is its own
+        // candidate.
+        let (matches, stats) = Finder::new("").find_iter_profiled(b"ab");
+        assert_eq!(vec![0, 1, 2], matches);
+        assert_eq!(3, stats.candidates());
+        assert_eq!(0, stats.false_positives());
+
+        let (matches, stats) = Finder::new("foo").find_iter_profiled(b"");
+        assert!(matches.is_empty());
+        assert_eq!(0, stats.candidates());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_n() {
+        use crate::memmem::find_n;
+
+        assert_eq!(Some(3), find_n(b"foo\r\nbar\r\n", b"\r\n"));
+        assert_eq!(Some(0), find_n(b"\r\n", b"\r\n"));
+        assert_eq!(None, find_n(b"\r", b"\r\n"));
+        assert_eq!(None, find_n(b"", b"\r\n"));
+        assert_eq!(Some(0), find_n(b"abc", b""));
+        assert_eq!(Some(2), find_n(b"abc", b"c"));
+        assert_eq!(Some(1), find_n(b"xabcd", b"abcd"));
+
+        // Haystacks on either side of the length where the search switches
+        // to the general purpose searcher.
+        for len in 60..70 {
+            let mut haystack = vec![b'a'; len];
+            assert_eq!(None, find_n(&haystack, b"ab"));
+            haystack[len - 1] = b'b';
+            assert_eq!(Some(len - 2), find_n(&haystack, b"ab"));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_segments() {
+        use crate::memmem::find_segments;
+
+        // The needle straddles three segments.
+        let segments: &[&[u8]] = &[b"xxab", b"cd", b"efyy"];
+        assert_eq!(Some((0, 2)), find_segments(segments, b"abcdef"));
+        assert_eq!(Some((0, 3)), find_segments(segments, b"bcde"));
+        assert_eq!(Some((1, 1)), find_segments(segments, b"def"));
+        assert_eq!(Some((2, 2)), find_segments(segments, b"yy"));
+        assert_eq!(None, find_segments(segments, b"abd"));
+
+        // Empty segments are skipped over.
+        let segments: &[&[u8]] = &[b"", b"a", b"", b"b", b""];
+        assert_eq!(Some((1, 0)), find_segments(segments, b"ab"));
+        assert_eq!(Some((3, 0)), find_segments(segments, b"b"));
+
+        assert_eq!(None, find_segments(&[], b"a"));
+        assert_eq!(None, find_segments(&[b"abc"], b""));
+    }
+
+    #[test]
+    fn find_within() {
+        use crate::memmem::{FindWithin, Finder};
+
+        let finder = Finder::new("abc");
+        let haystack = b"xxxxxabcxx";
+        // The occurrence starts just before the budget runs out, and ends
+        // past it.
+        assert_eq!(FindWithin::Found(5), finder.find_within(haystack, 6));
+        assert_eq!(
+            FindWithin::BudgetExhausted,
+            finder.find_within(haystack, 5)
+        );
+        assert_eq!(
+            FindWithin::BudgetExhausted,
+            finder.find_within(haystack, 0)
+        );
+        assert_eq!(FindWithin::Found(5), finder.find_within(haystack, 100));
+        assert_eq!(
+            FindWithin::Found(5),
+            finder.find_within(haystack, usize::MAX)
+        );
+
+        // Once the budget covers every position where the needle could
+        // start, a missing needle is reported as not found.
+        let haystack = b"xxxxxabx";
+        assert_eq!(FindWithin::NotFound, finder.find_within(haystack, 6));
+        assert_eq!(
+            FindWithin::BudgetExhausted,
+            finder.find_within(haystack, 5)
+        );
+        assert_eq!(FindWithin::NotFound, finder.find_within(b"ab", 0));
+        assert_eq!(FindWithin::NotFound, finder.find_within(b"", 0));
+
+        let finder = Finder::new("");
+        assert_eq!(FindWithin::Found(0), finder.find_within(b"abc", 0));
+        assert_eq!(FindWithin::Found(0), finder.find_within(b"", 0));
+    }
+
+    #[test]
+    fn find_in_suffix() {
+        use crate::memmem::Finder;
+
+        let finder = Finder::new("abc");
+        let haystack = b"abcxxabcxab";
+        assert_eq!(Some(5), finder.find_in_suffix(haystack, 6));
+        // An occurrence that starts before the suffix is not found, even
+        // though it ends inside it.
+        assert_eq!(None, finder.find_in_suffix(haystack, 5));
+        assert_eq!(None, finder.find_in_suffix(haystack, 0));
+
+        // A suffix longer than the haystack searches the whole haystack.
+        assert_eq!(Some(0), finder.find_in_suffix(haystack, 11));
+        assert_eq!(Some(0), finder.find_in_suffix(haystack, 12));
+        assert_eq!(Some(0), finder.find_in_suffix(haystack, usize::MAX));
+        assert_eq!(None, finder.find_in_suffix(b"", 10));
+
+        // The empty needle matches at the start of the suffix.
+        let finder = Finder::new("");
+        assert_eq!(Some(8), finder.find_in_suffix(haystack, 3));
+        assert_eq!(Some(11), finder.find_in_suffix(haystack, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn find_iter_with_gaps() {
+        use crate::memmem::{Finder, Segment};
+
+        let finder = Finder::new("--");
+        let got: Vec<Segment> =
+            finder.find_iter_with_gaps(b"--a----b").collect();
+        assert_eq!(
+            got,
+            vec![
+                Segment::Gap(b""),
+                Segment::Match(0),
+                Segment::Gap(b"a"),
+                Segment::Match(3),
+                Segment::Gap(b""),
+                Segment::Match(5),
+                Segment::Gap(b"b"),
+            ],
+        );
+
+        // Without any occurrences, the whole haystack is one gap.
+        let got: Vec<Segment> = finder.find_iter_with_gaps(b"a-b").collect();
+        assert_eq!(got, vec![Segment::Gap(b"a-b")]);
+        let got: Vec<Segment> = finder.find_iter_with_gaps(b"").collect();
+        assert_eq!(got, vec![Segment::Gap(b"")]);
+
+        // Concatenating the gaps and the needle at each match reconstructs
+        // the haystack.
+        let haystack = b"a--b--c-d--";
+        let mut rebuilt = vec![];
+        for segment in finder.find_iter_with_gaps(haystack) {
+            match segment {
+                Segment::Gap(gap) => rebuilt.extend_from_slice(gap),
+                Segment::Match(i) => {
+                    assert_eq!(rebuilt.len(), i);
+                    rebuilt.extend_from_slice(finder.needle());
+                }
+            }
+        }
+        assert_eq!(&rebuilt[..], &haystack[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn match_gaps() {
+        use crate::memmem::Finder;
+
+        // Fields of 5 bytes, each followed by a 2 byte delimiter.
+        let finder = Finder::new("\r\n");
+        let haystack = "abcde\r\n".repeat(100);
+        let gaps: Vec<(usize, usize)> =
+            finder.match_gaps(haystack.as_bytes()).collect();
+        assert_eq!(99, gaps.len());
+        assert!(gaps.iter().all(|&(start, next)| next - start == 7));
+        assert_eq!((5, 12), gaps[0]);
+
+        // Fewer than two occurrences yield no pairs.
+        assert_eq!(None, finder.match_gaps(b"ab\r\ncd").next());
+        assert_eq!(None, finder.match_gaps(b"").next());
+        let got: Vec<(usize, usize)> =
+            finder.match_gaps(b"\r\n\r\n").collect();
+        assert_eq!(vec![(0, 2)], got);
+    }
+
+    #[test]
+    fn find_aligned() {
+        use crate::memmem::Finder;
+
+        // Records of 4 bytes, where the needle is exactly one record long.
+        let finder = Finder::new("ABCD");
+        let haystack = b"xxABCDxxABCDABCD";
+        assert_eq!(Some(2), finder.find(haystack));
+        assert_eq!(Some(8), finder.find_aligned(haystack, 4));
+        assert_eq!(Some(8), finder.find_aligned(haystack, 8));
+        assert_eq!(Some(12), finder.find_aligned(haystack, 3));
+        assert_eq!(None, finder.find_aligned(haystack, 5));
+        assert_eq!(None, finder.find_aligned(&haystack[..11], 4));
+        // No constraint.
+        assert_eq!(Some(2), finder.find_aligned(haystack, 0));
+        assert_eq!(Some(2), finder.find_aligned(haystack, 1));
+        assert_eq!(None, finder.find_aligned(b"", 4));
+
+        // An aligned occurrence that overlaps with a skipped one.
+        let finder = Finder::new("aa");
+        assert_eq!(Some(2), finder.find_aligned(b"xaaa", 2));
+        assert_eq!(Some(4), finder.find_aligned(b"xaaaaa", 4));
+        assert_eq!(None, finder.find_aligned(b"xaaa", 4));
+
+        // The empty needle matches at every aligned offset, including the
+        // end of the haystack.
+        let finder = Finder::new("");
+        assert_eq!(Some(0), finder.find_aligned(b"abc", 4));
+        assert_eq!(Some(0), finder.find_aligned(b"", 4));
+        assert_eq!(None, Finder::new("b").find_aligned(b"ab", usize::MAX));
+    }
+
+    #[test]
+    fn find_nth() {
+        use crate::memmem::Finder;
+
+        let finder = Finder::new("aa");
+        assert_eq!(Some(0), finder.find_nth(0, b"aaaaa"));
+        assert_eq!(Some(2), finder.find_nth(1, b"aaaaa"));
+        assert_eq!(None, finder.find_nth(2, b"aaaaa"));
+        assert_eq!(Some(1), finder.find_nth_overlapping(1, b"aaaaa"));
+        assert_eq!(Some(3), finder.find_nth_overlapping(3, b"aaaaa"));
+        assert_eq!(None, finder.find_nth_overlapping(4, b"aaaaa"));
+
+        let finder = Finder::new("");
+        assert_eq!(Some(3), finder.find_nth(3, b"abc"));
+        assert_eq!(None, finder.find_nth(4, b"abc"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn finder_config() {
+        use crate::memmem::{
+            FinderConfig, FrequencyTable, Prefilter, PrefilterKind,
+        };
+
+        let mut config = FinderConfig::new("abc");
+        config.prefilter(Prefilter::None).frequencies(FrequencyTable::Default);
+        assert_eq!(b"abc", config.needle());
+        let finder = config.build_forward();
+        assert_eq!(PrefilterKind::None, finder.prefilter_info().kind());
+        assert_eq!(Some(3), finder.find(b"xyzabcabc"));
+        assert_eq!(Some(6), config.build_reverse().rfind(b"xyzabcabc"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "serde"))]
+    fn finder_config_serde_roundtrip() {
+        use crate::memmem::{FinderConfig, Prefilter};
+        use serde_test::{assert_tokens, Token};
+
+        let mut config = FinderConfig::new(b"a\xFFz");
+        config.prefilter(Prefilter::None);
+        assert_tokens(
+            &config,
+            &[
+                Token::Struct { name: "FinderConfig", len: 3 },
+                Token::Str("needle"),
+                Token::Seq { len: Some(3) },
+                Token::U8(b'a'),
+                Token::U8(0xFF),
+                Token::U8(b'z'),
+                Token::SeqEnd,
+                Token::Str("prefilter"),
+                Token::UnitVariant { name: "Prefilter", variant: "None" },
+                Token::Str("frequencies"),
+                Token::UnitVariant {
+                    name: "FrequencyTable",
+                    variant: "Default",
+                },
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn ascii_ignore_case() {
+        use crate::memmem::find_ascii_ignore_case as find;
+
+        assert_eq!(Some(4), find(b"Foo BAR", b"bar"));
+        assert_eq!(Some(0), find(b"Foo BAR", b"fOO"));
+        assert_eq!(Some(0), find(b"Foo BAR", b""));
+        assert_eq!(Some(1), find(b"Foo BAR", b"O"));
+        assert_eq!(None, find(b"Foo BAR", b"foo  bar"));
+        assert_eq!(
+            Some(25),
+            find(b"zzzzzzzzzzzzzzzzzzzzzzzzzFooBarBAZ", b"foobarbaz")
+        );
+        // Non-ASCII bytes must match exactly, so 0xDF (the Latin-1 sharp s,
+        // which folds to "ss" under Unicode rules) matches nothing but itself.
+        assert_eq!(None, find(b"s S \xFF \xDE", b"\xDF"));
+        assert_eq!(None, find(b"ss SS \xFF\xFF", b"\xDF\xDF"));
+        assert_eq!(Some(3), find(b"ss \xDF\xDF", b"\xDF\xDF"));
+        assert_eq!(Some(2), find(b"a\xFFA\xDF", b"a\xDF"));
+    }
+
     /// Run the substring search tests. `search` should be a closure that
     /// accepts a haystack and a needle and returns the starting position
     /// of the first occurrence of needle in the haystack, or `None` if one
@@ -35,6 +35,8 @@ use crate::memmem::{
 
 // Check that the functions below satisfy the Prefilter function type.
 const _: PrefilterFnTy = find;
+const _: PrefilterFnTy = find_ascii_ignore_case;
+const _: PrefilterFnTy = rfind;
 
 /// Look for a possible occurrence of needle. The position returned
 /// corresponds to the beginning of the occurrence, if one exists.
@@ -88,6 +90,101 @@ pub(crate) fn find(
     Some(i.saturating_sub(rare1i))
 }
 
+/// Look for a possible occurrence of needle, starting from the end of the
+/// haystack. The position returned corresponds to the end of the occurrence,
+/// if one exists.
+///
+/// Callers may assume that this never returns false negatives (i.e., it
+/// never misses an actual occurrence), but must check that the returned
+/// position corresponds to a match. That is, it can return false
+/// positives.
+///
+/// This should only be used with a `NeedleInfo` constructed for reverse
+/// searching, since it relies on the rare byte offsets being the rightmost
+/// ones.
+pub(crate) fn rfind(
+    prestate: &mut PrefilterState,
+    ninfo: &NeedleInfo,
+    haystack: &[u8],
+    needle: &[u8],
+) -> Option<usize> {
+    let mut end = haystack.len();
+    let (rare1i, rare2i) = ninfo.rarebytes.as_rare_usize();
+    let (rare1, rare2) = ninfo.rarebytes.as_rare_bytes(needle);
+    while prestate.is_effective() {
+        // Use a fast vectorized implementation to skip to the previous
+        // occurrence of the rarest byte (heuristically chosen) in the
+        // needle.
+        let found = crate::memrchr(rare1, &haystack[..end])?;
+        prestate.update(end - found - 1);
+        end = found;
+
+        // If we can't align our match with the haystack, then a match is
+        // impossible.
+        if found < rare1i || found - rare1i + needle.len() > haystack.len() {
+            continue;
+        }
+
+        // Align our rare2 byte with the haystack. A mismatch means that
+        // a match is impossible.
+        let aligned_rare2i = found - rare1i + rare2i;
+        if haystack[aligned_rare2i] != rare2 {
+            continue;
+        }
+
+        // We've done what we can. There might be a match here.
+        return Some(found - rare1i + needle.len());
+    }
+    // The only way we get here is if we believe our skipping heuristic
+    // has become ineffective. We're allowed to return false positives,
+    // so return the end of the last position at which the rarest byte could
+    // still be aligned with a match.
+    Some(core::cmp::min(haystack.len(), end + needle.len() - rare1i))
+}
+
+/// Like `find`, but looks for a possible ASCII case insensitive occurrence
+/// of needle.
+///
+/// The rare bytes are searched for in both of their ASCII cases, and the
+/// candidate byte aligned with the second rare byte is folded before being
+/// compared with the guard. Non-ASCII bytes are always compared exactly.
+///
+/// This should only be used with a `NeedleInfo` whose rare bytes were
+/// detected with `RareNeedleBytes::forward_ascii_ignore_case`.
+pub(crate) fn find_ascii_ignore_case(
+    prestate: &mut PrefilterState,
+    ninfo: &NeedleInfo,
+    haystack: &[u8],
+    needle: &[u8],
+) -> Option<usize> {
+    let mut i = 0;
+    let (rare1i, rare2i) = ninfo.rarebytes.as_rare_usize();
+    let (rare1, rare2) = ninfo.rarebytes.as_rare_bytes(needle);
+    let (rare1lo, rare1up) =
+        (rare1.to_ascii_lowercase(), rare1.to_ascii_uppercase());
+    while prestate.is_effective() {
+        let found = crate::memchr2(rare1lo, rare1up, &haystack[i..])?;
+        prestate.update(found);
+        i += found;
+
+        if i < rare1i {
+            i += 1;
+            continue;
+        }
+
+        let aligned_rare2i = i - rare1i + rare2i;
+        match haystack.get(aligned_rare2i) {
+            Some(b) if b.eq_ignore_ascii_case(&rare2) => {}
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        return Some(i - rare1i);
+    }
+    Some(i.saturating_sub(rare1i))
+}
+
 #[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
@@ -110,6 +207,50 @@ mod tests {
         assert_eq!(None, freqy_find(b"yayb", b"yzyz"));
     }
 
+    fn freqy_find_ascii_ignore_case(
+        haystack: &[u8],
+        needle: &[u8],
+    ) -> Option<usize> {
+        let ninfo = NeedleInfo::new_ascii_ignore_case(
+            needle,
+            &crate::memmem::DefaultHFR,
+        );
+        let mut prestate = PrefilterState::new();
+        find_ascii_ignore_case(&mut prestate, &ninfo, haystack, needle)
+    }
+
+    #[test]
+    fn freqy_forward_ascii_ignore_case() {
+        assert_eq!(Some(0), freqy_find_ascii_ignore_case(b"barfoo", b"BAR"));
+        assert_eq!(Some(3), freqy_find_ascii_ignore_case(b"FOObAr", b"bar"));
+        assert_eq!(Some(2), freqy_find_ascii_ignore_case(b"zzZY", b"zyzy"));
+        assert_eq!(None, freqy_find_ascii_ignore_case(b"zAzB", b"zyzy"));
+        assert_eq!(
+            None,
+            freqy_find_ascii_ignore_case(b"\xFF\xFF", b"\xDF\xDF")
+        );
+    }
+
+    fn freqy_rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        let ninfo = NeedleInfo::reverse(needle, &crate::memmem::DefaultHFR);
+        let mut prestate = PrefilterState::new();
+        rfind(&mut prestate, &ninfo, haystack, needle)
+    }
+
+    #[test]
+    fn freqy_reverse() {
+        assert_eq!(Some(3), freqy_rfind(b"BARFOO", b"BAR"));
+        assert_eq!(Some(6), freqy_rfind(b"FOOBAR", b"BAR"));
+        assert_eq!(None, freqy_rfind(b"zyzz", b"zyzy"));
+        assert_eq!(Some(4), freqy_rfind(b"zzzy", b"zyzy"));
+        assert_eq!(None, freqy_rfind(b"zazb", b"zyzy"));
+        assert_eq!(Some(5), freqy_rfind(b"yyzyz", b"yzyz"));
+        assert_eq!(None, freqy_rfind(b"yayb", b"yzyz"));
+        // The last occurrence of the rarest byte can't be aligned with a
+        // match, but an earlier one can.
+        assert_eq!(Some(3), freqy_rfind(b"xzyz", b"xzy"));
+    }
+
     #[test]
     #[cfg(not(miri))]
     fn prefilter_permutations() {
@@ -2,7 +2,7 @@ use core::mem::size_of;
 
 use crate::memmem::{
     prefilter::{PrefilterFnTy, PrefilterState},
-    vector::Vector,
+    vector::{Vector, PREFETCH_DISTANCE, PREFETCH_MIN_HAYSTACK_LEN},
     NeedleInfo,
 };
 
@@ -54,14 +54,51 @@ pub(crate) unsafe fn find<V: Vector>(
     if haystack.len() < min_haystack_len {
         return fallback(prestate, ninfo, haystack, needle);
     }
+    let found = find_pair::<V>(
+        rare1i,
+        rare2i,
+        needle[rare1i],
+        needle[rare2i],
+        haystack,
+    );
+    prestate.update(found.unwrap_or(haystack.len()));
+    found
+}
+
+/// Find the first position `i` in the given haystack such that
+/// `haystack[i + rare1i] == rare1` and `haystack[i + rare2i] == rare2`.
+///
+/// This is the candidate search performed by `find`, without any prefilter
+/// state. It is also exposed on its own as the packed pair finders in the
+/// `arch` module.
+///
+/// # Panics
+///
+/// If `rare1i > rare2i` or if the haystack is shorter than
+/// `rare2i + size_of::<V>()`.
+///
+/// # Safety
+///
+/// Same as for `find`.
+#[inline(always)]
+pub(crate) unsafe fn find_pair<V: Vector>(
+    rare1i: usize,
+    rare2i: usize,
+    rare1: u8,
+    rare2: u8,
+    haystack: &[u8],
+) -> Option<usize> {
+    assert!(rare1i <= rare2i, "offsets must be ordered");
+    let min_haystack_len = rare2i + size_of::<V>();
+    assert!(haystack.len() >= min_haystack_len, "haystack too small");
 
     let start_ptr = haystack.as_ptr();
     let end_ptr = start_ptr.add(haystack.len());
     let max_ptr = end_ptr.sub(min_haystack_len);
     let mut ptr = start_ptr;
 
-    let rare1chunk = V::splat(needle[rare1i]);
-    let rare2chunk = V::splat(needle[rare2i]);
+    let rare1chunk = V::splat(rare1);
+    let rare2chunk = V::splat(rare2);
 
     // N.B. I did experiment with unrolling the loop to deal with size(V)
     // bytes at a time and 2*size(V) bytes at a time. The double unroll
@@ -69,12 +106,28 @@ pub(crate) unsafe fn find<V: Vector>(
     // slower. In the end, I decided the complexity from unrolling wasn't
     // worth it. I used the memmem/krate/prebuilt/huge-en/ benchmarks to
     // compare.
-    while ptr <= max_ptr {
-        let m = find_in_chunk2(ptr, rare1i, rare2i, rare1chunk, rare2chunk);
-        if let Some(chunki) = m {
-            return Some(matched(prestate, start_ptr, ptr, chunki));
+    //
+    // As in the parent module, the loop is duplicated so that only large
+    // haystacks pay for the prefetch.
+    if haystack.len() >= PREFETCH_MIN_HAYSTACK_LEN {
+        while ptr <= max_ptr {
+            V::prefetch(ptr.wrapping_add(PREFETCH_DISTANCE));
+            let m =
+                find_in_chunk2(ptr, rare1i, rare2i, rare1chunk, rare2chunk);
+            if let Some(chunki) = m {
+                return Some(matched(start_ptr, ptr, chunki));
+            }
+            ptr = ptr.add(size_of::<V>());
+        }
+    } else {
+        while ptr <= max_ptr {
+            let m =
+                find_in_chunk2(ptr, rare1i, rare2i, rare1chunk, rare2chunk);
+            if let Some(chunki) = m {
+                return Some(matched(start_ptr, ptr, chunki));
+            }
+            ptr = ptr.add(size_of::<V>());
         }
-        ptr = ptr.add(size_of::<V>());
     }
     if ptr < end_ptr {
         // This routine immediately quits if a candidate match is found.
@@ -85,10 +138,9 @@ pub(crate) unsafe fn find<V: Vector>(
         ptr = max_ptr;
         let m = find_in_chunk2(ptr, rare1i, rare2i, rare1chunk, rare2chunk);
         if let Some(chunki) = m {
-            return Some(matched(prestate, start_ptr, ptr, chunki));
+            return Some(matched(start_ptr, ptr, chunki));
         }
     }
-    prestate.update(haystack.len());
     None
 }
 
@@ -173,8 +225,7 @@ unsafe fn find_in_chunk3<V: Vector>(
     Some(match_offsets.trailing_zeros() as usize)
 }
 
-/// Accepts a chunk-relative offset and returns a haystack relative offset
-/// after updating the prefilter state.
+/// Accepts a chunk-relative offset and returns a haystack relative offset.
 ///
 /// Why do we use this unlineable function when a search completes? Well,
 /// I don't know. Really. Obviously this function was not here initially.
@@ -188,15 +239,8 @@ unsafe fn find_in_chunk3<V: Vector>(
 /// across the board on the memmem/krate/prebuilt/huge-en/ benchmarks.
 #[cold]
 #[inline(never)]
-fn matched(
-    prestate: &mut PrefilterState,
-    start_ptr: *const u8,
-    ptr: *const u8,
-    chunki: usize,
-) -> usize {
-    let found = diff(ptr, start_ptr) + chunki;
-    prestate.update(found);
-    found
+fn matched(start_ptr: *const u8, ptr: *const u8, chunki: usize) -> usize {
+    diff(ptr, start_ptr) + chunki
 }
 
 /// Subtract `b` from `a` and return the difference. `a` must be greater than
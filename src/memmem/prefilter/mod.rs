@@ -3,8 +3,8 @@ use crate::memmem::{
 };
 
 mod fallback;
-#[cfg(memchr_runtime_simd)]
-mod genericsimd;
+#[cfg(any(memchr_runtime_simd, memchr_runtime_neon))]
+pub(crate) mod genericsimd;
 #[cfg(all(not(miri), target_arch = "wasm32", memchr_runtime_simd))]
 mod wasm;
 #[cfg(all(not(miri), target_arch = "x86_64", memchr_runtime_simd))]
@@ -51,8 +51,8 @@ impl<'a> Pre<'a> {
 /// A prefilter function.
 ///
 /// A prefilter function describes both forward and reverse searches.
-/// (Although, we don't currently implement prefilters for reverse searching.)
-/// In the case of a forward search, the position returned corresponds to
+/// (Although, only the memchr based fallback prefilter is implemented for
+/// reverse searching.) In the case of a forward search, the position returned corresponds to
 /// the starting offset of a match (confirmed or possible). Its minimum
 /// value is `0`, and its maximum value is `haystack.len() - 1`. In the case
 /// of a reverse search, the position returned corresponds to the position
@@ -73,7 +73,13 @@ impl<'a> Pre<'a> {
 /// Thus, callers must ensure that when a prefilter function is created that it
 /// is safe to call for the current environment.
 #[derive(Clone, Copy)]
-pub(crate) struct PrefilterFn(PrefilterFnTy);
+pub(crate) struct PrefilterFn {
+    /// The actual prefilter function.
+    prefn: PrefilterFnTy,
+    /// The kind of prefilter that `prefn` implements. This is only used to
+    /// report which prefilter was selected.
+    kind: PrefilterKind,
+}
 
 /// The type of a prefilter function. All prefilters must satisfy this
 /// signature.
@@ -98,8 +104,8 @@ pub(crate) type PrefilterFnTy = unsafe fn(
 // rarest byte and be done with it. (It is likely that this code path is
 // rarely exercised, since a higher level routine will probably dispatch to
 // Rabin-Karp for such a small haystack.)
-#[cfg(memchr_runtime_simd)]
-fn simple_memchr_fallback(
+#[cfg(any(memchr_runtime_simd, memchr_runtime_neon))]
+pub(crate) fn simple_memchr_fallback(
     _prestate: &mut PrefilterState,
     ninfo: &NeedleInfo,
     haystack: &[u8],
@@ -118,8 +124,16 @@ impl PrefilterFn {
     /// for all inputs in the current environment. For example, if the given
     /// prefilter function uses AVX instructions, then the caller must ensure
     /// that the appropriate AVX CPU features are enabled.
-    pub(crate) unsafe fn new(prefn: PrefilterFnTy) -> PrefilterFn {
-        PrefilterFn(prefn)
+    pub(crate) unsafe fn new(
+        kind: PrefilterKind,
+        prefn: PrefilterFnTy,
+    ) -> PrefilterFn {
+        PrefilterFn { prefn, kind }
+    }
+
+    /// Return the kind of this prefilter function.
+    pub(crate) fn kind(&self) -> PrefilterKind {
+        self.kind
     }
 
     /// Call the underlying prefilter function with the given arguments.
@@ -132,7 +146,7 @@ impl PrefilterFn {
     ) -> Option<usize> {
         // SAFETY: Callers have the burden of ensuring that a prefilter
         // function is safe to call for all inputs in the current environment.
-        unsafe { (self.0)(prestate, ninfo, haystack, needle) }
+        unsafe { (self.prefn)(prestate, ninfo, haystack, needle) }
     }
 }
 
@@ -167,7 +181,8 @@ impl core::fmt::Debug for PrefilterFn {
 /// disable its use. Nevertheless, this configuration option gives callers
 /// the ability to disable prefilters if you have knowledge that they won't be
 /// useful.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum Prefilter {
     /// Never used a prefilter in substring search.
@@ -191,6 +206,78 @@ impl Prefilter {
             _ => false,
         }
     }
+
+    pub(crate) fn is_auto(&self) -> bool {
+        match *self {
+            Prefilter::Auto => true,
+            _ => false,
+        }
+    }
+}
+
+/// The kind of prefilter selected by a substring searcher.
+///
+/// This is returned as part of a [`PrefilterInfo`] and is purely
+/// informational. It is mostly useful for debugging performance problems,
+/// since it doesn't affect which matches are reported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum PrefilterKind {
+    /// No prefilter is used. This happens when prefilters are disabled, when
+    /// the needle is too short, when the rarest byte in the needle is too
+    /// common for a prefilter to be worth it, or when the search strategy
+    /// chosen for the needle doesn't use a prefilter at all.
+    None,
+    /// A prefilter that uses `memchr` (or `memrchr`) to find occurrences of
+    /// the rarest byte in the needle, and then checks the second rarest byte
+    /// at its aligned position.
+    Memchr,
+    /// A vectorized prefilter that looks for both of the rarest bytes in the
+    /// needle at their aligned positions at the same time.
+    PackedPair,
+    /// An experimental prefilter that uses the SSE4.2 `PCMPESTRI` instruction
+    /// to look for the entire needle. This is only ever selected when the
+    /// `sse42-prefilter` feature is enabled.
+    Pcmpestri,
+}
+
+/// Information about the prefilter selected by a substring searcher.
+///
+/// This can be obtained from [`Finder::prefilter_info`](crate::memmem::Finder::prefilter_info)
+/// or [`FinderRev::prefilter_info`](crate::memmem::FinderRev::prefilter_info).
+/// It only describes what was selected when the finder was built. Note that
+/// a prefilter may still be dynamically disabled during a search if it is
+/// believed to not be carrying its weight.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PrefilterInfo {
+    kind: PrefilterKind,
+    rare1i: usize,
+    rare2i: usize,
+}
+
+impl PrefilterInfo {
+    pub(crate) fn new(
+        kind: PrefilterKind,
+        rare: &RareNeedleBytes,
+    ) -> PrefilterInfo {
+        let (rare1i, rare2i) = rare.as_rare_usize();
+        PrefilterInfo { kind, rare1i, rare2i }
+    }
+
+    /// Returns the kind of prefilter that was selected.
+    pub fn kind(&self) -> PrefilterKind {
+        self.kind
+    }
+
+    /// Returns the offsets in the needle of the rarest and second rarest
+    /// bytes, in that order, according to the frequency heuristic in use.
+    ///
+    /// These offsets are computed for every needle, even when no prefilter
+    /// is used. For needles shorter than two bytes or longer than 255 bytes,
+    /// both offsets are `0`.
+    pub fn rare_offsets(&self) -> (usize, usize) {
+        (self.rare1i, self.rare2i)
+    }
 }
 
 /// PrefilterState tracks state associated with the effectiveness of a
@@ -285,9 +372,11 @@ impl PrefilterState {
 
 /// Determine which prefilter function, if any, to use.
 ///
-/// This only applies to x86_64 when runtime SIMD detection is enabled (which
-/// is the default). In general, we try to use an AVX prefilter, followed by
-/// SSE and then followed by a generic one based on memchr.
+/// On x86_64 when runtime SIMD detection is enabled (which is the default), we
+/// try to use an AVX prefilter, followed by SSE. (If the experimental
+/// `sse42-prefilter` feature is enabled, then a PCMPESTRI prefilter is tried
+/// first for needles of at most 16 bytes.) On aarch64, we use a NEON
+/// prefilter. Otherwise, we fall back to a generic one based on memchr.
 #[inline(always)]
 pub(crate) fn forward<H: HeuristicFrequencyRank>(
     config: &Prefilter,
@@ -301,20 +390,65 @@ pub(crate) fn forward<H: HeuristicFrequencyRank>(
 
     #[cfg(all(not(miri), target_arch = "x86_64", memchr_runtime_simd))]
     {
+        #[cfg(feature = "sse42-prefilter")]
+        {
+            if cfg!(memchr_runtime_sse42)
+                && needle.len() <= x86::sse42::MAX_NEEDLE_LEN
+                && is_x86_feature_detected!("sse4.2")
+            {
+                // SAFETY: x86::sse42::find only requires the sse4.2 feature,
+                // which we've just checked above, and a needle that isn't too
+                // long, which we've also checked.
+                return unsafe {
+                    Some(PrefilterFn::new(
+                        PrefilterKind::Pcmpestri,
+                        x86::sse42::find,
+                    ))
+                };
+            }
+        }
         #[cfg(feature = "std")]
         {
             if cfg!(memchr_runtime_avx) {
                 if is_x86_feature_detected!("avx2") {
                     // SAFETY: x86::avx::find only requires the avx2 feature,
                     // which we've just checked above.
-                    return unsafe { Some(PrefilterFn::new(x86::avx::find)) };
+                    return unsafe {
+                        Some(PrefilterFn::new(
+                            PrefilterKind::PackedPair,
+                            x86::avx::find,
+                        ))
+                    };
                 }
             }
         }
         if cfg!(memchr_runtime_sse2) {
             // SAFETY: x86::sse::find only requires the sse2 feature, which is
             // guaranteed to be available on x86_64.
-            return unsafe { Some(PrefilterFn::new(x86::sse::find)) };
+            return unsafe {
+                Some(PrefilterFn::new(
+                    PrefilterKind::PackedPair,
+                    x86::sse::find,
+                ))
+            };
+        }
+    }
+    #[cfg(all(not(miri), target_arch = "aarch64", memchr_runtime_neon))]
+    {
+        use crate::arch::aarch64::neon::packedpair;
+
+        // SAFETY: packedpair::find only requires the neon feature, which we
+        // know is enabled at compile time by virtue of the
+        // memchr_runtime_neon cfg.
+        //
+        // The `if true` is here for the same reason as the wasm case below.
+        if true {
+            return unsafe {
+                Some(PrefilterFn::new(
+                    PrefilterKind::PackedPair,
+                    packedpair::find,
+                ))
+            };
         }
     }
     #[cfg(all(not(miri), target_arch = "wasm32", memchr_runtime_simd))]
@@ -324,7 +458,9 @@ pub(crate) fn forward<H: HeuristicFrequencyRank>(
         // Also note that the `if true` is here to prevent, on wasm with simd,
         // rustc warning about the code below being dead code.
         if true {
-            return unsafe { Some(PrefilterFn::new(wasm::find)) };
+            return unsafe {
+                Some(PrefilterFn::new(PrefilterKind::PackedPair, wasm::find))
+            };
         }
     }
     // Check that our rarest byte has a reasonably low rank. The main issue
@@ -334,7 +470,75 @@ pub(crate) fn forward<H: HeuristicFrequencyRank>(
     let rare1_rank = heuristic.rank(rare1) as usize;
     if rare1_rank <= MAX_FALLBACK_RANK {
         // SAFETY: fallback::find is safe to call in all environments.
-        return unsafe { Some(PrefilterFn::new(fallback::find)) };
+        return unsafe {
+            Some(PrefilterFn::new(PrefilterKind::Memchr, fallback::find))
+        };
+    }
+    None
+}
+
+/// Determine which prefilter function, if any, to use for a reverse search.
+///
+/// There are no vectorized reverse prefilters, so this only ever selects the
+/// memchr based fallback prefilter, and only if the rarest byte in the needle
+/// has a reasonably low rank. The rare bytes given must have been detected
+/// with `RareNeedleBytes::reverse`.
+///
+/// This is only ever enabled by `Prefilter::Auto`, so that any other
+/// configuration keeps reverse searches free of prefilters.
+#[inline(always)]
+pub(crate) fn reverse<H: HeuristicFrequencyRank>(
+    config: &Prefilter,
+    rare: &RareNeedleBytes,
+    needle: &[u8],
+    heuristic: H,
+) -> Option<PrefilterFn> {
+    if !config.is_auto() || needle.len() <= 1 {
+        return None;
+    }
+    let (rare1, _) = rare.as_rare_bytes(needle);
+    let rare1_rank = heuristic.rank(rare1) as usize;
+    if rare1_rank <= MAX_FALLBACK_RANK {
+        // SAFETY: fallback::rfind is safe to call in all environments.
+        return unsafe {
+            Some(PrefilterFn::new(PrefilterKind::Memchr, fallback::rfind))
+        };
+    }
+    None
+}
+
+/// Determine which prefilter function, if any, to use for an ASCII case
+/// insensitive forward search.
+///
+/// There are no vectorized prefilters for case insensitive searching, so this
+/// only ever selects the memchr based fallback prefilter. The rare bytes given
+/// must have been detected with `RareNeedleBytes::forward_ascii_ignore_case`.
+#[inline(always)]
+pub(crate) fn forward_ascii_ignore_case<H: HeuristicFrequencyRank>(
+    config: &Prefilter,
+    rare: &RareNeedleBytes,
+    needle: &[u8],
+    heuristic: H,
+) -> Option<PrefilterFn> {
+    if config.is_none() || needle.len() <= 1 {
+        return None;
+    }
+    // Since the fallback prefilter searches for both cases of the rarest
+    // byte, rank it by whichever case is more common.
+    let (rare1, _) = rare.as_rare_bytes(needle);
+    let rare1_rank = core::cmp::max(
+        heuristic.rank(rare1.to_ascii_lowercase()),
+        heuristic.rank(rare1.to_ascii_uppercase()),
+    ) as usize;
+    if rare1_rank <= MAX_FALLBACK_RANK {
+        // SAFETY: fallback::find_ascii_ignore_case is safe to call in all
+        // environments.
+        return unsafe {
+            Some(PrefilterFn::new(
+                PrefilterKind::Memchr,
+                fallback::find_ascii_ignore_case,
+            ))
+        };
     }
     None
 }
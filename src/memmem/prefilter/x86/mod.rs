@@ -3,3 +3,5 @@
 #[cfg(feature = "std")]
 pub(crate) mod avx;
 pub(crate) mod sse;
+#[cfg(feature = "sse42-prefilter")]
+pub(crate) mod sse42;
@@ -0,0 +1,126 @@
+/*
+This module implements an experimental prefilter based on the PCMPESTRI
+instruction. In "equal ordered" mode, PCMPESTRI reports the first position in
+a 16 byte chunk of the haystack at which the needle either occurs entirely, or
+at which a prefix of the needle runs off the end of the chunk. This lets it
+find candidates for needles of up to 16 bytes without relying on the
+background frequency distribution of bytes at all.
+
+Unlike the packed pair prefilters, every candidate reported here is either an
+actual occurrence of the needle or a partial occurrence straddling two chunks.
+The trade off is that PCMPESTRI is a fairly slow instruction. See the memchr
+sse42 module for a similar (unused) experiment.
+
+This is only compiled when the 'sse42-prefilter' feature is enabled, so that
+it can be compared against the default prefilters.
+*/
+
+use core::arch::x86_64::*;
+
+use crate::memmem::{
+    prefilter::{PrefilterFnTy, PrefilterState},
+    NeedleInfo,
+};
+
+// Check that the functions below satisfy the Prefilter function type.
+const _: PrefilterFnTy = find;
+
+/// The longest needle supported by this prefilter.
+pub(crate) const MAX_NEEDLE_LEN: usize = 16;
+
+const VECTOR_SIZE: usize = 16;
+const CONTROL_ORDERED: i32 = _SIDD_UBYTE_OPS
+    | _SIDD_CMP_EQUAL_ORDERED
+    | _SIDD_POSITIVE_POLARITY
+    | _SIDD_LEAST_SIGNIFICANT;
+
+/// A PCMPESTRI accelerated candidate finder for single-substring search.
+///
+/// # Safety
+///
+/// Callers must ensure that the sse4.2 CPU feature is enabled in the current
+/// environment, and that the needle is non-empty and no longer than
+/// `MAX_NEEDLE_LEN`.
+#[target_feature(enable = "sse4.2")]
+pub(crate) unsafe fn find(
+    prestate: &mut PrefilterState,
+    _ninfo: &NeedleInfo,
+    haystack: &[u8],
+    needle: &[u8],
+) -> Option<usize> {
+    debug_assert!(!needle.is_empty() && needle.len() <= MAX_NEEDLE_LEN);
+
+    // PCMPESTRI takes explicit lengths, so padding never participates in a
+    // comparison. We still need to copy the needle (and the tail of the
+    // haystack below) into a full vector to avoid reading out of bounds.
+    let mut buf = [0u8; VECTOR_SIZE];
+    buf[..needle.len()].copy_from_slice(needle);
+    let vneedle = _mm_loadu_si128(buf.as_ptr() as *const __m128i);
+    let nlen = needle.len() as i32;
+
+    let mut at = 0;
+    while at + VECTOR_SIZE <= haystack.len() {
+        let ptr = haystack[at..].as_ptr() as *const __m128i;
+        let chunk = _mm_loadu_si128(ptr);
+        let i = _mm_cmpestri(vneedle, nlen, chunk, 16, CONTROL_ORDERED);
+        if i < 16 {
+            return candidate(prestate, haystack, needle, at + i as usize);
+        }
+        at += VECTOR_SIZE;
+    }
+    if at < haystack.len() {
+        let rest = &haystack[at..];
+        let mut buf = [0u8; VECTOR_SIZE];
+        buf[..rest.len()].copy_from_slice(rest);
+        let chunk = _mm_loadu_si128(buf.as_ptr() as *const __m128i);
+        let i = _mm_cmpestri(
+            vneedle,
+            nlen,
+            chunk,
+            rest.len() as i32,
+            CONTROL_ORDERED,
+        );
+        if i < 16 {
+            return candidate(prestate, haystack, needle, at + i as usize);
+        }
+    }
+    prestate.update(haystack.len());
+    None
+}
+
+/// Report the candidate at the given position, unless the needle can't fit
+/// there. In that case, there can't be an occurrence at any later position
+/// either, since every position before this one was ruled out.
+#[inline(always)]
+fn candidate(
+    prestate: &mut PrefilterState,
+    haystack: &[u8],
+    needle: &[u8],
+    at: usize,
+) -> Option<usize> {
+    prestate.update(at);
+    if haystack.len() - at < needle.len() {
+        return None;
+    }
+    Some(at)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    #[test]
+    #[cfg(not(miri))]
+    fn prefilter_permutations() {
+        use crate::memmem::prefilter::tests::PrefilterTest;
+
+        if !is_x86_feature_detected!("sse4.2") {
+            return;
+        }
+        // SAFETY: super::find is safe to call for all inputs with a needle
+        // that isn't too long, on CPUs with sse4.2.
+        unsafe {
+            PrefilterTest::run_all_tests_filter(super::find, |t| {
+                t.needle.len() <= super::MAX_NEEDLE_LEN
+            })
+        };
+    }
+}
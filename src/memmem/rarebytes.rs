@@ -13,7 +13,12 @@ use super::HeuristicFrequencyRank;
 /// Note that we use `u8` to represent the offsets of the rare bytes in a
 /// needle to reduce space usage. This means that rare byte occurring after the
 /// first 255 bytes in a needle will never be used.
-#[derive(Clone, Copy, Debug, Default)]
+///
+/// The offsets documented below are the ones computed for forward searching.
+/// For reverse searching, the offsets are instead the rightmost offsets of
+/// the rarest bytes. That is, rare1i >= i for all i where
+/// needle[i] == needle[rare1i], and similarly for rare2i.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub(crate) struct RareNeedleBytes {
     /// The leftmost offset of the rarest byte in the needle, according to
     /// pre-computed frequency analysis. The "leftmost offset" means that
@@ -49,6 +54,38 @@ impl RareNeedleBytes {
     pub(crate) fn forward<H: HeuristicFrequencyRank>(
         needle: &[u8],
         h: &H,
+    ) -> RareNeedleBytes {
+        RareNeedleBytes::forward_by(needle, |b| h.rank(b), |b| b)
+    }
+
+    /// Like `forward`, but detects rare bytes for an ASCII case insensitive
+    /// search.
+    ///
+    /// Every byte is folded to its ASCII lowercase form before being ranked,
+    /// and the rank of an ASCII letter is the rank of its more frequent case,
+    /// since a candidate search has to look for both cases of that letter.
+    pub(crate) fn forward_ascii_ignore_case<H: HeuristicFrequencyRank>(
+        needle: &[u8],
+        h: &H,
+    ) -> RareNeedleBytes {
+        RareNeedleBytes::forward_by(
+            needle,
+            |b| {
+                let (lower, upper) =
+                    (b.to_ascii_lowercase(), b.to_ascii_uppercase());
+                core::cmp::max(h.rank(lower), h.rank(upper))
+            },
+            |b| b.to_ascii_lowercase(),
+        )
+    }
+
+    /// Detect the leftmost offsets of the two rarest bytes in the given
+    /// needle, where `rank` returns the frequency rank of a byte and `fold`
+    /// maps bytes that should be considered equivalent to the same byte.
+    fn forward_by(
+        needle: &[u8],
+        rank: impl Fn(u8) -> u8,
+        fold: impl Fn(u8) -> u8,
     ) -> RareNeedleBytes {
         if needle.len() <= 1 || needle.len() > core::u8::MAX as usize {
             // For needles bigger than u8::MAX, our offsets aren't big enough.
@@ -65,19 +102,20 @@ impl RareNeedleBytes {
         }
 
         // Find the rarest two bytes. We make them distinct by construction.
-        let (mut rare1, mut rare1i) = (needle[0], 0);
-        let (mut rare2, mut rare2i) = (needle[1], 1);
-        if h.rank(rare2) < h.rank(rare1) {
+        let (mut rare1, mut rare1i) = (fold(needle[0]), 0);
+        let (mut rare2, mut rare2i) = (fold(needle[1]), 1);
+        if rank(rare2) < rank(rare1) {
             core::mem::swap(&mut rare1, &mut rare2);
             core::mem::swap(&mut rare1i, &mut rare2i);
         }
         for (i, &b) in needle.iter().enumerate().skip(2) {
-            if h.rank(b) < h.rank(rare1) {
+            let b = fold(b);
+            if rank(b) < rank(rare1) {
                 rare2 = rare1;
                 rare2i = rare1i;
                 rare1 = b;
                 rare1i = i as u8;
-            } else if b != rare1 && h.rank(b) < h.rank(rare2) {
+            } else if b != rare1 && rank(b) < rank(rare2) {
                 rare2 = b;
                 rare2i = i as u8;
             }
@@ -90,6 +128,45 @@ impl RareNeedleBytes {
         RareNeedleBytes { rare1i, rare2i }
     }
 
+    /// Detect the rightmost offsets of the two rarest bytes in the given
+    /// needle.
+    ///
+    /// Using the rightmost offsets guarantees that a reverse prefilter,
+    /// which looks for the last occurrence of the rarest byte, never skips
+    /// past the last occurrence of the needle.
+    pub(crate) fn reverse<H: HeuristicFrequencyRank>(
+        needle: &[u8],
+        h: &H,
+    ) -> RareNeedleBytes {
+        if needle.len() <= 1 || needle.len() > core::u8::MAX as usize {
+            // See the forward case for why we bail out here.
+            return RareNeedleBytes { rare1i: 0, rare2i: 0 };
+        }
+
+        // Find the rarest two bytes, scanning from the end of the needle so
+        // that ties are broken by the rightmost offset.
+        let last = needle.len() - 1;
+        let (mut rare1, mut rare1i) = (needle[last], last as u8);
+        let (mut rare2, mut rare2i) = (needle[last - 1], (last - 1) as u8);
+        if h.rank(rare2) < h.rank(rare1) {
+            core::mem::swap(&mut rare1, &mut rare2);
+            core::mem::swap(&mut rare1i, &mut rare2i);
+        }
+        for (i, &b) in needle.iter().enumerate().rev().skip(2) {
+            if h.rank(b) < h.rank(rare1) {
+                rare2 = rare1;
+                rare2i = rare1i;
+                rare1 = b;
+                rare1i = i as u8;
+            } else if b != rare1 && h.rank(b) < h.rank(rare2) {
+                rare2 = b;
+                rare2i = i as u8;
+            }
+        }
+        assert_ne!(rare1i, rare2i);
+        RareNeedleBytes { rare1i, rare2i }
+    }
+
     /// Return the rare bytes in the given needle in the forward direction.
     /// The needle given must be the same one given to the RareNeedleBytes
     /// constructor.
@@ -101,7 +178,7 @@ impl RareNeedleBytes {
     /// second offset. This is useful when the caller doesn't care whether
     /// rare1 is rarer than rare2, but just wants to ensure that they are
     /// ordered with respect to one another.
-    #[cfg(memchr_runtime_simd)]
+    #[cfg(any(memchr_runtime_simd, memchr_runtime_neon))]
     pub(crate) fn as_rare_ordered_usize(&self) -> (usize, usize) {
         let (rare1i, rare2i) = self.as_rare_ordered_u8();
         (rare1i as usize, rare2i as usize)
@@ -109,7 +186,7 @@ impl RareNeedleBytes {
 
     /// Like as_rare_ordered_usize, but returns the offsets as their native
     /// u8 values.
-    #[cfg(memchr_runtime_simd)]
+    #[cfg(any(memchr_runtime_simd, memchr_runtime_neon))]
     pub(crate) fn as_rare_ordered_u8(&self) -> (u8, u8) {
         if self.rare1i <= self.rare2i {
             (self.rare1i, self.rare2i)
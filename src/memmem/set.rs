@@ -0,0 +1,136 @@
+use crate::memmem::Finder;
+
+/// A substring searcher for a fixed set of needles.
+///
+/// This reports the leftmost occurrence of any of its needles in a haystack,
+/// along with the index of the needle that occurred there. If more than one
+/// needle occurs at the leftmost offset, then the needle that was given first
+/// wins.
+///
+/// Currently, this is implemented by building a [`Finder`] for each needle
+/// and taking the leftmost match among them. Each search after the first is
+/// limited to the part of the haystack that could still contain a better
+/// match, but the total work is still proportional to the number of needles.
+/// Faster multi-substring algorithms may be used in the future.
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem::FinderSet;
+///
+/// let set = FinderSet::new(&["quux", "bar", "ba"]);
+/// assert_eq!(Some((4, 1)), set.find(b"foo bar baz"));
+/// assert_eq!(None, set.find(b"foo"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FinderSet<'n> {
+    finders: Vec<Finder<'n>>,
+}
+
+impl<'n> FinderSet<'n> {
+    /// Create a new finder set for the given needles.
+    ///
+    /// The index of each needle in the given slice is the index reported by
+    /// searches that match it.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needles: &[&'n B]) -> FinderSet<'n> {
+        let finders = needles.iter().map(|&needle| Finder::new(needle));
+        FinderSet { finders: finders.collect() }
+    }
+
+    /// Returns the offset of the leftmost occurrence of any needle in the
+    /// given haystack, along with the index of the needle that occurred.
+    ///
+    /// When more than one needle occurs at the leftmost offset, the lowest
+    /// needle index is reported.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use memchr::memmem::FinderSet;
+    ///
+    /// let set = FinderSet::new(&["bar", "foo", "fo"]);
+    /// // Both "foo" and "fo" occur at offset 2, but "foo" was given first.
+    /// assert_eq!(Some((2, 1)), set.find(b"xxfoobar"));
+    /// ```
+    pub fn find(&self, haystack: &[u8]) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize)> = None;
+        for (index, finder) in self.finders.iter().enumerate() {
+            // Only an occurrence that starts strictly before the best one
+            // found so far can replace it, so there's no need to look at
+            // any of the haystack beyond that.
+            let end = match best {
+                None => haystack.len(),
+                Some((0, _)) => break,
+                Some((offset, _)) => core::cmp::min(
+                    haystack.len(),
+                    offset - 1 + finder.needle().len(),
+                ),
+            };
+            if let Some(offset) = finder.find(&haystack[..end]) {
+                best = Some((offset, index));
+            }
+        }
+        best
+    }
+
+    /// Returns the number of needles in this set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.finders.len()
+    }
+
+    /// Returns true if and only if this set has no needles.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.finders.is_empty()
+    }
+
+    /// Returns the needle at the given index.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `index >= self.len()`.
+    #[inline]
+    pub fn needle(&self, index: usize) -> &[u8] {
+        self.finders[index].needle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn finder_set() {
+        use crate::memmem::FinderSet;
+
+        let set = FinderSet::new(&["bar", "foo", "oo"]);
+        assert_eq!(3, set.len());
+        assert_eq!(b"oo", set.needle(2));
+        assert_eq!(Some((0, 1)), set.find(b"foobar"));
+        assert_eq!(Some((1, 2)), set.find(b"xoobar"));
+        assert_eq!(Some((3, 0)), set.find(b"xxxbar"));
+        assert_eq!(None, set.find(b"xxx"));
+
+        // On equal offsets, the lowest needle index wins.
+        let set = FinderSet::new(&["ab", "abc", "a"]);
+        assert_eq!(Some((1, 0)), set.find(b"xabc"));
+        let set = FinderSet::new(&["abc", "ab", "a"]);
+        assert_eq!(Some((1, 0)), set.find(b"xabc"));
+        assert_eq!(Some((1, 1)), set.find(b"xabx"));
+
+        // An empty needle matches at the start of every haystack.
+        let set = FinderSet::new(&["a", ""]);
+        assert_eq!(Some((0, 1)), set.find(b"xa"));
+        assert_eq!(Some((0, 0)), set.find(b"ax"));
+
+        let set = FinderSet::new::<[u8]>(&[]);
+        assert!(set.is_empty());
+        assert_eq!(None, set.find(b"abc"));
+    }
+}
@@ -0,0 +1,520 @@
+use crate::memmem::Finder;
+
+/// Returns the offset, from the start of the reader, of the first occurrence
+/// of a needle in the bytes read from the given reader.
+///
+/// This reads from the reader until an occurrence is found or the reader is
+/// exhausted. Occurrences that span two reads are found. Any error returned
+/// by the reader, other than [`std::io::ErrorKind::Interrupted`], is
+/// returned.
+///
+/// An empty needle never matches, which is consistent with
+/// [`StreamFinder`].
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+///
+/// let reader = &b"foo bar baz"[..];
+/// assert_eq!(Some(4), memmem::find_reader(reader, b"bar")?);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[inline]
+pub fn find_reader<R: std::io::Read>(
+    reader: R,
+    needle: &[u8],
+) -> std::io::Result<Option<u64>> {
+    Finder::new(needle).stream_find_iter(reader).next().transpose()
+}
+
+/// Returns the offset, from the start of the reader, of the first occurrence
+/// of a needle in the bytes read from the given asynchronous reader.
+///
+/// This is the asynchronous version of [`find_reader`], and it has the same
+/// semantics. It uses a [`StreamFinder`] to find occurrences that span two
+/// reads.
+///
+/// This is only available when both the `std` and `tokio` features are
+/// enabled.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use memchr::memmem;
+/// use tokio::io::AsyncRead;
+///
+/// async fn has_bar<R: AsyncRead + Unpin>(reader: R) -> std::io::Result<bool> {
+///     Ok(memmem::find_async_reader(reader, b"bar").await?.is_some())
+/// }
+/// ```
+#[cfg(all(feature = "std", feature = "tokio"))]
+pub async fn find_async_reader<R: tokio::io::AsyncRead + Unpin>(
+    mut reader: R,
+    needle: &[u8],
+) -> std::io::Result<Option<u64>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut stream = StreamFinder::new(needle);
+    let mut buf = vec![0; STREAM_BUFFER_SIZE];
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) => return Ok(None),
+            Ok(n) => n,
+            Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {
+                continue
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(m) = stream.push(&buf[..n]) {
+            return Ok(Some(m.start()));
+        }
+    }
+}
+
+/// A substring searcher for a haystack that arrives in chunks.
+///
+/// Chunks are given to [`StreamFinder::push`] in order. The searcher retains
+/// the last few bytes of the data it has seen (at most one less than the
+/// length of the needle), so that occurrences of the needle that straddle
+/// the boundary between two chunks are still found. Offsets are reported
+/// relative to the start of the stream, and occurrences are reported in
+/// order and never overlap.
+///
+/// An empty needle never matches in a stream.
+///
+/// This is only available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// This shows how to find every occurrence of a needle, even when the caller
+/// doesn't control where the haystack is split.
+///
+/// ```
+/// use memchr::memmem::StreamFinder;
+///
+/// let mut stream = StreamFinder::new("\r\n");
+/// let mut offsets = vec![];
+/// for chunk in [&b"foo\r"[..], b"\nbar\r\nbaz\r\n"].iter() {
+///     let mut chunk = *chunk;
+///     while let Some(m) = stream.push(chunk) {
+///         offsets.push(m.start());
+///         chunk = &chunk[m.consumed()..];
+///     }
+/// }
+/// assert_eq!(offsets, vec![3, 8, 13]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StreamFinder<'n> {
+    finder: Finder<'n>,
+    /// The tail of the data consumed so far that could be the start of an
+    /// occurrence. This never contains a full needle.
+    carry: Vec<u8>,
+    /// The total number of bytes consumed so far.
+    offset: u64,
+}
+
+impl<'n> StreamFinder<'n> {
+    /// Create a new stream searcher for the given needle.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(needle: &'n B) -> StreamFinder<'n> {
+        StreamFinder::from_finder(Finder::new(needle))
+    }
+
+    /// Create a new stream searcher that uses the given finder, which permits
+    /// using a finder built with a
+    /// [`FinderBuilder`](crate::memmem::FinderBuilder).
+    #[inline]
+    pub fn from_finder(finder: Finder<'n>) -> StreamFinder<'n> {
+        StreamFinder { finder, carry: vec![], offset: 0 }
+    }
+
+    /// Search the next chunk of the stream.
+    ///
+    /// If an occurrence of the needle ends in this chunk, then only the
+    /// bytes of the chunk up to the end of that occurrence are consumed,
+    /// and the match is returned. The caller should push the rest of the
+    /// chunk, `&chunk[m.consumed()..]`, to find any later occurrences.
+    ///
+    /// If no occurrence ends in this chunk, then all of it is consumed and
+    /// `None` is returned.
+    pub fn push(&mut self, chunk: &[u8]) -> Option<StreamMatch> {
+        let nlen = self.finder.needle().len();
+        if nlen == 0 || chunk.is_empty() {
+            self.offset += chunk.len() as u64;
+            return None;
+        }
+        if !self.carry.is_empty() {
+            // An occurrence that starts in the carried bytes must end within
+            // the first `nlen - 1` bytes of this chunk, so only that much
+            // needs to be joined with them.
+            let carried = self.carry.len();
+            let joined = core::cmp::min(nlen - 1, chunk.len());
+            let mut window = self.carry.clone();
+            window.extend_from_slice(&chunk[..joined]);
+            if let Some(i) = self.finder.find(&window) {
+                if i < carried {
+                    let start = self.offset - (carried - i) as u64;
+                    return Some(self.consume(start, i + nlen - carried));
+                }
+            }
+        }
+        if let Some(i) = self.finder.find(chunk) {
+            let start = self.offset + i as u64;
+            return Some(self.consume(start, i + nlen));
+        }
+        // No occurrence ends in this chunk, so keep only the bytes that
+        // could still be the start of one.
+        let keep = nlen - 1;
+        if chunk.len() >= keep {
+            self.carry.clear();
+        } else {
+            let excess = (self.carry.len() + chunk.len()).saturating_sub(keep);
+            self.carry.drain(..excess);
+        }
+        let tail = chunk.len().saturating_sub(keep);
+        self.carry.extend_from_slice(&chunk[tail..]);
+        self.offset += chunk.len() as u64;
+        None
+    }
+
+    /// Returns the total number of bytes consumed from the stream so far.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the needle that this searcher looks for.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        self.finder.needle()
+    }
+
+    /// Forget all data pushed so far, as if this searcher were new.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.carry.clear();
+        self.offset = 0;
+    }
+
+    /// Record a match at `start` and consume `consumed` bytes of the current
+    /// chunk. Matches never overlap, so no bytes are carried past a match.
+    fn consume(&mut self, start: u64, consumed: usize) -> StreamMatch {
+        self.carry.clear();
+        self.offset += consumed as u64;
+        StreamMatch { start, end: self.offset, consumed }
+    }
+}
+
+/// The number of bytes requested from a reader at a time by the routines that
+/// search readers.
+const STREAM_BUFFER_SIZE: usize = 8 * (1 << 10);
+
+/// An iterator over non-overlapping substring matches in the bytes read from
+/// a reader.
+///
+/// This is created by [`Finder::stream_find_iter`]. Matches are reported by
+/// the offset, from the start of the reader, at which they begin.
+///
+/// `'n` is the lifetime of the needle and `R` is the type of the reader.
+///
+/// This is only available when the `std` feature is enabled.
+#[derive(Debug)]
+pub struct StreamFindIter<'n, R> {
+    stream: StreamFinder<'n>,
+    reader: R,
+    buf: Vec<u8>,
+    /// The range of `buf` that has been read but not yet searched.
+    pos: usize,
+    end: usize,
+    /// Set once the reader has reported EOF.
+    done: bool,
+}
+
+impl<'n, R: std::io::Read> StreamFindIter<'n, R> {
+    pub(crate) fn new(
+        stream: StreamFinder<'n>,
+        reader: R,
+    ) -> StreamFindIter<'n, R> {
+        StreamFindIter {
+            stream,
+            reader,
+            buf: vec![0; STREAM_BUFFER_SIZE],
+            pos: 0,
+            end: 0,
+            done: false,
+        }
+    }
+}
+
+impl<'n, R: std::io::Read> Iterator for StreamFindIter<'n, R> {
+    type Item = std::io::Result<u64>;
+
+    fn next(&mut self) -> Option<std::io::Result<u64>> {
+        loop {
+            if self.pos < self.end {
+                let chunk = &self.buf[self.pos..self.end];
+                if let Some(m) = self.stream.push(chunk) {
+                    self.pos += m.consumed();
+                    return Some(Ok(m.start()));
+                }
+                self.pos = self.end;
+            }
+            if self.done {
+                return None;
+            }
+            match self.reader.read(&mut self.buf) {
+                Ok(0) => self.done = true,
+                Ok(n) => {
+                    self.pos = 0;
+                    self.end = n;
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// An occurrence of a needle found by a [`StreamFinder`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StreamMatch {
+    start: u64,
+    end: u64,
+    consumed: usize,
+}
+
+impl StreamMatch {
+    /// The offset, from the start of the stream, at which the occurrence
+    /// starts.
+    #[inline]
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// The offset, from the start of the stream, at which the occurrence
+    /// ends (exclusive).
+    #[inline]
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// The number of bytes of the chunk given to [`StreamFinder::push`] that
+    /// were consumed. The rest of the chunk, starting at this index, should
+    /// be pushed next.
+    #[inline]
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Push the given chunks into a stream searcher for `needle` and return
+    /// the start offsets of every match reported.
+    fn stream_find_all(needle: &[u8], chunks: &[&[u8]]) -> Vec<usize> {
+        let mut stream = crate::memmem::StreamFinder::new(needle);
+        let mut starts = vec![];
+        for &chunk in chunks {
+            let mut chunk = chunk;
+            while let Some(m) = stream.push(chunk) {
+                assert_eq!(m.start() + needle.len() as u64, m.end());
+                assert_eq!(m.end(), stream.offset());
+                starts.push(m.start() as usize);
+                chunk = &chunk[m.consumed()..];
+            }
+        }
+        starts
+    }
+
+    #[test]
+    fn stream_finder_every_split() {
+        let tests: &[(&str, &str)] = &[
+            ("foo bar foo baz foo", "foo"),
+            ("aaaaaaa", "aa"),
+            ("abababab", "abab"),
+            ("xxabcxabcabc", "abc"),
+            ("a\r\n\r\nb\r\n", "\r\n"),
+            ("abcdefgh", "abcdefgh"),
+            ("abcdefg", "abcdefgh"),
+            ("abc", "x"),
+            ("", "abc"),
+        ];
+        for &(haystack, needle) in tests {
+            let (haystack, needle) = (haystack.as_bytes(), needle.as_bytes());
+            let expected: Vec<usize> = crate::memmem::Finder::new(needle)
+                .find_iter(haystack)
+                .collect();
+            for i in 0..=haystack.len() {
+                let (a, b) = haystack.split_at(i);
+                assert_eq!(expected, stream_find_all(needle, &[a, b]));
+            }
+            for size in 1..=haystack.len() {
+                let chunks: Vec<&[u8]> = haystack.chunks(size).collect();
+                assert_eq!(expected, stream_find_all(needle, &chunks));
+            }
+        }
+    }
+
+    /// A reader that returns at most a fixed number of bytes per read, and
+    /// that is interrupted before every other read.
+    struct AwkwardReader<'a> {
+        data: &'a [u8],
+        size: usize,
+        interrupt: bool,
+    }
+
+    impl<'a> std::io::Read for AwkwardReader<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            let n = core::cmp::min(self.size, self.data.len());
+            let n = core::cmp::min(n, buf.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn stream_find_iter_reader() {
+        let mut haystack = b"xyz".repeat(5000);
+        haystack.extend_from_slice(b"abcabc");
+        haystack.extend(b"xyz".repeat(3000));
+        haystack.extend_from_slice(b"abc");
+        let finder = crate::memmem::Finder::new("abc");
+        let expected: Vec<u64> =
+            finder.find_iter(&haystack).map(|i| i as u64).collect();
+        assert_eq!(3, expected.len());
+        for &size in &[1, 2, 3, 7, 4096, 8191, 8192, 8193, 100_000] {
+            let reader =
+                AwkwardReader { data: &haystack, size, interrupt: false };
+            let got = finder
+                .stream_find_iter(reader)
+                .collect::<std::io::Result<Vec<u64>>>()
+                .unwrap();
+            assert_eq!(expected, got, "read size {}", size);
+
+            let reader =
+                AwkwardReader { data: &haystack, size, interrupt: false };
+            let got = crate::memmem::find_reader(reader, b"abc").unwrap();
+            assert_eq!(Some(expected[0]), got);
+        }
+        assert_eq!(
+            None,
+            crate::memmem::find_reader(&b"abc"[..], b"").unwrap()
+        );
+    }
+
+    #[test]
+    fn stream_find_iter_error() {
+        struct Failing;
+
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::Other.into())
+            }
+        }
+
+        let err = crate::memmem::find_reader(Failing, b"abc").unwrap_err();
+        assert_eq!(std::io::ErrorKind::Other, err.kind());
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "tokio"))]
+    fn find_async_reader_split() {
+        use core::{
+            future::Future,
+            pin::Pin,
+            task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+        };
+        use tokio::io::{AsyncRead, ReadBuf};
+
+        // Yields one part per read, and is pending once between parts so
+        // that the searcher sees them as separate reads.
+        struct Parts {
+            parts: Vec<&'static [u8]>,
+            pending: bool,
+        }
+
+        impl AsyncRead for Parts {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                if self.pending {
+                    self.pending = false;
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                if !self.parts.is_empty() {
+                    buf.put_slice(self.parts.remove(0));
+                    self.pending = true;
+                }
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        // Polls a future to completion without a runtime. This is enough
+        // here since the readers above never wait on anything.
+        fn block_on<F: Future>(fut: F) -> F::Output {
+            fn raw() -> RawWaker {
+                RawWaker::new(core::ptr::null(), &VTABLE)
+            }
+            static VTABLE: RawWakerVTable =
+                RawWakerVTable::new(|_| raw(), |_| {}, |_| {}, |_| {});
+
+            let waker = unsafe { Waker::from_raw(raw()) };
+            let mut cx = Context::from_waker(&waker);
+            let mut fut = Box::pin(fut);
+            loop {
+                if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                    return out;
+                }
+            }
+        }
+
+        let reader =
+            Parts { parts: vec![b"xxxxxxa", b"b", b"cxx"], pending: false };
+        let found = block_on(crate::memmem::find_async_reader(reader, b"abc"));
+        assert_eq!(Some(6), found.unwrap());
+
+        let reader = Parts { parts: vec![], pending: false };
+        let found = block_on(crate::memmem::find_async_reader(reader, b"abc"));
+        assert_eq!(None, found.unwrap());
+
+        let found = block_on(crate::memmem::find_async_reader(
+            &b"foo bar baz"[..],
+            b"bar",
+        ));
+        assert_eq!(Some(4), found.unwrap());
+    }
+
+    #[test]
+    fn stream_finder_misc() {
+        use crate::memmem::StreamFinder;
+
+        let mut stream = StreamFinder::new("");
+        assert_eq!(None, stream.push(b"abc"));
+        assert_eq!(3, stream.offset());
+
+        let mut stream = StreamFinder::new("abc");
+        assert_eq!(None, stream.push(b"xab"));
+        let m = stream.push(b"cxabc").unwrap();
+        assert_eq!((1, 4, 1), (m.start(), m.end(), m.consumed()));
+        stream.reset();
+        assert_eq!(0, stream.offset());
+        // The carried "ab" is forgotten by reset.
+        assert_eq!(None, stream.push(b"cx"));
+        assert_eq!(b"abc", stream.needle());
+    }
+}
@@ -124,10 +124,10 @@ impl Forward {
     }
 
     /// Like find, but handles the degenerate substring test cases. This is
-    /// only useful for conveniently testing this substring implementation in
-    /// isolation.
-    #[cfg(test)]
-    fn find_general(
+    /// useful for testing this substring implementation in isolation, and for
+    /// exposing it as a standalone searcher in `arch::all::twoway`.
+    #[inline(always)]
+    pub(crate) fn find_general(
         &self,
         pre: Option<&mut Pre<'_>>,
         haystack: &[u8],
@@ -266,50 +266,59 @@ impl Reverse {
     /// Find the position of the last occurrence of this searcher's needle
     /// in the given haystack. If one does not exist, then return None.
     ///
-    /// This will automatically initialize prefilter state. This should only
-    /// be used for one-off searches.
+    /// This accepts prefilter state that is useful when using the same
+    /// searcher multiple times, such as in an iterator. The prefilter must
+    /// be a reverse prefilter.
     ///
     /// Callers must guarantee that the needle is non-empty and its length is
     /// <= the haystack's length.
     #[inline(always)]
     pub(crate) fn rfind(
         &self,
+        pre: Option<&mut Pre<'_>>,
         haystack: &[u8],
         needle: &[u8],
     ) -> Option<usize> {
         debug_assert!(!needle.is_empty(), "needle should not be empty");
         debug_assert!(needle.len() <= haystack.len(), "haystack too short");
-        // For the reverse case, we don't use a prefilter. It's plausible that
-        // perhaps we should, but it's a lot of additional code to do it, and
-        // it's not clear that it's actually worth it. If you have a really
-        // compelling use case for this, please file an issue.
         match self.0.shift {
             Shift::Small { period } => {
-                self.rfind_small_imp(haystack, needle, period)
+                self.rfind_small_imp(pre, haystack, needle, period)
             }
             Shift::Large { shift } => {
-                self.rfind_large_imp(haystack, needle, shift)
+                self.rfind_large_imp(pre, haystack, needle, shift)
             }
         }
     }
 
     /// Like rfind, but handles the degenerate substring test cases. This is
-    /// only useful for conveniently testing this substring implementation in
-    /// isolation.
-    #[cfg(test)]
-    fn rfind_general(&self, haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    /// useful for testing this substring implementation in isolation, and for
+    /// exposing it as a standalone searcher in `arch::all::twoway`.
+    #[inline(always)]
+    pub(crate) fn rfind_general(
+        &self,
+        pre: Option<&mut Pre<'_>>,
+        haystack: &[u8],
+        needle: &[u8],
+    ) -> Option<usize> {
         if needle.is_empty() {
             Some(haystack.len())
         } else if haystack.len() < needle.len() {
             None
         } else {
-            self.rfind(haystack, needle)
+            self.rfind(pre, haystack, needle)
         }
     }
 
+    // Like the forward direction, each reverse search implementation is
+    // explicitly inlined based on whether a prefilter will be used or not.
+    // A reverse prefilter returns the end of a candidate match, which is
+    // exactly what `pos` tracks below.
+
     #[inline(always)]
     fn rfind_small_imp(
         &self,
+        mut pre: Option<&mut Pre<'_>>,
         haystack: &[u8],
         needle: &[u8],
         period: usize,
@@ -318,6 +327,15 @@ impl Reverse {
         let mut pos = haystack.len();
         let mut shift = nlen;
         while pos >= nlen {
+            if let Some(pre) = pre.as_mut() {
+                if pre.should_call() {
+                    pos = pre.call(&haystack[..pos], needle)?;
+                    shift = nlen;
+                    if pos < nlen {
+                        return None;
+                    }
+                }
+            }
             if !self.0.byteset.contains(haystack[pos - nlen]) {
                 pos -= nlen;
                 shift = nlen;
@@ -348,6 +366,7 @@ impl Reverse {
     #[inline(always)]
     fn rfind_large_imp(
         &self,
+        mut pre: Option<&mut Pre<'_>>,
         haystack: &[u8],
         needle: &[u8],
         shift: usize,
@@ -355,6 +374,14 @@ impl Reverse {
         let nlen = needle.len();
         let mut pos = haystack.len();
         while pos >= nlen {
+            if let Some(pre) = pre.as_mut() {
+                if pre.should_call() {
+                    pos = pre.call(&haystack[..pos], needle)?;
+                    if pos < nlen {
+                        return None;
+                    }
+                }
+            }
             if !self.0.byteset.contains(haystack[pos - nlen]) {
                 pos -= nlen;
                 continue;
@@ -859,7 +886,7 @@ mod simpletests {
         haystack: &[u8],
         needle: &[u8],
     ) -> Option<usize> {
-        Reverse::new(needle).rfind_general(haystack, needle)
+        Reverse::new(needle).rfind_general(None, haystack, needle)
     }
 
     define_memmem_simple_tests!(twoway_find, twoway_rfind);
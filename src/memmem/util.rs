@@ -86,3 +86,23 @@ pub(crate) fn memcmp(x: &[u8], y: &[u8]) -> bool {
         vx == vy
     }
 }
+
+/// A helper for formatting a needle in `Debug` impls.
+///
+/// Needles that are valid UTF-8 are formatted like a `&str`. All other
+/// needles are formatted like a byte string literal, where any byte that
+/// isn't printable ASCII is escaped.
+pub(crate) struct DebugNeedle<'a>(pub(crate) &'a [u8]);
+
+impl<'a> core::fmt::Debug for DebugNeedle<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if let Ok(s) = core::str::from_utf8(self.0) {
+            return core::fmt::Debug::fmt(s, f);
+        }
+        write!(f, "b\"")?;
+        for &b in self.0 {
+            write!(f, "{}", core::ascii::escape_default(b))?;
+        }
+        write!(f, "\"")
+    }
+}
@@ -5,6 +5,9 @@
 /// __m256i types. It's likely that once std::simd becomes a thing, we can
 /// migrate to that since the operations required are quite simple.
 ///
+/// It is also implemented for wasm32's v128 and aarch64's uint8x16_t, where
+/// operations without a direct equivalent (like movemask) are emulated.
+///
 /// TODO: Consider moving this trait up a level and using it to implement
 /// memchr as well. The trait might need to grow one or two methods, but
 /// otherwise should be close to sufficient already.
@@ -29,8 +32,27 @@ pub(crate) trait Vector: Copy + core::fmt::Debug {
     unsafe fn cmpeq(self, vector2: Self) -> Self;
     /// _mm_and_si128 or _mm256_and_si256
     unsafe fn and(self, vector2: Self) -> Self;
+    /// _mm_prefetch with _MM_HINT_T0
+    ///
+    /// This is only a hint, so `data` may point anywhere, including past the
+    /// end of the haystack. It does nothing on targets without a stable
+    /// prefetch intrinsic.
+    #[inline(always)]
+    unsafe fn prefetch(_data: *const u8) {}
 }
 
+/// Haystacks at least this long are searched with a loop that prefetches
+/// the bytes `PREFETCH_DISTANCE` ahead of the current position.
+///
+/// Smaller haystacks tend to be in cache already, where prefetching doesn't
+/// help. On an AVX2 machine, searching prefixes of the en-huge corpus for a
+/// needle that never occurs showed no difference up to 32KB, and a 1.5x
+/// speedup starting at 64KB.
+pub(crate) const PREFETCH_MIN_HAYSTACK_LEN: usize = 1 << 16;
+
+/// How far ahead of the current position, in bytes, to prefetch.
+pub(crate) const PREFETCH_DISTANCE: usize = 1024;
+
 #[cfg(target_arch = "x86_64")]
 mod x86sse {
     use super::Vector;
@@ -61,6 +83,11 @@ mod x86sse {
         unsafe fn and(self, vector2: Self) -> __m128i {
             _mm_and_si128(self, vector2)
         }
+
+        #[inline(always)]
+        unsafe fn prefetch(data: *const u8) {
+            _mm_prefetch(data as *const i8, _MM_HINT_T0)
+        }
     }
 }
 
@@ -94,6 +121,11 @@ mod x86avx {
         unsafe fn and(self, vector2: Self) -> __m256i {
             _mm256_and_si256(self, vector2)
         }
+
+        #[inline(always)]
+        unsafe fn prefetch(data: *const u8) {
+            _mm_prefetch(data as *const i8, _MM_HINT_T0)
+        }
     }
 }
 
@@ -129,3 +161,48 @@ mod wasm_simd128 {
         }
     }
 }
+
+#[cfg(all(target_arch = "aarch64", memchr_runtime_neon))]
+mod aarch64neon {
+    use super::Vector;
+    use core::arch::aarch64::*;
+
+    impl Vector for uint8x16_t {
+        #[inline(always)]
+        unsafe fn splat(byte: u8) -> uint8x16_t {
+            vdupq_n_u8(byte)
+        }
+
+        #[inline(always)]
+        unsafe fn load_unaligned(data: *const u8) -> uint8x16_t {
+            vld1q_u8(data)
+        }
+
+        /// NEON has no movemask instruction, so this emulates it by spreading
+        /// the high bit of each lane across the whole lane, keeping only the
+        /// bit for that lane's position within the low or high 8 lanes, and
+        /// then summing each half horizontally.
+        #[inline(always)]
+        unsafe fn movemask(self) -> u32 {
+            const BITS: [u8; 16] =
+                [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+            let high = vreinterpretq_u8_s8(vshrq_n_s8::<7>(
+                vreinterpretq_s8_u8(self),
+            ));
+            let bits = vandq_u8(high, vld1q_u8(BITS.as_ptr()));
+            let lo = u32::from(vaddv_u8(vget_low_u8(bits)));
+            let hi = u32::from(vaddv_u8(vget_high_u8(bits)));
+            lo | (hi << 8)
+        }
+
+        #[inline(always)]
+        unsafe fn cmpeq(self, vector2: Self) -> uint8x16_t {
+            vceqq_u8(self, vector2)
+        }
+
+        #[inline(always)]
+        unsafe fn and(self, vector2: Self) -> uint8x16_t {
+            vandq_u8(self, vector2)
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/// How the vectorized substring searchers verify a candidate match.
+///
+/// Searchers for needles of 2 to 32 bytes find candidates by looking for two
+/// of the needle's rare bytes a vector at a time, and then compare the whole
+/// needle against the haystack at each candidate. This controls how that
+/// comparison is done. Longer needles are searched with Two-Way, which
+/// doesn't verify candidates this way, so this has no effect on them.
+///
+/// This never changes which matches are reported. It only matters when a
+/// haystack has many candidates that share a long prefix with the needle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Verifier {
+    /// Compare 16 bytes at a time with vector instructions for needles of at
+    /// least 16 bytes, and use [`Verifier::Scalar`] for shorter needles.
+    ///
+    /// This is the default.
+    Simd,
+    /// Compare 4 bytes at a time with scalar instructions.
+    Scalar,
+}
+
+impl Default for Verifier {
+    fn default() -> Verifier {
+        Verifier::Simd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "std")]
+    fn verifier_does_not_change_matches() {
+        use crate::memmem::{
+            testsimples::run_search_tests_fwd, FinderBuilder, Verifier,
+        };
+
+        // Every candidate but the last shares all but the final byte with
+        // the needle, which is the worst case for verification.
+        let needle = b"QZeeeeeeeeeeeeeeeeeet";
+        let mut haystack = Vec::new();
+        for _ in 0..100 {
+            haystack.extend_from_slice(&needle[..needle.len() - 1]);
+            haystack.push(b'x');
+        }
+        haystack.extend_from_slice(needle);
+        for &verifier in &[Verifier::Simd, Verifier::Scalar] {
+            let mut b = FinderBuilder::new();
+            b.verifier(verifier);
+            run_search_tests_fwd(|h, n| b.build_forward(n).find(h));
+            let finder = b.build_forward(needle);
+            assert_eq!(
+                Some(haystack.len() - needle.len()),
+                finder.find(&haystack)
+            );
+        }
+    }
+}
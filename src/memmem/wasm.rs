@@ -1,6 +1,6 @@
 use core::arch::wasm32::v128;
 
-use crate::memmem::{genericsimd, NeedleInfo};
+use crate::memmem::{genericsimd, NeedleInfo, Verifier};
 
 /// A `v128` accelerated vectorized substring search routine that only works on
 /// small needles.
@@ -10,11 +10,15 @@ pub(crate) struct Forward(genericsimd::Forward);
 impl Forward {
     /// Create a new "generic simd" forward searcher. If one could not be
     /// created from the given inputs, then None is returned.
-    pub(crate) fn new(ninfo: &NeedleInfo, needle: &[u8]) -> Option<Forward> {
+    pub(crate) fn new(
+        ninfo: &NeedleInfo,
+        needle: &[u8],
+        verifier: Verifier,
+    ) -> Option<Forward> {
         if !cfg!(memchr_runtime_simd) {
             return None;
         }
-        genericsimd::Forward::new(ninfo, needle).map(Forward)
+        genericsimd::Forward::new(ninfo, needle, verifier).map(Forward)
     }
 
     /// Returns the minimum length of haystack that is needed for this searcher
@@ -37,13 +41,15 @@ impl Forward {
     /// The implementation of find marked with the appropriate target feature.
     #[target_feature(enable = "simd128")]
     fn find_impl(&self, haystack: &[u8], needle: &[u8]) -> Option<usize> {
-        unsafe { genericsimd::fwd_find::<v128>(&self.0, haystack, needle) }
+        unsafe {
+            genericsimd::fwd_find::<v128, v128>(&self.0, haystack, needle)
+        }
     }
 }
 
 #[cfg(all(test, feature = "std", not(miri)))]
 mod tests {
-    use crate::memmem::{prefilter::PrefilterState, NeedleInfo};
+    use crate::memmem::{prefilter::PrefilterState, NeedleInfo, Verifier};
 
     fn find(
         _: &mut PrefilterState,
@@ -51,7 +57,9 @@ mod tests {
         haystack: &[u8],
         needle: &[u8],
     ) -> Option<usize> {
-        super::Forward::new(ninfo, needle).unwrap().find(haystack, needle)
+        super::Forward::new(ninfo, needle, Verifier::default())
+            .unwrap()
+            .find(haystack, needle)
     }
 
     #[test]
@@ -64,7 +72,11 @@ mod tests {
                 // filter our tests such that Forward::new will be guaranteed
                 // to succeed. (And also remove tests with a haystack that is
                 // too small.)
-                let fwd = match super::Forward::new(&t.ninfo, &t.needle) {
+                let fwd = match super::Forward::new(
+                    &t.ninfo,
+                    &t.needle,
+                    Verifier::default(),
+                ) {
                     None => return false,
                     Some(fwd) => fwd,
                 };
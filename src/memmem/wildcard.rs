@@ -0,0 +1,181 @@
+use crate::memmem::{DefaultHFR, HeuristicFrequencyRank};
+
+/// A substring searcher for a needle in which some bytes are wildcards.
+///
+/// A wildcard matches any byte. Which bytes of the needle are wildcards is
+/// given by a mask with one entry per byte of the needle: `true` means that
+/// the byte must match, and `false` means that the byte is a wildcard, whose
+/// value in the needle is ignored. This is useful for scanning binary data
+/// for signatures in which some bytes, like addresses or immediates, vary.
+///
+/// The searcher picks the two must-match bytes that are believed to be
+/// rarest, using the same heuristic as [`Finder`](crate::memmem::Finder).
+/// Candidates are found by running [`memchr`](crate::memchr()) on the rarest
+/// byte and checking the other, and each candidate is then verified against
+/// every must-match byte. Wildcards never contribute to finding candidates.
+///
+/// # Complexity
+///
+/// Unlike [`Finder`](crate::memmem::Finder), searches are not guaranteed to
+/// run in linear time. In the worst case, they run in
+/// `O(needle.len() * haystack.len())` time.
+///
+/// # Examples
+///
+/// This shows how to find an x86 `mov` instruction with any register
+/// operand.
+///
+/// ```
+/// use memchr::memmem::WildcardFinder;
+///
+/// let needle = b"\x48\x00\x89";
+/// let mask = [true, false, true];
+/// let finder = WildcardFinder::new(needle, &mask);
+/// assert_eq!(Some(2), finder.find(b"\x90\x90\x48\xC7\x89\xC3"));
+/// assert_eq!(None, finder.find(b"\x48\xC7\x8B"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct WildcardFinder<'n> {
+    needle: &'n [u8],
+    mask: &'n [bool],
+    // The offsets of the rarest and second rarest must-match bytes in the
+    // needle. `rare1` is only `None` when every byte is a wildcard, and
+    // `rare2` is `None` when there are fewer than two must-match bytes.
+    rare1: Option<usize>,
+    rare2: Option<usize>,
+}
+
+impl<'n> WildcardFinder<'n> {
+    /// Create a new finder for the given needle and mask.
+    ///
+    /// `mask[i]` is `true` if `needle[i]` must match, and `false` if any byte
+    /// may occur at that position.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `needle` and `mask` do not have the same length.
+    #[inline]
+    pub fn new<B: ?Sized + AsRef<[u8]>>(
+        needle: &'n B,
+        mask: &'n [bool],
+    ) -> WildcardFinder<'n> {
+        let needle = needle.as_ref();
+        assert_eq!(
+            needle.len(),
+            mask.len(),
+            "needle and mask must have the same length",
+        );
+        let rank = |i: &usize| DefaultHFR.rank(needle[*i]);
+        let must_match = || (0..needle.len()).filter(|&i| mask[i]);
+        let rare1 = must_match().min_by_key(rank);
+        let rare2 =
+            must_match().filter(|&i| Some(i) != rare1).min_by_key(rank);
+        WildcardFinder { needle, mask, rare1, rare2 }
+    }
+
+    /// Returns the index of the first occurrence of this finder's needle in
+    /// the given haystack, with wildcards matching any byte.
+    ///
+    /// The index returned is the start of the whole needle, including any
+    /// leading wildcards. A needle with no must-match bytes matches at `0`,
+    /// as long as the haystack is at least as long as the needle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use memchr::memmem::WildcardFinder;
+    ///
+    /// let finder = WildcardFinder::new("?ab?", &[false, true, true, false]);
+    /// assert_eq!(Some(3), finder.find(b"ab xabx"));
+    /// // The wildcards must still be matched by some byte.
+    /// assert_eq!(None, finder.find(b"xab"));
+    /// ```
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        // The largest position at which the needle can start.
+        let last = haystack.len().checked_sub(self.needle.len())?;
+        let rare1 = match self.rare1 {
+            None => return Some(0),
+            Some(rare1) => rare1,
+        };
+        let mut at = 0;
+        while at <= last {
+            let window = &haystack[at + rare1..=last + rare1];
+            let candidate = at + crate::memchr(self.needle[rare1], window)?;
+            let rare2_matches = match self.rare2 {
+                None => true,
+                Some(i) => haystack[candidate + i] == self.needle[i],
+            };
+            if rare2_matches && self.is_match_at(haystack, candidate) {
+                return Some(candidate);
+            }
+            at = candidate + 1;
+        }
+        None
+    }
+
+    /// Returns the needle that this finder searches for. Wildcard positions
+    /// contain whatever bytes were given to the constructor.
+    #[inline]
+    pub fn needle(&self) -> &[u8] {
+        self.needle
+    }
+
+    /// Returns the mask of must-match positions in the needle.
+    #[inline]
+    pub fn mask(&self) -> &[bool] {
+        self.mask
+    }
+
+    /// Returns true if every must-match byte of the needle matches the
+    /// haystack at the given position. The needle must fit in the haystack
+    /// at that position.
+    #[inline]
+    fn is_match_at(&self, haystack: &[u8], at: usize) -> bool {
+        let window = &haystack[at..at + self.needle.len()];
+        self.needle
+            .iter()
+            .zip(self.mask)
+            .zip(window)
+            .all(|((&n, &must_match), &h)| !must_match || n == h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn wildcard_finder() {
+        use crate::memmem::WildcardFinder;
+
+        // Leading and trailing wildcards are part of the match, so the
+        // offset reported is where the leading wildcard matched.
+        let mask = [false, false, true, true, false];
+        let finder = WildcardFinder::new("??ab?", &mask);
+        assert_eq!(Some(1), finder.find(b"xyzabz"));
+        assert_eq!(Some(0), finder.find(b"\x00\xFFab\x00"));
+        assert_eq!(Some(3), finder.find(b"ab.xyab!"));
+        // Not enough room for the leading or trailing wildcards.
+        assert_eq!(None, finder.find(b"zab?"));
+        assert_eq!(None, finder.find(b"xyab"));
+        assert_eq!(None, finder.find(b""));
+
+        // Wildcard positions ignore the byte given in the needle.
+        let finder =
+            WildcardFinder::new(b"\x48\xFF\x89", &[true, false, true]);
+        assert_eq!(Some(0), finder.find(b"\x48\x00\x89"));
+        assert_eq!(Some(1), finder.find(b"\x48\x48\x01\x89"));
+        assert_eq!(None, finder.find(b"\x48\x89\x00"));
+
+        // A needle consisting only of wildcards matches wherever it fits.
+        let finder = WildcardFinder::new("???", &[false; 3]);
+        assert_eq!(Some(0), finder.find(b"abc"));
+        assert_eq!(None, finder.find(b"ab"));
+        let finder = WildcardFinder::new("", &[]);
+        assert_eq!(Some(0), finder.find(b""));
+    }
+
+    #[test]
+    #[should_panic]
+    fn wildcard_finder_mask_length() {
+        crate::memmem::WildcardFinder::new("abc", &[true, false]);
+    }
+}
@@ -7,7 +7,7 @@ pub(crate) use self::std::Forward;
 mod std {
     use core::arch::x86_64::{__m128i, __m256i};
 
-    use crate::memmem::{genericsimd, NeedleInfo};
+    use crate::memmem::{genericsimd, NeedleInfo, Verifier};
 
     /// An AVX accelerated vectorized substring search routine that only works
     /// on small needles.
@@ -20,11 +20,12 @@ mod std {
         pub(crate) fn new(
             ninfo: &NeedleInfo,
             needle: &[u8],
+            verifier: Verifier,
         ) -> Option<Forward> {
             if !cfg!(memchr_runtime_avx) || !is_x86_feature_detected!("avx2") {
                 return None;
             }
-            genericsimd::Forward::new(ninfo, needle).map(Forward)
+            genericsimd::Forward::new(ninfo, needle, verifier).map(Forward)
         }
 
         /// Returns the minimum length of haystack that is needed for this
@@ -61,9 +62,13 @@ mod std {
             needle: &[u8],
         ) -> Option<usize> {
             if haystack.len() < self.0.min_haystack_len::<__m256i>() {
-                genericsimd::fwd_find::<__m128i>(&self.0, haystack, needle)
+                genericsimd::fwd_find::<__m128i, __m128i>(
+                    &self.0, haystack, needle,
+                )
             } else {
-                genericsimd::fwd_find::<__m256i>(&self.0, haystack, needle)
+                genericsimd::fwd_find::<__m256i, __m128i>(
+                    &self.0, haystack, needle,
+                )
             }
         }
     }
@@ -73,7 +78,7 @@ mod std {
 // simpler. This avoids needing a lot more conditional compilation.
 #[cfg(not(feature = "std"))]
 mod nostd {
-    use crate::memmem::NeedleInfo;
+    use crate::memmem::{NeedleInfo, Verifier};
 
     #[derive(Clone, Copy, Debug)]
     pub(crate) struct Forward(());
@@ -82,6 +87,7 @@ mod nostd {
         pub(crate) fn new(
             ninfo: &NeedleInfo,
             needle: &[u8],
+            verifier: Verifier,
         ) -> Option<Forward> {
             None
         }
@@ -102,7 +108,7 @@ mod nostd {
 
 #[cfg(all(test, feature = "std", not(miri)))]
 mod tests {
-    use crate::memmem::{prefilter::PrefilterState, NeedleInfo};
+    use crate::memmem::{prefilter::PrefilterState, NeedleInfo, Verifier};
 
     fn find(
         _: &mut PrefilterState,
@@ -110,7 +116,9 @@ mod tests {
         haystack: &[u8],
         needle: &[u8],
     ) -> Option<usize> {
-        super::Forward::new(ninfo, needle).unwrap().find(haystack, needle)
+        super::Forward::new(ninfo, needle, Verifier::default())
+            .unwrap()
+            .find(haystack, needle)
     }
 
     #[test]
@@ -128,7 +136,11 @@ mod tests {
                 // filter our tests such that Forward::new will be guaranteed
                 // to succeed. (And also remove tests with a haystack that is
                 // too small.)
-                let fwd = match super::Forward::new(&t.ninfo, &t.needle) {
+                let fwd = match super::Forward::new(
+                    &t.ninfo,
+                    &t.needle,
+                    Verifier::default(),
+                ) {
                     None => return false,
                     Some(fwd) => fwd,
                 };
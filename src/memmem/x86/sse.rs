@@ -1,6 +1,6 @@
 use core::arch::x86_64::__m128i;
 
-use crate::memmem::{genericsimd, NeedleInfo};
+use crate::memmem::{genericsimd, NeedleInfo, Verifier};
 
 /// An SSE accelerated vectorized substring search routine that only works on
 /// small needles.
@@ -10,11 +10,15 @@ pub(crate) struct Forward(genericsimd::Forward);
 impl Forward {
     /// Create a new "generic simd" forward searcher. If one could not be
     /// created from the given inputs, then None is returned.
-    pub(crate) fn new(ninfo: &NeedleInfo, needle: &[u8]) -> Option<Forward> {
+    pub(crate) fn new(
+        ninfo: &NeedleInfo,
+        needle: &[u8],
+        verifier: Verifier,
+    ) -> Option<Forward> {
         if !cfg!(memchr_runtime_sse2) {
             return None;
         }
-        genericsimd::Forward::new(ninfo, needle).map(Forward)
+        genericsimd::Forward::new(ninfo, needle, verifier).map(Forward)
     }
 
     /// Returns the minimum length of haystack that is needed for this searcher
@@ -49,13 +53,13 @@ impl Forward {
         haystack: &[u8],
         needle: &[u8],
     ) -> Option<usize> {
-        genericsimd::fwd_find::<__m128i>(&self.0, haystack, needle)
+        genericsimd::fwd_find::<__m128i, __m128i>(&self.0, haystack, needle)
     }
 }
 
 #[cfg(all(test, feature = "std", not(miri)))]
 mod tests {
-    use crate::memmem::{prefilter::PrefilterState, NeedleInfo};
+    use crate::memmem::{prefilter::PrefilterState, NeedleInfo, Verifier};
 
     fn find(
         _: &mut PrefilterState,
@@ -63,7 +67,9 @@ mod tests {
         haystack: &[u8],
         needle: &[u8],
     ) -> Option<usize> {
-        super::Forward::new(ninfo, needle).unwrap().find(haystack, needle)
+        super::Forward::new(ninfo, needle, Verifier::default())
+            .unwrap()
+            .find(haystack, needle)
     }
 
     #[test]
@@ -78,7 +84,11 @@ mod tests {
                 // filter our tests such that Forward::new will be guaranteed
                 // to succeed. (And also remove tests with a haystack that is
                 // too small.)
-                let fwd = match super::Forward::new(&t.ninfo, &t.needle) {
+                let fwd = match super::Forward::new(
+                    &t.ninfo,
+                    &t.needle,
+                    Verifier::default(),
+                ) {
                     None => return false,
                     Some(fwd) => fwd,
                 };
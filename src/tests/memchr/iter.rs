@@ -1,6 +1,10 @@
 use quickcheck::quickcheck;
 
-use crate::{tests::memchr::testdata::memchr_tests, Memchr, Memchr2, Memchr3};
+use crate::{
+    lines, lines_with_terminator, rlines,
+    tests::memchr::testdata::memchr_tests, ByteSet, Memchr, Memchr2, Memchr3,
+    MemchrNot, MemchrNotRev, MemchrRev, MemchrSet, MemchrSetRev,
+};
 
 #[test]
 fn memchr1_iter() {
@@ -48,6 +52,299 @@ fn memrchr3_iter() {
     }
 }
 
+#[test]
+fn memchr_double_ended_meet() {
+    let haystack = b"a.b.c.d.e";
+    let mut it = Memchr::new(b'.', haystack);
+    assert_eq!(Some(1), it.next());
+    assert_eq!(Some(7), it.next_back());
+    assert_eq!(Some(3), it.next());
+    assert_eq!(Some(5), it.next_back());
+    assert_eq!(None, it.next());
+    assert_eq!(None, it.next_back());
+
+    let mut it = Memchr2::new(b'.', b'c', haystack);
+    assert_eq!(Some(7), it.next_back());
+    assert_eq!(Some(1), it.next());
+    assert_eq!(Some(5), it.next_back());
+    assert_eq!(Some(4), it.next_back());
+    assert_eq!(Some(3), it.next());
+    assert_eq!(None, it.next_back());
+    assert_eq!(None, it.next());
+}
+
+#[test]
+fn memchr_remainder() {
+    let haystack = b"a.b.c.d.e";
+    let mut it = Memchr::new(b'.', haystack);
+    assert_eq!(b"a.b.c.d.e", it.remainder());
+    assert_eq!(Some(1), it.next());
+    assert_eq!(b"b.c.d.e", it.remainder());
+    assert_eq!(Some(7), it.next_back());
+    assert_eq!(b"b.c.d", it.remainder());
+    assert_eq!(Some(3), it.next());
+    assert_eq!(Some(5), it.next());
+    assert_eq!(b"d", it.remainder());
+    assert_eq!(None, it.next());
+    assert_eq!(b"d", it.remainder());
+
+    let mut it = Memchr2::new(b'b', b'd', haystack);
+    assert_eq!(Some(2), it.next());
+    assert_eq!(Some(6), it.next_back());
+    assert_eq!(b".c.", it.remainder());
+
+    let mut it = Memchr3::new(b'x', b'y', b'z', haystack);
+    assert_eq!(None, it.next());
+    assert_eq!(b"a.b.c.d.e", it.remainder());
+}
+
+#[test]
+fn memchr_rev_iter() {
+    for test in memchr_tests() {
+        test.iter_one(true, MemchrRev::new);
+        test.iter_one(false, |n1, corpus| MemchrRev::new(n1, corpus).rev());
+    }
+}
+
+#[test]
+fn memchr_rev_remainder() {
+    let haystack = b"a.b.c.d.e";
+    let mut it = MemchrRev::new(b'.', haystack);
+    assert_eq!(b"a.b.c.d.e", it.remainder());
+    assert_eq!(Some(7), it.next());
+    assert_eq!(Some(5), it.next());
+    assert_eq!(b"a.b.c", it.remainder());
+    assert_eq!(Some(1), it.next_back());
+    assert_eq!(b"b.c", it.remainder());
+    assert_eq!(Some(3), it.next());
+    assert_eq!(None, it.next());
+    assert_eq!(b"b", it.remainder());
+}
+
+#[test]
+fn memchr_set_iter() {
+    // A set with one member finds the same bytes as `Memchr`.
+    for test in memchr_tests() {
+        test.iter_one(false, |n1, corpus| {
+            MemchrSet::new(&ByteSet::from_bytes(&[n1]), corpus)
+        });
+        test.iter_one(true, |n1, corpus| {
+            MemchrSetRev::new(&ByteSet::from_bytes(&[n1]), corpus)
+        });
+    }
+}
+
+#[test]
+fn memchr_set_remainder() {
+    let set = ByteSet::from_bytes(b".,");
+    let haystack = b"a.b,c.d,e";
+    let mut it = MemchrSet::new(&set, haystack);
+    assert_eq!(Some(1), it.next());
+    assert_eq!(Some(7), it.next_back());
+    assert_eq!(b"b,c.d", it.remainder());
+    assert_eq!(vec![3, 5], it.collect::<Vec<usize>>());
+
+    let mut it = MemchrSetRev::new(&set, haystack);
+    assert_eq!(Some(7), it.next());
+    assert_eq!(Some(5), it.next());
+    assert_eq!(b"a.b,c", it.remainder());
+    assert_eq!(Some(1), it.next_back());
+    assert_eq!(vec![3], it.collect::<Vec<usize>>());
+
+    let mut it = MemchrSetRev::new(&ByteSet::new(), haystack);
+    assert_eq!(None, it.next());
+    assert_eq!(haystack, it.remainder());
+}
+
+quickcheck! {
+    fn qc_memchr_set_iter_rev_is_reverse(
+        members: Vec<u8>,
+        corpus: Vec<u8>
+    ) -> bool {
+        let set = ByteSet::from_bytes(&members);
+        let fwd: Vec<usize> = MemchrSet::new(&set, &corpus).collect();
+        let mut rev: Vec<usize> = MemchrSetRev::new(&set, &corpus).collect();
+        rev.reverse();
+        let expected: Vec<usize> = (0..corpus.len())
+            .filter(|&i| set.contains(corpus[i]))
+            .collect();
+        fwd == expected && rev == expected
+    }
+}
+
+#[test]
+fn memchr_not_sparse_buffer() {
+    // A buffer that is mostly padding, with data bytes scattered at
+    // positions that land both inside and at the edges of vectors.
+    let mut haystack = vec![0u8; 1000];
+    for &i in &[0, 1, 15, 16, 31, 32, 63, 100, 101, 102, 511, 998, 999] {
+        haystack[i] = 0xFF ^ (i as u8);
+    }
+    for len in 0..haystack.len() {
+        let haystack = &haystack[..len];
+        let expected: Vec<usize> =
+            (0..haystack.len()).filter(|&i| haystack[i] != 0).collect();
+        let fwd: Vec<usize> = MemchrNot::new(0, haystack).collect();
+        let mut rev: Vec<usize> = MemchrNotRev::new(0, haystack).collect();
+        rev.reverse();
+        assert_eq!(expected, fwd);
+        assert_eq!(expected, rev);
+    }
+}
+
+#[test]
+fn memchr_not_remainder() {
+    let haystack = b"\x00a\x00\x00b\x00c";
+    let mut it = MemchrNot::new(0, haystack);
+    assert_eq!(Some(1), it.next());
+    assert_eq!(Some(6), it.next_back());
+    assert_eq!(b"\x00\x00b\x00", it.remainder());
+    assert_eq!(vec![4], it.collect::<Vec<usize>>());
+
+    let mut it = MemchrNotRev::new(0, haystack);
+    assert_eq!(Some(6), it.next());
+    assert_eq!(b"\x00a\x00\x00b\x00", it.remainder());
+    assert_eq!(Some(1), it.next_back());
+    assert_eq!(vec![4], it.collect::<Vec<usize>>());
+
+    let mut it = MemchrNot::new(b'a', b"aaaa");
+    assert_eq!(None, it.next());
+    assert_eq!(None, it.next_back());
+}
+
+quickcheck! {
+    fn qc_memchr_not_iter_matches_filter(n1: u8, corpus: Vec<u8>) -> bool {
+        let expected: Vec<usize> =
+            (0..corpus.len()).filter(|&i| corpus[i] != n1).collect();
+        let fwd: Vec<usize> = MemchrNot::new(n1, &corpus).collect();
+        let mut rev: Vec<usize> = MemchrNotRev::new(n1, &corpus).collect();
+        rev.reverse();
+        fwd == expected && rev == expected
+    }
+}
+
+#[test]
+fn memchr_count_remaining() {
+    let haystack = b"a.b.c.d.e";
+    let mut it = Memchr::new(b'.', haystack);
+    assert_eq!(4, it.count_remaining());
+    assert_eq!(Some(1), it.next());
+    assert_eq!(3, it.count_remaining());
+    assert_eq!(Some(7), it.next_back());
+    assert_eq!(2, it.count_remaining());
+    assert_eq!(Some(3), it.next());
+    assert_eq!(Some(5), it.next());
+    assert_eq!(0, it.count_remaining());
+    assert_eq!(None, it.next());
+
+    let mut it = Memchr2::new(b'b', b'd', haystack);
+    assert_eq!(Some(2), it.next());
+    assert_eq!(1, it.count_remaining());
+
+    let mut it = Memchr3::new(b'a', b'c', b'e', haystack);
+    assert_eq!(Some(8), it.next_back());
+    assert_eq!(2, it.count_remaining());
+}
+
+#[test]
+fn memchr_const_new() {
+    const HAYSTACK: &[u8] = b"a.b,c;d";
+    const ONE: Memchr<'static> = Memchr::new(b'.', HAYSTACK);
+    const TWO: Memchr2<'static> = Memchr2::new(b'.', b',', HAYSTACK);
+    const THREE: Memchr3<'static> = Memchr3::new(b'.', b',', b';', HAYSTACK);
+    static STATIC: Memchr<'static> = Memchr::new(b'.', HAYSTACK);
+
+    assert_eq!(vec![1], ONE.collect::<Vec<usize>>());
+    assert_eq!(vec![1, 3], TWO.collect::<Vec<usize>>());
+    assert_eq!(vec![1, 3, 5], THREE.collect::<Vec<usize>>());
+    assert_eq!(HAYSTACK, STATIC.remainder());
+}
+
+#[test]
+fn lines_mixed_terminators() {
+    let haystack = b"one\ntwo\r\n\r\nthree\rfour\n\nlast";
+    let got: Vec<&[u8]> = lines(haystack).collect();
+    let expected: Vec<&[u8]> =
+        vec![b"one", b"two", b"", b"three\rfour", b"", b"last"];
+    assert_eq!(expected, got);
+
+    let got: Vec<&[u8]> = lines_with_terminator(haystack).collect();
+    let expected: Vec<&[u8]> =
+        vec![b"one\n", b"two\r\n", b"\r\n", b"three\rfour\n", b"\n", b"last"];
+    assert_eq!(expected, got);
+
+    // A terminated final line does not produce an empty line after it.
+    let got: Vec<&[u8]> = lines(b"a\r\nb\n").collect();
+    assert_eq!(vec![&b"a"[..], b"b"], got);
+    assert_eq!(0, lines(b"").count());
+    assert_eq!(vec![&b""[..]], lines(b"\r\n").collect::<Vec<_>>());
+
+    let mut it = lines(b"a\nb\nc");
+    assert_eq!(Some(&b"a"[..]), it.next());
+    assert_eq!(b"b\nc", it.remainder());
+}
+
+#[test]
+fn rlines_from_end() {
+    let got: Vec<&[u8]> = rlines(b"a\nb\nc").collect();
+    assert_eq!(vec![&b"c"[..], b"b", b"a"], got);
+    let got: Vec<&[u8]> = rlines(b"a\nb\n").collect();
+    assert_eq!(vec![&b"b"[..], b"a"], got);
+    let got: Vec<&[u8]> = rlines(b"a\r\n\r\nb\r\n").collect();
+    assert_eq!(vec![&b"b"[..], b"", b"a"], got);
+    assert_eq!(0, rlines(b"").count());
+    assert_eq!(vec![&b""[..]], rlines(b"\n").collect::<Vec<_>>());
+
+    // Lines may be taken from both ends until they meet.
+    let mut it = lines(b"a\nb\nc\nd\n");
+    assert_eq!(Some(&b"a"[..]), it.next());
+    assert_eq!(Some(&b"d"[..]), it.next_back());
+    assert_eq!(b"b\nc\n", it.remainder());
+    assert_eq!(Some(&b"c"[..]), it.next_back());
+    assert_eq!(Some(&b"b"[..]), it.next());
+    assert_eq!(None, it.next());
+    assert_eq!(None, it.next_back());
+}
+
+quickcheck! {
+    fn qc_lines_matches_naive(corpus: Vec<u8>) -> bool {
+        // Use a small alphabet so that terminators are common.
+        let data: Vec<u8> = corpus
+            .iter()
+            .map(|&b| [b'a', b'\r', b'\n'][b as usize % 3])
+            .collect();
+        let mut expected: Vec<&[u8]> = data.split(|&b| b == b'\n').collect();
+        let last = expected.pop().unwrap();
+        for line in expected.iter_mut() {
+            if line.last() == Some(&b'\r') {
+                *line = &line[..line.len() - 1];
+            }
+        }
+        if !last.is_empty() {
+            expected.push(last);
+        }
+        let got: Vec<&[u8]> = lines(&data).collect();
+        let joined: Vec<u8> =
+            lines_with_terminator(&data).flatten().copied().collect();
+        got == expected && joined == data
+    }
+
+    fn qc_rlines_is_reverse_of_lines(corpus: Vec<u8>) -> bool {
+        let data: Vec<u8> = corpus
+            .iter()
+            .map(|&b| [b'a', b'\r', b'\n'][b as usize % 3])
+            .collect();
+        let mut expected: Vec<&[u8]> = lines(&data).collect();
+        expected.reverse();
+        let got: Vec<&[u8]> = rlines(&data).collect();
+        let mut terminated: Vec<&[u8]> =
+            lines_with_terminator(&data).rev().collect();
+        terminated.reverse();
+        got == expected
+            && terminated == lines_with_terminator(&data).collect::<Vec<_>>()
+    }
+}
+
 quickcheck! {
     fn qc_memchr_double_ended_iter(
         needle: u8, data: Vec<u8>, take_side: Vec<bool>
@@ -95,6 +392,47 @@ quickcheck! {
             .eq(positions3(needle1, needle2, needle3, &data))
     }
 
+    fn qc_memchr_double_ended_iter_dense(
+        data: Vec<u8>, take_side: Vec<bool>
+    ) -> bool {
+        // The tests above almost never find a match in random data, so use a
+        // small alphabet here to ensure that the front and back of the
+        // iterator frequently meet in the middle.
+        let data: Vec<u8> = data.iter().map(|&b| b % 4).collect();
+        let mut take_side = take_side;
+        if take_side.is_empty() { take_side.push(false) };
+        let sides = || take_side.iter().cycle().cloned();
+
+        let found1 = double_ended_take(Memchr::new(0, &data), sides());
+        let found2 = double_ended_take(Memchr2::new(0, 1, &data), sides());
+        let found3 = double_ended_take(Memchr3::new(0, 1, 2, &data), sides());
+        found1.iter().cloned().eq(positions1(0, &data))
+            && found2.iter().cloned().eq(positions2(0, 1, &data))
+            && found3.iter().cloned().eq(positions3(0, 1, 2, &data))
+    }
+
+    fn qc_memchr_remainder(data: Vec<u8>, take_side: Vec<bool>) -> bool {
+        // The remainder should always be the bytes strictly between the
+        // last match found from the front and the last match found from the
+        // back.
+        let data: Vec<u8> = data.iter().map(|&b| b % 4).collect();
+        let mut it = Memchr::new(0, &data);
+        let (mut start, mut end) = (0, data.len());
+        for take_front in take_side {
+            if take_front {
+                if let Some(i) = it.next() {
+                    start = i + 1;
+                }
+            } else if let Some(i) = it.next_back() {
+                end = i;
+            }
+            if it.remainder() != &data[start..end] {
+                return false;
+            }
+        }
+        true
+    }
+
     fn qc_memchr1_iter(data: Vec<u8>) -> bool {
         let needle = 0;
         let answer = positions1(needle, &data);
@@ -129,6 +467,21 @@ quickcheck! {
         answer.eq(Memchr3::new(needle1, needle2, needle3, &data))
     }
 
+    fn qc_memchr_count_remaining(
+        data: Vec<u8>, front: u8, back: u8
+    ) -> bool {
+        let data: Vec<u8> = data.iter().map(|&b| b % 4).collect();
+        let mut it = Memchr::new(0, &data);
+        for _ in 0..front % 8 {
+            it.next();
+        }
+        for _ in 0..back % 8 {
+            it.next_back();
+        }
+        let expected = it.count_remaining();
+        expected == it.count()
+    }
+
     fn qc_memchr3_rev_iter(data: Vec<u8>) -> bool {
         let needle1 = 0;
         let needle2 = 1;
@@ -1,10 +1,21 @@
 use quickcheck::quickcheck;
 
 use crate::{
-    memchr,
+    count, find_ascii_control, find_ascii_control_except, find_elem, memchr,
     memchr::{fallback, naive},
-    memchr2, memchr3, memrchr, memrchr2, memrchr3,
+    memchr2, memchr2_count, memchr2_iter, memchr2_u16, memchr2_which, memchr3,
+    memchr3_count, memchr3_iter, memchr3_u16, memchr3_which, memchr_all_into,
+    memchr_ascii_whitespace, memchr_batch, memchr_fn, memchr_in, memchr_iter,
+    memchr_mask, memchr_nearest, memchr_not, memchr_not_ascii_whitespace,
+    memchr_not_range, memchr_nth, memchr_range, memchr_set, memchr_u16,
+    memrchr, memrchr2, memrchr2_u16, memrchr3, memrchr3_u16, memrchr_all_into,
+    memrchr_ascii_whitespace, memrchr_in, memrchr_not,
+    memrchr_not_ascii_whitespace, memrchr_not_range, memrchr_nth,
+    memrchr_range, memrchr_set, memrchr_u16, mismatch, rsplit_once,
+    split_once,
     tests::memchr::testdata::memchr_tests,
+    trim, trim_any, trim_end, trim_end_any, trim_start, trim_start_any,
+    try_memchr_in, try_memrchr_in, ByteSet, Config, MemchrSet,
 };
 
 #[test]
@@ -91,6 +102,548 @@ fn memrchr3_fallback_find() {
     }
 }
 
+#[cfg(all(
+    not(miri),
+    target_arch = "x86_64",
+    memchr_runtime_simd,
+    memchr_runtime_avx512
+))]
+mod avx512 {
+    use quickcheck::quickcheck;
+
+    use crate::{
+        memchr::{naive, x86::avx512},
+        tests::memchr::testdata::memchr_tests,
+    };
+
+    // The AVX-512 routines are only used when the CPU supports them, so these
+    // tests call them directly to make sure they agree with every other
+    // implementation whenever they can run.
+
+    #[test]
+    fn memchr1_avx512_find() {
+        if !avx512::is_available() {
+            return;
+        }
+        for test in memchr_tests() {
+            test.one(false, |n1, h| unsafe { avx512::memchr(n1, h) });
+            test.one(true, |n1, h| unsafe { avx512::memrchr(n1, h) });
+        }
+    }
+
+    #[test]
+    fn memchr2_avx512_find() {
+        if !avx512::is_available() {
+            return;
+        }
+        for test in memchr_tests() {
+            test.two(false, |n1, n2, h| unsafe { avx512::memchr2(n1, n2, h) });
+            test.two(true, |n1, n2, h| unsafe { avx512::memrchr2(n1, n2, h) });
+        }
+    }
+
+    #[test]
+    fn memchr3_avx512_find() {
+        if !avx512::is_available() {
+            return;
+        }
+        for test in memchr_tests() {
+            test.three(false, |n1, n2, n3, h| unsafe {
+                avx512::memchr3(n1, n2, n3, h)
+            });
+            test.three(true, |n1, n2, n3, h| unsafe {
+                avx512::memrchr3(n1, n2, n3, h)
+            });
+        }
+    }
+
+    quickcheck! {
+        fn qc_avx512_matches_naive(
+            n1: u8, n2: u8, n3: u8, corpus: Vec<u8>
+        ) -> bool {
+            if !avx512::is_available() {
+                return true;
+            }
+            // Repeat the corpus so that the vector loops are exercised more
+            // often than not.
+            let h = &corpus.repeat(4);
+            unsafe {
+                avx512::memchr(n1, h) == naive::memchr(n1, h)
+                    && avx512::memchr2(n1, n2, h) == naive::memchr2(n1, n2, h)
+                    && avx512::memchr3(n1, n2, n3, h)
+                        == naive::memchr3(n1, n2, n3, h)
+                    && avx512::memrchr(n1, h) == naive::memrchr(n1, h)
+                    && avx512::memrchr2(n1, n2, h)
+                        == naive::memrchr2(n1, n2, h)
+                    && avx512::memrchr3(n1, n2, n3, h)
+                        == naive::memrchr3(n1, n2, n3, h)
+            }
+        }
+    }
+}
+
+#[test]
+fn detected_backend() {
+    use crate::arch::{self, Backend};
+
+    let backend: Backend = arch::detected();
+    assert_eq!(backend, arch::detected());
+    // Searching shouldn't change the cached result.
+    assert_eq!(Some(1), memchr(b'a', b"xa"));
+    assert_eq!(backend, arch::detected());
+
+    #[cfg(all(
+        not(miri),
+        feature = "std",
+        target_arch = "x86_64",
+        memchr_runtime_avx
+    ))]
+    {
+        let avx512 = cfg!(memchr_runtime_avx512)
+            && is_x86_feature_detected!("avx2")
+            && is_x86_feature_detected!("avx512f")
+            && is_x86_feature_detected!("avx512bw");
+        let expected = if avx512 {
+            Backend::Avx512
+        } else if is_x86_feature_detected!("avx2") {
+            Backend::Avx2
+        } else {
+            Backend::Sse2
+        };
+        assert_eq!(expected, backend);
+    }
+    #[cfg(any(miri, feature = "force-fallback"))]
+    assert_eq!(Backend::Scalar, backend);
+}
+
+#[test]
+fn has_simd_backend_agrees_with_detected() {
+    use crate::arch::{self, Backend};
+
+    // A vectorized implementation is always usable when one was compiled,
+    // since SSE2 is part of the x86_64 baseline.
+    let scalar = matches!(arch::detected(), Backend::Libc | Backend::Scalar);
+    assert_eq!(!scalar, arch::has_simd_backend());
+    #[cfg(any(miri, feature = "force-fallback"))]
+    assert!(!arch::has_simd_backend());
+}
+
+// The vectorized routines hand short haystacks off to narrower vectors or to
+// a scalar loop. This checks that each haystack length is handled by the
+// implementation we expect, so that a regression to a slower path for long
+// haystacks doesn't go unnoticed.
+#[cfg(all(not(miri), feature = "std"))]
+#[test]
+fn backend_used_at_length_boundaries() {
+    use crate::arch::{self, last_backend_used, Backend};
+
+    // The minimum haystack length for which each vectorized implementation
+    // is used. Shorter haystacks use the next entry in the list.
+    fn expected(detected: Backend, reverse: bool, len: usize) -> Backend {
+        let tiers: &[(Backend, usize)] = match (detected, reverse) {
+            (Backend::Avx512, false) => &[
+                (Backend::Avx512, 64),
+                (Backend::Avx2, 32),
+                (Backend::Sse2, 16),
+            ],
+            // The AVX2 reverse search uses a scalar loop instead of SSE2 for
+            // short haystacks.
+            (Backend::Avx512, true) => {
+                &[(Backend::Avx512, 64), (Backend::Avx2, 32)]
+            }
+            (Backend::Avx2, false) => {
+                &[(Backend::Avx2, 32), (Backend::Sse2, 16)]
+            }
+            (Backend::Avx2, true) => &[(Backend::Avx2, 32)],
+            (Backend::Sse2, _) => &[(Backend::Sse2, 16)],
+            (Backend::Simd128, _) => &[(Backend::Simd128, 16)],
+            (other, _) => return other,
+        };
+        tiers
+            .iter()
+            .find(|&&(_, min)| len >= min)
+            .map_or(Backend::Scalar, |&(backend, _)| backend)
+    }
+
+    let detected = arch::detected();
+    for len in 1..=200 {
+        let mut haystack = vec![b'a'; len];
+        haystack[len / 2] = b'z';
+
+        last_backend_used();
+        assert_eq!(Some(len / 2), memchr(b'z', &haystack));
+        let backend = last_backend_used();
+        assert_eq!(Some(expected(detected, false, len)), backend, "{}", len);
+
+        assert_eq!(Some(len / 2), memrchr(b'z', &haystack));
+        let backend = last_backend_used();
+        assert_eq!(Some(expected(detected, true, len)), backend, "{}", len);
+    }
+
+    // Empty haystacks never reach an implementation.
+    assert_eq!(None, memchr(b'z', b""));
+    assert_eq!(None, last_backend_used());
+}
+
+// Only the single byte routines record a backend for every implementation,
+// but the AVX-512 routines record one for two and three bytes too, since
+// they're the only way to tell whether AVX-512 handled a search.
+#[cfg(all(not(miri), feature = "std"))]
+#[test]
+fn backend_used_by_avx512_multi_byte_search() {
+    use crate::arch::{self, last_backend_used, Backend};
+
+    if arch::detected() != Backend::Avx512 {
+        return;
+    }
+    for len in 64..=200 {
+        let mut haystack = vec![b'a'; len];
+        haystack[len / 2] = b'z';
+        let check = |name: &str, found: Option<usize>| {
+            assert_eq!(Some(len / 2), found, "{}, {}", name, len);
+            let backend = last_backend_used();
+            assert_eq!(Some(Backend::Avx512), backend, "{}, {}", name, len);
+        };
+        check("memchr2", memchr2(b'y', b'z', &haystack));
+        check("memchr3", memchr3(b'x', b'y', b'z', &haystack));
+        check("memrchr2", memrchr2(b'y', b'z', &haystack));
+        check("memrchr3", memrchr3(b'x', b'y', b'z', &haystack));
+    }
+}
+
+// This allocates a haystack a little over 2GB in size, so it's ignored by
+// default. Run it with 'cargo test -- --ignored huge_haystack'. Since the
+// buffer is zeroed, most of it is typically never backed by real memory.
+//
+// All offset arithmetic in the search routines is done on pointers that stay
+// within the haystack (which Rust guarantees is at most isize::MAX bytes) or
+// on usize differences between them, so positions past i32::MAX should be
+// reported correctly. This test checks that.
+#[test]
+#[ignore]
+#[cfg(target_pointer_width = "64")]
+fn huge_haystack_offsets() {
+    let pos = i32::MAX as usize + 10;
+    let mut haystack = vec![0u8; i32::MAX as usize + 100];
+    haystack[pos] = b'z';
+    haystack[pos + 1] = b'y';
+
+    assert_eq!(Some(pos), memchr(b'z', &haystack));
+    assert_eq!(Some(pos), memchr2(b'z', b'x', &haystack));
+    assert_eq!(Some(pos), memchr3(b'z', b'y', b'x', &haystack));
+    assert_eq!(Some(pos), memrchr(b'z', &haystack));
+    assert_eq!(Some(pos + 1), memrchr2(b'z', b'y', &haystack));
+    assert_eq!(Some(pos + 1), memrchr3(b'z', b'y', b'x', &haystack));
+    assert_eq!(1, count(b'z', &haystack));
+    assert_eq!(Some(pos), crate::memmem::find(&haystack, b"zy"));
+    assert_eq!(Some(pos), crate::memmem::rfind(&haystack, b"zy"));
+}
+
+#[test]
+fn count1() {
+    for test in memchr_tests() {
+        test.count_one(count);
+    }
+}
+
+#[test]
+fn count1_fallback() {
+    for test in memchr_tests() {
+        test.count_one(fallback::count);
+    }
+}
+
+#[test]
+fn count2() {
+    for test in memchr_tests() {
+        test.count_two(memchr2_count);
+    }
+}
+
+#[test]
+fn count2_fallback() {
+    for test in memchr_tests() {
+        test.count_two(fallback::count2);
+    }
+}
+
+#[test]
+fn count3() {
+    for test in memchr_tests() {
+        test.count_three(memchr3_count);
+    }
+}
+
+#[test]
+fn count3_fallback() {
+    for test in memchr_tests() {
+        test.count_three(fallback::count3);
+    }
+}
+
+#[test]
+fn memchr_nth_find() {
+    for test in memchr_tests() {
+        test.nth_one(false, memchr_nth);
+    }
+}
+
+#[test]
+fn memchr_nth_fallback_find() {
+    for test in memchr_tests() {
+        test.nth_one(false, fallback::memchr_nth);
+    }
+}
+
+#[test]
+fn memrchr_nth_find() {
+    for test in memchr_tests() {
+        test.nth_one(true, memrchr_nth);
+    }
+}
+
+#[test]
+fn memrchr_nth_fallback_find() {
+    for test in memchr_tests() {
+        test.nth_one(true, fallback::memrchr_nth);
+    }
+}
+
+#[test]
+fn memchr_all_into_find() {
+    for test in memchr_tests() {
+        test.all_into_one(false, memchr_all_into);
+        test.all_into_one(false, |n1, h, out| {
+            fallback::memchr_all_into(n1, out, h)
+        });
+    }
+}
+
+#[test]
+fn memrchr_all_into_find() {
+    for test in memchr_tests() {
+        test.all_into_one(true, memrchr_all_into);
+        test.all_into_one(true, |n1, h, out| {
+            fallback::memrchr_all_into(n1, out, h)
+        });
+    }
+}
+
+#[test]
+fn memchr_all_into_full() {
+    let haystack = "a".repeat(300);
+    let mut out = [0; 100];
+    assert_eq!(100, memchr_all_into(b'a', haystack.as_bytes(), &mut out));
+    assert!(out.iter().cloned().eq(0..100));
+    assert_eq!(100, memrchr_all_into(b'a', haystack.as_bytes(), &mut out));
+    assert!(out.iter().cloned().eq((200..300).rev()));
+    assert_eq!(0, memchr_all_into(b'a', haystack.as_bytes(), &mut []));
+    assert_eq!(0, memrchr_all_into(b'a', haystack.as_bytes(), &mut []));
+}
+
+#[test]
+fn memchr_not_find() {
+    single_byte_tests(b'a', b'z', |h| memchr_not(b'a', h));
+}
+
+#[test]
+fn memchr_not_fallback_find() {
+    single_byte_tests(b'a', b'z', |h| fallback::memchr_not(b'a', h));
+}
+
+#[test]
+fn memrchr_not_find() {
+    single_byte_tests(b'a', b'z', |h| memrchr_not(b'a', h));
+}
+
+#[test]
+fn memrchr_not_fallback_find() {
+    single_byte_tests(b'a', b'z', |h| fallback::memrchr_not(b'a', h));
+}
+
+#[test]
+fn memchr_range_find() {
+    single_byte_tests(b'a', b'5', |h| memchr_range(b'0', b'9', h));
+    single_byte_tests(b'a', 0xFF, |h| memchr_range(0x80, 0xFF, h));
+    single_byte_tests(0xFF, b'\x00', |h| memchr_range(0x00, 0x7F, h));
+}
+
+#[test]
+fn memchr_range_fallback_find() {
+    single_byte_tests(b'a', b'5', |h| fallback::memchr_range(b'0', b'9', h));
+}
+
+#[test]
+fn memrchr_range_find() {
+    single_byte_tests(b'a', b'5', |h| memrchr_range(b'0', b'9', h));
+    single_byte_tests(b'a', 0xFF, |h| memrchr_range(0x80, 0xFF, h));
+    single_byte_tests(0xFF, b'\x00', |h| memrchr_range(0x00, 0x7F, h));
+}
+
+#[test]
+fn memrchr_range_fallback_find() {
+    single_byte_tests(b'a', b'5', |h| fallback::memrchr_range(b'0', b'9', h));
+}
+
+#[test]
+fn memchr_not_range_find() {
+    single_byte_tests(b'5', b'a', |h| memchr_not_range(b'0', b'9', h));
+    single_byte_tests(b'5', b'/', |h| memchr_not_range(b'0', b'9', h));
+    single_byte_tests(b'a', 0x80, |h| memchr_not_range(0x00, 0x7F, h));
+}
+
+#[test]
+fn memchr_not_range_fallback_find() {
+    single_byte_tests(b'5', b'a', |h| {
+        fallback::memchr_not_range(b'0', b'9', h)
+    });
+}
+
+#[test]
+fn memrchr_not_range_find() {
+    single_byte_tests(b'5', b'a', |h| memrchr_not_range(b'0', b'9', h));
+    single_byte_tests(b'5', b'/', |h| memrchr_not_range(b'0', b'9', h));
+    single_byte_tests(b'a', 0x80, |h| memrchr_not_range(0x00, 0x7F, h));
+}
+
+#[test]
+fn memrchr_not_range_fallback_find() {
+    single_byte_tests(b'5', b'a', |h| {
+        fallback::memrchr_not_range(b'0', b'9', h)
+    });
+}
+
+#[test]
+fn memchr_ascii_whitespace_find() {
+    for &b in b" \t\n\x0C\r" {
+        single_byte_tests(b'a', b, memchr_ascii_whitespace);
+        single_byte_tests(b'a', b, memrchr_ascii_whitespace);
+        single_byte_tests(b'a', b, fallback::memchr_ascii_whitespace);
+        single_byte_tests(b'a', b, fallback::memrchr_ascii_whitespace);
+    }
+    // Vertical tab is not whitespace according to u8::is_ascii_whitespace.
+    single_byte_tests(b'\x0B', b' ', memchr_ascii_whitespace);
+    single_byte_tests(b'\x0B', b' ', memrchr_ascii_whitespace);
+}
+
+#[test]
+fn find_ascii_control_find() {
+    for b in (0x00..0x20).chain(Some(0x7F)) {
+        single_byte_tests(b'a', b, find_ascii_control);
+        single_byte_tests(0x80, b, find_ascii_control);
+        single_byte_tests(b'~', b, fallback::find_ascii_control);
+    }
+    // The bytes just outside of the control ranges are not control bytes.
+    for &b in &[b' ', b'~', 0x80, 0xFF] {
+        single_byte_tests(b, b'\n', find_ascii_control);
+    }
+}
+
+#[test]
+fn find_ascii_control_except_find() {
+    let haystack = b"a\tb\nc\r\nd\x1b";
+    assert_eq!(Some(1), find_ascii_control_except(b"", haystack));
+    assert_eq!(Some(3), find_ascii_control_except(b"\t", haystack));
+    assert_eq!(Some(8), find_ascii_control_except(b"\t\r\n", haystack));
+    assert_eq!(None, find_ascii_control_except(b"\t\r\n\x1b", haystack));
+    assert_eq!(None, find_ascii_control_except(b"\n", b""));
+    // Allowing bytes that aren't control characters changes nothing.
+    assert_eq!(Some(1), find_ascii_control_except(b"abc", haystack));
+}
+
+#[test]
+fn mismatch_find() {
+    // Use lengths that cover the scalar, single vector and unrolled paths,
+    // with every possible position for the first difference.
+    let a: Vec<u8> = (0..200u8).collect();
+    for offset in 0..4 {
+        let a = &a[offset..];
+        for len in 0..a.len() {
+            let a = &a[..len];
+            assert_eq!(None, mismatch(a, a));
+            assert_eq!(None, fallback::mismatch(a, a));
+            for i in 0..len {
+                let mut b = a.to_vec();
+                b[i] = !b[i];
+                b[len - 1] ^= 1;
+                assert_eq!(Some(i), mismatch(a, &b), "len: {}", len);
+                assert_eq!(Some(i), fallback::mismatch(a, &b));
+            }
+        }
+    }
+
+    // A strict prefix differs at the end of the shorter slice.
+    assert_eq!(Some(3), mismatch(b"abc", b"abcd"));
+    assert_eq!(Some(3), mismatch(b"abcd", b"abc"));
+    assert_eq!(Some(0), mismatch(b"", b"a"));
+    assert_eq!(None, mismatch(b"", b""));
+    assert_eq!(Some(1), mismatch(b"abc", b"a"));
+}
+
+#[test]
+fn memchr_not_ascii_whitespace_find() {
+    for &b in b" \t\n\x0C\r" {
+        single_byte_tests(b, b'\x0B', memchr_not_ascii_whitespace);
+        single_byte_tests(b, b'\x0B', memrchr_not_ascii_whitespace);
+        single_byte_tests(b, b'a', fallback::memchr_not_ascii_whitespace);
+        single_byte_tests(b, b'a', fallback::memrchr_not_ascii_whitespace);
+    }
+}
+
+/// Tests a search routine on haystacks consisting entirely of `fill`, except
+/// for possibly a single `other` byte, at a variety of lengths, positions and
+/// alignments. The routine is expected to report the position of `other`.
+/// Since there is at most one such byte, this works for both forward and
+/// reverse routines.
+fn single_byte_tests<F: Fn(&[u8]) -> Option<usize>>(
+    fill: u8,
+    other: u8,
+    f: F,
+) {
+    for align in 0..4 {
+        for len in 0..200 {
+            let mut data = vec![fill; align + len];
+            assert_eq!(
+                None,
+                f(&data[align..]),
+                "len: {}, alignment: {}",
+                len,
+                align
+            );
+            for pos in 0..len {
+                data[align + pos] = other;
+                assert_eq!(
+                    Some(pos),
+                    f(&data[align..]),
+                    "len: {}, position: {}, alignment: {}",
+                    len,
+                    pos,
+                    align
+                );
+                data[align + pos] = fill;
+            }
+        }
+    }
+}
+
+quickcheck! {
+    fn qc_mismatch_matches_naive(
+        prefix: Vec<u8>,
+        a: Vec<u8>,
+        b: Vec<u8>
+    ) -> bool {
+        // A shared prefix makes the first difference land anywhere, rather
+        // than almost always at the start.
+        let a: Vec<u8> = prefix.iter().chain(&a).map(|&x| x % 2).collect();
+        let b: Vec<u8> = prefix.iter().chain(&b).map(|&x| x % 2).collect();
+        let len = core::cmp::min(a.len(), b.len());
+        let expected = a.iter().zip(&b).position(|(x, y)| x != y).or(
+            if a.len() != b.len() { Some(len) } else { None }
+        );
+        mismatch(&a, &b) == expected
+    }
+}
+
 quickcheck! {
     fn qc_memchr1_matches_naive(n1: u8, corpus: Vec<u8>) -> bool {
         memchr(n1, &corpus) == naive::memchr(n1, &corpus)
@@ -132,3 +685,788 @@ quickcheck! {
         memrchr3(n1, n2, n3, &corpus) == naive::memrchr3(n1, n2, n3, &corpus)
     }
 }
+
+quickcheck! {
+    fn qc_count1_matches_iter(n1: u8, corpus: Vec<u8>) -> bool {
+        let expected = memchr_iter(n1, &corpus).count();
+        count(n1, &corpus) == expected
+            && fallback::count(n1, &corpus) == expected
+            && naive::count(n1, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_count2_matches_iter(n1: u8, n2: u8, corpus: Vec<u8>) -> bool {
+        let expected = memchr2_iter(n1, n2, &corpus).count();
+        memchr2_count(n1, n2, &corpus) == expected
+            && fallback::count2(n1, n2, &corpus) == expected
+            && naive::count2(n1, n2, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_count3_matches_iter(
+        n1: u8, n2: u8, n3: u8,
+        corpus: Vec<u8>
+    ) -> bool {
+        let expected = memchr3_iter(n1, n2, n3, &corpus).count();
+        memchr3_count(n1, n2, n3, &corpus) == expected
+            && fallback::count3(n1, n2, n3, &corpus) == expected
+            && naive::count3(n1, n2, n3, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_count_repeated_needles(n1: u8, corpus: Vec<u8>) -> bool {
+        let expected = count(n1, &corpus);
+        memchr2_count(n1, n1, &corpus) == expected
+            && memchr3_count(n1, n1, n1, &corpus) == expected
+            && fallback::count2(n1, n1, &corpus) == expected
+            && fallback::count3(n1, n1, n1, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_not_matches_naive(
+        n1: u8, run: usize, corpus: Vec<u8>
+    ) -> bool {
+        // Prefix the random corpus with a run of the needle, since otherwise
+        // the first byte of a random corpus almost never matches.
+        let mut data = vec![n1; run % 300];
+        data.extend(corpus);
+        memchr_not(n1, &data) == naive::memchr_not(n1, &data)
+    }
+}
+
+quickcheck! {
+    fn qc_memrchr_not_matches_naive(
+        n1: u8, run: usize, corpus: Vec<u8>
+    ) -> bool {
+        let mut data = corpus;
+        data.extend(vec![n1; run % 300]);
+        memrchr_not(n1, &data) == naive::memrchr_not(n1, &data)
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_range_matches_naive(
+        lo: u8, hi: u8, corpus: Vec<u8>
+    ) -> bool {
+        let expected = corpus.iter().position(|&b| lo <= b && b <= hi);
+        memchr_range(lo, hi, &corpus) == expected
+            && fallback::memchr_range(lo, hi, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_memrchr_range_matches_naive(
+        lo: u8, hi: u8, corpus: Vec<u8>
+    ) -> bool {
+        let expected = corpus.iter().rposition(|&b| lo <= b && b <= hi);
+        memrchr_range(lo, hi, &corpus) == expected
+            && fallback::memrchr_range(lo, hi, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_not_range_matches_naive(
+        lo: u8, hi: u8, corpus: Vec<u8>
+    ) -> bool {
+        let expected = corpus.iter().position(|&b| b < lo || b > hi);
+        memchr_not_range(lo, hi, &corpus) == expected
+            && fallback::memchr_not_range(lo, hi, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_memrchr_not_range_matches_naive(
+        lo: u8, hi: u8, corpus: Vec<u8>
+    ) -> bool {
+        let expected = corpus.iter().rposition(|&b| b < lo || b > hi);
+        memrchr_not_range(lo, hi, &corpus) == expected
+            && fallback::memrchr_not_range(lo, hi, &corpus) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_ascii_whitespace_matches_naive(corpus: Vec<u8>) -> bool {
+        memchr_ascii_whitespace(&corpus)
+            == naive::memchr_ascii_whitespace(&corpus)
+            && memrchr_ascii_whitespace(&corpus)
+                == naive::memrchr_ascii_whitespace(&corpus)
+    }
+}
+
+quickcheck! {
+    fn qc_find_ascii_control_matches_is_ascii_control(
+        corpus: Vec<u8>, allowed: Vec<u8>
+    ) -> bool {
+        // Make control characters rare, so that matches often happen in
+        // the middle of long haystacks.
+        let data: Vec<u8> = corpus
+            .repeat(4)
+            .into_iter()
+            .map(|b| if b % 16 == 0 { b % 0x20 } else { b | 0x20 })
+            .collect();
+        let allowed: Vec<u8> = allowed.iter().map(|&b| b % 0x20).collect();
+        let expected = data.iter().position(|b| b.is_ascii_control());
+        let expected_except = data
+            .iter()
+            .position(|b| b.is_ascii_control() && !allowed.contains(b));
+        find_ascii_control(&data) == expected
+            && fallback::find_ascii_control(&data) == expected
+            && find_ascii_control_except(&allowed, &data) == expected_except
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_not_ascii_whitespace_matches_naive(
+        run: usize, corpus: Vec<u8>
+    ) -> bool {
+        // Surround the random corpus with whitespace, since otherwise the
+        // first and last bytes almost never are whitespace.
+        let mut data = b" \t\n\x0C\r".repeat(run % 60);
+        data.extend(corpus);
+        data.extend(b" \t\n\x0C\r".repeat(run % 60));
+        memchr_not_ascii_whitespace(&data)
+            == naive::memchr_not_ascii_whitespace(&data)
+            && memrchr_not_ascii_whitespace(&data)
+                == naive::memrchr_not_ascii_whitespace(&data)
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_nth_matches_iter(n: u8, n1: u8, corpus: Vec<u8>) -> bool {
+        // Use a small alphabet so that there are lots of matches.
+        let data: Vec<u8> = corpus.iter().map(|&b| b % 4).collect();
+        let (n, n1) = (n as usize % 64, n1 % 4);
+        let expected = memchr_iter(n1, &data).nth(n);
+        memchr_nth(n, n1, &data) == expected
+            && fallback::memchr_nth(n, n1, &data) == expected
+            && naive::memchr_nth(n, n1, &data) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_memrchr_nth_matches_iter(n: u8, n1: u8, corpus: Vec<u8>) -> bool {
+        let data: Vec<u8> = corpus.iter().map(|&b| b % 4).collect();
+        let (n, n1) = (n as usize % 64, n1 % 4);
+        let expected = memchr_iter(n1, &data).rev().nth(n);
+        memrchr_nth(n, n1, &data) == expected
+            && fallback::memrchr_nth(n, n1, &data) == expected
+            && naive::memrchr_nth(n, n1, &data) == expected
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_all_into_matches_iter(
+        size: u8, n1: u8, corpus: Vec<u8>
+    ) -> bool {
+        let data: Vec<u8> = corpus.iter().map(|&b| b % 4).collect();
+        let n1 = n1 % 4;
+        let expected: Vec<usize> =
+            memchr_iter(n1, &data).take(size as usize).collect();
+        let mut out = vec![0; size as usize];
+        let n = memchr_all_into(n1, &data, &mut out);
+        let mut out2 = vec![0; size as usize];
+        let n2 = fallback::memchr_all_into(n1, &mut out2, &data);
+        out[..n] == expected[..] && out2[..n2] == expected[..]
+    }
+}
+
+quickcheck! {
+    fn qc_memrchr_all_into_matches_iter(
+        size: u8, n1: u8, corpus: Vec<u8>
+    ) -> bool {
+        let data: Vec<u8> = corpus.iter().map(|&b| b % 4).collect();
+        let n1 = n1 % 4;
+        let expected: Vec<usize> =
+            memchr_iter(n1, &data).rev().take(size as usize).collect();
+        let mut out = vec![0; size as usize];
+        let n = memrchr_all_into(n1, &data, &mut out);
+        let mut out2 = vec![0; size as usize];
+        let n2 = fallback::memrchr_all_into(n1, &mut out2, &data);
+        out[..n] == expected[..] && out2[..n2] == expected[..]
+    }
+}
+
+#[test]
+fn memchr_u16_whole_code_units() {
+    // The needle's byte appears in every code unit, but only ever as part of
+    // a different code unit, except for the single real match. Vary the
+    // length and the position of the match so that every vector loop and
+    // every tail is exercised.
+    let (needle, decoy1, decoy2) = (0x000A, 0x0A00, 0x0A0B);
+    for len in 1..70 {
+        let mut haystack: Vec<u16> = (0..len)
+            .map(|i| if i % 2 == 0 { decoy1 } else { decoy2 })
+            .collect();
+        assert_eq!(None, memchr_u16(needle, &haystack));
+        assert_eq!(None, memrchr_u16(needle, &haystack));
+        assert_eq!(None, fallback::memchr_u16(needle, &haystack));
+        assert_eq!(None, fallback::memrchr_u16(needle, &haystack));
+        for i in 0..len {
+            let original = haystack[i];
+            haystack[i] = needle;
+            assert_eq!(Some(i), memchr_u16(needle, &haystack));
+            assert_eq!(Some(i), memrchr_u16(needle, &haystack));
+            assert_eq!(Some(i), memchr2_u16(needle, 0x0B0A, &haystack));
+            assert_eq!(
+                Some(i),
+                memrchr3_u16(0x0B0A, 0xFFFF, needle, &haystack)
+            );
+            assert_eq!(Some(i), fallback::memchr_u16(needle, &haystack));
+            assert_eq!(Some(i), fallback::memrchr_u16(needle, &haystack));
+            haystack[i] = original;
+        }
+    }
+    assert_eq!(None, memchr_u16(0, &[]));
+    assert_eq!(None, memrchr3_u16(0, 1, 2, &[]));
+}
+
+quickcheck! {
+    fn qc_memchr_u16_matches_naive(
+        n1: u16, n2: u16, n3: u16, corpus: Vec<u16>
+    ) -> bool {
+        // Restrict each byte of every code unit to a small alphabet, so that
+        // needles often match and often share one byte with a code unit.
+        let small = |u: u16| u & 0x0303;
+        let (n1, n2, n3) = (small(n1), small(n2), small(n3));
+        let h: Vec<u16> = corpus.repeat(3).into_iter().map(small).collect();
+        memchr_u16(n1, &h) == naive::memchr_u16(n1, &h)
+            && memchr2_u16(n1, n2, &h) == naive::memchr2_u16(n1, n2, &h)
+            && memchr3_u16(n1, n2, n3, &h)
+                == naive::memchr3_u16(n1, n2, n3, &h)
+            && memrchr_u16(n1, &h) == naive::memrchr_u16(n1, &h)
+            && memrchr2_u16(n1, n2, &h) == naive::memrchr2_u16(n1, n2, &h)
+            && memrchr3_u16(n1, n2, n3, &h)
+                == naive::memrchr3_u16(n1, n2, n3, &h)
+            && fallback::memchr3_u16(n1, n2, n3, &h)
+                == naive::memchr3_u16(n1, n2, n3, &h)
+            && fallback::memrchr3_u16(n1, n2, n3, &h)
+                == naive::memrchr3_u16(n1, n2, n3, &h)
+    }
+}
+
+#[test]
+fn find_elem_u32_whole_elements() {
+    // As with memchr_u16, the needle's low bits appear in every element, but
+    // only the single real match may be reported.
+    let (needle, decoy) = (0x0000_00FFu32, 0x00FF_00FFu32);
+    for len in 1..40 {
+        let mut haystack = vec![decoy; len];
+        assert_eq!(None, find_elem(needle, &haystack));
+        for i in 0..len {
+            haystack[i] = needle;
+            assert_eq!(Some(i), find_elem(needle, &haystack));
+            assert_eq!(Some(i), find_elem(needle as i32, &to_i32(&haystack)));
+            haystack[i] = decoy;
+        }
+    }
+    assert_eq!(None, find_elem(0u32, &[]));
+}
+
+fn to_i32(slice: &[u32]) -> Vec<i32> {
+    slice.iter().map(|&u| u as i32).collect()
+}
+
+quickcheck! {
+    fn qc_find_elem_matches_scalar(
+        n8: u8, n16: u16, n32: u32, corpus: Vec<u32>
+    ) -> bool {
+        // Use a small alphabet in each byte so that needles often match, and
+        // often match only some of the bytes of an element.
+        let corpus: Vec<u32> =
+            corpus.repeat(3).into_iter().map(|u| u & 0x0101_0101).collect();
+        let (n8, n16, n32) = (n8 & 0x01, n16 & 0x0101, n32 & 0x0101_0101);
+        let h8: Vec<u8> = corpus.iter().map(|&u| u as u8).collect();
+        let h16: Vec<u16> = corpus.iter().map(|&u| u as u16).collect();
+        let h32 = corpus;
+        let i8s: Vec<i8> = h8.iter().map(|&u| u as i8).collect();
+        let i16s: Vec<i16> = h16.iter().map(|&u| u as i16).collect();
+        let i32s = to_i32(&h32);
+
+        fn scalar<T: PartialEq>(needle: T, haystack: &[T]) -> Option<usize> {
+            haystack.iter().position(|x| *x == needle)
+        }
+        find_elem(n8, &h8) == scalar(n8, &h8)
+            && find_elem(n16, &h16) == scalar(n16, &h16)
+            && find_elem(n32, &h32) == scalar(n32, &h32)
+            && find_elem(n8 as i8, &i8s) == scalar(n8 as i8, &i8s)
+            && find_elem(n16 as i16, &i16s) == scalar(n16 as i16, &i16s)
+            && find_elem(n32 as i32, &i32s) == scalar(n32 as i32, &i32s)
+    }
+}
+
+quickcheck! {
+    fn qc_trim_matches_naive(byte: u8, set: Vec<u8>, corpus: Vec<u8>) -> bool {
+        // Use a small alphabet so that there are long runs to trim.
+        let data: Vec<u8> = corpus.iter().map(|&b| b % 3).collect();
+        let (byte, set): (u8, Vec<u8>) =
+            (byte % 3, set.iter().take(2).map(|&b| b % 3).collect());
+
+        let keep = |b: &u8| *b != byte;
+        let start = data.iter().position(keep).unwrap_or(data.len());
+        let end = data.iter().rposition(keep).map_or(0, |i| i + 1);
+        let keep_any = |b: &u8| !set.contains(b);
+        let start_any = data.iter().position(keep_any).unwrap_or(data.len());
+        let end_any = data.iter().rposition(keep_any).map_or(0, |i| i + 1);
+
+        trim_start(byte, &data) == &data[start..]
+            && trim_end(byte, &data) == &data[..end]
+            && trim(byte, &data) == &data[start..end.max(start)]
+            && trim_start_any(&set, &data) == &data[start_any..]
+            && trim_end_any(&set, &data) == &data[..end_any]
+            && trim_any(&set, &data) == &data[start_any..end_any.max(start_any)]
+    }
+}
+
+#[test]
+fn memchr_in_absolute_offsets() {
+    let haystack = b"a.b.c.d.e";
+    let n = haystack.len();
+    assert_eq!(Some(1), memchr_in(b'.', haystack, 0..n));
+    assert_eq!(Some(3), memchr_in(b'.', haystack, 2..n));
+    assert_eq!(Some(7), memchr_in(b'.', haystack, 7..n));
+    // Ranges at the very tail of the buffer.
+    assert_eq!(Some(8), memchr_in(b'e', haystack, n - 1..n));
+    assert_eq!(None, memchr_in(b'.', haystack, n - 1..n));
+    assert_eq!(None, memchr_in(b'e', haystack, n..n));
+    // The range's end is respected too.
+    assert_eq!(None, memchr_in(b'e', haystack, 0..n - 1));
+
+    // Exercise the vectorized paths with long ranges.
+    let mut long = vec![b'a'; 1000];
+    long[999] = b'z';
+    for start in 0..100 {
+        assert_eq!(Some(999), memchr_in(b'z', &long, start..1000));
+        assert_eq!(None, memchr_in(b'z', &long, start..999));
+    }
+}
+
+#[test]
+fn memrchr_in_absolute_offsets() {
+    let haystack = b"a.b.c.d.e";
+    let n = haystack.len();
+    assert_eq!(Some(7), memrchr_in(b'.', haystack, 0..n));
+    assert_eq!(Some(5), memrchr_in(b'.', haystack, 0..7));
+    assert_eq!(Some(1), memrchr_in(b'.', haystack, 0..3));
+    // Ranges at the very head of the buffer.
+    assert_eq!(Some(0), memrchr_in(b'a', haystack, 0..1));
+    assert_eq!(None, memrchr_in(b'.', haystack, 0..1));
+    assert_eq!(None, memrchr_in(b'a', haystack, 0..0));
+    // The range's start is respected too.
+    assert_eq!(None, memrchr_in(b'a', haystack, 1..n));
+
+    // Exercise the vectorized paths with long ranges.
+    let mut long = vec![b'a'; 1000];
+    long[0] = b'z';
+    for end in 900..1000 {
+        assert_eq!(Some(0), memrchr_in(b'z', &long, 0..end));
+        assert_eq!(None, memrchr_in(b'z', &long, 1..end));
+    }
+}
+
+quickcheck! {
+    fn qc_memrchr_in_matches_memrchr(
+        n1: u8,
+        corpus: Vec<u8>,
+        start: usize,
+        end: usize
+    ) -> bool {
+        let end = if corpus.is_empty() { 0 } else { end % corpus.len() };
+        let start = if end == 0 { 0 } else { start % end };
+        let expected = memrchr(n1, &corpus[start..end]).map(|i| start + i);
+        memrchr_in(n1, &corpus, start..end) == expected
+    }
+}
+
+/// Computes the mask returned by `memchr_mask` one bit at a time.
+fn scalar_mask(needle: u8, haystack: &[u8]) -> u64 {
+    let mut mask = 0;
+    for (i, &b) in haystack.iter().enumerate().take(64) {
+        if b == needle {
+            mask |= 1 << i;
+        }
+    }
+    mask
+}
+
+#[test]
+fn memchr_mask_every_length() {
+    // Fill the haystack so that matches land at irregular positions, and
+    // so that the bytes past its end would match if they were examined.
+    let data: Vec<u8> = (0..100u32).map(|i| (i * i % 7) as u8).collect();
+    for len in 0..=64 {
+        for needle in 0..8 {
+            let haystack = &data[..len];
+            let expected = scalar_mask(needle, haystack);
+            assert_eq!(expected, memchr_mask(needle, haystack), "{}", len);
+            assert_eq!(expected, fallback::memchr_mask(needle, haystack));
+            assert_eq!(expected, naive::memchr_mask(needle, haystack));
+        }
+        let all = [b'a'; 64];
+        let expected = if len == 64 { u64::MAX } else { (1 << len) - 1 };
+        assert_eq!(expected, memchr_mask(b'a', &all[..len]));
+        assert_eq!(0, memchr_mask(b'b', &all[..len]));
+    }
+    // Longer haystacks only report their first 64 positions.
+    assert_eq!(scalar_mask(3, &data), memchr_mask(3, &data));
+    assert_eq!(scalar_mask(3, &data[..64]), memchr_mask(3, &data));
+}
+
+quickcheck! {
+    fn qc_memchr_mask_matches_scalar(n1: u8, corpus: Vec<u8>) -> bool {
+        memchr_mask(n1, &corpus) == scalar_mask(n1, &corpus)
+    }
+}
+
+#[test]
+fn memchr_nearest_ties_and_boundaries() {
+    let haystack = b"a.bcd.e";
+    // Both dots are two bytes away from 3, so the left one wins.
+    assert_eq!(Some(1), memchr_nearest(b'.', haystack, 3));
+    assert_eq!(Some(5), memchr_nearest(b'.', haystack, 4));
+    assert_eq!(Some(5), memchr_nearest(b'.', haystack, 6));
+    assert_eq!(Some(1), memchr_nearest(b'.', haystack, 2));
+    // A match at the pivot has distance zero.
+    assert_eq!(Some(1), memchr_nearest(b'.', haystack, 1));
+    // Pivots at either end of the haystack.
+    assert_eq!(Some(1), memchr_nearest(b'.', haystack, 0));
+    assert_eq!(Some(5), memchr_nearest(b'.', haystack, haystack.len()));
+    assert_eq!(Some(0), memchr_nearest(b'a', haystack, haystack.len()));
+    assert_eq!(Some(6), memchr_nearest(b'e', haystack, 0));
+    assert_eq!(None, memchr_nearest(b'z', haystack, 0));
+    assert_eq!(None, memchr_nearest(b'z', haystack, haystack.len()));
+    assert_eq!(None, memchr_nearest(b'a', b"", 0));
+
+    // Exercise the vectorized paths with matches far from the pivot.
+    let mut long = vec![b'a'; 1000];
+    long[100] = b'z';
+    long[900] = b'z';
+    assert_eq!(Some(100), memchr_nearest(b'z', &long, 500));
+    assert_eq!(Some(900), memchr_nearest(b'z', &long, 501));
+    assert_eq!(Some(100), memchr_nearest(b'z', &long, 0));
+    assert_eq!(Some(900), memchr_nearest(b'z', &long, 1000));
+}
+
+#[test]
+#[should_panic]
+fn memchr_nearest_pivot_out_of_bounds() {
+    memchr_nearest(b'a', b"abc", 4);
+}
+
+quickcheck! {
+    fn qc_memchr_nearest_is_closest(
+        n1: u8,
+        corpus: Vec<u8>,
+        pivot: usize
+    ) -> bool {
+        let pivot = pivot % (corpus.len() + 1);
+        // The first match in order of distance, with ties going left.
+        let expected = (0..corpus.len())
+            .filter(|&i| corpus[i] == n1)
+            .min_by_key(|&i| (i.abs_diff(pivot), i));
+        memchr_nearest(n1, &corpus, pivot) == expected
+    }
+}
+
+#[test]
+fn memchr_which_coinciding_needles() {
+    assert_eq!(Some((1, b'b')), memchr2_which(b'b', b'b', b"abc"));
+    assert_eq!(Some((2, b'c')), memchr3_which(b'c', b'c', b'c', b"abc"));
+    assert_eq!(Some((0, b'a')), memchr3_which(b'x', b'a', b'a', b"abc"));
+    assert_eq!(None, memchr3_which(b'x', b'x', b'y', b"abc"));
+    assert_eq!(None, memchr2_which(b'a', b'b', b""));
+}
+
+quickcheck! {
+    fn qc_memchr2_which_matches_memchr2(
+        n1: u8,
+        n2: u8,
+        corpus: Vec<u8>
+    ) -> bool {
+        let expected = memchr2(n1, n2, &corpus).map(|i| (i, corpus[i]));
+        let got = memchr2_which(n1, n2, &corpus);
+        got == expected && got.iter().all(|&(_, b)| b == n1 || b == n2)
+    }
+
+    fn qc_memchr3_which_matches_memchr3(
+        n1: u8,
+        n2: u8,
+        n3: u8,
+        corpus: Vec<u8>
+    ) -> bool {
+        let expected = memchr3(n1, n2, n3, &corpus).map(|i| (i, corpus[i]));
+        let got = memchr3_which(n1, n2, n3, &corpus);
+        got == expected
+            && got.iter().all(|&(_, b)| b == n1 || b == n2 || b == n3)
+    }
+}
+
+/// Build a set from the given bytes, along with a 256 entry table with the
+/// same members to compare against.
+fn byte_set(members: &[u8]) -> (ByteSet, [bool; 256]) {
+    let (mut set, mut table) = (ByteSet::new(), [false; 256]);
+    for &b in members {
+        set.insert(b);
+        table[b as usize] = true;
+    }
+    (set, table)
+}
+
+#[test]
+fn memchr_set_every_position() {
+    let (empty, _) = byte_set(&[]);
+    let (set, _) = byte_set(b"\x00\x7F\x80\xFF,;");
+    for len in 0..100 {
+        let haystack = vec![b'a'; len];
+        assert_eq!(None, memchr_set(&empty, &haystack));
+        assert_eq!(None, memchr_set(&set, &haystack));
+        assert_eq!(None, memrchr_set(&empty, &haystack));
+        assert_eq!(None, memrchr_set(&set, &haystack));
+        for at in 0..len {
+            for &b in b"\x00\x7F\x80\xFF,;" {
+                let mut haystack = haystack.clone();
+                haystack[at] = b;
+                assert_eq!(Some(at), memchr_set(&set, &haystack));
+                assert_eq!(Some(at), memrchr_set(&set, &haystack));
+                assert_eq!(None, memchr_set(&empty, &haystack));
+                assert_eq!(None, memrchr_set(&empty, &haystack));
+                // Bytes that share a nibble with a member don't match.
+                haystack[at] = b ^ 0x10;
+                assert_eq!(None, memchr_set(&set, &haystack));
+                haystack[at] = b ^ 0x01;
+                assert_eq!(None, memchr_set(&set, &haystack));
+                assert_eq!(None, memrchr_set(&set, &haystack));
+            }
+        }
+    }
+}
+
+#[test]
+fn byte_set_classes_match_predicates() {
+    type Predicate = fn(&u8) -> bool;
+    let classes: &[(ByteSet, Predicate)] = &[
+        (ByteSet::ascii_digit(), u8::is_ascii_digit),
+        (ByteSet::ascii_hexdigit(), u8::is_ascii_hexdigit),
+        (ByteSet::ascii_alphabetic(), u8::is_ascii_alphabetic),
+        (ByteSet::ascii_alphanumeric(), u8::is_ascii_alphanumeric),
+        (ByteSet::ascii_whitespace(), u8::is_ascii_whitespace),
+        (ByteSet::ascii_punctuation(), u8::is_ascii_punctuation),
+    ];
+    for &(set, predicate) in classes {
+        assert_eq!(set, ByteSet::from_fn(|b| predicate(&b)));
+        for b in 0..=255u8 {
+            assert_eq!(predicate(&b), set.contains(b), "byte {}", b);
+        }
+    }
+}
+
+#[test]
+fn byte_set_construction() {
+    assert_eq!(3, ByteSet::from_bytes(b".,;").len());
+    assert!(ByteSet::new().is_empty());
+    assert!(ByteSet::from_bytes(b"").is_empty());
+    assert_eq!(256, ByteSet::from_range(0..=255).len());
+    assert_eq!(1, ByteSet::from_range(255..=255).len());
+    let (start, end) = (1, 0);
+    assert_eq!(0, ByteSet::from_range(start..=end).len());
+    assert_eq!(ByteSet::from_bytes(b"abc"), ByteSet::from_range(b'a'..=b'c'));
+
+    let mut set = ByteSet::from_bytes(b"ab");
+    set.insert(b'c');
+    set.insert(b'c');
+    set.remove(b'a');
+    set.remove(b'z');
+    assert_eq!(ByteSet::from_bytes(b"bc"), set);
+    assert_eq!("{98, 99}", format!("{:?}", set));
+}
+
+quickcheck! {
+    fn qc_byte_set_operations(a: Vec<u8>, b: Vec<u8>) -> bool {
+        let (sa, sb) = (ByteSet::from_bytes(&a), ByteSet::from_bytes(&b));
+        let (union, inter) = (sa.union(sb), sa.intersection(sb));
+        let complement = sa.complement();
+        (0..=255u8).all(|x| {
+            let (in_a, in_b) = (a.contains(&x), b.contains(&x));
+            sa.contains(x) == in_a
+                && union.contains(x) == (in_a || in_b)
+                && inter.contains(x) == (in_a && in_b)
+                && complement.contains(x) != in_a
+        }) && sa.len() + complement.len() == 256
+            && union.len() + inter.len() == sa.len() + sb.len()
+    }
+
+    fn qc_memchr_set_matches_table(members: Vec<u8>, corpus: Vec<u8>) -> bool {
+        let (set, table) = byte_set(&members);
+        let expected = corpus.iter().position(|&b| table[b as usize]);
+        (0..256).all(|b| set.contains(b as u8) == table[b])
+            && memchr_set(&set, &corpus) == expected
+    }
+
+    fn qc_memrchr_set_matches_table(
+        members: Vec<u8>,
+        corpus: Vec<u8>
+    ) -> bool {
+        let (set, table) = byte_set(&members);
+        let expected = corpus.iter().rposition(|&b| table[b as usize]);
+        let found: Vec<usize> = MemchrSet::new(&set, &corpus).collect();
+        let last = found.last().copied();
+        memrchr_set(&set, &corpus) == expected && last == expected
+    }
+
+    fn qc_memchr_set_matches_table_small_alphabet(
+        members: Vec<u8>,
+        corpus: Vec<u8>
+    ) -> bool {
+        // Restrict the haystack to bytes close to the members, so that
+        // matches occur often and at every offset.
+        let members: Vec<u8> =
+            members.iter().take(4).map(|&b| b | 0x0F).collect();
+        let corpus: Vec<u8> = corpus.iter().map(|&b| b | 0x0F).collect();
+        let (set, table) = byte_set(&members);
+        let expected = corpus.iter().position(|&b| table[b as usize]);
+        let rexpected = corpus.iter().rposition(|&b| table[b as usize]);
+        memchr_set(&set, &corpus) == expected
+            && memrchr_set(&set, &corpus) == rexpected
+    }
+}
+
+#[test]
+fn split_once_both_sides() {
+    let empty: &[u8] = b"";
+    assert_eq!(Some((&b"a"[..], &b"b=c"[..])), split_once(b'=', b"a=b=c"));
+    assert_eq!(Some((&b"a=b"[..], &b"c"[..])), rsplit_once(b'=', b"a=b=c"));
+    assert_eq!(None, split_once(b'=', b"abc"));
+    assert_eq!(None, rsplit_once(b'=', b"abc"));
+    assert_eq!(None, split_once(b'=', b""));
+    assert_eq!(None, rsplit_once(b'=', b""));
+    // A delimiter at either end leaves an empty side.
+    assert_eq!(Some((&b"key"[..], empty)), split_once(b'=', b"key="));
+    assert_eq!(Some((&b"key"[..], empty)), rsplit_once(b'=', b"key="));
+    assert_eq!(Some((empty, &b"v"[..])), split_once(b'=', b"=v"));
+    assert_eq!(Some((empty, empty)), rsplit_once(b'=', b"="));
+}
+
+quickcheck! {
+    fn qc_split_once_matches_str_split_once(
+        n1: u8,
+        corpus: Vec<u8>
+    ) -> bool {
+        // Restrict to a small alphabet so that the delimiter occurs often.
+        let corpus: String =
+            corpus.iter().map(|&b| (b'a' + b % 4) as char).collect();
+        let n1 = b'a' + n1 % 4;
+        fn as_bytes<'a>(
+            (a, b): (&'a str, &'a str),
+        ) -> (&'a [u8], &'a [u8]) {
+            (a.as_bytes(), b.as_bytes())
+        }
+        let delim = (n1 as char).to_string();
+        split_once(n1, corpus.as_bytes())
+            == corpus.split_once(&*delim).map(as_bytes)
+            && rsplit_once(n1, corpus.as_bytes())
+                == corpus.rsplit_once(&*delim).map(as_bytes)
+    }
+}
+
+#[test]
+#[should_panic]
+fn memchr_in_out_of_bounds() {
+    memchr_in(b'a', b"abc", 2..4);
+}
+
+#[test]
+fn try_memchr_in_rejects_bad_ranges() {
+    let haystack = b"a.b.c";
+    assert_eq!(Ok(Some(3)), try_memchr_in(b'.', haystack, 2..5));
+    assert_eq!(Ok(Some(3)), try_memrchr_in(b'.', haystack, 0..5));
+    assert_eq!(Ok(None), try_memchr_in(b'.', haystack, 5..5));
+
+    // Over-long ranges.
+    let err = try_memchr_in(b'.', haystack, 2..6).unwrap_err();
+    assert_eq!(2..6, err.range());
+    assert_eq!(5, err.haystack_len());
+    assert!(!err.is_inverted());
+    assert!(try_memchr_in(b'.', haystack, 6..6).is_err());
+    assert!(try_memrchr_in(b'.', haystack, 0..usize::MAX).is_err());
+    assert!(try_memrchr_in(b'.', b"", 0..1).is_err());
+
+    // Inverted ranges, including ones whose end is also out of bounds.
+    #[allow(clippy::reversed_empty_ranges)]
+    let (inverted, inverted_long) = (3..2, usize::MAX..6);
+    let err = try_memchr_in(b'.', haystack, inverted).unwrap_err();
+    assert!(err.is_inverted());
+    assert!(try_memrchr_in(b'.', haystack, inverted_long).is_err());
+}
+
+quickcheck! {
+    fn qc_try_memchr_in_matches_memchr_in(
+        haystack: Vec<u8>,
+        start: usize,
+        end: usize
+    ) -> bool {
+        // Keep the bounds near the haystack so that both valid and invalid
+        // ranges are common.
+        let (start, end) = (start % 40, end % 40);
+        let (n1, valid) = (b'a', start <= end && end <= haystack.len());
+        let (fwd, rev) = (
+            try_memchr_in(n1, &haystack, start..end),
+            try_memrchr_in(n1, &haystack, start..end),
+        );
+        if !valid {
+            return fwd.is_err() && rev.is_err();
+        }
+        fwd == Ok(memchr_in(n1, &haystack, start..end))
+            && rev == Ok(memrchr_in(n1, &haystack, start..end))
+    }
+
+    fn qc_config_matches_default(
+        threshold: u8,
+        n1: u8,
+        n2: u8,
+        n3: u8,
+        corpus: Vec<u8>
+    ) -> bool {
+        let c = Config::new().simd_threshold(threshold as usize);
+        let h = &corpus[..];
+        c.memchr(n1, h) == memchr(n1, h)
+            && c.memchr2(n1, n2, h) == memchr2(n1, n2, h)
+            && c.memchr3(n1, n2, n3, h) == memchr3(n1, n2, n3, h)
+            && c.memrchr(n1, h) == memrchr(n1, h)
+            && c.memrchr2(n1, n2, h) == memrchr2(n1, n2, h)
+            && c.memrchr3(n1, n2, n3, h) == memrchr3(n1, n2, n3, h)
+    }
+}
+
+quickcheck! {
+    fn qc_memchr_fn_matches_memchr(n1: u8, corpus: Vec<u8>) -> bool {
+        let find = memchr_fn();
+        (0..=corpus.len()).all(|i| {
+            find(n1, &corpus[i..]) == memchr(n1, &corpus[i..])
+        })
+    }
+
+    fn qc_memchr_batch_matches_memchr(
+        n1: u8,
+        corpus: Vec<u8>,
+        lens: Vec<u8>
+    ) -> bool {
+        let mut haystacks = vec![];
+        let mut rest = &corpus[..];
+        for len in lens {
+            let len = core::cmp::min(len as usize, rest.len());
+            haystacks.push(&rest[..len]);
+            rest = &rest[len..];
+        }
+        haystacks.push(rest);
+
+        let mut out = vec![Some(usize::MAX)];
+        memchr_batch(n1, haystacks.iter().copied(), &mut out);
+        let expected: Vec<Option<usize>> = core::iter::once(Some(usize::MAX))
+            .chain(haystacks.iter().map(|h| memchr(n1, h)))
+            .collect();
+        out == expected
+    }
+}
@@ -4,7 +4,15 @@
 //
 // These tests are also run when the 'std' feature is not enabled.
 
-use crate::{memchr, memchr2, memchr3, memrchr, memrchr2, memrchr3};
+use crate::{
+    count, find_ascii_control, memchr, memchr2, memchr2_count, memchr3,
+    memchr3_count, memchr_all_into, memchr_ascii_whitespace, memchr_not,
+    memchr_not_ascii_whitespace, memchr_not_range, memchr_nth, memchr_range,
+    memrchr, memrchr2, memrchr3, memrchr_all_into, memrchr_ascii_whitespace,
+    memrchr_not, memrchr_not_ascii_whitespace, memrchr_not_range, memrchr_nth,
+    memrchr_range, trim, trim_any, trim_end, trim_end_any, trim_start,
+    trim_start_any,
+};
 
 #[test]
 fn simple() {
@@ -20,4 +28,70 @@ fn simple() {
     assert_eq!(memrchr2(b'z', b'y', b"abcda"), None);
     assert_eq!(memrchr3(b'a', b'z', b'b', b"abcda"), Some(4));
     assert_eq!(memrchr3(b'z', b'y', b'x', b"abcda"), None);
+    assert_eq!(count(b'a', b"abcda"), 2);
+    assert_eq!(count(b'z', b"abcda"), 0);
+    assert_eq!(memchr2_count(b'a', b'b', b"abcda"), 3);
+    assert_eq!(memchr2_count(b'a', b'a', b"abcda"), 2);
+    assert_eq!(memchr3_count(b'a', b'b', b'd', b"abcda"), 4);
+    assert_eq!(memchr3_count(b'z', b'y', b'x', b"abcda"), 0);
+    assert_eq!(memchr_not(b'a', b"aabca"), Some(2));
+    assert_eq!(memchr_not(b'z', b"aabca"), Some(0));
+    assert_eq!(memchr_not(b'a', b"aaaaa"), None);
+    assert_eq!(memchr_not(b'a', b""), None);
+    assert_eq!(memrchr_not(b'a', b"aabca"), Some(3));
+    assert_eq!(memrchr_not(b'a', b"aaaaa"), None);
+    assert_eq!(memchr_range(b'b', b'c', b"abcda"), Some(1));
+    assert_eq!(memchr_range(b'x', b'z', b"abcda"), None);
+    assert_eq!(memchr_range(b'c', b'b', b"abcda"), None);
+    assert_eq!(memrchr_range(b'b', b'c', b"abcda"), Some(2));
+    assert_eq!(memrchr_range(b'c', b'b', b"abcda"), None);
+    assert_eq!(memchr_not_range(b'a', b'c', b"abcda"), Some(3));
+    assert_eq!(memchr_not_range(b'b', b'z', b"abcda"), Some(0));
+    assert_eq!(memchr_not_range(b'a', b'z', b"abcda"), None);
+    assert_eq!(memchr_not_range(b'z', b'a', b"abcda"), Some(0));
+    assert_eq!(memrchr_not_range(b'a', b'c', b"abcda"), Some(3));
+    assert_eq!(memrchr_not_range(b'a', b'z', b"abcda"), None);
+    assert_eq!(memchr_ascii_whitespace(b"ab\tc d"), Some(2));
+    assert_eq!(memchr_ascii_whitespace(b"ab\x0Bcd"), None);
+    assert_eq!(memrchr_ascii_whitespace(b"ab\tc d"), Some(4));
+    assert_eq!(memchr_not_ascii_whitespace(b" \r\nab "), Some(3));
+    assert_eq!(memchr_not_ascii_whitespace(b" \r\n"), None);
+    assert_eq!(memrchr_not_ascii_whitespace(b" ab\r\n"), Some(2));
+    assert_eq!(memchr_nth(0, b'a', b"abcda"), Some(0));
+    assert_eq!(memchr_nth(1, b'a', b"abcda"), Some(4));
+    assert_eq!(memchr_nth(2, b'a', b"abcda"), None);
+    assert_eq!(memrchr_nth(0, b'a', b"abcda"), Some(4));
+    assert_eq!(memrchr_nth(1, b'a', b"abcda"), Some(0));
+    assert_eq!(memrchr_nth(2, b'a', b"abcda"), None);
+    let mut out = [0; 1];
+    assert_eq!(memchr_all_into(b'a', b"abcda", &mut out), 1);
+    assert_eq!(out, [0]);
+    assert_eq!(memrchr_all_into(b'a', b"abcda", &mut out), 1);
+    assert_eq!(out, [4]);
+    assert_eq!(find_ascii_control(b"ab\x7Fc\x00"), Some(2));
+    assert_eq!(find_ascii_control(b"ab c~"), None);
+    assert_eq!(trim_start(b'a', b"aabca"), b"bca");
+    assert_eq!(trim_end(b'a', b"aabca"), b"aabc");
+    assert_eq!(trim(b'a', b"aabca"), b"bc");
+    assert_eq!(trim_start_any(b"ab", b"abcba"), b"cba");
+    assert_eq!(trim_end_any(b"ab", b"abcba"), b"abc");
+    assert_eq!(trim_any(b"ab", b"abcba"), b"c");
+}
+
+#[test]
+fn trim_all_or_nothing() {
+    // Trimming a slice made up entirely of trimmed bytes yields an empty
+    // slice, from either end.
+    for haystack in [&b""[..], b"a", b"aaaa"] {
+        assert_eq!(trim_start(b'a', haystack), b"");
+        assert_eq!(trim_end(b'a', haystack), b"");
+        assert_eq!(trim(b'a', haystack), b"");
+        assert_eq!(trim_start_any(b"a", haystack), b"");
+        assert_eq!(trim_end_any(b"ba", haystack), b"");
+        assert_eq!(trim_any(b"a", haystack), b"");
+    }
+    assert_eq!(trim_any(b"ab", b"abba"), b"");
+    // Trimming with an empty set or an absent byte is a no-op.
+    assert_eq!(trim_any(b"", b"abba"), b"abba");
+    assert_eq!(trim(b'z', b"abba"), b"abba");
 }
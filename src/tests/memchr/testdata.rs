@@ -1,4 +1,4 @@
-use std::iter::repeat;
+use std::{cmp, iter::repeat};
 
 /// Create a sequence of tests that should be run by memchr implementations.
 pub fn memchr_tests() -> Vec<MemchrTest> {
@@ -175,6 +175,126 @@ impl MemchrTest {
         }
     }
 
+    pub fn count_one<F: Fn(u8, &[u8]) -> usize>(&self, f: F) {
+        let needles = match self.needles(1) {
+            None => return,
+            Some(needles) => needles,
+        };
+        for align in 0..130 {
+            let corpus = self.corpus(align);
+            assert_eq!(
+                self.positions(align, false).len(),
+                f(needles[0], corpus.as_bytes()),
+                "count for {:?} failed in: {:?} (len: {}, alignment: {})",
+                needles[0] as char,
+                corpus,
+                corpus.len(),
+                align
+            );
+        }
+    }
+
+    pub fn nth_one<F: Fn(usize, u8, &[u8]) -> Option<usize>>(
+        &self,
+        reverse: bool,
+        f: F,
+    ) {
+        let needles = match self.needles(1) {
+            None => return,
+            Some(needles) => needles,
+        };
+        for align in 0..130 {
+            let corpus = self.corpus(align);
+            let positions = self.positions(align, reverse);
+            for n in 0..=positions.len() {
+                assert_eq!(
+                    positions.get(n).cloned(),
+                    f(n, needles[0], corpus.as_bytes()),
+                    "nth {} for {:?} failed in: {:?} \
+                     (len: {}, alignment: {})",
+                    n,
+                    needles[0] as char,
+                    corpus,
+                    corpus.len(),
+                    align
+                );
+            }
+        }
+    }
+
+    pub fn all_into_one<F: Fn(u8, &[u8], &mut [usize]) -> usize>(
+        &self,
+        reverse: bool,
+        f: F,
+    ) {
+        let needles = match self.needles(1) {
+            None => return,
+            Some(needles) => needles,
+        };
+        for align in 0..130 {
+            let corpus = self.corpus(align);
+            let positions = self.positions(align, reverse);
+            // Test with a buffer that's too small, just big enough and
+            // bigger than necessary.
+            for size in &[positions.len() / 2, positions.len(), 100] {
+                let mut out = vec![usize::MAX; *size];
+                let n = f(needles[0], corpus.as_bytes(), &mut out);
+                let expected_n = cmp::min(*size, positions.len());
+                assert_eq!(
+                    &positions[..expected_n],
+                    &out[..n],
+                    "all_into for {:?} failed in: {:?} \
+                     (len: {}, alignment: {}, buffer size: {})",
+                    needles[0] as char,
+                    corpus,
+                    corpus.len(),
+                    align,
+                    size
+                );
+                // Nothing beyond what was written should be touched.
+                assert!(out[n..].iter().all(|&i| i == usize::MAX));
+            }
+        }
+    }
+
+    pub fn count_two<F: Fn(u8, u8, &[u8]) -> usize>(&self, f: F) {
+        let needles = match self.needles(2) {
+            None => return,
+            Some(needles) => needles,
+        };
+        for align in 0..130 {
+            let corpus = self.corpus(align);
+            assert_eq!(
+                self.positions(align, false).len(),
+                f(needles[0], needles[1], corpus.as_bytes()),
+                "count for {:?} failed in: {:?} (len: {}, alignment: {})",
+                needles,
+                corpus,
+                corpus.len(),
+                align
+            );
+        }
+    }
+
+    pub fn count_three<F: Fn(u8, u8, u8, &[u8]) -> usize>(&self, f: F) {
+        let needles = match self.needles(3) {
+            None => return,
+            Some(needles) => needles,
+        };
+        for align in 0..130 {
+            let corpus = self.corpus(align);
+            assert_eq!(
+                self.positions(align, false).len(),
+                f(needles[0], needles[1], needles[2], corpus.as_bytes()),
+                "count for {:?} failed in: {:?} (len: {}, alignment: {})",
+                needles,
+                corpus,
+                corpus.len(),
+                align
+            );
+        }
+    }
+
     pub fn two<F: Fn(u8, u8, &[u8]) -> Option<usize>>(
         &self,
         reverse: bool,